@@ -17,8 +17,8 @@
 use crate::keychain::Keychain;
 use crate::libwallet::api_impl::foreign;
 use crate::libwallet::{
-	BlockFees, CbData, Error, NodeClient, NodeVersionInfo, Slate, VersionInfo, WalletInst,
-	WalletLCProvider,
+	BlockFees, CbData, Error, NodeClient, NodeVersionInfo, PendingSlateRequest, Slate, VersionInfo,
+	WalletInst, WalletLCProvider,
 };
 use crate::util::secp::key::SecretKey;
 use crate::util::Mutex;
@@ -66,6 +66,9 @@ where
 	pub wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	/// Flag to normalize some output during testing. Can mostly be ignored.
 	pub doctest_mode: bool,
+	/// Set by the listeners. If the receive policy requires the manual approval,
+	/// incoming slates are parked until the wallet owner approves them.
+	pub approval_queue: bool,
 	/// foreign check middleware
 	middleware: Option<ForeignCheckMiddleware>,
 	/// Stored keychain mask (in case the stored wallet seed is tokenized)
@@ -166,6 +169,7 @@ where
 		Foreign {
 			wallet_inst,
 			doctest_mode: false,
+			approval_queue: false,
 			middleware,
 			keychain_mask,
 		}
@@ -379,6 +383,10 @@ where
 	/// # Remarks
 	///
 	/// * This method will store a partially completed transaction in the wallet's transaction log.
+	/// * If `approval_queue` is set and the receive policy requires the manual approval, the slate
+	/// is parked and the `PendingApproval` error is returned until the wallet owner approves it with
+	/// [`approve_pending_slate`](struct.Owner.html#method.approve_pending_slate). The same slate
+	/// sent again after the approval gets the response.
 	///
 	/// # Example
 	/// Set up as in [new](struct.Foreign.html#method.new) method above.
@@ -414,6 +422,19 @@ where
 				Some(slate),
 			)?;
 		}
+		if self.approval_queue {
+			if let Some(s) = foreign::approval_queue_check(
+				&mut **w,
+				(&self.keychain_mask).as_ref(),
+				slate,
+				PendingSlateRequest::ReceiveTx,
+				address.clone(),
+				dest_acct_name,
+				message.clone(),
+			)? {
+				return Ok(s);
+			}
+		}
 
 		foreign::receive_tx(
 			&mut **w,
@@ -451,6 +472,10 @@ where
 	/// containing the new finalized slate.
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
+	/// # Remarks
+	///
+	/// * The approval queue applies the same way as for [`receive_tx`](struct.Foreign.html#method.receive_tx).
+	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
@@ -486,6 +511,19 @@ where
 				Some(slate),
 			)?;
 		}
+		if self.approval_queue {
+			if let Some(s) = foreign::approval_queue_check(
+				&mut **w,
+				(&self.keychain_mask).as_ref(),
+				slate,
+				PendingSlateRequest::FinalizeInvoiceTx,
+				None,
+				None,
+				None,
+			)? {
+				return Ok(s);
+			}
+		}
 		foreign::finalize_invoice_tx(&mut **w, (&self.keychain_mask).as_ref(), slate, true)
 	}
 
@@ -534,7 +572,7 @@ macro_rules! doctest_helper_setup_doc_env_foreign {
 			HTTPNodeClient::new(&wallet_config.check_node_api_http_addr, None).unwrap();
 		let mut wallet = Box::new(
 			DefaultWalletImpl::<'static, HTTPNodeClient>::new(node_client.clone()).unwrap(),
-			)
+		)
 			as Box<
 				WalletInst<
 					'static,
@@ -542,7 +580,7 @@ macro_rules! doctest_helper_setup_doc_env_foreign {
 					HTTPNodeClient,
 					ExtKeychain,
 				>,
-				>;
+			>;
 		let lc = wallet.lc_provider().unwrap();
 		let _ = lc.set_top_level_directory(&wallet_config.data_file_dir);
 		lc.open_wallet(None, pw, false, false, None);
@@ -56,6 +56,10 @@ pub trait ForeignRpc {
 					"V3B",
 					"V3",
 					"V2"
+				],
+				"supported_slate_encodings": [
+					"Json",
+					"Compact"
 				]
 			}
 		}
@@ -596,6 +600,7 @@ where
 		message: Option<String>,
 	) -> Result<VersionedSlate, ErrorKind> {
 		let version = in_slate.version();
+		let compact = in_slate.is_compact();
 		let slate_from = Slate::from(in_slate);
 		let out_slate = Foreign::receive_tx(
 			self,
@@ -605,13 +610,20 @@ where
 			message,
 		)
 		.map_err(|e| e.kind())?;
+		if compact {
+			return Ok(VersionedSlate::into_compact(out_slate));
+		}
 		Ok(VersionedSlate::into_version(out_slate, version))
 	}
 
 	fn finalize_invoice_tx(&self, in_slate: VersionedSlate) -> Result<VersionedSlate, ErrorKind> {
 		let version = in_slate.version();
+		let compact = in_slate.is_compact();
 		let out_slate =
 			Foreign::finalize_invoice_tx(self, &Slate::from(in_slate)).map_err(|e| e.kind())?;
+		if compact {
+			return Ok(VersionedSlate::into_compact(out_slate));
+		}
 		Ok(VersionedSlate::into_version(out_slate, version))
 	}
 
@@ -878,7 +890,7 @@ macro_rules! doctest_helper_json_rpc_foreign_assert_response {
 			$blocks_to_mine,
 			$init_tx,
 			$init_invoice_tx,
-			)
+		)
 		.unwrap()
 		.unwrap();
 
@@ -887,7 +899,7 @@ macro_rules! doctest_helper_json_rpc_foreign_assert_response {
 				"(left != right) \nleft: {}\nright: {}",
 				serde_json::to_string_pretty(&response).unwrap(),
 				serde_json::to_string_pretty(&expected_response).unwrap()
-				);
-			}
+			);
+		}
 	};
 }
@@ -18,21 +18,27 @@ use chrono::prelude::*;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use uuid::Uuid;
 
-use crate::config::{MQSConfig, TorConfig, WalletConfig};
-use crate::core::core::Transaction;
+use crate::config::{
+	FeePolicyConfig, MQSConfig, RebroadcastConfig, ReceivePolicyConfig, TorConfig, WalletConfig,
+};
+use crate::core::core::{transaction, Transaction};
 use crate::core::global;
 use crate::impls::create_sender;
 use crate::keychain::{Identifier, Keychain};
 use crate::libwallet::api_impl::owner_updater::{start_updater_log_thread, StatusMessage};
 use crate::libwallet::api_impl::{owner, owner_swap, owner_updater};
+use crate::libwallet::proof::payment_proof::{self, PaymentProofDocument};
 use crate::libwallet::proof::tx_proof::TxProof;
 use crate::libwallet::swap::fsm::state::{StateEtaInfo, StateId, StateProcessRespond};
 use crate::libwallet::swap::types::{Action, SwapTransactionsConfirmations};
 use crate::libwallet::swap::{message::Message, swap::Swap, swap::SwapJournalRecord};
 use crate::libwallet::{
-	AcctPathMapping, Error, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient,
-	NodeHeightResult, OutputCommitMapping, PaymentProof, Slate, SwapStartArgs, TxLogEntry,
-	WalletInfo, WalletInst, WalletLCProvider,
+	AcctPathMapping, BatchSendArgs, BatchSendEntry, BatchSendResult, ColdSignRequest,
+	ColdSignResponse, ColdSignStep, Contact, Error, ErrorKind, ExpiredTx, FeeEstimate, InitTxArgs,
+	Invoice, IssueInvoiceTxArgs, NodeClient, NodeHeightResult, OutputCommitMapping, OutputData,
+	PaymentProof, PendingSlate, ScanStatus, Slate, SpendPolicyArgs, SpendPolicyStatus,
+	SwapStartArgs, TransferArgs, TransferResult, TxLogEntry, WalletInfo, WalletInst,
+	WalletLCProvider, WalletSettings,
 };
 use crate::util::logger::LoggingConfig;
 use crate::util::secp::key::SecretKey;
@@ -246,6 +252,107 @@ where
 		*lock = tor_config;
 	}
 
+	// Settings are kept by the lifecycle provider, so they survive the wallet close and open
+	fn update_wallet_settings<F>(&self, f: F) -> Result<(), Error>
+	where
+		F: FnOnce(&mut WalletSettings),
+	{
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		let mut settings = lc.wallet_settings();
+		f(&mut settings);
+		lc.set_wallet_settings(settings);
+		Ok(())
+	}
+
+	/// Set the receive policy that the foreign API of this wallet instance applies to the
+	/// incoming slates.
+	///
+	/// # Arguments
+	/// * `policy` - The optional [ReceivePolicyConfig](#), None accepts all slates
+	/// # Returns
+	/// * Ok(()) if the policy was set
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_receive_policy(&self, policy: Option<ReceivePolicyConfig>) -> Result<(), Error> {
+		self.update_wallet_settings(|s| s.receive_policy = policy)
+	}
+
+	/// Enable the verification of the node data against the header chain that this wallet
+	/// instance syncs and verifies itself.
+	///
+	/// # Arguments
+	/// * `enabled` - If true, the outputs, kernels and block hashes from the node are checked
+	/// against the verified header chain
+	/// * `checkpoint` - Optional height and hash of the header where the chain is anchored.
+	/// None trusts the node header one day below the tip at the first sync.
+	/// # Returns
+	/// * Ok(()) if the settings were updated
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_header_verification(
+		&self,
+		enabled: bool,
+		checkpoint: Option<(u64, String)>,
+	) -> Result<(), Error> {
+		self.update_wallet_settings(|s| {
+			s.header_verification = enabled;
+			s.header_checkpoint = checkpoint;
+		})
+	}
+
+	/// Set the number of threads that rewind the range proofs during the chain scan of
+	/// this wallet instance.
+	///
+	/// # Arguments
+	/// * `threads` - Number of the threads, at least one thread is used
+	/// # Returns
+	/// * Ok(()) if the settings were updated
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_scan_threads(&self, threads: usize) -> Result<(), Error> {
+		self.update_wallet_settings(|s| s.scan_threads = std::cmp::max(threads, 1))
+	}
+
+	/// Set the rebroadcast of the posted transactions that the updater of this wallet
+	/// instance does.
+	///
+	/// # Arguments
+	/// * `config` - The optional [RebroadcastConfig](#), None disables the rebroadcast
+	/// # Returns
+	/// * Ok(()) if the settings were updated
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_rebroadcast_config(&self, config: Option<RebroadcastConfig>) -> Result<(), Error> {
+		self.update_wallet_settings(|s| s.rebroadcast = config)
+	}
+
+	/// Enable or disable the cancellation of the transactions with an expired TTL that
+	/// the wallet update of this wallet instance does.
+	///
+	/// # Arguments
+	/// * `enabled` - If true, the expired transactions are cancelled
+	/// # Returns
+	/// * Ok(()) if the settings were updated
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_auto_cancel_expired(&self, enabled: bool) -> Result<(), Error> {
+		self.update_wallet_settings(|s| s.auto_cancel_expired = enabled)
+	}
+
+	/// Set the fee policy of the transactions that this wallet instance sends.
+	///
+	/// # Arguments
+	/// * `policy` - The optional [FeePolicyConfig](#), None restores the network default
+	/// fee without limits
+	/// # Returns
+	/// * Ok(()) if the settings were updated
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn set_fee_policy(&self, policy: Option<FeePolicyConfig>) -> Result<(), Error> {
+		self.update_wallet_settings(|s| s.fee_policy = policy.unwrap_or_default())
+	}
+
 	/// Returns a list of accounts stored in the wallet (i.e. mappings between
 	/// user-specified labels and BIP32 derivation paths.
 	/// # Arguments
@@ -459,6 +566,73 @@ where
 		)
 	}
 
+	/// Freezes the output, coin selection will not use it until it is unfrozen. A frozen output
+	/// is still spent if the caller lists it explicitly in
+	/// [`InitTxArgs::outputs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html).
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `commit` - Hex encoded commitment of the output.
+	///
+	/// # Returns
+	/// * Ok with the updated [OutputData](../grin_wallet_libwallet/types/struct.OutputData.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the output is not
+	/// found or an error is encountered.
+
+	pub fn freeze_output(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		commit: &str,
+	) -> Result<OutputData, Error> {
+		owner::set_output_frozen(self.wallet_inst.clone(), keychain_mask, commit, true)
+	}
+
+	/// Makes the frozen output available for the coin selection again.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `commit` - Hex encoded commitment of the output.
+	///
+	/// # Returns
+	/// * Ok with the updated [OutputData](../grin_wallet_libwallet/types/struct.OutputData.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the output is not
+	/// found or an error is encountered.
+
+	pub fn unfreeze_output(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		commit: &str,
+	) -> Result<OutputData, Error> {
+		owner::set_output_frozen(self.wallet_inst.clone(), keychain_mask, commit, false)
+	}
+
+	/// Sets the note and the tags of the output. Both replace the existing values, `None` and
+	/// an empty list clear them.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `commit` - Hex encoded commitment of the output.
+	/// * `note` - Free form note.
+	/// * `tags` - Tags of the output, duplicates are dropped.
+	///
+	/// # Returns
+	/// * Ok with the updated [OutputData](../grin_wallet_libwallet/types/struct.OutputData.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the output is not
+	/// found or an error is encountered.
+
+	pub fn label_output(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		commit: &str,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<OutputData, Error> {
+		owner::label_output(self.wallet_inst.clone(), keychain_mask, commit, note, tags)
+	}
+
 	/// Returns a list of [Transaction Log Entries](../grin_wallet_libwallet/types/struct.TxLogEntry.html)
 	/// from the active account in the wallet.
 	///
@@ -540,6 +714,119 @@ where
 		Ok(res)
 	}
 
+	/// Sets the local note and tags of the transaction from the active account. They are
+	/// searchable with [`TxLogEntry::matches`](../grin_wallet_libwallet/types/struct.TxLogEntry.html#method.matches)
+	/// and, unlike the slate messages, are never sent to the other party.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `tx_id` - If present, the transaction log entry of id `i`.
+	/// * `tx_slate_id` - If `tx_id` is not set, the transaction with this slate uuid.
+	/// * `note` - Free form note, `None` clears it.
+	/// * `tags` - Tags of the transaction, duplicates are dropped, an empty list clears them.
+	///
+	/// # Returns
+	/// * Ok with the updated [TxLogEntry](../grin_wallet_libwallet/types/struct.TxLogEntry.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the transaction
+	/// is not found or an error is encountered.
+
+	pub fn label_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<TxLogEntry, Error> {
+		owner::label_tx(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			tx_id,
+			tx_slate_id,
+			note,
+			tags,
+		)
+	}
+
+	/// Returns the contacts of the local address book.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with the list of [Contacts](../grin_wallet_libwallet/types/struct.Contact.html)
+	/// ordered by name
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn retrieve_contacts(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<Contact>, Error> {
+		owner::retrieve_contacts(self.wallet_inst.clone(), keychain_mask)
+	}
+
+	/// Returns the address book contact.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `name` - Name of the contact, with or without the `@` prefix.
+	///
+	/// # Returns
+	/// * Ok with the [Contact](../grin_wallet_libwallet/types/struct.Contact.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the contact
+	/// is not found or an error is encountered.
+
+	pub fn get_contact(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		name: &str,
+	) -> Result<Contact, Error> {
+		owner::get_contact(self.wallet_inst.clone(), keychain_mask, name)
+	}
+
+	/// Adds the contact to the address book, the contact with the same name is replaced.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `contact` - The contact. Method is one of `http`, `tor` or `mqs`.
+	///
+	/// # Returns
+	/// * Ok with the stored [Contact](../grin_wallet_libwallet/types/struct.Contact.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the contact
+	/// is invalid or an error is encountered.
+
+	pub fn save_contact(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		contact: Contact,
+	) -> Result<Contact, Error> {
+		owner::save_contact(self.wallet_inst.clone(), keychain_mask, contact)
+	}
+
+	/// Removes the contact from the address book.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `name` - Name of the contact, with or without the `@` prefix.
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the contact
+	/// is not found or an error is encountered.
+
+	pub fn delete_contact(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		name: &str,
+	) -> Result<(), Error> {
+		owner::delete_contact(self.wallet_inst.clone(), keychain_mask, name)
+	}
+
 	/// Returns summary information from the active account in the wallet.
 	///
 	/// # Arguments
@@ -786,6 +1073,273 @@ where
 		}
 	}
 
+	/// Estimates the fee of the transaction that [`init_send_tx`](struct.Owner.html#method.init_send_tx)
+	/// would build with the same arguments, with the `[fee_policy]` of the wallet config applied.
+	/// The outputs are selected the same way, but nothing is locked and no slate is created.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`InitTxArgs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html),
+	/// the amount, selection strategy and number of change outputs are used for the estimate.
+	///
+	/// # Returns
+	/// * Ok with the [`FeeEstimate`](../grin_wallet_libwallet/api_impl/types/struct.FeeEstimate.html).
+	/// The estimate is returned even if the fee is over the limit of the fee policy, check it
+	/// with `exceeds_max_fee`.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if there are not
+	/// enough funds or an error is encountered.
+
+	pub fn estimate_fee(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: InitTxArgs,
+	) -> Result<FeeEstimate, Error> {
+		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+		if args.minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"Minimum_confirmations can not be smaller than 1".to_owned(),
+			)
+			.into());
+		}
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::estimate_fee(&mut **w, &args, 1)
+	}
+
+	/// Pays many recipients in one go, intended for pool and exchange payouts.
+	/// The inputs for the whole batch are selected and locked once. Every recipient gets its
+	/// own slate, exchanged by `http` or `mwcmqs` and finalized, that spends the change of the
+	/// previous one. All finalized transactions are aggregated into a single transaction
+	/// that is posted once, the intermediate change outputs are cut through, so the batch
+	/// has a single change output. Every recipient keeps its own kernel, tx log entry and
+	/// payment proof, because it signs its own part of the transaction.
+	///
+	/// Recipients that fail get their slates rolled back, the next recipient spends the same
+	/// inputs. If the aggregated transaction can't be posted, the failure is reported for
+	/// every posted recipient and the locks are kept. The aggregated transaction is stored
+	/// for all of them, it can be posted again with the repost or by the rebroadcast.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`BatchSendArgs`](../grin_wallet_libwallet/types/struct.BatchSendArgs.html),
+	/// the recipients and the shared selection settings.
+	///
+	/// # Returns
+	/// * The outcome for every recipient, in the same order as `args.entries`
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the batch
+	/// couldn't be started at all.
+	pub fn send_batch_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: BatchSendArgs,
+	) -> Result<Vec<BatchSendResult>, Error> {
+		if args.minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"Minimum_confirmations can not be smaller than 1".to_owned(),
+			)
+			.into());
+		}
+
+		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+
+		let mut inputs = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			owner::select_batch_inputs(&mut **w, &args)?
+		};
+
+		let mut results: Vec<BatchSendResult> = Vec::with_capacity(args.entries.len());
+		let mut finalized: Vec<(usize, Slate)> = Vec::new();
+
+		for (i, entry) in args.entries.iter().enumerate() {
+			let mut result = BatchSendResult::new(entry);
+			let init_res = {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::init_batch_send_tx(
+					&mut **w,
+					keychain_mask,
+					&args,
+					entry,
+					&inputs,
+					self.doctest_mode,
+				)
+			};
+			match init_res {
+				Ok((slate, change)) => {
+					result.slate_id = Some(slate.id);
+					result.fee = slate.fee;
+					match self.exchange_batch_slate(keychain_mask, entry, &slate) {
+						Ok(s) => {
+							finalized.push((i, s));
+							inputs = change.into_iter().collect();
+						}
+						Err(e) => {
+							error!("Batch payment to {} failed, {}", entry.dest, e);
+							result.error = Some(format!("{}", e));
+							let _ =
+								self.rollback_batch_slate(keychain_mask, &args, &slate.id, &inputs);
+						}
+					}
+				}
+				Err(e) => result.error = Some(format!("{}", e)),
+			}
+			results.push(result);
+		}
+
+		if finalized.is_empty() {
+			return Ok(results);
+		}
+
+		let txs: Vec<Transaction> = finalized.iter().map(|(_, s)| s.tx.clone()).collect();
+		let post_res = transaction::aggregate(&txs)
+			.map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to aggregate batch transactions, {}", e))
+					.into()
+			})
+			.and_then(|tx| {
+				{
+					let mut w_lock = self.wallet_inst.lock();
+					let w = w_lock.lc_provider()?.wallet_inst()?;
+					let slate_ids: Vec<Uuid> = finalized.iter().map(|(_, s)| s.id).collect();
+					owner::store_batch_tx(&mut **w, &slate_ids, &tx)?;
+				}
+				self.post_tx(keychain_mask, &tx, args.fluff)
+			});
+		match post_res {
+			Ok(_) => info!(
+				"Batch transaction with {} payments posted successfully",
+				finalized.len()
+			),
+			Err(e) => {
+				// The transactions are finalized, the locks are kept for the repost
+				error!("Unable to post batch transaction, {}", e);
+				for (i, _) in &finalized {
+					results[*i].error = Some(format!("Unable to post batch transaction, {}", e));
+				}
+			}
+		}
+
+		Ok(results)
+	}
+
+	// Send a single batch slate to the recipient and finalize the response
+	fn exchange_batch_slate(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		entry: &BatchSendEntry,
+		slate: &Slate,
+	) -> Result<Slate, Error> {
+		match entry.method.as_ref() {
+			"http" | "mwcmqs" => {}
+			_ => {
+				return Err(ErrorKind::ClientCallback(format!(
+					"unsupported payment method: {}",
+					entry.method
+				))
+				.into());
+			}
+		}
+
+		let comm_adapter = {
+			let tor_config_lock = self.tor_config.lock();
+			create_sender(
+				&entry.method,
+				&entry.dest,
+				&entry.apisecret,
+				tor_config_lock.clone(),
+			)
+			.map_err(|e| ErrorKind::GenericError(format!("Unable to create a sender, {}", e)))?
+		};
+
+		let mut res_slate = comm_adapter.send_tx(slate).map_err(|e| {
+			ErrorKind::ClientCallback(format!(
+				"Unable to send slate {} with {}, {}",
+				slate.id, entry.method, e
+			))
+		})?;
+
+		// Restore back ttl, because it can be gone
+		res_slate.ttl_cutoff_height = slate.ttl_cutoff_height.clone();
+		// Checking is sender didn't do any harm to slate
+		Slate::compare_slates_send(slate, &res_slate)?;
+		self.verify_slate_messages(keychain_mask, &res_slate)?;
+		self.finalize_tx(keychain_mask, &res_slate)
+	}
+
+	// Release the locks of the batch slate that wasn't completed
+	fn rollback_batch_slate(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &BatchSendArgs,
+		slate_id: &Uuid,
+		inputs: &[OutputData],
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::cancel_batch_send_tx(&mut **w, keychain_mask, args, slate_id, inputs).map_err(|e| {
+			error!("Unable to roll back batch slate {}, {}", slate_id, e);
+			e
+		})
+	}
+
+	/// Moves funds between two accounts of this wallet in one step. The transaction is
+	/// built by the source account, received by the destination account, finalized and
+	/// posted. The tx log entries of both accounts reference each other with `linked_tx`.
+	///
+	/// If the transaction can't be posted, both sides of the transfer are cancelled and
+	/// the locked outputs are released.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`TransferArgs`](../grin_wallet_libwallet/types/struct.TransferArgs.html),
+	/// the accounts, the amount and the selection settings.
+	///
+	/// # Returns
+	/// * The slate id and the linked tx log entries of both accounts
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an account
+	/// doesn't exist, the funds are not sufficient or the transaction can't be posted.
+	pub fn transfer(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: TransferArgs,
+	) -> Result<TransferResult, Error> {
+		if args.minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"Minimum_confirmations can not be smaller than 1".to_owned(),
+			)
+			.into());
+		}
+
+		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+
+		let slate = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			owner::init_transfer_tx(&mut **w, keychain_mask, &args, self.doctest_mode)?
+		};
+
+		let res = self
+			.finalize_tx(keychain_mask, &slate)
+			.and_then(|slate| self.post_tx(keychain_mask, &slate.tx, args.fluff));
+		if let Err(e) = res {
+			error!("Unable to complete transfer {}, {}", slate.id, e);
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let _ = owner::cancel_transfer_tx(&mut **w, keychain_mask, &args, &slate.id);
+			return Err(e);
+		}
+
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::transfer_txs(&mut **w, &args, &slate.id)
+	}
+
 	/// Issues a new invoice transaction slate, essentially a `request for payment`.
 	/// The slate created by this function will contain the amount, an output for the amount,
 	/// as well as round 1 of singature creation complete. The slate should then be send
@@ -848,70 +1402,298 @@ where
 	/// # Arguments
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
-	/// * `slate` - The transaction [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html). The
-	/// payer should have filled in round 1 and 2.
-	/// * `args` - [`InitTxArgs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html),
-	/// transaction initialization arguments. See struct documentation for further detail.
+	/// * `slate` - The transaction [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html). The
+	/// payer should have filled in round 1 and 2.
+	/// * `args` - [`InitTxArgs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html),
+	/// transaction initialization arguments. See struct documentation for further detail.
+	///
+	/// # Returns
+	/// * ``Ok([`slate`](../grin_wallet_libwallet/slate/struct.Slate.html))` if successful,
+	/// containing the updated slate.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// // . . .
+	/// // The slate has been recieved from the invoicer, somehow
+	/// # let slate = Slate::blank(2);
+	/// let args = InitTxArgs {
+	///     src_acct_name: None,
+	///     amount: slate.amount,
+	///     minimum_confirmations: 2,
+	///     max_outputs: 500,
+	///     num_change_outputs: 1,
+	///     selection_strategy_is_use_all: false,
+	///     ..Default::default()
+	/// };
+	///
+	/// let result = api_owner.process_invoice_tx(None, &slate, args);
+	///
+	/// if let Ok(slate) = result {
+	/// // If result okay, send back to the invoicer
+	/// // . . .
+	/// }
+	/// ```
+
+	pub fn process_invoice_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		slate: &Slate,
+		args: InitTxArgs,
+	) -> Result<Slate, Error> {
+		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+
+		//minimum_confirmations cannot be zero.
+		let minimum_confirmations = args.minimum_confirmations.clone();
+		if minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"minimum_confirmations can not smaller than 1".to_owned(),
+			)
+			.into());
+		}
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::process_invoice_tx(
+			&mut **w,
+			keychain_mask,
+			slate,
+			args,
+			self.doctest_mode,
+			true,
+		)
+	}
+
+	/// Returns the invoices issued by this wallet with
+	/// [`issue_invoice_tx`](struct.Owner.html#method.issue_invoice_tx). An invoice is
+	/// marked as paid when the payer's slate is finalized with the
+	/// [Foreign API's `finalize_invoice_tx`](struct.Foreign.html#method.finalize_invoice_tx) method.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `refresh_from_node` - If true, the wallet will attempt to contact
+	/// a node to update the chain height before checking the invoice expiry.
+	/// * `invoice_id` - If `Some(Uuid)`, only return the invoice with the given slate id.
+	/// * `reference` - If `Some(String)`, only return the invoices with the given external reference.
+	///
+	/// # Returns
+	/// * `(bool, Vec<Invoice>)` - A tuple:
+	/// * The first `bool` element indicates whether the data was successfully
+	/// refreshed from the node (note this may be false even if the `refresh_from_node`
+	/// argument was set to `true`.
+	/// * The second element contains the list of invoices, ordered by creation time.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.retrieve_invoices(None, true, None, Some("order-42".to_owned()));
+	///
+	/// if let Ok((was_updated, invoices)) = result {
+	///     //...
+	/// }
+	/// ```
+	pub fn retrieve_invoices(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		refresh_from_node: bool,
+		invoice_id: Option<Uuid>,
+		reference: Option<String>,
+	) -> Result<(bool, Vec<Invoice>), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let refresh_from_node = match self.updater_running.load(Ordering::Relaxed) {
+			true => false,
+			false => refresh_from_node,
+		};
+		owner::retrieve_invoices(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&tx,
+			refresh_from_node,
+			invoice_id,
+			reference,
+		)
+	}
+
+	/// Cancels an open invoice. The output of the invoice transaction is cancelled
+	/// as well, so the invoice can't be finalized after that.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `invoice_id` - Slate id of the invoice
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn cancel_invoice(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		invoice_id: Uuid,
+	) -> Result<(), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		owner::cancel_invoice(self.wallet_inst.clone(), keychain_mask, &tx, invoice_id)
+	}
+
+	/// Returns the incoming slates parked in the approval queue. Slates are parked by the
+	/// foreign API listeners when the receive policy `manual_approval` is on.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `slate_id` - If `Some(Uuid)`, only return the slate with the given id.
+	///
+	/// # Returns
+	/// * Ok with the list of parked slates, ordered by the time they arrived. Pending slates that
+	/// passed the approval timeout are reported as expired.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn retrieve_pending_slates(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		slate_id: Option<Uuid>,
+	) -> Result<Vec<PendingSlate>, Error> {
+		owner::retrieve_pending_slates(self.wallet_inst.clone(), keychain_mask, slate_id)
+	}
+
+	/// Approves a parked slate. The slate is processed as the foreign API would do it, and the
+	/// result is kept for the sender. HTTP and Tor senders get it when they send the slate again,
+	/// the MWCMQS listener sends it back to the sender.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `slate_id` - Id of the parked slate
+	///
+	/// # Returns
+	/// * Ok([`slate`](../grin_wallet_libwallet/slate/struct.Slate.html)) with the response slate
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn approve_pending_slate(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		slate_id: Uuid,
+	) -> Result<Slate, Error> {
+		owner::approve_pending_slate(self.wallet_inst.clone(), keychain_mask, slate_id)
+	}
+
+	/// Rejects a parked slate. The sender gets an error when the slate is sent again.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `slate_id` - Id of the parked slate
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn reject_pending_slate(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		slate_id: Uuid,
+	) -> Result<(), Error> {
+		owner::reject_pending_slate(self.wallet_inst.clone(), keychain_mask, slate_id)
+	}
+
+	// The spending policy is a guard against a compromised API client, so the wallet
+	// password is required to change it, the keychain mask is not enough
+	fn check_admin_password(
+		&self,
+		password: ZeroingString,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.get_mnemonic(None, password, wallet_data_dir)
+			.map_err(|_| ErrorKind::GenericError("Wrong wallet password".to_string()))?;
+		Ok(())
+	}
+
+	/// Sets the outbound spending policy of an account. The policy is checked by
+	/// [`init_send_tx`](struct.Owner.html#method.init_send_tx) and
+	/// [`process_invoice_tx`](struct.Owner.html#method.process_invoice_tx), the violations
+	/// are reported as `ErrorKind::SpendPolicyViolation`. The new policy replaces the current one.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `password` - Wallet password, changing the policy requires it
+	/// * `wallet_data_dir` - Optional wallet data dir, as for [`get_mnemonic`](struct.Owner.html#method.get_mnemonic)
+	/// * `args` - Policy settings and the account
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn set_spend_policy(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		password: ZeroingString,
+		wallet_data_dir: Option<&str>,
+		args: &SpendPolicyArgs,
+	) -> Result<(), Error> {
+		self.check_admin_password(password, wallet_data_dir)?;
+		owner::set_spend_policy(self.wallet_inst.clone(), keychain_mask, args)
+	}
+
+	/// Removes the outbound spending policy of an account
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `password` - Wallet password, changing the policy requires it
+	/// * `wallet_data_dir` - Optional wallet data dir, as for [`get_mnemonic`](struct.Owner.html#method.get_mnemonic)
+	/// * `acct_name` - Account name, the active account if None
 	///
 	/// # Returns
-	/// * ``Ok([`slate`](../grin_wallet_libwallet/slate/struct.Slate.html))` if successful,
-	/// containing the updated slate.
+	/// * Ok(()) if successful
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn clear_spend_policy(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		password: ZeroingString,
+		wallet_data_dir: Option<&str>,
+		acct_name: Option<String>,
+	) -> Result<(), Error> {
+		self.check_admin_password(password, wallet_data_dir)?;
+		owner::clear_spend_policy(self.wallet_inst.clone(), keychain_mask, &acct_name)
+	}
+
+	/// Returns the outbound spending policy of an account with the amounts sent during
+	/// the last 24 hours and 7 days
 	///
-	/// # Example
-	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
-	/// ```
-	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
-	///
-	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
-	///
-	/// // . . .
-	/// // The slate has been recieved from the invoicer, somehow
-	/// # let slate = Slate::blank(2);
-	/// let args = InitTxArgs {
-	///     src_acct_name: None,
-	///     amount: slate.amount,
-	///     minimum_confirmations: 2,
-	///     max_outputs: 500,
-	///     num_change_outputs: 1,
-	///     selection_strategy_is_use_all: false,
-	///     ..Default::default()
-	/// };
-	///
-	/// let result = api_owner.process_invoice_tx(None, &slate, args);
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `acct_name` - Account name, the active account if None
 	///
-	/// if let Ok(slate) = result {
-	/// // If result okay, send back to the invoicer
-	/// // . . .
-	/// }
-	/// ```
-
-	pub fn process_invoice_tx(
+	/// # Returns
+	/// * Ok(None) if the account doesn't have a policy
+	/// * Ok(Some([`SpendPolicyStatus`](../grin_wallet_libwallet/api_impl/types/struct.SpendPolicyStatus.html)))
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn retrieve_spend_policy(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-		slate: &Slate,
-		args: InitTxArgs,
-	) -> Result<Slate, Error> {
-		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
-
-		//minimum_confirmations cannot be zero.
-		let minimum_confirmations = args.minimum_confirmations.clone();
-		if minimum_confirmations < 1 {
-			return Err(ErrorKind::ClientCallback(
-				"minimum_confirmations can not smaller than 1".to_owned(),
-			)
-			.into());
+		acct_name: Option<String>,
+	) -> Result<Option<SpendPolicyStatus>, Error> {
+		{
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
 		}
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::process_invoice_tx(
-			&mut **w,
-			keychain_mask,
-			slate,
-			args,
-			self.doctest_mode,
-			true,
-		)
+		owner::retrieve_spend_policy(self.wallet_inst.clone(), &acct_name)
 	}
 
 	/// Locks the outputs associated with the inputs to the transaction in the given
@@ -1057,6 +1839,10 @@ where
 	/// Posts a completed transaction to the listening node for validation and inclusion in a block
 	/// for mining.
 	///
+	/// The wallet transactions of the posted transaction are tracked by the updater. If their
+	/// kernels don't get to the chain, the transaction is posted again as the `[rebroadcast]`
+	/// config section defines. That includes the transaction whose post here fails.
+	///
 	/// # Arguments
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
@@ -1117,6 +1903,9 @@ where
 			let w = w_lock.lc_provider()?.wallet_inst()?;
 			// Test keychain mask, to keep API consistent
 			let _ = w.keychain(keychain_mask)?;
+			// Transactions that the node loses or doesn't get because it is down are posted
+			// again by the updater, so the tracking starts before the post
+			owner::track_posted_tx(&mut **w, keychain_mask, tx)?;
 			w.w2n_client().clone()
 		};
 		owner::post_tx(&client, tx, fluff)
@@ -1197,6 +1986,191 @@ where
 		)
 	}
 
+	/// Reports the unconfirmed transactions of all accounts whose TTL cutoff height is
+	/// reached at the current node height. Nothing is changed, this is a dry run of the
+	/// cancellation that the wallet update does when `auto_cancel_expired` is enabled.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with the [`ExpiredTx`](../grin_wallet_libwallet/api_impl/types/struct.ExpiredTx.html)
+	/// list, with the locked inputs that would be unlocked and the unconfirmed outputs that
+	/// would be deleted
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the node is not
+	/// reachable or an error is encountered.
+
+	pub fn retrieve_expired_txs(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<ExpiredTx>, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::retrieve_expired_txs(&mut **w, keychain_mask)
+	}
+
+	/// Exports the unspent outputs of the active account for the online wallet. Called on
+	/// the offline cold wallet, the node is not contacted.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with the outputs for `import_cold_outputs`
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is
+	/// encountered.
+
+	pub fn export_cold_outputs(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<OutputData>, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::export_cold_outputs(&mut **w)
+	}
+
+	/// Starts tracking the outputs of the offline cold wallet in the active account. The
+	/// wallet update checks them by the commitment. The account must be used for the cold
+	/// outputs only, the key ids of the cold wallet can't be mixed with the own ones.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `outputs` - Outputs from `export_cold_outputs` of the offline wallet.
+	///
+	/// # Returns
+	/// * Ok with the number of the new outputs, the tracked ones are skipped
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an output
+	/// collides with an output of this wallet or an error is encountered.
+
+	pub fn import_cold_outputs(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		outputs: Vec<OutputData>,
+	) -> Result<usize, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::import_cold_outputs(&mut **w, keychain_mask, outputs)
+	}
+
+	/// Initiates a send from the cold wallet outputs of the active account. The inputs are
+	/// selected and the fee is set the same way as `init_send_tx` does it, but nothing is
+	/// signed. The request is carried to the offline wallet for `cold_sign`.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`InitTxArgs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html),
+	/// payment proofs and `send_args` are not supported.
+	///
+	/// # Returns
+	/// * Ok with the [`ColdSignRequest`](../grin_wallet_libwallet/api_impl/types/struct.ColdSignRequest.html)
+	/// of the send step
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if there are not
+	/// enough funds or an error is encountered.
+
+	pub fn init_cold_send_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: InitTxArgs,
+	) -> Result<ColdSignRequest, Error> {
+		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+		if args.minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"Minimum_confirmations can not be smaller than 1".to_owned(),
+			)
+			.into());
+		}
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::init_cold_send_tx(&mut **w, args, self.doctest_mode)
+	}
+
+	/// Builds the request for the offline cold wallet to receive the slate from the other
+	/// party, or to sign the slate that the other party returned.
+	///
+	/// # Arguments
+	///
+	/// * `step` - [`ColdSignStep`](../grin_wallet_libwallet/api_impl/types/enum.ColdSignStep.html),
+	/// `Receive` or `Sign`.
+	/// * `slate` - The slate from the other party.
+	/// * `message` - Optional message for the participant data of the receiver.
+	///
+	/// # Returns
+	/// * Ok with the [`ColdSignRequest`](../grin_wallet_libwallet/api_impl/types/struct.ColdSignRequest.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) for the send step.
+
+	pub fn cold_sign_request(
+		&self,
+		step: ColdSignStep,
+		slate: &Slate,
+		message: Option<String>,
+	) -> Result<ColdSignRequest, Error> {
+		owner::cold_sign_request(step, slate, message)
+	}
+
+	/// Signs the request with the keychain of the offline cold wallet. The send step adds
+	/// the inputs, the change outputs with their range proofs and the public data of the
+	/// first round. The receive step adds the output and the partial signature. The sign
+	/// step adds the partial signature of the sender. The node is not contacted.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `request` - The request from the online wallet.
+	///
+	/// # Returns
+	/// * Ok with the [`ColdSignResponse`](../grin_wallet_libwallet/api_impl/types/struct.ColdSignResponse.html)
+	/// for `process_cold_sign_response` of the online wallet
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the inputs
+	/// don't belong to the wallet or an error is encountered.
+
+	pub fn cold_sign(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		request: &ColdSignRequest,
+	) -> Result<ColdSignResponse, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::cold_sign(&mut **w, keychain_mask, request, self.doctest_mode)
+	}
+
+	/// Records the response of the offline cold wallet in the active account. The spent
+	/// cold outputs are locked and the new ones are tracked as unconfirmed. The response
+	/// of the sign step is finalized and ready for `post_tx`.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `response` - The response of the offline wallet.
+	///
+	/// # Returns
+	/// * Ok with the slate for the other party, or the finalized slate
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the response
+	/// doesn't match the tracked outputs or an error is encountered.
+
+	pub fn process_cold_sign_response(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		response: &ColdSignResponse,
+	) -> Result<Slate, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::process_cold_sign_response(&mut **w, keychain_mask, response)
+	}
+
 	/// Retrieves the stored transaction associated with a TxLogEntry. Can be used even after the
 	/// transaction has completed.
 	///
@@ -1387,6 +2361,54 @@ where
 		)
 	}
 
+	/// Returns the progress of the chain scan. The progress of the full scan is saved after
+	/// every batch of outputs, so a [`scan`](struct.Owner.html#method.scan) that was paused,
+	/// failed or interrupted by the wallet restart continues from the last saved PMMR index
+	/// when it is called again with the same `start_height`.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with a [`ScanStatus`](../grin_wallet_libwallet/api_impl/types/struct.ScanStatus.html).
+	/// `checkpoint` is None if no scan is running and there is nothing to resume.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn scan_status(&self, keychain_mask: Option<&SecretKey>) -> Result<ScanStatus, Error> {
+		{
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
+		}
+		owner::scan_status(self.wallet_inst.clone())
+	}
+
+	/// Pauses the running scan after the current batch of outputs. The scan returns
+	/// `ErrorKind::ScanPaused`, the next [`scan`](struct.Owner.html#method.scan) with the
+	/// same `start_height` resumes it.
+	///
+	/// # Returns
+	/// * `true` if the scan is asked to pause, `false` if no scan is running
+	pub fn pause_scan(&self) -> bool {
+		owner::pause_scan(self.wallet_inst.clone())
+	}
+
+	/// Cancels the running scan after the current batch of outputs, the scan returns
+	/// `ErrorKind::ScanCancelled`. If no scan is running, the progress of the interrupted
+	/// scan is dropped, so the next scan starts from the beginning.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	pub fn cancel_scan(&self, keychain_mask: Option<&SecretKey>) -> Result<(), Error> {
+		owner::cancel_scan(self.wallet_inst.clone(), keychain_mask)
+	}
+
 	/// Dump wallet data (outputs,transactions) into the logs
 	pub fn dump_wallet_data(&self, file_name: Option<String>) -> Result<(), Error> {
 		let tx = {
@@ -2253,6 +3275,55 @@ where
 		owner::get_stored_tx_proof(self.wallet_inst.clone(), tx_id)
 	}
 
+	/// Build a standalone payment proof document for a confirmed transaction.
+	/// Document includes the kernel and the block where it was mined, so it can be
+	/// verified offline with `proof::payment_proof::verify_payment_proof_document`.
+	pub fn export_payment_proof_document(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		refresh_from_node: bool,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+	) -> Result<PaymentProofDocument, Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let refresh_from_node = match self.updater_running.load(Ordering::Relaxed) {
+			true => false,
+			false => refresh_from_node,
+		};
+		owner::export_payment_proof_document(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&tx,
+			refresh_from_node,
+			tx_id,
+			tx_slate_id,
+		)
+	}
+
+	/// Verify a standalone payment proof document. Signatures are always validated, if
+	/// `check_node` is true the kernel and the block are checked against the node as well.
+	pub fn verify_payment_proof_document(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		doc: &PaymentProofDocument,
+		check_node: bool,
+	) -> Result<(), Error> {
+		if !check_node {
+			return payment_proof::verify_payment_proof_document(doc);
+		}
+		let client = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
+			w.w2n_client().clone()
+		};
+		payment_proof::verify_payment_proof_document_on_chain(doc, &client)
+	}
+
 	/// Verifies a [PaymentProof](../grin_wallet_libwallet/api_impl/types/struct.PaymentProof.html)
 	/// This process entails:
 	///
@@ -2523,7 +3594,7 @@ macro_rules! doctest_helper_setup_doc_env {
 			HTTPNodeClient::new(&wallet_config.check_node_api_http_addr, None).unwrap();
 		let mut wallet = Box::new(
 			DefaultWalletImpl::<'static, HTTPNodeClient>::new(node_client.clone()).unwrap(),
-			)
+		)
 			as Box<
 				WalletInst<
 					'static,
@@ -2531,7 +3602,7 @@ macro_rules! doctest_helper_setup_doc_env {
 					HTTPNodeClient,
 					ExtKeychain,
 				>,
-				>;
+			>;
 		let lc = wallet.lc_provider().unwrap();
 		let _ = lc.set_top_level_directory(&wallet_config.data_file_dir);
 		lc.open_wallet(None, pw, false, false, None);
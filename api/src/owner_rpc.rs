@@ -17,17 +17,21 @@
 #![allow(deprecated)]
 use uuid::Uuid;
 
+use crate::config::{FeePolicyConfig, ReceivePolicyConfig};
 use crate::core::core::Transaction;
 use crate::keychain::{Identifier, Keychain};
+use crate::libwallet::proof::payment_proof::PaymentProofDocument;
 use crate::libwallet::slate_versions::v3::TransactionV3;
 use crate::libwallet::{
-	AcctPathMapping, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient, NodeHeightResult,
-	OutputCommitMapping, Slate, TxLogEntry, VersionedSlate, WalletInfo, WalletLCProvider,
+	AcctPathMapping, Contact, ErrorKind, ExpiredTx, FeeEstimate, InitTxArgs, Invoice,
+	IssueInvoiceTxArgs, NodeClient, NodeHeightResult, OutputCommitMapping, OutputData,
+	PendingSlate, ScanStatus, Slate, SpendPolicyArgs, SpendPolicyStatus, TransferArgs,
+	TransferResult, TxLogEntry, VersionedSlate, WalletInfo, WalletLCProvider,
 };
 use crate::types::TxLogEntryAPI;
 use crate::util;
 use crate::util::secp::pedersen;
-use crate::util::Mutex;
+use crate::util::{Mutex, ZeroingString};
 use crate::{Owner, OwnerRpcS};
 use easy_jsonrpc_mw;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
@@ -1392,6 +1396,921 @@ pub trait OwnerRpc: Sync + Send {
 	```
 	 */
 	fn node_height(&self) -> Result<NodeHeightResult, ErrorKind>;
+
+	/**
+	Networked version of [Owner::export_payment_proof_document](struct.Owner.html#method.export_payment_proof_document).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "export_payment_proof_document",
+		"params": [false, null, null],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"PaymentProofRetrieval": "Transaction ID or Slate UUID must be specified"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn export_payment_proof_document(
+		&self,
+		refresh_from_node: bool,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+	) -> Result<PaymentProofDocument, ErrorKind>;
+
+	/**
+	Networked version of [Owner::verify_payment_proof_document](struct.Owner.html#method.verify_payment_proof_document).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "verify_payment_proof_document",
+		"params": [
+			{
+				"proof_type": "mwc_payment_proof",
+				"version": 2,
+				"amount": "2000000000",
+				"kernel": {
+					"features": "Plain",
+					"fee": "8000000",
+					"lock_height": "0",
+					"excess": "08b3b8b83c622f630141a66c9cad96e19c78f745e4e2ddea85439f05d14a404640",
+					"excess_sig": "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+				},
+				"sender_address": "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5",
+				"sender_sig": "3045022100945b57de1e8b9f7863c4f4c5698d5617ffa55748c80a8324729f98ce5ef86509022063f6bc511d80046f6f21c9476344ed8d948234cc32a0b022d720161798e09861",
+				"recipient_address": "xmgceW7Z2phenRwaBeKvTRZkPMJarwLFa8h5LW5bdHKucaKTeuE2",
+				"recipient_sig": "30440220050ccd7244a8e1bcad8724a26bef6e0bc3df85f09dfc41870635711627955c4c02202b3d3599a7371bcc685315876c54cdf956a8c990ce6526f6be8e50591bde3be2",
+				"block_height": "8",
+				"block_hash": "0000000000000000000000000000000000000000000000000000000000000000"
+			},
+			false
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"PaymentProofParsing": "Unsupported payment proof document version 2"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn verify_payment_proof_document(
+		&self,
+		doc: PaymentProofDocument,
+		check_node: bool,
+	) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_invoices](struct.Owner.html#method.retrieve_invoices).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_invoices",
+		"params": [false, null, null],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				false,
+				[]
+			]
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn retrieve_invoices(
+		&self,
+		refresh_from_node: bool,
+		invoice_id: Option<Uuid>,
+		reference: Option<String>,
+	) -> Result<(bool, Vec<Invoice>), ErrorKind>;
+
+	/**
+	Networked version of [Owner::cancel_invoice](struct.Owner.html#method.cancel_invoice).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "cancel_invoice",
+		"params": ["0436430c-2b02-624c-2032-570501212b00"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"InvoiceError": "Invoice 0436430c-2b02-624c-2032-570501212b00 not found"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn cancel_invoice(&self, invoice_id: Uuid) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_receive_policy](struct.Owner.html#method.set_receive_policy).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_receive_policy",
+		"params": [
+			{
+				"min_amount": 1000000000,
+				"max_pending_receives": 10,
+				"sender_rate_limit": 5,
+				"sender_rate_period_secs": 3600,
+				"allowed_senders": null,
+				"denied_senders": ["anonymous"],
+				"require_payment_proof": false,
+				"manual_approval": false,
+				"approval_timeout_secs": 3600,
+				"max_pending_approvals": 100,
+				"max_pending_approvals_per_sender": 10
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn set_receive_policy(&self, policy: Option<ReceivePolicyConfig>) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_pending_slates](struct.Owner.html#method.retrieve_pending_slates).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_pending_slates",
+		"params": [null],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn retrieve_pending_slates(
+		&self,
+		slate_id: Option<Uuid>,
+	) -> Result<Vec<PendingSlate>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::approve_pending_slate](struct.Owner.html#method.approve_pending_slate).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "approve_pending_slate",
+		"params": ["0436430c-2b02-624c-2032-570501212b00"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"ApprovalQueueError": "Slate 0436430c-2b02-624c-2032-570501212b00 is not found in the approval queue"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn approve_pending_slate(&self, slate_id: Uuid) -> Result<VersionedSlate, ErrorKind>;
+
+	/**
+	Networked version of [Owner::reject_pending_slate](struct.Owner.html#method.reject_pending_slate).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "reject_pending_slate",
+		"params": ["0436430c-2b02-624c-2032-570501212b00"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"ApprovalQueueError": "Slate 0436430c-2b02-624c-2032-570501212b00 is not found in the approval queue"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn reject_pending_slate(&self, slate_id: Uuid) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_spend_policy](struct.Owner.html#method.set_spend_policy).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_spend_policy",
+		"params": [
+			"",
+			{
+				"src_acct_name": null,
+				"max_per_tx": "1000000000",
+				"max_24h": "5000000000",
+				"max_7d": null,
+				"allowed_destinations": null,
+				"new_destination_cooldown_secs": null
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn set_spend_policy(&self, password: String, args: SpendPolicyArgs) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::clear_spend_policy](struct.Owner.html#method.clear_spend_policy).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "clear_spend_policy",
+		"params": ["", "savings"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"UnknownAccountLabel": "savings"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn clear_spend_policy(
+		&self,
+		password: String,
+		acct_name: Option<String>,
+	) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_spend_policy](struct.Owner.html#method.retrieve_spend_policy).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_spend_policy",
+		"params": [null],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn retrieve_spend_policy(
+		&self,
+		acct_name: Option<String>,
+	) -> Result<Option<SpendPolicyStatus>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::scan_status](struct.Owner.html#method.scan_status).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "scan_status",
+		"params": [],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"running": false,
+				"checkpoint": null,
+				"percent_complete": 0
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn scan_status(&self) -> Result<ScanStatus, ErrorKind>;
+
+	/**
+	Networked version of [Owner::pause_scan](struct.Owner.html#method.pause_scan).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "pause_scan",
+		"params": [],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": false
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn pause_scan(&self) -> Result<bool, ErrorKind>;
+
+	/**
+	Networked version of [Owner::cancel_scan](struct.Owner.html#method.cancel_scan).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "cancel_scan",
+		"params": [],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn cancel_scan(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::freeze_output](struct.Owner.html#method.freeze_output).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "freeze_output",
+		"params": ["08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"OutputNotFound": "08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn freeze_output(&self, commit: String) -> Result<OutputData, ErrorKind>;
+
+	/**
+	Networked version of [Owner::unfreeze_output](struct.Owner.html#method.unfreeze_output).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "unfreeze_output",
+		"params": ["08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"OutputNotFound": "08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn unfreeze_output(&self, commit: String) -> Result<OutputData, ErrorKind>;
+
+	/**
+	Networked version of [Owner::label_output](struct.Owner.html#method.label_output).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "label_output",
+		"params": [
+			"08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7",
+			"cold storage",
+			["savings"]
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"OutputNotFound": "08e1da9e6dc4d6e808a718b2f110a991dd775d65ce5ae408a4e1f002a4961aa9e7"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn label_output(
+		&self,
+		commit: String,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<OutputData, ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_contacts](struct.Owner.html#method.retrieve_contacts).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_contacts",
+		"params": [],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn retrieve_contacts(&self) -> Result<Vec<Contact>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_contact](struct.Owner.html#method.get_contact).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_contact",
+		"params": ["bob"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"ContactError": "Contact @bob is not found"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn get_contact(&self, name: String) -> Result<Contact, ErrorKind>;
+
+	/**
+	Networked version of [Owner::save_contact](struct.Owner.html#method.save_contact).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "save_contact",
+		"params": [
+			{
+				"name": "@bob",
+				"address": "http://127.0.0.1:3415",
+				"method": "HTTP",
+				"slate_version": null,
+				"proof_address": null
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"name": "bob",
+				"address": "http://127.0.0.1:3415",
+				"method": "http",
+				"slate_version": null,
+				"proof_address": null
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn save_contact(&self, contact: Contact) -> Result<Contact, ErrorKind>;
+
+	/**
+	Networked version of [Owner::delete_contact](struct.Owner.html#method.delete_contact).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "delete_contact",
+		"params": ["bob"],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"ContactError": "Contact @bob is not found"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn delete_contact(&self, name: String) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::label_tx](struct.Owner.html#method.label_tx).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "label_tx",
+		"params": [
+			1,
+			null,
+			"rent",
+			["home"]
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"TransactionDoesntExist": "1"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn label_tx(
+		&self,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<TxLogEntryAPI, ErrorKind>;
+
+	/**
+	Networked version of [Owner::transfer](struct.Owner.html#method.transfer).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "transfer",
+		"params": [
+			{
+				"src_acct_name": null,
+				"dest_acct_name": "savings",
+				"amount": "1000000000",
+				"minimum_confirmations": 0
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"ClientCallback": "Minimum_confirmations can not be smaller than 1"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn transfer(&self, args: TransferArgs) -> Result<TransferResult, ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_expired_txs](struct.Owner.html#method.retrieve_expired_txs).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_expired_txs",
+		"params": [],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn retrieve_expired_txs(&self) -> Result<Vec<ExpiredTx>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_auto_cancel_expired](struct.Owner.html#method.set_auto_cancel_expired).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_auto_cancel_expired",
+		"params": [false],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn set_auto_cancel_expired(&self, enabled: bool) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::estimate_fee](struct.Owner.html#method.estimate_fee).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "estimate_fee",
+		"params": [
+			{
+				"amount": "200000000"
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"NotEnoughFunds": {
+					"available": 0,
+					"available_disp": "0.0",
+					"needed": 209000000,
+					"needed_disp": "0.209"
+				}
+			}
+		}
+	}
+	# "#
+	# , false, 4, false, false, false, false);
+	```
+	*/
+	fn estimate_fee(&self, args: InitTxArgs) -> Result<FeeEstimate, ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_fee_policy](struct.Owner.html#method.set_fee_policy).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_fee_policy",
+		"params": [
+			{
+				"fee_per_kweight": 2000000,
+				"max_fee": 100000000,
+				"max_fee_percent": 5
+			}
+		],
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false);
+	```
+	*/
+	fn set_fee_policy(&self, policy: Option<FeePolicyConfig>) -> Result<(), ErrorKind>;
 }
 
 impl<'a, L, C, K> OwnerRpc for Owner<L, C, K>
@@ -1550,6 +2469,157 @@ where
 	fn node_height(&self) -> Result<NodeHeightResult, ErrorKind> {
 		Owner::node_height(self, None).map_err(|e| e.kind())
 	}
+
+	fn export_payment_proof_document(
+		&self,
+		refresh_from_node: bool,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+	) -> Result<PaymentProofDocument, ErrorKind> {
+		Owner::export_payment_proof_document(self, None, refresh_from_node, tx_id, tx_slate_id)
+			.map_err(|e| e.kind())
+	}
+
+	fn verify_payment_proof_document(
+		&self,
+		doc: PaymentProofDocument,
+		check_node: bool,
+	) -> Result<(), ErrorKind> {
+		Owner::verify_payment_proof_document(self, None, &doc, check_node).map_err(|e| e.kind())
+	}
+
+	fn retrieve_invoices(
+		&self,
+		refresh_from_node: bool,
+		invoice_id: Option<Uuid>,
+		reference: Option<String>,
+	) -> Result<(bool, Vec<Invoice>), ErrorKind> {
+		Owner::retrieve_invoices(self, None, refresh_from_node, invoice_id, reference)
+			.map_err(|e| e.kind())
+	}
+
+	fn cancel_invoice(&self, invoice_id: Uuid) -> Result<(), ErrorKind> {
+		Owner::cancel_invoice(self, None, invoice_id).map_err(|e| e.kind())
+	}
+
+	fn set_receive_policy(&self, policy: Option<ReceivePolicyConfig>) -> Result<(), ErrorKind> {
+		Owner::set_receive_policy(self, policy).map_err(|e| e.kind())
+	}
+
+	fn retrieve_pending_slates(
+		&self,
+		slate_id: Option<Uuid>,
+	) -> Result<Vec<PendingSlate>, ErrorKind> {
+		Owner::retrieve_pending_slates(self, None, slate_id).map_err(|e| e.kind())
+	}
+
+	fn approve_pending_slate(&self, slate_id: Uuid) -> Result<VersionedSlate, ErrorKind> {
+		let slate = Owner::approve_pending_slate(self, None, slate_id).map_err(|e| e.kind())?;
+		let version = slate.lowest_version();
+		Ok(VersionedSlate::into_version(slate, version))
+	}
+
+	fn reject_pending_slate(&self, slate_id: Uuid) -> Result<(), ErrorKind> {
+		Owner::reject_pending_slate(self, None, slate_id).map_err(|e| e.kind())
+	}
+
+	fn set_spend_policy(&self, password: String, args: SpendPolicyArgs) -> Result<(), ErrorKind> {
+		Owner::set_spend_policy(self, None, ZeroingString::from(password), None, &args)
+			.map_err(|e| e.kind())
+	}
+
+	fn clear_spend_policy(
+		&self,
+		password: String,
+		acct_name: Option<String>,
+	) -> Result<(), ErrorKind> {
+		Owner::clear_spend_policy(self, None, ZeroingString::from(password), None, acct_name)
+			.map_err(|e| e.kind())
+	}
+
+	fn retrieve_spend_policy(
+		&self,
+		acct_name: Option<String>,
+	) -> Result<Option<SpendPolicyStatus>, ErrorKind> {
+		Owner::retrieve_spend_policy(self, None, acct_name).map_err(|e| e.kind())
+	}
+
+	fn scan_status(&self) -> Result<ScanStatus, ErrorKind> {
+		Owner::scan_status(self, None).map_err(|e| e.kind())
+	}
+
+	fn pause_scan(&self) -> Result<bool, ErrorKind> {
+		Ok(Owner::pause_scan(self))
+	}
+
+	fn cancel_scan(&self) -> Result<(), ErrorKind> {
+		Owner::cancel_scan(self, None).map_err(|e| e.kind())
+	}
+
+	fn freeze_output(&self, commit: String) -> Result<OutputData, ErrorKind> {
+		Owner::freeze_output(self, None, &commit).map_err(|e| e.kind())
+	}
+
+	fn unfreeze_output(&self, commit: String) -> Result<OutputData, ErrorKind> {
+		Owner::unfreeze_output(self, None, &commit).map_err(|e| e.kind())
+	}
+
+	fn label_output(
+		&self,
+		commit: String,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<OutputData, ErrorKind> {
+		Owner::label_output(self, None, &commit, note, tags).map_err(|e| e.kind())
+	}
+
+	fn retrieve_contacts(&self) -> Result<Vec<Contact>, ErrorKind> {
+		Owner::retrieve_contacts(self, None).map_err(|e| e.kind())
+	}
+
+	fn get_contact(&self, name: String) -> Result<Contact, ErrorKind> {
+		Owner::get_contact(self, None, &name).map_err(|e| e.kind())
+	}
+
+	fn save_contact(&self, contact: Contact) -> Result<Contact, ErrorKind> {
+		Owner::save_contact(self, None, contact).map_err(|e| e.kind())
+	}
+
+	fn delete_contact(&self, name: String) -> Result<(), ErrorKind> {
+		Owner::delete_contact(self, None, &name).map_err(|e| e.kind())
+	}
+
+	fn label_tx(
+		&self,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
+		note: Option<String>,
+		tags: Vec<String>,
+	) -> Result<TxLogEntryAPI, ErrorKind> {
+		Owner::label_tx(self, None, tx_id, tx_slate_id, note, tags)
+			.map(|tx| TxLogEntryAPI::from_txlogemtry(&tx))
+			.map_err(|e| e.kind())
+	}
+
+	fn transfer(&self, args: TransferArgs) -> Result<TransferResult, ErrorKind> {
+		Owner::transfer(self, None, args).map_err(|e| e.kind())
+	}
+
+	fn retrieve_expired_txs(&self) -> Result<Vec<ExpiredTx>, ErrorKind> {
+		Owner::retrieve_expired_txs(self, None).map_err(|e| e.kind())
+	}
+
+	fn set_auto_cancel_expired(&self, enabled: bool) -> Result<(), ErrorKind> {
+		Owner::set_auto_cancel_expired(self, enabled).map_err(|e| e.kind())
+	}
+
+	fn estimate_fee(&self, args: InitTxArgs) -> Result<FeeEstimate, ErrorKind> {
+		Owner::estimate_fee(self, None, args).map_err(|e| e.kind())
+	}
+
+	fn set_fee_policy(&self, policy: Option<FeePolicyConfig>) -> Result<(), ErrorKind> {
+		Owner::set_fee_policy(self, policy).map_err(|e| e.kind())
+	}
 }
 
 /// helper to set up a real environment to run integrated doctests
@@ -1817,7 +2887,7 @@ macro_rules! doctest_helper_json_rpc_owner_assert_response {
 		// disable for now on windows
 		// TODO: Fix properly
 		#[cfg(not(target_os = "windows"))]
-			{
+		{
 			use grin_wallet_api::run_doctest_owner;
 			use serde_json;
 			use serde_json::Value;
@@ -1842,7 +2912,7 @@ macro_rules! doctest_helper_json_rpc_owner_assert_response {
 				$lock_tx,
 				$finalize_tx,
 				$payment_proof,
-				)
+			)
 			.unwrap()
 			.unwrap();
 
@@ -1852,7 +2922,7 @@ macro_rules! doctest_helper_json_rpc_owner_assert_response {
 					serde_json::to_string_pretty(&response).unwrap(),
 					serde_json::to_string_pretty(&expected_response).unwrap()
 				);
-				}
 			}
+		}
 	};
 }
@@ -86,6 +86,14 @@ fn comments() -> HashMap<String, String> {
 #include the foreign API endpoints on the same port as the owner
 #API. Useful for networking environments like AWS ECS that make
 #it difficult to access multiple ports on a single service.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"owner_api_metrics".to_string(),
+		"
+#serve the wallet metrics in the Prometheus text format at /metrics
+#on the owner API port. The endpoint uses the owner API authentication.
 "
 		.to_string(),
 	);
@@ -109,6 +117,71 @@ fn comments() -> HashMap<String, String> {
 		"dark_background_color_scheme".to_string(),
 		"
 #Whether to use the black background color scheme for command line
+"
+		.to_string(),
+	);
+	retval.insert(
+		"verify_node_headers".to_string(),
+		"
+#Verify the block headers (proof of work and difficulty) reported by the node and reject
+#outputs and block hashes that don't belong to the verified header chain.
+#Without a checkpoint the first sync trusts the header one day below the node tip.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"header_checkpoint_height".to_string(),
+		"
+#Height of a known header where the verified header chain is anchored.
+#The sync from the checkpoint fetches all headers above it, keep it recent.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"header_checkpoint_hash".to_string(),
+		"
+#Hash of the header at the checkpoint height
+"
+		.to_string(),
+	);
+	retval.insert(
+		"scan_threads".to_string(),
+		"
+#Number of threads that check the chain outputs during the scan. The next batch of
+#outputs is downloaded from the node while the current one is checked.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"auto_cancel_expired".to_string(),
+		"
+#Cancel the unconfirmed transactions when the chain passes their TTL cutoff height.
+#Locked inputs are released, unconfirmed outputs and the slate contexts are deleted.
+#Run the 'expired_txs' command to see the transactions that the next update cancels.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"minimum_confirmations".to_string(),
+		"
+#Minimum confirmations of the balance that the owner API updater publishes to the
+#event stream subscribers
+"
+		.to_string(),
+	);
+	retval.insert(
+		"api_call_threads".to_string(),
+		"
+#Maximum number of the owner and foreign API calls that run at the same time.
+#Other calls wait for a free thread.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"api_call_timeout_secs".to_string(),
+		"
+#Time in seconds for an API call to wait for a thread and run. A call that times out
+#is not interrupted, it keeps its thread until it finishes.
 "
 		.to_string(),
 	);
@@ -262,6 +335,249 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"mwcmqs_use_tls".to_string(),
+		"
+#connect to the mqs server with https. Set to false for a self hosted
+#broker (mwcmqs-broker) that runs on plain http
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"mwcmqs_compact_slates".to_string(),
+		"
+#send slates in the compact binary encoding. Receiver wallet must support it, older wallets understand json only
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[receive_policy]".to_string(),
+		"
+#########################################
+### RECEIVE POLICY CONFIGURATION      ###
+#########################################
+
+#Rules applied to the slates received by the listeners. Rejected slates are logged
+#and the sender gets the violated rule back as an error.
+#Sender can be a payment proof address, mwcmqs address or tor onion address.
+
+#minimum amount in nanoMWC to accept
+#min_amount = 1000000000
+
+#maximum number of received transactions that are not confirmed yet
+#max_pending_receives = 100
+
+#maximum number of slates accepted from the same sender during sender_rate_period_secs.
+#Slates without the sender address and the payment proof share one limit
+#sender_rate_limit = 10
+
+#if set, only slates from these senders are accepted. Slates without the sender address
+#and the payment proof are rejected then
+#allowed_senders = [\"<sender address>\"]
+
+#slates from these senders are rejected, \"anonymous\" rejects the slates without the
+#sender address and the payment proof
+#denied_senders = []
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"sender_rate_period_secs".to_string(),
+		"
+#period in seconds for sender_rate_limit
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"require_payment_proof".to_string(),
+		"
+#reject slates that don't request a payment proof
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"manual_approval".to_string(),
+		"
+#park the incoming slates until they are approved or rejected by the wallet owner.
+#The http and tor senders get the 'pending approval' error and should retry later,
+#mwcmqs senders get the reply when the slate is approved
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"approval_timeout_secs".to_string(),
+		"
+#time in seconds after which the parked slates expire
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"max_pending_approvals".to_string(),
+		"
+#maximum number of slates waiting for the approval, the new slates are rejected
+#when the queue is full
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"max_pending_approvals_per_sender".to_string(),
+		"
+#maximum number of slates from the same sender waiting for the approval
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[node_failover]".to_string(),
+		"
+#########################################
+### NODE FAILOVER CONFIGURATION       ###
+#########################################
+
+#Additional nodes the wallet switches to when check_node_api_http_addr is down or
+#behind. All nodes use the same node api secret. Example:
+#[[node_failover.nodes]]
+#url = \"http://127.0.0.1:3413\"
+#priority = 1
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"max_blocks_behind".to_string(),
+		"
+#nodes that are more blocks behind the best known node are not used
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"health_check_interval_secs".to_string(),
+		"
+#time in seconds between the node health checks
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"broadcast_post_tx".to_string(),
+		"
+#post the transactions to all healthy nodes
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[rebroadcast]".to_string(),
+		"
+#########################################
+### REBROADCAST CONFIGURATION         ###
+#########################################
+
+#The updater re-posts the posted transactions which kernels are not on the chain,
+#for example because the node was restarted and lost its mempool.
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"enabled".to_string(),
+		"
+#re-post the unconfirmed transactions
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"interval_secs".to_string(),
+		"
+#time in seconds after the post before the first rebroadcast
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"backoff_factor".to_string(),
+		"
+#every next rebroadcast waits this many times longer than the previous one
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"max_retries".to_string(),
+		"
+#number of rebroadcasts before the wallet gives up on the transaction
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"cancel_after_retries".to_string(),
+		"
+#cancel the transaction and release its inputs when the retries are exhausted. If false,
+#the transaction stays unconfirmed until it is cancelled or expires
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[fee_policy]".to_string(),
+		"
+#########################################
+### FEE POLICY CONFIGURATION          ###
+#########################################
+
+#Fee of the transactions that this wallet sends. The transaction weight counts 4 units
+#for an output, 1 for a kernel and -1 for an input, the fee is the weight times the base fee.
+#fee_per_kweight - fee in nanoMWC per 1000 weight units. The network default is used if it
+#is not set. A lower rate is raised to the default, nodes reject the transactions that pay less.
+#max_fee - transactions with a larger fee in nanoMWC are rejected
+#max_fee_percent - transactions with a fee larger than this percent of the amount are rejected
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[owner_api]".to_string(),
+		"
+#########################################
+### OWNER API ACCESS CONFIGURATION    ###
+#########################################
+
+#Named keys for the owner API listener. A client authenticates with basic auth,
+#key id as the user name and key secret as the password. The owner API secret
+#(user name 'mwc') stays an admin key.
+#Permissions: read, receive (invoices), spend (transactions, the active account
+#and the updater), admin (everything, including the mnemonic and the wallet
+#open/close). spend_limit is the maximum amount in nanoMWC of a single transaction,
+#for a batch payout it is the total of the batch.
+#[[owner_api.keys]]
+#id = \"exchange\"
+#secret = \"<long random secret>\"
+#permission = \"spend\"
+#spend_limit = 10000000000
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"audit_log_path".to_string(),
+		"
+#every call above the read permission and every denied call is appended to this file.
+#Relative path is resolved from the wallet data directory
+"
+		.to_string(),
+	);
+
 	retval
 }
 
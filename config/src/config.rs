@@ -28,7 +28,10 @@ use toml;
 use crate::comments::insert_comments;
 use crate::core::global;
 use crate::types::{ConfigError, GlobalWalletConfig, GlobalWalletConfigMembers};
-use crate::types::{MQSConfig, TorConfig, WalletConfig};
+use crate::types::{
+	FeePolicyConfig, MQSConfig, NodeFailoverConfig, OwnerApiConfig, RebroadcastConfig,
+	ReceivePolicyConfig, TorConfig, WalletConfig,
+};
 use crate::util::logger::LoggingConfig;
 
 /// Wallet configuration file name
@@ -191,6 +194,11 @@ impl Default for GlobalWalletConfigMembers {
 			logging: Some(LoggingConfig::default()),
 			tor: Some(TorConfig::default()),
 			mqs: Some(MQSConfig::default()),
+			receive_policy: Some(ReceivePolicyConfig::default()),
+			owner_api: Some(OwnerApiConfig::default()),
+			node_failover: Some(NodeFailoverConfig::default()),
+			rebroadcast: Some(RebroadcastConfig::default()),
+			fee_policy: Some(FeePolicyConfig::default()),
 			wallet: WalletConfig::default(),
 		}
 	}
@@ -46,6 +46,8 @@ pub struct WalletConfig {
 	pub owner_api_include_foreign: Option<bool>,
 	/// Whether to include the mwcmqs listener
 	pub owner_api_include_mqs_listener: Option<bool>,
+	/// Whether to serve the Prometheus metrics at /metrics on the Owner API
+	pub owner_api_metrics: Option<bool>,
 	///Index used to derive address
 	pub grinbox_address_index: Option<u32>,
 	/// The directory in which wallet files are stored
@@ -62,6 +64,23 @@ pub struct WalletConfig {
 	pub dark_background_color_scheme: Option<bool>,
 	/// Wallet data directory. Default none is 'wallet_data'
 	pub wallet_data_dir: Option<String>,
+	/// If Some(true), sync a verified header chain and check the node data against it
+	/// before the outputs are confirmed
+	pub verify_node_headers: Option<bool>,
+	/// Height of the header where the verified header chain is anchored
+	pub header_checkpoint_height: Option<u64>,
+	/// Hash of the header at the checkpoint height
+	pub header_checkpoint_hash: Option<String>,
+	/// Number of threads that rewind the range proofs during the chain scan
+	pub scan_threads: Option<usize>,
+	/// If Some(true), the wallet update cancels the unconfirmed transactions with expired TTL
+	pub auto_cancel_expired: Option<bool>,
+	/// Minimum confirmations of the balance that the owner API updater publishes
+	pub minimum_confirmations: Option<u64>,
+	/// Maximum number of the API calls that run at the same time
+	pub api_call_threads: Option<usize>,
+	/// Time in seconds for an API call to wait for a thread and run
+	pub api_call_timeout_secs: Option<u64>,
 	/// Electrum nodes for secondary coins
 	/// Key: <coin>_[main|test]_[1|2]
 	/// Value: url
@@ -80,6 +99,7 @@ impl Default for WalletConfig {
 			check_node_api_http_addr: "http://127.0.0.1:3413".to_string(),
 			owner_api_include_foreign: Some(false),
 			owner_api_include_mqs_listener: Some(false),
+			owner_api_metrics: Some(false),
 			data_file_dir: ".".to_string(),
 			grinbox_address_index: None,
 			no_commit_cache: Some(false),
@@ -87,6 +107,14 @@ impl Default for WalletConfig {
 			tls_certificate_key: None,
 			dark_background_color_scheme: Some(true),
 			wallet_data_dir: None,
+			verify_node_headers: Some(false),
+			header_checkpoint_height: None,
+			header_checkpoint_hash: None,
+			scan_threads: Some(4),
+			auto_cancel_expired: Some(true),
+			minimum_confirmations: Some(10),
+			api_call_threads: Some(8),
+			api_call_timeout_secs: Some(900),
 			swap_electrumx_addr: Some(
 				[
 					("btc_main_1", "btc.main1.swap.mwc.mw:18337"),
@@ -199,6 +227,10 @@ pub struct MQSConfig {
 	pub mwcmqs_domain: String,
 	/// mwcmqs port
 	pub mwcmqs_port: u16,
+	/// Connect to the server with https. Self hosted brokers can run on plain http
+	pub mwcmqs_use_tls: Option<bool>,
+	/// Send slates in the compact binary encoding. Receiver wallets must support it
+	pub mwcmqs_compact_slates: Option<bool>,
 }
 
 impl Default for MQSConfig {
@@ -206,6 +238,182 @@ impl Default for MQSConfig {
 		MQSConfig {
 			mwcmqs_domain: "mqs.mwc.mw".to_owned(),
 			mwcmqs_port: 443,
+			mwcmqs_use_tls: Some(true),
+			mwcmqs_compact_slates: Some(false),
+		}
+	}
+}
+
+/// Rules the wallet applies to the slates received by the foreign API listeners
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReceivePolicyConfig {
+	/// Minimum amount (in nanoMWC) the wallet accepts
+	pub min_amount: Option<u64>,
+	/// Maximum number of received and not yet confirmed transactions
+	pub max_pending_receives: Option<u32>,
+	/// Maximum number of slates accepted from the same sender during the rate period
+	pub sender_rate_limit: Option<u32>,
+	/// Rate limit period in seconds
+	pub sender_rate_period_secs: Option<u64>,
+	/// If set, only the slates from these senders (proof address, mwcmqs address or onion) are accepted.
+	/// The slates without the sender address and the payment proof are rejected then.
+	pub allowed_senders: Option<Vec<String>>,
+	/// Slates from these senders (proof address, mwcmqs address or onion) are rejected.
+	/// `anonymous` rejects the slates without the sender address and the payment proof.
+	pub denied_senders: Option<Vec<String>>,
+	/// Reject the slates without a payment proof request
+	pub require_payment_proof: Option<bool>,
+	/// Park the incoming slates until the wallet owner approves or rejects them
+	pub manual_approval: Option<bool>,
+	/// Time in seconds after which the parked slates expire
+	pub approval_timeout_secs: Option<u64>,
+	/// Maximum number of slates waiting for the approval
+	pub max_pending_approvals: Option<u32>,
+	/// Maximum number of slates from the same sender waiting for the approval
+	pub max_pending_approvals_per_sender: Option<u32>,
+}
+
+impl Default for ReceivePolicyConfig {
+	fn default() -> ReceivePolicyConfig {
+		ReceivePolicyConfig {
+			min_amount: None,
+			max_pending_receives: None,
+			sender_rate_limit: None,
+			sender_rate_period_secs: Some(3600),
+			allowed_senders: None,
+			denied_senders: None,
+			require_payment_proof: Some(false),
+			manual_approval: Some(false),
+			approval_timeout_secs: Some(3600),
+			max_pending_approvals: Some(100),
+			max_pending_approvals_per_sender: Some(10),
+		}
+	}
+}
+
+/// Additional node for the node client failover
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NodeEndpointConfig {
+	/// Node foreign API address
+	pub url: String,
+	/// Nodes with the lower value are preferred. check_node_api_http_addr has priority 0.
+	pub priority: Option<u32>,
+}
+
+/// Node client failover across several nodes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NodeFailoverConfig {
+	/// Nodes that are behind the best known height by more blocks are not used
+	pub max_blocks_behind: Option<u64>,
+	/// Time in seconds between the node health checks
+	pub health_check_interval_secs: Option<u64>,
+	/// Post the transactions to all healthy nodes, not only to the active one
+	pub broadcast_post_tx: Option<bool>,
+	/// Additional nodes. If not set, the wallet uses check_node_api_http_addr only.
+	pub nodes: Option<Vec<NodeEndpointConfig>>,
+}
+
+impl Default for NodeFailoverConfig {
+	fn default() -> NodeFailoverConfig {
+		NodeFailoverConfig {
+			max_blocks_behind: Some(3),
+			health_check_interval_secs: Some(60),
+			broadcast_post_tx: Some(false),
+			nodes: None,
+		}
+	}
+}
+
+/// Rebroadcast of the posted transactions that didn't make it to the chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RebroadcastConfig {
+	/// Re-post the transactions which kernels are not found on the chain
+	pub enabled: Option<bool>,
+	/// Time in seconds after the post before the first rebroadcast
+	pub interval_secs: Option<u64>,
+	/// Every next rebroadcast waits this many times longer than the previous one
+	pub backoff_factor: Option<u32>,
+	/// Number of rebroadcasts before the wallet gives up on the transaction
+	pub max_retries: Option<u32>,
+	/// Cancel the transaction when the retries are exhausted
+	pub cancel_after_retries: Option<bool>,
+}
+
+impl Default for RebroadcastConfig {
+	fn default() -> RebroadcastConfig {
+		RebroadcastConfig {
+			enabled: Some(true),
+			interval_secs: Some(600),
+			backoff_factor: Some(2),
+			max_retries: Some(5),
+			cancel_after_retries: Some(true),
+		}
+	}
+}
+
+/// Fee of the transactions that the wallet builds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeePolicyConfig {
+	/// Fee in nanoMWC per 1000 units of the transaction weight, None for the network default
+	pub fee_per_kweight: Option<u64>,
+	/// Largest fee in nanoMWC that a single transaction can pay
+	pub max_fee: Option<u64>,
+	/// Largest fee as a percent of the sent amount
+	pub max_fee_percent: Option<u64>,
+}
+
+impl Default for FeePolicyConfig {
+	fn default() -> FeePolicyConfig {
+		FeePolicyConfig {
+			fee_per_kweight: None,
+			max_fee: None,
+			max_fee_percent: None,
+		}
+	}
+}
+
+/// Permission level of an owner API key. Every level includes the levels below it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnerApiPermission {
+	/// Balances, outputs, transactions and other read only calls
+	Read,
+	/// Issue and cancel invoices, approve and reject the parked slates
+	Receive,
+	/// Send, finalize, post and cancel transactions, switch the active account, run the
+	/// updater, and manage the frozen outputs, labels and contacts
+	Spend,
+	/// Everything, including the mnemonic, password and wallet lifecycle calls
+	Admin,
+}
+
+/// Named key for the owner API listener
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OwnerApiKey {
+	/// Key id, used as the basic auth user name and in the audit log
+	pub id: String,
+	/// Key secret, used as the basic auth password
+	pub secret: String,
+	/// Calls allowed with this key
+	pub permission: OwnerApiPermission,
+	/// Maximum amount (in nanoMWC) of a single transaction sent with this key
+	pub spend_limit: Option<u64>,
+}
+
+/// Access control of the owner API listener
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OwnerApiConfig {
+	/// Audit log of the privileged calls. Relative path is resolved from the wallet data dir.
+	pub audit_log_path: Option<String>,
+	/// API keys. If not set, the owner API secret is the only key.
+	pub keys: Option<Vec<OwnerApiKey>>,
+}
+
+impl Default for OwnerApiConfig {
+	fn default() -> OwnerApiConfig {
+		OwnerApiConfig {
+			audit_log_path: Some("owner_api_audit.log".to_owned()),
+			keys: None,
 		}
 	}
 }
@@ -229,6 +437,21 @@ pub struct GlobalWalletConfigMembers {
 	pub tor: Option<TorConfig>,
 	/// MQS config
 	pub mqs: Option<MQSConfig>,
+	/// Receive policy config
+	#[serde(default)]
+	pub receive_policy: Option<ReceivePolicyConfig>,
+	/// Owner API access control config
+	#[serde(default)]
+	pub owner_api: Option<OwnerApiConfig>,
+	/// Node client failover config
+	#[serde(default)]
+	pub node_failover: Option<NodeFailoverConfig>,
+	/// Rebroadcast of the unconfirmed transactions config
+	#[serde(default)]
+	pub rebroadcast: Option<RebroadcastConfig>,
+	/// Transaction fee config
+	#[serde(default)]
+	pub fee_policy: Option<FeePolicyConfig>,
 	/// Logging config
 	pub logging: Option<LoggingConfig>,
 }
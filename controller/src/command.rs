@@ -20,9 +20,13 @@ use crate::config::{MQSConfig, TorConfig, WalletConfig, WALLET_CONFIG_FILE_NAME}
 use crate::core::{core, global};
 use crate::error::{Error, ErrorKind};
 use crate::impls::{create_sender, SlateGetter as _};
-use crate::impls::{PathToSlate, SlatePutter};
+use crate::impls::{CompactPathToSlate, PathToSlate, SlatePutter};
 use crate::keychain;
-use crate::libwallet::{InitTxArgs, IssueInvoiceTxArgs, NodeClient, WalletLCProvider};
+use crate::libwallet::{
+	BatchSendArgs, BatchSendEntry, ColdSignRequest, ColdSignResponse, ColdSignStep, Contact,
+	FeeEstimate, InitTxArgs, IssueInvoiceTxArgs, NodeClient, OutputData, SpendPolicyArgs,
+	TransferArgs, WalletLCProvider,
+};
 use crate::util::secp::key::SecretKey;
 use crate::util::{Mutex, ZeroingString};
 use crate::{controller, display};
@@ -30,7 +34,8 @@ use chrono::Utc;
 use grin_wallet_impls::adapters::{create_swap_message_sender, validate_tor_address};
 use grin_wallet_impls::{Address, MWCMQSAddress, Publisher};
 use grin_wallet_libwallet::api_impl::owner_swap;
-use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
+use grin_wallet_libwallet::proof::payment_proof::PaymentProofDocument;
+use grin_wallet_libwallet::proof::proofaddress::{self, ProvableAddress};
 use grin_wallet_libwallet::proof::tx_proof::TxProof;
 use grin_wallet_libwallet::swap::message;
 use grin_wallet_libwallet::swap::trades;
@@ -240,6 +245,7 @@ where
 		g_args.api_secret.clone(),
 		g_args.tls_conf.clone(),
 		config.owner_api_include_foreign.clone(),
+		config.owner_api_metrics.clone(),
 		Some(tor_config.clone()),
 	)
 	.map_err(|e| ErrorKind::LibWallet(format!("Unable to start Listener, {}", e)))?;
@@ -308,10 +314,38 @@ pub struct SendArgs {
 	pub target_slate_version: Option<u16>,
 	pub payment_proof_address: Option<ProvableAddress>,
 	pub ttl_blocks: Option<u64>,
+	pub lock_height: Option<u64>,
 	pub exclude_change_outputs: bool,
 	pub minimum_confirmations_change_outputs: u64,
 	pub address: Option<String>,      //this is only for file proof.
 	pub outputs: Option<Vec<String>>, // Outputs to use. If None, all outputs can be used
+	pub compact_slate: bool,          // Write the file slate in the compact binary encoding
+	pub contact: Option<String>,      // Address book contact to send to, replaces method and dest
+	pub request_payment_proof: bool,
+}
+
+// Destination, method, slate version and proof address of the send come from the contact
+fn resolve_contact(args: &mut SendArgs, contact: Contact) -> Result<(), Error> {
+	args.method = contact.method;
+	args.dest = contact.address;
+	if args.target_slate_version.is_none() {
+		args.target_slate_version = contact.slate_version;
+	}
+	if args.request_payment_proof && args.payment_proof_address.is_none() {
+		let name = contact.name;
+		let proof_address = contact.proof_address.unwrap_or(args.dest.clone());
+		let proof_address = ProvableAddress::from_str(&proofaddress::address_to_pubkey(
+			proof_address,
+		))
+		.map_err(|e| {
+			ErrorKind::ArgumentError(format!(
+				"Unable to get the payment proof address of @{}, {}",
+				name, e
+			))
+		})?;
+		args.payment_proof_address = Some(proof_address);
+	}
+	Ok(())
 }
 
 pub fn send<L, C, K>(
@@ -328,10 +362,15 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
+	let mut args = args;
+	if let Some(name) = args.contact.clone() {
+		let contact = owner_api.get_contact(keychain_mask, &name)?;
+		resolve_contact(&mut args, contact)?;
+	}
 	let wallet_inst = owner_api.wallet_inst.clone();
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		if args.estimate_selection_strategies {
-			let mut strategies: Vec<(&str, u64, u64)> = Vec::new();
+			let mut strategies: Vec<(&str, FeeEstimate)> = Vec::new();
 			for strategy in vec!["smallest", "all"] {
 				let init_args = InitTxArgs {
 					src_acct_name: None,
@@ -340,15 +379,13 @@ where
 					max_outputs: args.max_outputs as u32,
 					num_change_outputs: args.change_outputs as u32,
 					selection_strategy_is_use_all: strategy == "all",
-					estimate_only: Some(true),
 					exclude_change_outputs: Some(args.exclude_change_outputs),
 					minimum_confirmations_change_outputs: args.minimum_confirmations_change_outputs,
 					address: args.address.clone(),
 					outputs: args.outputs.clone(),
 					..Default::default()
 				};
-				let slate = api.init_send_tx(m, init_args, 1)?;
-				strategies.push((strategy, slate.amount, slate.fee));
+				strategies.push((strategy, api.estimate_fee(m, init_args)?));
 			}
 			display::estimate(args.amount, strategies, dark_scheme);
 		} else {
@@ -364,6 +401,7 @@ where
 				payment_proof_recipient_address: args.payment_proof_address.clone(),
 				address: args.address.clone(),
 				ttl_blocks: args.ttl_blocks,
+				lock_height: args.lock_height,
 				send_args: None,
 				exclude_change_outputs: Some(args.exclude_change_outputs),
 				minimum_confirmations_change_outputs: args.minimum_confirmations_change_outputs,
@@ -426,14 +464,13 @@ where
 
 			match args.method.as_str() {
 				"file" => {
-					PathToSlate((&args.dest).into())
-						.put_tx(&slate)
-						.map_err(|e| {
-							ErrorKind::IO(format!(
-								"Unable to store the file at {}, {}",
-								args.dest, e
-							))
-						})?;
+					let slate_putter: Box<dyn SlatePutter> = match args.compact_slate {
+						true => Box::new(CompactPathToSlate((&args.dest).into())),
+						false => Box::new(PathToSlate((&args.dest).into())),
+					};
+					slate_putter.put_tx(&slate).map_err(|e| {
+						ErrorKind::IO(format!("Unable to store the file at {}, {}", args.dest, e))
+					})?;
 					api.tx_lock_outputs(m, &slate, Some(String::from("file")), 0)?;
 					return Ok(());
 				}
@@ -490,6 +527,273 @@ where
 	Ok(())
 }
 
+/// Arguments for the send_batch command
+pub struct SendBatchArgs {
+	pub entries: Vec<BatchSendEntry>,
+	pub minimum_confirmations: u64,
+	pub selection_strategy: String,
+	pub fluff: bool,
+	pub target_slate_version: Option<u16>,
+	pub ttl_blocks: Option<u64>,
+}
+
+pub fn send_batch<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	mqs_config: Option<MQSConfig>,
+	args: SendBatchArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	// mwcmqs responses are delivered to the listener, so it must be running
+	if args.entries.iter().any(|e| e.method == "mwcmqs")
+		&& grin_wallet_impls::adapters::get_mwcmqs_brocker().is_none()
+	{
+		let mqs_config = mqs_config.ok_or(ErrorKind::MQSConfig(format!("NO MQS config!")))?;
+		let km = match keychain_mask.as_ref() {
+			None => None,
+			Some(&m) => Some(m.to_owned()),
+		};
+		let _ = controller::init_start_mwcmqs_listener(
+			owner_api.wallet_inst.clone(),
+			mqs_config,
+			Arc::new(Mutex::new(km)),
+			false,
+		)?;
+		thread::sleep(Duration::from_millis(2000));
+	}
+
+	let batch_args = BatchSendArgs {
+		src_acct_name: None,
+		minimum_confirmations: args.minimum_confirmations,
+		max_outputs: 500,
+		selection_strategy_is_use_all: args.selection_strategy == "all",
+		target_slate_version: args.target_slate_version,
+		ttl_blocks: args.ttl_blocks,
+		fluff: args.fluff,
+		entries: args.entries,
+	};
+
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let results = api.send_batch_tx(m, batch_args)?;
+		display::batch_send_results(&results, dark_scheme);
+		let failed = results.iter().filter(|r| !r.is_ok()).count();
+		if failed > 0 {
+			return Err(ErrorKind::LibWallet(format!(
+				"{} of {} batch payments failed",
+				failed,
+				results.len()
+			))
+			.into());
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Arguments for the transfer command
+pub struct TransferCmdArgs {
+	/// Source account, None for the active account
+	pub from: Option<String>,
+	pub to: String,
+	pub amount: u64,
+	pub minimum_confirmations: u64,
+	pub selection_strategy: String,
+	pub change_outputs: usize,
+	pub message: Option<String>,
+	pub fluff: bool,
+}
+
+pub fn transfer<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: TransferCmdArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let transfer_args = TransferArgs {
+		src_acct_name: args.from,
+		dest_acct_name: args.to,
+		amount: args.amount,
+		minimum_confirmations: args.minimum_confirmations,
+		max_outputs: 500,
+		num_change_outputs: args.change_outputs as u32,
+		selection_strategy_is_use_all: args.selection_strategy == "all",
+		message: args.message,
+		fluff: args.fluff,
+	};
+
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let res = api.transfer(m, transfer_args)?;
+		info!(
+			"Transfer [{}] of {} MWC is posted, source tx {}, destination tx {}",
+			res.slate_id,
+			core::amount_to_hr_string(res.dest_tx.amount_credited, false),
+			res.src_tx.id,
+			res.dest_tx.id
+		);
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// What the cold command does
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColdAction {
+	/// Offline: write the unspent outputs to the file
+	ExportOutputs,
+	/// Online: track the outputs from the file
+	ImportOutputs,
+	/// Online: select the cold outputs and write the send request
+	Send,
+	/// Online: write the request to receive the slate
+	Receive,
+	/// Online: write the request to sign the slate that the receiver returned
+	Finalize,
+	/// Offline: sign the request and write the response
+	Sign,
+	/// Online: record the response, write the slate or post the finalized transaction
+	Process,
+}
+
+/// Cold command arguments
+pub struct ColdArgs {
+	pub action: ColdAction,
+	pub input: Option<String>,
+	pub dest: Option<String>,
+	pub amount: u64,
+	pub minimum_confirmations: u64,
+	pub selection_strategy: String,
+	pub change_outputs: usize,
+	pub message: Option<String>,
+	pub ttl_blocks: Option<u64>,
+	pub fluff: bool,
+}
+
+fn read_cold_file<T: serde::de::DeserializeOwned>(file: &Option<String>) -> Result<T, Error> {
+	let file = file
+		.as_ref()
+		.ok_or_else(|| ErrorKind::ArgumentError("Missing input file".to_string()))?;
+	let mut data = String::new();
+	File::open(file)
+		.and_then(|mut f| f.read_to_string(&mut data))
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to read file {}, {}", file, e)))?;
+	Ok(json::from_str(&data)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to parse file {}, {}", file, e)))?)
+}
+
+fn write_cold_file<T: serde::Serialize>(file: &Option<String>, data: &T) -> Result<(), Error> {
+	let file = file
+		.as_ref()
+		.ok_or_else(|| ErrorKind::ArgumentError("Missing destination file".to_string()))?;
+	let data = json::to_string_pretty(data)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to serialize data, {}", e)))?;
+	File::create(file)
+		.and_then(|mut f| {
+			f.write_all(data.as_bytes())?;
+			f.sync_all()
+		})
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to save file {}, {}", file, e)))?;
+	Ok(())
+}
+
+pub fn cold<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: ColdArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match args.action {
+			ColdAction::ExportOutputs => {
+				let outputs = api.export_cold_outputs(m)?;
+				write_cold_file(&args.dest, &outputs)?;
+				info!("{} cold wallet outputs are exported", outputs.len());
+			}
+			ColdAction::ImportOutputs => {
+				let outputs: Vec<OutputData> = read_cold_file(&args.input)?;
+				let n = api.import_cold_outputs(m, outputs)?;
+				info!("{} new cold wallet outputs are tracked", n);
+			}
+			ColdAction::Send => {
+				let init_args = InitTxArgs {
+					src_acct_name: None,
+					amount: args.amount,
+					minimum_confirmations: args.minimum_confirmations,
+					max_outputs: 500,
+					num_change_outputs: args.change_outputs as u32,
+					selection_strategy_is_use_all: args.selection_strategy == "all",
+					message: args.message.clone(),
+					ttl_blocks: args.ttl_blocks,
+					..Default::default()
+				};
+				let request = api.init_cold_send_tx(m, init_args)?;
+				write_cold_file(&args.dest, &request)?;
+				info!(
+					"Send request for slate {} is ready, sign it with the offline wallet",
+					request.slate.id
+				);
+			}
+			ColdAction::Receive | ColdAction::Finalize => {
+				let input = args
+					.input
+					.as_ref()
+					.ok_or_else(|| ErrorKind::ArgumentError("Missing input file".to_string()))?;
+				let slate = PathToSlate(input.into()).get_tx()?;
+				let step = match args.action {
+					ColdAction::Receive => ColdSignStep::Receive,
+					_ => ColdSignStep::Sign,
+				};
+				let request = api.cold_sign_request(step, &slate, args.message.clone())?;
+				write_cold_file(&args.dest, &request)?;
+				info!(
+					"Request for slate {} is ready, sign it with the offline wallet",
+					slate.id
+				);
+			}
+			ColdAction::Sign => {
+				let request: ColdSignRequest = read_cold_file(&args.input)?;
+				let response = api.cold_sign(m, &request)?;
+				write_cold_file(&args.dest, &response)?;
+				info!(
+					"Slate {} is signed, process the response with the online wallet",
+					response.slate.id
+				);
+			}
+			ColdAction::Process => {
+				let response: ColdSignResponse = read_cold_file(&args.input)?;
+				let slate = api.process_cold_sign_response(m, &response)?;
+				if response.step == ColdSignStep::Sign {
+					api.post_tx(m, &slate.tx, args.fluff)?;
+					info!("Transaction {} is finalized and posted", slate.id);
+				} else {
+					let dest = args.dest.as_ref().ok_or_else(|| {
+						ErrorKind::ArgumentError("Missing destination file".to_string())
+					})?;
+					PathToSlate(dest.into()).put_tx(&slate)?;
+					info!(
+						"Slate {} for the other party is written to {}",
+						slate.id, dest
+					);
+				}
+			}
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Receive command argument
 pub struct ReceiveArgs {
 	pub input: String,
@@ -527,7 +831,13 @@ where
 		)?;
 		Ok(())
 	})?;
-	PathToSlate(format!("{}.response", args.input).into()).put_tx(&slate)?;
+	// Response is written in the same encoding as the sender used
+	let response_file = format!("{}.response", args.input);
+	if PathToSlate((&args.input).into()).is_compact()? {
+		CompactPathToSlate(response_file.into()).put_tx(&slate)?;
+	} else {
+		PathToSlate(response_file.into()).put_tx(&slate)?;
+	}
 	info!(
 		"Response file {}.response generated, and can be sent back to the transaction originator.",
 		args.input
@@ -643,6 +953,160 @@ where
 	Ok(())
 }
 
+/// Invoices Args
+pub struct InvoicesArgs {
+	pub id: Option<Uuid>,
+	pub reference: Option<String>,
+}
+
+pub fn invoices<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: InvoicesArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let res = api.node_height(m)?;
+		let (_, invoices) = api.retrieve_invoices(m, true, args.id, args.reference.clone())?;
+		display::invoices(res.height, &invoices, dark_scheme);
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Cancel Invoice Args
+pub struct CancelInvoiceArgs {
+	pub id: Uuid,
+}
+
+pub fn cancel_invoice<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: CancelInvoiceArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match api.cancel_invoice(m, args.id) {
+			Ok(_) => {
+				info!("Invoice {} Cancelled", args.id);
+				Ok(())
+			}
+			Err(e) => {
+				error!("Invoice Cancellation failed: {}", e);
+				Err(
+					ErrorKind::LibWallet(format!("Unable to cancel Invoice {}, {}", args.id, e))
+						.into(),
+				)
+			}
+		}
+	})?;
+	Ok(())
+}
+
+/// Pending Slates Args
+pub struct PendingSlatesArgs {
+	pub id: Option<Uuid>,
+	pub approve: bool,
+	pub reject: bool,
+}
+
+pub fn pending_slates<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: PendingSlatesArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match (args.id, args.approve, args.reject) {
+			(Some(id), true, _) => match api.approve_pending_slate(m, id) {
+				Ok(_) => {
+					info!("Slate {} Approved", id);
+					Ok(())
+				}
+				Err(e) => {
+					error!("Slate approval failed: {}", e);
+					Err(
+						ErrorKind::LibWallet(format!("Unable to approve slate {}, {}", id, e))
+							.into(),
+					)
+				}
+			},
+			(Some(id), _, true) => match api.reject_pending_slate(m, id) {
+				Ok(_) => {
+					info!("Slate {} Rejected", id);
+					Ok(())
+				}
+				Err(e) => {
+					error!("Slate rejection failed: {}", e);
+					Err(
+						ErrorKind::LibWallet(format!("Unable to reject slate {}, {}", id, e))
+							.into(),
+					)
+				}
+			},
+			_ => {
+				let pending = api.retrieve_pending_slates(m, args.id)?;
+				display::pending_slates(&pending, dark_scheme);
+				Ok(())
+			}
+		}
+	})?;
+	Ok(())
+}
+
+/// Arguments for the spend_policy command
+pub struct SpendPolicyCmdArgs {
+	/// New policy of the account, None to display the current one
+	pub policy: Option<SpendPolicyArgs>,
+	pub clear: bool,
+	/// Wallet password, required to change the policy
+	pub password: Option<ZeroingString>,
+}
+
+pub fn spend_policy<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	wallet_data_dir: Option<&str>,
+	args: SpendPolicyCmdArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let password = args.password.clone().unwrap_or(ZeroingString::from(""));
+		if args.clear {
+			api.clear_spend_policy(m, password, wallet_data_dir, None)?;
+			info!("Spending policy is removed");
+		} else if let Some(policy) = &args.policy {
+			api.set_spend_policy(m, password, wallet_data_dir, policy)?;
+			info!("Spending policy is updated");
+		}
+		match api.retrieve_spend_policy(m, None)? {
+			Some(policy) => display::spend_policy(&policy, dark_scheme),
+			None => println!("\nAccount doesn't have a spending policy\n"),
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Arguments for the process_invoice command
 pub struct ProcessInvoiceArgs {
 	pub message: Option<String>,
@@ -673,7 +1137,7 @@ where
 	let wallet_inst = owner_api.wallet_inst.clone();
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		if args.estimate_selection_strategies {
-			let mut strategies: Vec<(&str, u64, u64)> = Vec::new();
+			let mut strategies: Vec<(&str, FeeEstimate)> = Vec::new();
 			for strategy in vec!["smallest", "all"] {
 				let init_args = InitTxArgs {
 					src_acct_name: None,
@@ -682,11 +1146,9 @@ where
 					max_outputs: args.max_outputs as u32,
 					num_change_outputs: 1u32,
 					selection_strategy_is_use_all: strategy == "all",
-					estimate_only: Some(true),
 					..Default::default()
 				};
-				let slate = api.init_send_tx(m, init_args, 1)?;
-				strategies.push((strategy, slate.amount, slate.fee));
+				strategies.push((strategy, api.estimate_fee(m, init_args)?));
 			}
 			display::estimate(slate.amount, strategies, dark_scheme);
 		} else {
@@ -779,12 +1241,21 @@ where
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		let (validated, wallet_info) =
 			api.retrieve_summary_info(m, true, args.minimum_confirmations)?;
+		let node_url = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			w.w2n_client().active_node_url()
+		};
 		display::info(
 			&g_args.account,
 			&wallet_info,
 			validated || updater_running,
+			&node_url,
 			dark_scheme,
 		);
+		if let Some(policy) = api.retrieve_spend_policy(m, None)? {
+			display::spend_policy(&policy, dark_scheme);
+		}
 		Ok(())
 	})?;
 	Ok(())
@@ -817,10 +1288,76 @@ where
 	Ok(())
 }
 
+/// Freeze and unfreeze command args
+pub struct FreezeOutputArgs {
+	/// Hex encoded commitment of the output
+	pub commit: String,
+	pub frozen: bool,
+}
+
+pub fn freeze_output<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: FreezeOutputArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match args.frozen {
+			true => {
+				api.freeze_output(m, &args.commit)?;
+				info!("Output {} is frozen", args.commit);
+			}
+			false => {
+				api.unfreeze_output(m, &args.commit)?;
+				info!("Output {} is unfrozen", args.commit);
+			}
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Label output command args
+pub struct LabelOutputArgs {
+	/// Hex encoded commitment of the output
+	pub commit: String,
+	pub note: Option<String>,
+	pub tags: Vec<String>,
+}
+
+pub fn label_output<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: LabelOutputArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let output = api.label_output(m, &args.commit, args.note, args.tags)?;
+		info!(
+			"Output {} is labeled, note: {}, tags: {}",
+			args.commit,
+			output.note.unwrap_or_default(),
+			output.tags.join(", ")
+		);
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Txs command args
 pub struct TxsArgs {
 	pub id: Option<u32>,
 	pub tx_slate_id: Option<Uuid>,
+	/// Show only the transactions with this text in the note, tags, address or messages
+	pub search: Option<String>,
 }
 
 pub fn txs<L, C, K>(
@@ -838,7 +1375,10 @@ where
 	let updater_running = owner_api.updater_running.load(Ordering::Relaxed);
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		let res = api.node_height(m)?;
-		let (validated, txs) = api.retrieve_txs(m, true, args.id, args.tx_slate_id)?;
+		let (validated, mut txs) = api.retrieve_txs(m, true, args.id, args.tx_slate_id)?;
+		if let Some(query) = &args.search {
+			txs.retain(|t| t.matches(query));
+		}
 		let include_status = !args.id.is_some() && !args.tx_slate_id.is_some();
 		display::txs(
 			&g_args.account,
@@ -887,6 +1427,72 @@ where
 	Ok(())
 }
 
+/// Label tx command args
+pub struct LabelTxArgs {
+	pub id: Option<u32>,
+	pub tx_slate_id: Option<Uuid>,
+	pub note: Option<String>,
+	pub tags: Vec<String>,
+}
+
+pub fn label_tx<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: LabelTxArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let tx = api.label_tx(m, args.id, args.tx_slate_id, args.note, args.tags)?;
+		info!(
+			"Transaction {} is labeled, note: {}, tags: {}",
+			tx.id,
+			tx.note.unwrap_or_default(),
+			tx.tags.join(", ")
+		);
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Address book command args
+pub struct AddressBookArgs {
+	/// Contact to add, it replaces the contact with the same name
+	pub add: Option<Contact>,
+	/// Name of the contact to remove
+	pub remove: Option<String>,
+}
+
+pub fn address_book<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: AddressBookArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		if let Some(contact) = args.add {
+			let contact = api.save_contact(m, contact)?;
+			info!("Contact @{} is saved", contact.name);
+		} else if let Some(name) = args.remove {
+			api.delete_contact(m, &name)?;
+			info!("Contact @{} is removed", name.trim_start_matches('@'));
+		} else {
+			let contacts = api.retrieve_contacts(m)?;
+			display::contacts(&contacts, dark_scheme);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Post
 pub struct PostArgs {
 	pub input: String,
@@ -1036,6 +1642,30 @@ where
 	Ok(())
 }
 
+/// Report the transactions that the wallet update cancels because of the expired TTL
+pub fn expired_txs<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let res = api.node_height(m)?;
+		let expired = api.retrieve_expired_txs(m)?;
+		if expired.is_empty() {
+			info!("No transactions with expired TTL at height {}", res.height);
+		} else {
+			display::expired_txs(res.height, &expired, dark_scheme);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// wallet check
 pub struct CheckArgs {
 	pub delete_unconfirmed: bool,
@@ -1110,6 +1740,8 @@ pub struct ProofExportArgs {
 	pub output_file: String,
 	pub id: Option<u32>,
 	pub tx_slate_id: Option<Uuid>,
+	/// Export the standalone payment proof document instead of the tx proof
+	pub document: bool,
 }
 
 pub fn proof_export<L, C, K>(
@@ -1123,7 +1755,13 @@ where
 	K: keychain::Keychain + 'static,
 {
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
-		let result = api.get_stored_tx_proof(m, args.id);
+		let result = if args.document {
+			api.export_payment_proof_document(m, true, args.id, args.tx_slate_id)
+				.and_then(|doc| doc.to_json())
+		} else {
+			api.get_stored_tx_proof(m, args.id)
+				.map(|p| json::to_string_pretty(&p).unwrap())
+		};
 		match result {
 			Ok(p) => {
 				// actually export proof
@@ -1133,14 +1771,12 @@ where
 						args.output_file, e
 					))
 				})?;
-				proof_file
-					.write_all(json::to_string_pretty(&p).unwrap().as_bytes())
-					.map_err(|e| {
-						ErrorKind::GenericError(format!(
-							"Unable to save the proof file {}, {}",
-							args.output_file, e
-						))
-					})?;
+				proof_file.write_all(p.as_bytes()).map_err(|e| {
+					ErrorKind::GenericError(format!(
+						"Unable to save the proof file {}, {}",
+						args.output_file, e
+					))
+				})?;
 				proof_file.sync_all().map_err(|e| {
 					ErrorKind::GenericError(format!(
 						"Unable to save file {}, {}",
@@ -1166,11 +1802,13 @@ where
 /// Proof Verify Args
 pub struct ProofVerifyArgs {
 	pub input_file: String,
+	/// For the payment proof document, check the kernel with the node
+	pub check_node: bool,
 }
 
 pub fn proof_verify<L, C, K>(
-	_owner_api: &mut Owner<L, C, K>,
-	_keychain_mask: Option<&SecretKey>,
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
 	args: ProofVerifyArgs,
 ) -> Result<(), Error>
 where
@@ -1198,6 +1836,11 @@ where
 	let mut proof = String::new();
 	file.read_to_string(&mut proof)
 		.map_err(|e| ErrorKind::LibWallet(format!("Unable to read proof data, {}", e)))?;
+
+	if let Ok(doc) = PaymentProofDocument::from_json(&proof) {
+		return proof_document_verify(owner_api, keychain_mask, &doc, args.check_node);
+	}
+
 	let tx_pf: TxProof = serde_json::from_str(&proof)
 		.map_err(|e| ErrorKind::LibWallet(format!("Unable to deserialize proof data, {}", e)))?;
 
@@ -1215,6 +1858,53 @@ where
 	}
 }
 
+fn proof_document_verify<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	doc: &PaymentProofDocument,
+	check_node: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let result = if check_node {
+		controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+			api.verify_payment_proof_document(m, doc, true)?;
+			Ok(())
+		})
+	} else {
+		grin_wallet_libwallet::proof::payment_proof::verify_payment_proof_document(doc)
+			.map_err(|e| e.into())
+	};
+
+	match result {
+		Ok(_) => {
+			grin_wallet_libwallet::proof::tx_proof::proof_ok(
+				Some(doc.sender_address.public_key.clone()),
+				doc.recipient_address.public_key.clone(),
+				doc.amount,
+				vec![],
+				crate::util::to_hex(doc.kernel.excess.0.to_vec()),
+			);
+			if check_node {
+				println!(
+					"kernel is mined at height {}, block {}",
+					doc.block_height, doc.block_hash
+				);
+			} else {
+				println!("kernel was not checked with the node, use --node to validate it");
+			}
+			Ok(())
+		}
+		Err(e) => {
+			error!("Unable to verify payment proof document. {}", e);
+			Err(ErrorKind::LibWallet(format!("Proof not valid: {}", e)).into())
+		}
+	}
+}
+
 pub fn dump_wallet_data<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<&SecretKey>,
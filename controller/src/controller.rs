@@ -16,8 +16,8 @@
 //! invocations) as needed.
 use crate::api::{self, ApiServer, BasicAuthMiddleware, ResponseFuture, Router, TLSConfig};
 use crate::libwallet::{
-	NodeClient, NodeVersionInfo, Slate, WalletInst, WalletLCProvider,
-	GRIN_BLOCK_HEADER_VERSION,
+	ErrorKind as LibwalletErrorKind, NodeClient, NodeVersionInfo, PendingSlate, PendingSlateStatus,
+	Slate, WalletInst, WalletLCProvider, GRIN_BLOCK_HEADER_VERSION,
 };
 use crate::util::secp::key::SecretKey;
 use crate::util::{from_hex, static_secp_instance, to_base64, Mutex};
@@ -30,10 +30,12 @@ use hyper::{Body, Request, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
-use grin_wallet_impls::{Address, CloseReason, MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, Publisher,
-						Subscriber, SubscriptionHandler};
-use grin_wallet_libwallet::wallet_lock;
+use grin_wallet_impls::{
+	Address, CloseReason, MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, Publisher, Subscriber,
+	SubscriptionHandler,
+};
 use grin_wallet_libwallet::swap::message::Message;
+use grin_wallet_libwallet::wallet_lock;
 use grin_wallet_util::grin_core::core;
 
 use crate::apiwallet::{
@@ -42,18 +44,22 @@ use crate::apiwallet::{
 };
 use crate::config::{MQSConfig, TorConfig};
 use crate::core::global;
+use crate::executor::run_blocking;
 use crate::impls::tor::config as tor_config;
 use crate::impls::tor::process as tor_process;
 use crate::keychain::Keychain;
+use crate::metrics::{self, MetricsHandler};
+use crate::owner_access::{self, AccessKey, OwnerApiAccess};
+use crate::owner_ws::OwnerAPIHandlerWs;
 use easy_jsonrpc_mw::{Handler, MaybeReply};
 use grin_wallet_libwallet::proof::crypto;
 use grin_wallet_libwallet::proof::proofaddress;
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::pin::Pin;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock};
 use std::thread;
+use std::time::Instant;
 
 lazy_static! {
 	pub static ref MWC_OWNER_BASIC_REALM: HeaderValue =
@@ -110,10 +116,10 @@ pub fn get_tor_address<L, C, K>(
 	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 ) -> Result<String, Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	let mask = keychain_mask.lock();
 	// eventually want to read a list of service config keys
@@ -124,7 +130,8 @@ pub fn get_tor_address<L, C, K>(
 	let sec_key = proofaddress::payment_proof_address_secret(&k).map_err(|e| {
 		ErrorKind::TorConfig(format!("Unable to build key for onion address, {}", e))
 	})?;
-	let onion_addr = OnionV3Address::from_private(&sec_key.0).map_err(|e| ErrorKind::GenericError(format!("Unable to build Onion address, {}", e)))?;
+	let onion_addr = OnionV3Address::from_private(&sec_key.0)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to build Onion address, {}", e)))?;
 	Ok(format!("{}", onion_addr))
 }
 
@@ -135,10 +142,10 @@ pub fn init_tor_listener<L, C, K>(
 	addr: &str,
 	tor_base: Option<&str>,
 ) -> Result<tor_process::TorProcess, Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	let mut process = tor_process::TorProcess::new();
 	let mask = keychain_mask.lock();
@@ -153,7 +160,7 @@ pub fn init_tor_listener<L, C, K>(
 		format!("{}/tor/listener", lc.get_top_level_directory()?)
 	};
 
-	let sec_key = proofaddress::payment_proof_address_secret(&k ).map_err(|e| {
+	let sec_key = proofaddress::payment_proof_address_secret(&k).map_err(|e| {
 		ErrorKind::TorConfig(format!("Unable to build key for onion address, {}", e))
 	})?;
 	let onion_address = OnionV3Address::from_private(&sec_key.0)
@@ -177,7 +184,10 @@ pub fn init_tor_listener<L, C, K>(
 			ErrorKind::TorProcess(format!("Unable to start tor at {}, {}", tor_path, e).into())
 		})?;
 
-	TOR_ONION_ADDRESS.write().unwrap().replace( format!("{}", onion_address) );
+	TOR_ONION_ADDRESS
+		.write()
+		.unwrap()
+		.replace(format!("{}", onion_address));
 
 	Ok(process)
 }
@@ -190,11 +200,11 @@ pub fn owner_single_use<L, F, C, K>(
 	api_context: Option<&mut Owner<L, C, K>>,
 	f: F,
 ) -> Result<(), Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		F: FnOnce(&mut Owner<L, C, K>, Option<&SecretKey>) -> Result<(), Error>,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	F: FnOnce(&mut Owner<L, C, K>, Option<&SecretKey>) -> Result<(), Error>,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	match api_context {
 		Some(c) => f(c, keychain_mask)?,
@@ -205,7 +215,7 @@ pub fn owner_single_use<L, F, C, K>(
 					return Err(ErrorKind::GenericError(format!(
 						"Instantiated wallet or Owner API context must be provided"
 					))
-						.into())
+					.into())
 				}
 			};
 			f(&mut Owner::new(wallet, None, None), keychain_mask)?
@@ -221,11 +231,11 @@ pub fn foreign_single_use<'a, L, F, C, K>(
 	keychain_mask: Option<SecretKey>,
 	f: F,
 ) -> Result<(), Error>
-	where
-		L: WalletLCProvider<'a, C, K>,
-		F: FnOnce(&mut Foreign<'a, L, C, K>) -> Result<(), Error>,
-		C: NodeClient + 'a,
-		K: Keychain + 'a,
+where
+	L: WalletLCProvider<'a, C, K>,
+	F: FnOnce(&mut Foreign<'a, L, C, K>) -> Result<(), Error>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
 {
 	f(&mut Foreign::new(
 		wallet,
@@ -239,12 +249,12 @@ pub fn foreign_single_use<'a, L, F, C, K>(
 
 fn controller_derive_address_key<'a, L, C, K>(
 	wallet: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
-	keychain_mask: Option<&SecretKey>
+	keychain_mask: Option<&SecretKey>,
 ) -> Result<SecretKey, Error>
-	where
-		L: WalletLCProvider<'a, C, K>,
-		C: NodeClient + 'a,
-		K: Keychain + 'a,
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
 {
 	wallet_lock!(wallet, w);
 	let k = w.keychain(keychain_mask)?;
@@ -254,10 +264,10 @@ fn controller_derive_address_key<'a, L, C, K>(
 
 #[derive(Clone)]
 pub struct Controller<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Wallet instance
 	name: String,
@@ -269,17 +279,17 @@ pub struct Controller<L, C, K>
 	// Autoinvoice
 	max_auto_accept_invoice: Option<u64>,
 
-	slate_send_channel: Arc<Mutex< HashMap< uuid::Uuid, Sender<Slate>>>>,
+	slate_send_channel: Arc<Mutex<HashMap<uuid::Uuid, Sender<Slate>>>>,
 	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 	// what to do with logs. Print them to console or into the logs
 	print_to_log: bool,
 }
 
 impl<L, C, K> Controller<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	pub fn new(
 		name: &str,
@@ -288,10 +298,10 @@ impl<L, C, K> Controller<L, C, K>
 		max_auto_accept_invoice: Option<u64>,
 		print_to_log: bool,
 	) -> Self
-		where
-			L: WalletLCProvider<'static, C, K>,
-			C: NodeClient + 'static,
-			K: Keychain + 'static,
+	where
+		L: WalletLCProvider<'static, C, K>,
+		C: NodeClient + 'static,
+		K: Keychain + 'static,
 	{
 		if max_auto_accept_invoice.is_some() && global::is_mainnet() {
 			panic!("Auto invoicing must be disabled for the mainnet");
@@ -331,7 +341,8 @@ impl<L, C, K> Controller<L, C, K>
 		dest_acct_name: Option<&str>,
 	) -> Result<(), Error> {
 		let owner_api = Owner::new(self.wallet.clone(), None, None);
-		let foreign_api = Foreign::new(self.wallet.clone(), None, None);
+		let mut foreign_api = Foreign::new(self.wallet.clone(), None, None);
+		foreign_api.approval_queue = true;
 		let mask = self.keychain_mask.lock().clone();
 
 		if slate.num_participants > slate.participant_data.len() {
@@ -376,6 +387,7 @@ impl<L, C, K> Controller<L, C, K>
 					target_slate_version: None,
 					/// Number of blocks from current after which TX should be ignored
 					ttl_blocks: None,
+					lock_height: None,
 					/// If set, require a payment proof for the particular recipient
 					payment_proof_recipient_address: None,
 					address: Some(from.get_full_name()),
@@ -394,16 +406,37 @@ impl<L, C, K> Controller<L, C, K>
 
 				*slate = owner_api.process_invoice_tx((&mask).as_ref(), slate, params)?;
 
-				owner_api.tx_lock_outputs((&mask).as_ref(), slate, Some(from.get_full_name()), 1)?;
+				owner_api.tx_lock_outputs(
+					(&mask).as_ref(),
+					slate,
+					Some(from.get_full_name()),
+					1,
+				)?;
 			} else {
-				let s = foreign_api
-					.receive_tx(slate, Some(from.get_full_name()), dest_acct_name, None)
-					.map_err(|e| {
-						ErrorKind::LibWallet(format!(
+				let s = match foreign_api.receive_tx(
+					slate,
+					Some(from.get_full_name()),
+					dest_acct_name,
+					None,
+				) {
+					Ok(s) => s,
+					Err(e) => {
+						if let LibwalletErrorKind::PendingApproval(_) = e.kind() {
+							// Reply is sent when the wallet owner approves the slate
+							self.do_log_info(format!(
+								"slate [{}] from [{}] is waiting for the approval",
+								slate.id.to_string(),
+								from.get_stripped()
+							));
+							return Ok(());
+						}
+						return Err(ErrorKind::LibWallet(format!(
 							"Unable to process incoming slate, receive_tx failed, {}",
 							e
 						))
-					})?;
+						.into());
+					}
+				};
 				*slate = s;
 			}
 
@@ -434,7 +467,10 @@ impl<L, C, K> Controller<L, C, K>
 				let _ = slate_sender.send(slate_immutable);
 			} else {
 				// Report error. We are not processing any finalization transactions if nobody waiting for that
-				self.do_log_warn(format!("Get back slate {}. Because slate arrive too late, wallet not processing it", slate.id));
+				self.do_log_warn(format!(
+					"Get back slate {}. Because slate arrive too late, wallet not processing it",
+					slate.id
+				));
 			}
 
 			Ok(())
@@ -443,14 +479,15 @@ impl<L, C, K> Controller<L, C, K>
 
 	fn process_incoming_swap_message(
 		&self,
-		swapmessage: Message
+		swapmessage: Message,
 	) -> Result<Option<Message>, Error> {
 		let owner_api = Owner::new(self.wallet.clone(), None, None);
 		let mask = self.keychain_mask.lock().clone();
 
 		let msg_str = serde_json::to_string(&swapmessage).map_err(|e| {
 			ErrorKind::ProcessSwapMessageError(format!(
-				"Error in processing incoming swap message from mqs, {}", e
+				"Error in processing incoming swap message from mqs, {}",
+				e
 			))
 		})?;
 		let ack_msg = owner_api.swap_income_message((&mask).as_ref(), msg_str)?;
@@ -484,10 +521,10 @@ impl<L, C, K> Controller<L, C, K>
 }
 
 impl<L, C, K> SubscriptionHandler for Controller<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K>,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K>,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	fn on_open(&self) {
 		self.do_log_warn(format!("listener started for [{}]", self.name));
@@ -523,8 +560,7 @@ impl<L, C, K> SubscriptionHandler for Controller<L, C, K>
 			));
 		};
 
-		let result = self
-			.process_incoming_slate(from, slate, None);
+		let result = self.process_incoming_slate(from, slate, None);
 
 		//send the message back
 		match result {
@@ -538,7 +574,10 @@ impl<L, C, K> SubscriptionHandler for Controller<L, C, K>
 
 		match result {
 			Ok(message) => return message,
-			Err(e) => { self.do_log_error(format!("{}", e)); None }
+			Err(e) => {
+				self.do_log_error(format!("{}", e));
+				None
+			}
 		}
 	}
 
@@ -563,12 +602,10 @@ impl<L, C, K> SubscriptionHandler for Controller<L, C, K>
 		))
 	}
 
-	fn set_notification_channels(
-		&self,
-		slate_id: &uuid::Uuid,
-		slate_send_channel: Sender<Slate>,
-	) {
-		self.slate_send_channel.lock().insert(slate_id.clone(), slate_send_channel);
+	fn set_notification_channels(&self, slate_id: &uuid::Uuid, slate_send_channel: Sender<Slate>) {
+		self.slate_send_channel
+			.lock()
+			.insert(slate_id.clone(), slate_send_channel);
 	}
 
 	fn reset_notification_channels(&self, slate_id: &uuid::Uuid) {
@@ -582,21 +619,15 @@ pub fn init_start_mwcmqs_listener<L, C, K>(
 	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 	wait_for_thread: bool,
 ) -> Result<(MWCMQPublisher, MWCMQSubscriber), Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	warn!("Starting MWCMQS Listener");
 
 	//start mwcmqs listener
-	start_mwcmqs_listener(
-		wallet,
-		mqs_config,
-		wait_for_thread,
-		keychain_mask,
-		true,
-	)
+	start_mwcmqs_listener(wallet, mqs_config, wait_for_thread, keychain_mask, true)
 		.map_err(|e| ErrorKind::GenericError(format!("cannot start mqs listener, {}", e)).into())
 }
 
@@ -608,13 +639,15 @@ pub fn start_mwcmqs_listener<L, C, K>(
 	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 	print_to_log: bool,
 ) -> Result<(MWCMQPublisher, MWCMQSubscriber), Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	if grin_wallet_impls::adapters::get_mwcmqs_brocker().is_some() {
-		return Err(ErrorKind::GenericError("mwcmqs listener is already running".to_string()).into());
+		return Err(
+			ErrorKind::GenericError("mwcmqs listener is already running".to_string()).into(),
+		);
 	}
 
 	// make sure wallet is not locked, if it is try to unlock with no passphrase
@@ -623,15 +656,18 @@ pub fn start_mwcmqs_listener<L, C, K>(
 		"starting mwcmqs listener for {}:{}...",
 		mqs_config.mwcmqs_domain, mqs_config.mwcmqs_port
 	);
-	info!("the addres index is {}... ", proofaddress::get_address_index() );
+	info!(
+		"the addres index is {}... ",
+		proofaddress::get_address_index()
+	);
 
 	let mwcmqs_domain = mqs_config.mwcmqs_domain;
 	let mwcmqs_port = mqs_config.mwcmqs_port;
+	let mwcmqs_compact_slates = mqs_config.mwcmqs_compact_slates.unwrap_or(false);
+	let mwcmqs_use_tls = mqs_config.mwcmqs_use_tls.unwrap_or(true);
 
-	let mwcmqs_secret_key = controller_derive_address_key(
-		wallet.clone(),
-		keychain_mask.lock().as_ref(),
-	)?;
+	let mwcmqs_secret_key =
+		controller_derive_address_key(wallet.clone(), keychain_mask.lock().as_ref())?;
 	let mwc_pub_key = crypto::public_key_from_secret_key(&mwcmqs_secret_key)?;
 
 	let mwcmqs_address = MWCMQSAddress::new(
@@ -643,7 +679,7 @@ pub fn start_mwcmqs_listener<L, C, K>(
 	let controller = Controller::new(
 		&mwcmqs_address.get_stripped(),
 		wallet.clone(),
-		keychain_mask,
+		keychain_mask.clone(),
 		None,
 		print_to_log,
 	);
@@ -653,6 +689,8 @@ pub fn start_mwcmqs_listener<L, C, K>(
 		&mwcmqs_secret_key,
 		mwcmqs_domain,
 		mwcmqs_port,
+		mwcmqs_use_tls,
+		mwcmqs_compact_slates,
 		print_to_log,
 		Box::new(controller.clone()),
 	);
@@ -677,6 +715,22 @@ pub fn start_mwcmqs_listener<L, C, K>(
 	// Publishing this running MQS service
 	crate::impls::init_mwcmqs_access_data(mwcmqs_publisher.clone(), mwcmqs_subscriber.clone());
 
+	// Slates approved from the approval queue are sent back while the listener is running
+	let approval_publisher = mwcmqs_publisher.clone();
+	let _ = thread::Builder::new()
+		.name("mwcmqs-approvals".to_string())
+		.spawn(move || {
+			while grin_wallet_impls::adapters::get_mwcmqs_brocker().is_some() {
+				let mask = keychain_mask.lock().clone();
+				if let Err(e) =
+					deliver_approved_slates(wallet.clone(), mask.as_ref(), &approval_publisher)
+				{
+					warn!("Unable to send back the approved slates, {}", e);
+				}
+				thread::sleep(std::time::Duration::from_secs(10));
+			}
+		});
+
 	if wait_for_thread {
 		let _ = thread.join();
 	}
@@ -684,6 +738,52 @@ pub fn start_mwcmqs_listener<L, C, K>(
 	Ok((mwcmqs_publisher, mwcmqs_subscriber))
 }
 
+/// Send the approved slates back to the mwcmqs senders. HTTP and Tor senders get them
+/// when they send the slate again.
+fn deliver_approved_slates<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	publisher: &MWCMQPublisher,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let approved: Vec<PendingSlate> = {
+		wallet_lock!(wallet, w);
+		w.pending_slate_iter()
+			.filter(|p| p.status == PendingSlateStatus::Approved && !p.delivered)
+			.filter(|p| {
+				p.address
+					.as_ref()
+					.map(|a| a.starts_with("mwcmqs://"))
+					.unwrap_or(false)
+			})
+			.collect()
+	};
+
+	for mut p in approved {
+		let response = match p.response.clone() {
+			Some(r) => Slate::from(r),
+			None => continue,
+		};
+		let to = MWCMQSAddress::from_str(p.address.as_ref().unwrap())?;
+		publisher.post_slate(&response, &to)?;
+		info!(
+			"approved slate [{}] sent back to [{}]",
+			p.id,
+			to.get_stripped()
+		);
+
+		wallet_lock!(wallet, w);
+		p.delivered = true;
+		let mut batch = w.batch(keychain_mask)?;
+		batch.save_pending_slate(p)?;
+		batch.commit()?;
+	}
+	Ok(())
+}
 
 /// Listener version, providing same API but listening for requests on a
 /// port and wrapping the calls
@@ -696,12 +796,13 @@ pub fn owner_listener<L, C, K>(
 	api_secret: Option<String>,
 	tls_config: Option<TLSConfig>,
 	owner_api_include_foreign: Option<bool>,
+	owner_api_metrics: Option<bool>,
 	tor_config: Option<TorConfig>,
 ) -> Result<(), Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	let mut running_foreign = false;
 	if owner_api_include_foreign.unwrap_or(false) {
@@ -709,16 +810,25 @@ pub fn owner_listener<L, C, K>(
 	}
 
 	if *OWNER_API_RUNNING.read().unwrap() {
-		return Err( ErrorKind::GenericError("Owner API is already up and running".to_string()).into() );
+		return Err(
+			ErrorKind::GenericError("Owner API is already up and running".to_string()).into(),
+		);
 	}
 	if running_foreign && *FOREIGN_API_RUNNING.read().unwrap() {
-		return Err( ErrorKind::GenericError("Foreign API is already up and running".to_string()).into() );
+		return Err(
+			ErrorKind::GenericError("Foreign API is already up and running".to_string()).into(),
+		);
 	}
 
 	//I don't know why but it seems the warn message in controller.rs will get printed to console.
 	warn!("owner listener started {}", addr);
+	let access = Arc::new(OwnerApiAccess::new(
+		owner_access::get_owner_api_config(),
+		api_secret.clone(),
+	));
 	let mut router = Router::new();
-	if api_secret.is_some() {
+	// With the named API keys the owner handlers authenticate the requests themselves
+	if api_secret.is_some() && !access.keys_enabled() {
 		let api_basic_auth =
 			"Basic ".to_string() + &to_base64(&("mwc:".to_string() + &api_secret.unwrap()));
 		let basic_auth_middleware = Arc::new(BasicAuthMiddleware::new(
@@ -729,13 +839,15 @@ pub fn owner_listener<L, C, K>(
 		router.add_middleware(basic_auth_middleware);
 	}
 
-	let api_handler_v2 = OwnerAPIHandlerV2::new(wallet.clone(), tor_config.clone());
+	let api_handler_v2 = OwnerAPIHandlerV2::new(wallet.clone(), tor_config.clone(), access.clone());
 	let api_handler_v3 = OwnerAPIHandlerV3::new(
 		wallet.clone(),
 		keychain_mask.clone(),
 		tor_config,
 		running_foreign,
+		access.clone(),
 	);
+	let api_handler_ws = OwnerAPIHandlerWs::new(api_handler_v3.owner_api.clone(), access.clone());
 
 	router
 		.add_route("/v2/owner", Arc::new(api_handler_v2))
@@ -749,6 +861,22 @@ pub fn owner_listener<L, C, K>(
 			ErrorKind::GenericError(format!("Router failed to add route /v3/owner, {}", e))
 		})?;
 
+	router
+		.add_route("/v3/owner/ws", Arc::new(api_handler_ws))
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Router failed to add route /v3/owner/ws, {}", e))
+		})?;
+
+	if owner_api_metrics.unwrap_or(false) {
+		warn!("Serving the wallet metrics at {}/metrics", addr);
+		let metrics_handler = MetricsHandler::new(wallet.clone(), keychain_mask.clone(), access);
+		router
+			.add_route("/metrics", Arc::new(metrics_handler))
+			.map_err(|e| {
+				ErrorKind::GenericError(format!("Router failed to add route /metrics, {}", e))
+			})?;
+	}
+
 	// If so configured, add the foreign API to the same port
 	if running_foreign {
 		warn!("Starting HTTP Foreign API on Owner server at {}.", addr);
@@ -794,13 +922,15 @@ pub fn foreign_listener<L, C, K>(
 	tls_config: Option<TLSConfig>,
 	use_tor: bool,
 ) -> Result<(), Error>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	if *FOREIGN_API_RUNNING.read().unwrap() {
-		return Err( ErrorKind::GenericError("Foreign API is already up and running".to_string()).into() );
+		return Err(
+			ErrorKind::GenericError("Foreign API is already up and running".to_string()).into(),
+		);
 	}
 
 	// Check if wallet has been opened first
@@ -858,72 +988,92 @@ pub fn foreign_listener<L, C, K>(
 
 /// V2 API Handler/Wrapper for owner functions
 pub struct OwnerAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Wallet instance
 	pub wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 	pub tor_config: Option<TorConfig>,
+	/// API keys and audit log
+	access: Arc<OwnerApiAccess>,
 }
 
 impl<L, C, K> OwnerAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Create a new owner API handler for GET methods
 	pub fn new(
 		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 		tor_config: Option<TorConfig>,
+		access: Arc<OwnerApiAccess>,
 	) -> OwnerAPIHandlerV2<L, C, K> {
-		OwnerAPIHandlerV2 { wallet, tor_config }
+		OwnerAPIHandlerV2 {
+			wallet,
+			tor_config,
+			access,
+		}
 	}
 
-	async fn call_api(req: Request<Body>, api: Owner<L, C, K>) -> Result<serde_json::Value, Error> {
-	let val: serde_json::Value = parse_body(req).await?;
-	match OwnerRpc::handle_request(&api, val) {
-		MaybeReply::Reply(r) => Ok(r),
-		MaybeReply::DontReply => {
-			// Since it's http, we need to return something. We return [] because jsonrpc
-			// clients will parse it as an empty batch response.
-			Ok(serde_json::json!([]))
+	fn call_api(
+		val: serde_json::Value,
+		api: Owner<L, C, K>,
+		access: Arc<OwnerApiAccess>,
+		caller: AccessKey,
+	) -> Result<serde_json::Value, Error> {
+		if let Err(e) = access.check_request(&caller, &val) {
+			return Ok(e);
+		}
+		match OwnerRpc::handle_request(&api, val.clone()) {
+			MaybeReply::Reply(r) => {
+				let (was_error, _) = OwnerV3Helpers::check_error_response(&r);
+				access.audit_result(&caller, &val, was_error);
+				Ok(r)
+			}
+			MaybeReply::DontReply => {
+				// Since it's http, we need to return something. We return [] because jsonrpc
+				// clients will parse it as an empty batch response.
+				Ok(serde_json::json!([]))
+			}
 		}
 	}
-}
 
-async fn handle_post_request(
-	req: Request<Body>,
-	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
-	tor_config: Option<TorConfig>,
-) -> Result<Response<Body>, Error> {
-	let api = Owner::new(wallet, None, tor_config);
-
-	//Here is a wrapper to call future from that.
-	// Issue that we can't call future form future
-	let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
-		let future = Self::call_api(req, api);
-		Box::pin(future)
-	};
-	let res = crate::executor::RunHandlerInThread::new(handler).await?;
+	async fn handle_post_request(
+		req: Request<Body>,
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		tor_config: Option<TorConfig>,
+		access: Arc<OwnerApiAccess>,
+		caller: AccessKey,
+	) -> Result<Response<Body>, Error> {
+		let api = Owner::new(wallet, None, tor_config);
 
-	Ok(json_response_pretty(&res))
-}
+		let val: serde_json::Value = parse_body(req).await?;
+		let res = run_blocking(move || Self::call_api(val, api, access, caller)).await?;
+
+		Ok(json_response_pretty(&res))
+	}
 }
 
 impl<L, C, K> api::Handler for OwnerAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		let caller = match self.access.authenticate(&req) {
+			Some(k) => k,
+			None => return Box::pin(async { Ok(create_unauthorized_response()) }),
+		};
 		let wallet = self.wallet.clone();
 		let tor_config = self.tor_config.clone();
+		let access = self.access.clone();
 		Box::pin(async move {
-			match Self::handle_post_request(req, wallet, tor_config).await {
+			match Self::handle_post_request(req, wallet, tor_config, access, caller).await {
 				Ok(r) => Ok(r),
 				Err(e) => {
 					error!("Request Error: {:?}", e);
@@ -941,10 +1091,10 @@ impl<L, C, K> api::Handler for OwnerAPIHandlerV2<L, C, K>
 /// V3 API Handler/Wrapper for owner functions, which include a secure
 /// mode + lifecycle functions
 pub struct OwnerAPIHandlerV3<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Wallet instance
 	pub wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
@@ -961,6 +1111,9 @@ pub struct OwnerAPIHandlerV3<L, C, K>
 	/// Whether we're running the foreign API on the same port, and therefore
 	/// have to store the mask in-process
 	pub running_foreign: bool,
+
+	/// API keys and audit log
+	access: Arc<OwnerApiAccess>,
 }
 
 pub struct OwnerV3Helpers;
@@ -1020,7 +1173,7 @@ impl OwnerV3Helpers {
 				-32001,
 				"Encryption must be enabled. Please call 'init_secure_api` first",
 			)
-				.as_json_value()),
+			.as_json_value()),
 		}
 	}
 
@@ -1067,7 +1220,7 @@ impl OwnerV3Helpers {
 				-32002,
 				"Encrypted request internal error",
 			)
-				.as_json_value());
+			.as_json_value());
 		}
 		let shared_key = share_key_ref.as_ref().unwrap();
 		let enc_req: EncryptedRequest = serde_json::from_value(req.clone()).map_err(|e| {
@@ -1076,7 +1229,7 @@ impl OwnerV3Helpers {
 				-32002,
 				&format!("Encrypted request format error: {}", e),
 			)
-				.as_json_value()
+			.as_json_value()
 		})?;
 		let id = enc_req.id.clone();
 		let res = enc_req.decrypt(&shared_key).map_err(|e| {
@@ -1099,7 +1252,7 @@ impl OwnerV3Helpers {
 				-32002,
 				"Encrypted response internal error",
 			)
-				.as_json_value());
+			.as_json_value());
 		}
 		let shared_key = share_key_ref.as_ref().unwrap();
 		let enc_res = EncryptedResponse::from_json(id, res, &shared_key).map_err(|e| {
@@ -1112,7 +1265,7 @@ impl OwnerV3Helpers {
 				-32002,
 				&format!("Encrypted response format error: {}", e),
 			)
-				.as_json_value()
+			.as_json_value()
 		})?;
 		Ok(res)
 	}
@@ -1187,10 +1340,10 @@ impl OwnerV3Helpers {
 }
 
 impl<L, C, K> OwnerAPIHandlerV3<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K>,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K>,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Create a new owner API handler for GET methods
 	pub fn new(
@@ -1198,6 +1351,7 @@ impl<L, C, K> OwnerAPIHandlerV3<L, C, K>
 		keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 		tor_config: Option<TorConfig>,
 		running_foreign: bool,
+		access: Arc<OwnerApiAccess>,
 	) -> OwnerAPIHandlerV3<L, C, K> {
 		let owner_api = Owner::new(wallet.clone(), None, tor_config.clone());
 		owner_api.set_tor_config(tor_config);
@@ -1208,109 +1362,130 @@ impl<L, C, K> OwnerAPIHandlerV3<L, C, K>
 			shared_key: Arc::new(Mutex::new(None)),
 			keychain_mask: keychain_mask,
 			running_foreign,
+			access,
 		}
 	}
 
-	async fn call_api(
-	req: Request<Body>,
-	key: Arc<Mutex<Option<SecretKey>>>,
-	mask: Arc<Mutex<Option<SecretKey>>>,
-	running_foreign: bool,
-	api: Arc<Owner<L, C, K>>,
-) -> Result<serde_json::Value, Error> {
-	let mut val: serde_json::Value = parse_body(req).await?;
-	let mut is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
-	let mut was_encrypted = false;
-	let mut encrypted_req_id = JsonId::StrId(String::from(""));
-	if !is_init_secure_api {
-		if let Err(v) = OwnerV3Helpers::check_encryption_started(key.clone()) {
-			return Ok(v);
-		}
-		let res = OwnerV3Helpers::decrypt_request(key.clone(), &val);
-		match res {
-			Err(e) => return Ok(e),
-			Ok(v) => {
-				encrypted_req_id = v.0.clone();
-				val = v.1;
+	fn call_api(
+		mut val: serde_json::Value,
+		key: Arc<Mutex<Option<SecretKey>>>,
+		mask: Arc<Mutex<Option<SecretKey>>>,
+		running_foreign: bool,
+		api: Arc<Owner<L, C, K>>,
+		access: Arc<OwnerApiAccess>,
+		caller: AccessKey,
+	) -> Result<serde_json::Value, Error> {
+		let mut is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
+		let mut was_encrypted = false;
+		let mut encrypted_req_id = JsonId::StrId(String::from(""));
+		if !is_init_secure_api {
+			if let Err(v) = OwnerV3Helpers::check_encryption_started(key.clone()) {
+				return Ok(v);
 			}
-		}
-		was_encrypted = true;
-	}
-	// check again, in case it was an encrypted call to init_secure_api
-	is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
-	// also need to intercept open/close wallet requests
-	let is_open_wallet = OwnerV3Helpers::is_open_wallet(&val);
-	match OwnerRpcS::handle_request(&*api, val) {
-		MaybeReply::Reply(mut r) => {
-			let (_was_error, unencrypted_intercept) =
-				OwnerV3Helpers::check_error_response(&r.clone());
-			if is_open_wallet && running_foreign {
-				OwnerV3Helpers::update_mask(mask, &r.clone());
+			let res = OwnerV3Helpers::decrypt_request(key.clone(), &val);
+			match res {
+				Err(e) => return Ok(e),
+				Ok(v) => {
+					encrypted_req_id = v.0.clone();
+					val = v.1;
+				}
 			}
+			was_encrypted = true;
+		}
+		// check again, in case it was an encrypted call to init_secure_api
+		is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
+		// also need to intercept open/close wallet requests
+		let is_open_wallet = OwnerV3Helpers::is_open_wallet(&val);
+		if let Err(e) = access.check_request(&caller, &val) {
 			if was_encrypted {
-				let res = OwnerV3Helpers::encrypt_response(
-					key.clone(),
-					&encrypted_req_id,
-					&unencrypted_intercept,
+				return Ok(
+					match OwnerV3Helpers::encrypt_response(key.clone(), &encrypted_req_id, &e) {
+						Ok(v) => v,
+						Err(v) => v,
+					},
 				);
-				r = match res {
-					Ok(v) => v,
-					Err(v) => return Ok(v),
+			}
+			return Ok(e);
+		}
+		let request = val.clone();
+		match OwnerRpcS::handle_request(&*api, val) {
+			MaybeReply::Reply(mut r) => {
+				let (was_error, unencrypted_intercept) =
+					OwnerV3Helpers::check_error_response(&r.clone());
+				access.audit_result(&caller, &request, was_error);
+				if is_open_wallet && running_foreign {
+					OwnerV3Helpers::update_mask(mask, &r.clone());
+				}
+				if was_encrypted {
+					let res = OwnerV3Helpers::encrypt_response(
+						key.clone(),
+						&encrypted_req_id,
+						&unencrypted_intercept,
+					);
+					r = match res {
+						Ok(v) => v,
+						Err(v) => return Ok(v),
+					}
 				}
+				// intercept init_secure_api response (after encryption,
+				// in case it was an encrypted call to 'init_api_secure')
+				if is_init_secure_api {
+					OwnerV3Helpers::update_owner_api_shared_key(
+						key.clone(),
+						&unencrypted_intercept,
+						api.shared_key.lock().clone(),
+					);
+				}
+				Ok(r)
 			}
-			// intercept init_secure_api response (after encryption,
-			// in case it was an encrypted call to 'init_api_secure')
-			if is_init_secure_api {
-				OwnerV3Helpers::update_owner_api_shared_key(
-					key.clone(),
-					&unencrypted_intercept,
-					api.shared_key.lock().clone(),
-				);
+			MaybeReply::DontReply => {
+				// Since it's http, we need to return something. We return [] because jsonrpc
+				// clients will parse it as an empty batch response.
+				Ok(serde_json::json!([]))
 			}
-			Ok(r)
-		}
-		MaybeReply::DontReply => {
-			// Since it's http, we need to return something. We return [] because jsonrpc
-			// clients will parse it as an empty batch response.
-			Ok(serde_json::json!([]))
 		}
 	}
-}
 
 	async fn handle_post_request(
-	req: Request<Body>,
-	key: Arc<Mutex<Option<SecretKey>>>,
-	mask: Arc<Mutex<Option<SecretKey>>>,
-	running_foreign: bool,
-	api: Arc<Owner<L, C, K>>,
-) -> Result<Response<Body>, Error> {
-	//Here is a wrapper to call future from that.
-	// Issue that we can't call future form future
-	let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
-		let future = Self::call_api(req, key, mask, running_foreign, api);
-		Box::pin(future)
-	};
-	let res = crate::executor::RunHandlerInThread::new(handler).await?;
+		req: Request<Body>,
+		key: Arc<Mutex<Option<SecretKey>>>,
+		mask: Arc<Mutex<Option<SecretKey>>>,
+		running_foreign: bool,
+		api: Arc<Owner<L, C, K>>,
+		access: Arc<OwnerApiAccess>,
+		caller: AccessKey,
+	) -> Result<Response<Body>, Error> {
+		let val: serde_json::Value = parse_body(req).await?;
+		let res = run_blocking(move || {
+			Self::call_api(val, key, mask, running_foreign, api, access, caller)
+		})
+		.await?;
 
-	//let res = Self::call_api(req, key, mask, running_foreign, api).await?;
-	Ok(json_response_pretty(&res))
-}
+		Ok(json_response_pretty(&res))
+	}
 }
 
 impl<L, C, K> api::Handler for OwnerAPIHandlerV3<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		let caller = match self.access.authenticate(&req) {
+			Some(k) => k,
+			None => return Box::pin(async { Ok(create_unauthorized_response()) }),
+		};
 		let key = self.shared_key.clone();
 		let mask = self.keychain_mask.clone();
 		let running_foreign = self.running_foreign;
 		let api = self.owner_api.clone();
+		let access = self.access.clone();
 
 		Box::pin(async move {
-			match Self::handle_post_request(req, key, mask, running_foreign, api).await {
+			match Self::handle_post_request(req, key, mask, running_foreign, api, access, caller)
+				.await
+			{
 				Ok(r) => Ok(r),
 				Err(e) => {
 					error!("Request Error: {:?}", e);
@@ -1326,10 +1501,10 @@ impl<L, C, K> api::Handler for OwnerAPIHandlerV3<L, C, K>
 }
 /// V2 API Handler/Wrapper for foreign functions
 pub struct ForeignAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Wallet instance
 	pub wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
@@ -1338,10 +1513,10 @@ pub struct ForeignAPIHandlerV2<L, C, K>
 }
 
 impl<L, C, K> ForeignAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	/// Create a new foreign API handler for GET methods
 	pub fn new(
@@ -1354,44 +1529,42 @@ impl<L, C, K> ForeignAPIHandlerV2<L, C, K>
 		}
 	}
 
-	async fn call_api(
-	req: Request<Body>,
-	api: Foreign<'static, L, C, K>,
-) -> Result<serde_json::Value, Error> {
-	let val: serde_json::Value = parse_body(req).await?;
-	match ForeignRpc::handle_request(&api, val) {
-		MaybeReply::Reply(r) => Ok(r),
-		MaybeReply::DontReply => {
-			// Since it's http, we need to return something. We return [] because jsonrpc
-			// clients will parse it as an empty batch response.
-			Ok(serde_json::json!([]))
+	fn call_api(
+		val: serde_json::Value,
+		api: Foreign<'static, L, C, K>,
+	) -> Result<serde_json::Value, Error> {
+		match ForeignRpc::handle_request(&api, val) {
+			MaybeReply::Reply(r) => Ok(r),
+			MaybeReply::DontReply => {
+				// Since it's http, we need to return something. We return [] because jsonrpc
+				// clients will parse it as an empty batch response.
+				Ok(serde_json::json!([]))
+			}
 		}
 	}
-}
 
-async fn handle_post_request(
-	req: Request<Body>,
-	mask: Option<SecretKey>,
-	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
-) -> Result<Response<Body>, Error> {
-	let api = Foreign::new(wallet, mask, Some(check_middleware));
-
-	//Here is a wrapper to call future from that.
-	// Issue that we can't call future form future
-	let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
-		let future = Self::call_api(req, api);
-		Box::pin(future)
-	};
-	let res = crate::executor::RunHandlerInThread::new(handler).await?;
-	Ok(json_response_pretty(&res))
-}
+	async fn handle_post_request(
+		req: Request<Body>,
+		mask: Option<SecretKey>,
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	) -> Result<Response<Body>, Error> {
+		let mut api = Foreign::new(wallet, mask, Some(check_middleware));
+		api.approval_queue = true;
+
+		let val: serde_json::Value = parse_body(req).await?;
+		let method = val["method"].as_str().unwrap_or("").to_string();
+		let started = Instant::now();
+		let res = run_blocking(move || Self::call_api(val, api)).await;
+		metrics::record_foreign_call(&method, started.elapsed(), &res);
+		Ok(json_response_pretty(&res?))
+	}
 }
 
 impl<L, C, K> api::Handler for ForeignAPIHandlerV2<L, C, K>
-	where
-		L: WalletLCProvider<'static, C, K> + 'static,
-		C: NodeClient + 'static,
-		K: Keychain + 'static,
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
 {
 	fn post(&self, req: Request<Body>) -> ResponseFuture {
 		let mask = self.keychain_mask.lock().clone();
@@ -1416,8 +1589,8 @@ impl<L, C, K> api::Handler for ForeignAPIHandlerV2<L, C, K>
 // Utility to serialize a struct into JSON and produce a sensible Response
 // out of it.
 fn _json_response<T>(s: &T) -> Response<Body>
-	where
-		T: Serialize,
+where
+	T: Serialize,
 {
 	match serde_json::to_string(s) {
 		Ok(json) => response(StatusCode::OK, json),
@@ -1430,8 +1603,8 @@ fn _json_response<T>(s: &T) -> Response<Body>
 
 // pretty-printed version of above
 fn json_response_pretty<T>(s: &T) -> Response<Body>
-	where
-		T: Serialize,
+where
+	T: Serialize,
 {
 	match serde_json::to_string_pretty(s) {
 		Ok(json) => response(StatusCode::OK, json),
@@ -1454,6 +1627,22 @@ fn create_error_response(e: Error) -> Response<Body> {
 		.unwrap()
 }
 
+fn create_unauthorized_response() -> Response<Body> {
+	Response::builder()
+		.status(StatusCode::UNAUTHORIZED)
+		.header(
+			hyper::header::WWW_AUTHENTICATE,
+			MWC_OWNER_BASIC_REALM.clone(),
+		)
+		.header("access-control-allow-origin", "*")
+		.header(
+			"access-control-allow-headers",
+			"Content-Type, Authorization",
+		)
+		.body("Unauthorized".into())
+		.unwrap()
+}
+
 fn create_ok_response(json: &str) -> Response<Body> {
 	Response::builder()
 		.status(StatusCode::OK)
@@ -1488,8 +1677,8 @@ fn response<T: Into<Body>>(status: StatusCode, text: T) -> Response<Body> {
 }
 
 async fn parse_body<T>(req: Request<Body>) -> Result<T, Error>
-	where
-			for<'de> T: Deserialize<'de> + Send + 'static,
+where
+	for<'de> T: Deserialize<'de> + Send + 'static,
 {
 	let body = body::to_bytes(req.into_body())
 		.await
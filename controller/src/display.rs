@@ -18,7 +18,8 @@ use crate::libwallet::swap::fsm::state::StateEtaInfo;
 use crate::libwallet::swap::swap;
 use crate::libwallet::swap::types::{Action, Role};
 use crate::libwallet::{
-	AcctPathMapping, Error, OutputCommitMapping, OutputStatus, TxLogEntry, WalletInfo,
+	AcctPathMapping, BatchSendResult, Contact, Error, ExpiredTx, FeeEstimate, Invoice,
+	OutputCommitMapping, OutputStatus, PendingSlate, SpendPolicyStatus, TxLogEntry, WalletInfo,
 };
 
 use crate::util;
@@ -58,7 +59,10 @@ pub fn outputs(
 		bMG->"Coinbase?",
 		bMG->"# Confirms",
 		bMG->"Value",
-		bMG->"Tx"
+		bMG->"Tx",
+		bMG->"Frozen?",
+		bMG->"Note",
+		bMG->"Tags"
 	]);
 
 	for m in outputs {
@@ -83,6 +87,9 @@ pub fn outputs(
 			None => "".to_owned(),
 			Some(t) => t.to_string(),
 		};
+		let frozen = format!("{}", m.output.frozen);
+		let note = m.output.note.clone().unwrap_or_default();
+		let tags = m.output.tags.join(", ");
 
 		if dark_background_color_scheme {
 			table.add_row(row![
@@ -95,6 +102,9 @@ pub fn outputs(
 				bFB->num_confirmations,
 				bFG->value,
 				bFC->tx,
+				bFR->frozen,
+				bFC->note,
+				bFC->tags,
 			]);
 		} else {
 			table.add_row(row![
@@ -107,6 +117,9 @@ pub fn outputs(
 				bFB->num_confirmations,
 				bFG->value,
 				bFD->tx,
+				bFR->frozen,
+				bFD->note,
+				bFD->tags,
 			]);
 		}
 	}
@@ -156,6 +169,7 @@ pub fn txs(
 			bMG->"Address",
 			bMG->"Creation Time",
 			bMG->"TTL Cutoff Height",
+			bMG->"Lock Height",
 			bMG->"Confirmed?",
 			bMG->"Height",
 			bMG->"Confirmation Time",
@@ -168,6 +182,7 @@ pub fn txs(
 			bMG->"Payment \nProof",
 			bMG->"Kernel",
 			bMG->"Tx \nData",
+			bMG->"Note",
 		]);
 	} else {
 		// 'short' format is used by mwc 713 wallet
@@ -207,6 +222,10 @@ pub fn txs(
 			Some(b) => format!("{}", b),
 			None => "None".to_owned(),
 		};
+		let lock_height = match t.lock_height {
+			Some(b) => format!("{}", b),
+			None => "None".to_owned(),
+		};
 		let confirmation_ts = match t.confirmation_ts {
 			Some(m) => format!("{}", m.format("%Y-%m-%d %H:%M:%S")),
 			None => "None".to_owned(),
@@ -241,6 +260,11 @@ pub fn txs(
 			Some(e) => util::to_hex(e.0.to_vec()),
 			None => "None".to_owned(),
 		};
+		let note = match (&t.note, t.tags.is_empty()) {
+			(Some(n), false) => format!("{} [{}]", n, t.tags.join(", ")),
+			(None, false) => format!("[{}]", t.tags.join(", ")),
+			(n, true) => n.clone().unwrap_or_default(),
+		};
 		let payment_proof = if has_proof(t) {
 			"Yes".to_owned()
 		} else {
@@ -256,6 +280,7 @@ pub fn txs(
 					bFC->address,
 					bFB->creation_ts,
 					bFB->ttl_cutoff_height,
+					bFB->lock_height,
 					bFC->confirmed,
 					bFC->height,
 					bFB->confirmation_ts,
@@ -268,6 +293,7 @@ pub fn txs(
 					bfG->payment_proof,
 					bFB->kernel_excess,
 					bFb->tx_data,
+					bFC->note,
 				]);
 			} else {
 				if t.confirmed {
@@ -277,6 +303,8 @@ pub fn txs(
 						bFD->slate_id,
 						bFD->address,
 						bFB->creation_ts,
+						bFB->ttl_cutoff_height,
+						bFB->lock_height,
 						bFg->confirmed,
 						bFg->height,
 						bFB->confirmation_ts,
@@ -289,6 +317,7 @@ pub fn txs(
 						bfG->payment_proof,
 						bFB->kernel_excess,
 						bFB->tx_data,
+						bFD->note,
 					]);
 				} else {
 					table.add_row(row![
@@ -297,6 +326,8 @@ pub fn txs(
 						bFD->slate_id,
 						bFD->address,
 						bFB->creation_ts,
+						bFB->ttl_cutoff_height,
+						bFB->lock_height,
 						bFR->confirmed,
 						bFR->height,
 						bFB->confirmation_ts,
@@ -309,6 +340,7 @@ pub fn txs(
 						bfG->payment_proof,
 						bFB->kernel_excess,
 						bFB->tx_data,
+						bFD->note,
 					]);
 				}
 			}
@@ -347,12 +379,14 @@ pub fn info(
 	account: &str,
 	wallet_info: &WalletInfo,
 	validated: bool,
+	node_url: &str,
 	dark_background_color_scheme: bool,
 ) {
 	println!(
 		"\n____ Wallet Summary Info - Account '{}' as of height {} ____\n",
 		account, wallet_info.last_confirmed_height,
 	);
+	println!("Node: {}\n", node_url);
 
 	let mut table = table!();
 
@@ -436,8 +470,7 @@ pub fn estimate(
 	amount: u64,
 	strategies: Vec<(
 		&str, // strategy
-		u64,  // total amount to be locked
-		u64,  // fee
+		FeeEstimate,
 	)>,
 	dark_background_color_scheme: bool,
 ) {
@@ -452,20 +485,367 @@ pub fn estimate(
 		bMG->"Selection strategy",
 		bMG->"Fee",
 		bMG->"Will be locked",
+		bMG->"Inputs",
+		bMG->"Outputs",
 	]);
 
-	for (strategy, total, fee) in strategies {
+	let mut max_fee = None;
+	for (strategy, e) in &strategies {
+		let fee = if e.exceeds_max_fee() {
+			format!("{} (over the limit)", amount_to_hr_string(e.fee, false))
+		} else {
+			amount_to_hr_string(e.fee, false)
+		};
 		if dark_background_color_scheme {
 			table.add_row(row![
 				bFC->strategy,
-				FR->amount_to_hr_string(fee, false),
-				FY->amount_to_hr_string(total, false),
+				FR->fee,
+				FY->amount_to_hr_string(e.total, false),
+				FB->e.num_inputs,
+				FB->e.num_outputs,
 			]);
 		} else {
 			table.add_row(row![
 				bFD->strategy,
-				FR->amount_to_hr_string(fee, false),
-				FY->amount_to_hr_string(total, false),
+				FR->fee,
+				FY->amount_to_hr_string(e.total, false),
+				FD->e.num_inputs,
+				FD->e.num_outputs,
+			]);
+		}
+		max_fee = max_fee.or(e.max_fee);
+	}
+	table.printstd();
+	if let Some(max_fee) = max_fee {
+		println!(
+			"Fee policy rejects the transactions with fee over {}",
+			amount_to_hr_string(max_fee, false)
+		);
+	}
+	println!();
+}
+
+/// Display the outcome of a batch payout
+pub fn batch_send_results(results: &Vec<BatchSendResult>, dark_background_color_scheme: bool) {
+	let sent = results.iter().filter(|r| r.is_ok()).count();
+	println!(
+		"\nBatch payout: {} of {} payments sent\n",
+		sent,
+		results.len()
+	);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Destination",
+		bMG->"Amount",
+		bMG->"Fee",
+		bMG->"Slate ID",
+		bMG->"Status",
+	]);
+
+	for r in results {
+		let slate_id = match r.slate_id {
+			Some(id) => id.to_string(),
+			None => "None".to_owned(),
+		};
+		let status = match &r.error {
+			Some(e) => format!("Failed: {}", e),
+			None => "Sent".to_owned(),
+		};
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFC->r.dest,
+				FY->amount_to_hr_string(r.amount, false),
+				FR->amount_to_hr_string(r.fee, false),
+				bFB->slate_id,
+				bFC->status,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->r.dest,
+				FY->amount_to_hr_string(r.amount, false),
+				FR->amount_to_hr_string(r.fee, false),
+				bFB->slate_id,
+				bFD->status,
+			]);
+		}
+	}
+	table.printstd();
+	println!();
+}
+
+/// Display invoices issued by the wallet
+pub fn invoices(cur_height: u64, invoices: &Vec<Invoice>, dark_background_color_scheme: bool) {
+	println!(
+		"\n____ Invoices - Block Height: {} ____\n",
+		cur_height.to_string().magenta()
+	);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Slate ID",
+		bMG->"Amount",
+		bMG->"Reference",
+		bMG->"Memo",
+		bMG->"Created",
+		bMG->"Expiry Height",
+		bMG->"Status",
+		bMG->"Paid",
+	]);
+
+	for i in invoices {
+		let reference = i.reference.clone().unwrap_or("None".to_owned());
+		let memo = i.memo.clone().unwrap_or("None".to_owned());
+		let creation_ts = format!("{}", i.creation_ts.format("%Y-%m-%d %H:%M:%S"));
+		let expiry = match i.expiry_height {
+			Some(h) => h.to_string(),
+			None => "None".to_owned(),
+		};
+		let paid_ts = match i.paid_ts {
+			Some(t) => format!("{}", t.format("%Y-%m-%d %H:%M:%S")),
+			None => "None".to_owned(),
+		};
+		let status = format!("{}", i.status);
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFB->i.id,
+				FY->amount_to_hr_string(i.amount, false),
+				bFC->reference,
+				bFC->memo,
+				bFB->creation_ts,
+				bFB->expiry,
+				bFC->status,
+				bFB->paid_ts,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->i.id,
+				FY->amount_to_hr_string(i.amount, false),
+				bFD->reference,
+				bFD->memo,
+				bFB->creation_ts,
+				bFB->expiry,
+				bFD->status,
+				bFB->paid_ts,
+			]);
+		}
+	}
+	table.printstd();
+	println!();
+}
+
+/// Display incoming slates from the approval queue
+pub fn pending_slates(pending: &Vec<PendingSlate>, dark_background_color_scheme: bool) {
+	println!("\n____ Slates Waiting For Approval ____\n",);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Slate ID",
+		bMG->"Request",
+		bMG->"Amount",
+		bMG->"Sender",
+		bMG->"Received",
+		bMG->"Expires",
+		bMG->"Status",
+	]);
+
+	for p in pending {
+		let sender = p.address.clone().unwrap_or("http".to_owned());
+		let creation_ts = format!("{}", p.creation_ts.format("%Y-%m-%d %H:%M:%S"));
+		let expiry_ts = format!("{}", p.expiry_ts.format("%Y-%m-%d %H:%M:%S"));
+		let request = format!("{}", p.request);
+		let status = format!("{}", p.status);
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFB->p.id,
+				bFC->request,
+				FY->amount_to_hr_string(p.slate.amount, false),
+				bFC->sender,
+				bFB->creation_ts,
+				bFB->expiry_ts,
+				bFC->status,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->p.id,
+				bFD->request,
+				FY->amount_to_hr_string(p.slate.amount, false),
+				bFD->sender,
+				bFB->creation_ts,
+				bFB->expiry_ts,
+				bFD->status,
+			]);
+		}
+	}
+	table.printstd();
+	println!();
+}
+
+/// Display the spending policy of the account with the amounts already sent
+pub fn spend_policy(status: &SpendPolicyStatus, dark_background_color_scheme: bool) {
+	println!(
+		"\n____ Spending Policy - Account '{}' ____\n",
+		status.acct_name
+	);
+
+	let limit = |max: Option<u64>| match max {
+		Some(m) => amount_to_hr_string(m, false),
+		None => "No limit".to_owned(),
+	};
+	let policy = &status.policy;
+	let rows = vec![
+		(
+			"Max per transaction",
+			limit(policy.max_per_tx),
+			"".to_owned(),
+		),
+		(
+			"Max in 24 hours",
+			limit(policy.max_24h),
+			format!("{} sent", amount_to_hr_string(status.spent_24h, false)),
+		),
+		(
+			"Max in 7 days",
+			limit(policy.max_7d),
+			format!("{} sent", amount_to_hr_string(status.spent_7d, false)),
+		),
+		(
+			"New destination cool-down",
+			format!("{} sec", policy.new_destination_cooldown_secs.unwrap_or(0)),
+			"".to_owned(),
+		),
+	];
+
+	let mut table = table!();
+	for (name, value, sent) in rows {
+		if dark_background_color_scheme {
+			table.add_row(row![bFG->name, FY->value, FB->sent]);
+		} else {
+			table.add_row(row![bFD->name, FB->value, FD->sent]);
+		}
+	}
+	match &policy.allowed_destinations {
+		None => {
+			table.add_row(row![bFG->"Allowed destinations", FY->"Any", ""]);
+		}
+		Some(list) => {
+			for d in list {
+				let added_ts = format!("added {}", d.added_ts.format("%Y-%m-%d %H:%M:%S"));
+				table.add_row(row![bFG->"Allowed destination", FC->d.address, FB->added_ts]);
+			}
+		}
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display the address book
+pub fn contacts(contacts: &Vec<Contact>, dark_background_color_scheme: bool) {
+	println!("\n____ Address Book ____\n",);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Name",
+		bMG->"Method",
+		bMG->"Address",
+		bMG->"Slate Version",
+		bMG->"Proof Address",
+	]);
+
+	for c in contacts {
+		let name = format!("@{}", c.name);
+		let slate_version = match c.slate_version {
+			Some(v) => v.to_string(),
+			None => "Auto".to_owned(),
+		};
+		let proof_address = c.proof_address.clone().unwrap_or_default();
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFY->name,
+				bFC->c.method,
+				bFC->c.address,
+				bFB->slate_version,
+				bFB->proof_address,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->name,
+				bFD->c.method,
+				bFD->c.address,
+				bFB->slate_version,
+				bFB->proof_address,
+			]);
+		}
+	}
+	table.printstd();
+	println!();
+}
+
+/// Display the transactions with expired TTL and the outputs their cancellation changes
+pub fn expired_txs(cur_height: u64, expired: &Vec<ExpiredTx>, dark_background_color_scheme: bool) {
+	println!(
+		"\n____ Transactions with expired TTL at height {} ____\n",
+		cur_height
+	);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Id",
+		bMG->"Type",
+		bMG->"Shared Transaction Id",
+		bMG->"TTL Cutoff Height",
+		bMG->"Net Difference",
+		bMG->"Inputs To Unlock",
+		bMG->"Outputs To Delete",
+	]);
+
+	for e in expired {
+		let t = &e.tx;
+		let id = format!("{}", t.id);
+		let entry_type = format!("{}", t.tx_type);
+		let slate_id = match t.tx_slate_id {
+			Some(m) => format!("{}", m),
+			None => "None".to_owned(),
+		};
+		let ttl_cutoff_height = t
+			.ttl_cutoff_height
+			.map(|h| h.to_string())
+			.unwrap_or_default();
+		let net_diff = if t.amount_credited >= t.amount_debited {
+			core::amount_to_hr_string(t.amount_credited - t.amount_debited, true)
+		} else {
+			format!(
+				"-{}",
+				core::amount_to_hr_string(t.amount_debited - t.amount_credited, true)
+			)
+		};
+		let inputs = format!("{}", e.locked_inputs.len());
+		let outputs = format!("{}", e.unconfirmed_outputs.len());
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFC->id,
+				bFC->entry_type,
+				bFC->slate_id,
+				bFB->ttl_cutoff_height,
+				bFY->net_diff,
+				bFG->inputs,
+				bFR->outputs,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->id,
+				bFD->entry_type,
+				bFD->slate_id,
+				bFB->ttl_cutoff_height,
+				bFD->net_diff,
+				bFG->inputs,
+				bFR->outputs,
 			]);
 		}
 	}
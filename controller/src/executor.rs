@@ -1,119 +1,84 @@
-use crate::error::Error;
-use futures::task::{waker_ref, ArcWake, Context, Poll};
-use futures::Future;
-use std::pin::Pin;
+//! Runs the wallet calls of the API handlers. The calls are blocking, so they run on the
+//! tokio blocking thread pool of the API server runtime. The number of the calls that
+//! run at the same time is bounded and every call has a timeout, both come from the
+//! wallet config.
+
+use crate::error::{Error, ErrorKind};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
-use std::thread;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 
-// Custom executor example. It is not much related to this implementation because
-// rust future model was changed a lot.
-// https://rust-lang.github.io/async-book/02_execution/04_executor.html
-
-pub struct RunHandlerInThread {
-	// running flag is modifiable from worker thread and Poll.
-	running: Arc<AtomicBool>,
-	waker: Arc<RwLock<Option<std::task::Waker>>>,
-	// Need option because join require ownership transfer. That is why can't belong to 'self' dicertly
-	// (State, Result<Response<Body>>)  - resulting from API call as required by gotham
-	worker_thread: RwLock<Option<thread::JoinHandle<Result<serde_json::Value, Error>>>>,
-}
+/// Default maximum number of the wallet calls that run at the same time. Other calls
+/// are waiting.
+pub const API_CALL_THREADS: usize = 8;
 
-// We don't want to wake up. Our runner will be pretty dammy, but it is good enough for Rest API.
-struct FakeWaker {}
+/// Default time for a call to wait for a thread and run
+pub const API_CALL_TIMEOUT: Duration = Duration::from_secs(900);
 
-impl ArcWake for FakeWaker {
-	fn wake_by_ref(_arc_self: &Arc<Self>) {
-		// No wake needed
-	}
+lazy_static! {
+	static ref API_CALL_LIMITS: RwLock<(usize, Duration)> =
+		RwLock::new((API_CALL_THREADS, API_CALL_TIMEOUT));
+	// Sized at the first call
+	static ref API_CALL_SLOTS: Semaphore = Semaphore::new(API_CALL_LIMITS.read().unwrap().0);
 }
 
-impl RunHandlerInThread {
-	pub fn new<F>(handler: F) -> RunHandlerInThread
-	where
-		F: Send
-			+ Sync
-			+ 'static
-			+ FnOnce() -> Pin<Box<dyn std::future::Future<Output = Result<serde_json::Value, Error>>>>,
-	{
-		// 'self' variables
-		let running = Arc::new(AtomicBool::new(true));
-		let waker: Arc<RwLock<Option<std::task::Waker>>> = Arc::new(RwLock::new(None));
-
-		// thread variables to move
-		let thr_waker = waker.clone();
-		let thr_running = running.clone();
-
-		let worker_thread = thread::Builder::new()
-			.name("RunHandlerInThread".to_string())
-			.spawn(move || {
-				let mut future = handler();
-
-				let fw = Arc::new(FakeWaker {});
-				let waker = waker_ref(&fw);
-				let mut context = &mut Context::from_waker(&*waker);
-
-				let result: Option<Result<serde_json::Value, Error>>;
-
-				loop {
-					match future.as_mut().poll(&mut context) {
-						Poll::Pending => {
-							thread::sleep(Duration::from_millis(200));
-							continue;
-						}
-						Poll::Ready(res) => {
-							result = Some(res);
-							break;
-						}
-					}
-				}
-
-				thr_running.store(false, Ordering::Relaxed);
+/// Set the maximum number of the wallet calls that run at the same time and the call
+/// timeout. The number of calls is applied only if it is set before the first call.
+pub fn set_api_call_limits(threads: usize, timeout: Duration) {
+	*API_CALL_LIMITS.write().unwrap() = (std::cmp::max(threads, 1), timeout);
+}
 
-				if let Some(waker) = thr_waker.write().unwrap().take() {
-					waker.wake();
-				}
-				result.unwrap()
-			})
-			.unwrap();
+// Cancels the call when the request future is dropped: the client disconnected or the
+// call timed out
+struct CancelOnDrop(Arc<AtomicBool>);
 
-		Self {
-			running,
-			waker,
-			worker_thread: RwLock::new(Some(worker_thread)),
-		}
+impl Drop for CancelOnDrop {
+	fn drop(&mut self) {
+		self.0.store(true, Ordering::Relaxed);
 	}
 }
 
-impl Future for RunHandlerInThread {
-	type Output = Result<serde_json::Value, Error>;
-
-	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		if self.waker.read().unwrap().is_none() {
-			// Update current task. at the first polling.
-			// Task is needed by thread to notify future executor that job is done
-			self.waker.write().unwrap().replace(cx.waker().clone());
-		}
-
-		if self.running.load(Ordering::Relaxed) {
-			// We are still running.
-			Poll::Pending
-		} else {
-			// The job is done. From the thread we should be able to reprieve the results.
-			// Because of all ownership staff we can process this once only.
-			// Result Must be OK with Ready or Error.
-			// In this case futures executor guarantee call it once and satisfy get tread data once limitation
-
-			// JoinHandle::join required ownership transfer. That is why it can be done once.
-			if let Some(thr_info) = self.worker_thread.write().unwrap().take() {
-				// Gettign results from the task
-				let result = thr_info.join().unwrap();
-				Poll::Ready(result)
-			} else {
-				// Likely double processing. See comments above.
-				panic!("Background thread for REST API died or double processed!");
+/// Run the wallet call on the blocking thread pool. The call is skipped if the request is
+/// cancelled before it started.
+///
+/// A timeout doesn't interrupt a started call: the request gets the timeout error, but the
+/// call keeps running on its thread and keeps its slot until it finishes, then the result
+/// is dropped. Calls that hang hold the slots, so the other calls are waiting for them.
+pub async fn run_blocking<F>(handler: F) -> Result<serde_json::Value, Error>
+where
+	F: FnOnce() -> Result<serde_json::Value, Error> + Send + 'static,
+{
+	let timeout = API_CALL_LIMITS.read().unwrap().1;
+	let cancelled = Arc::new(AtomicBool::new(false));
+	let _cancel = CancelOnDrop(cancelled.clone());
+
+	let call = async move {
+		// The permit is released by the thread, so a timed out call still holds its slot
+		let permit = API_CALL_SLOTS.acquire().await;
+		tokio::task::spawn_blocking(move || {
+			let _permit = permit;
+			if cancelled.load(Ordering::Relaxed) {
+				return Err(ErrorKind::GenericError("API call is cancelled".to_string()).into());
 			}
+			handler()
+		})
+		.await
+		.map_err(|e| ErrorKind::GenericError(format!("API call failed, {}", e)))?
+	};
+
+	match tokio::time::timeout(timeout, call).await {
+		Ok(res) => res,
+		Err(_) => {
+			warn!(
+				"API call didn't finish in {} seconds, it keeps running",
+				timeout.as_secs()
+			);
+			Err(ErrorKind::GenericError(format!(
+				"API call timed out after {} seconds",
+				timeout.as_secs()
+			))
+			.into())
 		}
 	}
 }
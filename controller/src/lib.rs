@@ -39,5 +39,8 @@ pub mod controller;
 pub mod display;
 mod error;
 pub mod executor;
+pub mod metrics;
+pub mod owner_access;
+pub mod owner_ws;
 
 pub use crate::error::{Error, ErrorKind};
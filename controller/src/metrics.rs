@@ -0,0 +1,504 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallet metrics in the Prometheus text format, served at `/metrics` by the owner
+//! listener. Wallet data is read on every scrape, the foreign API calls are counted
+//! by the API handlers.
+
+use crate::api::{self, ResponseFuture};
+use crate::executor::run_blocking;
+use crate::impls::{get_mwcmqs_brocker, is_mwcmqs_connected};
+use crate::keychain::Keychain;
+use crate::libwallet::api_impl::owner_swap;
+use crate::libwallet::internal::updater;
+use crate::libwallet::{get_updater_stats, NodeClient, OutputStatus, WalletInst, WalletLCProvider};
+use crate::owner_access::OwnerApiAccess;
+use crate::util::secp::key::SecretKey;
+use crate::util::Mutex;
+use crate::Error;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Minimum confirmations for the reported balances
+const METRICS_MIN_CONFIRMATIONS: u64 = 10;
+
+/// Foreign API method label for the methods that the API doesn't have
+const UNKNOWN_METHOD: &str = "unknown";
+
+#[derive(Default)]
+struct CallStats {
+	count: u64,
+	errors: u64,
+	seconds: f64,
+}
+
+lazy_static! {
+	// Foreign API calls by method
+	static ref FOREIGN_API_CALLS: RwLock<BTreeMap<String, CallStats>> =
+		RwLock::new(BTreeMap::new());
+}
+
+/// Count the foreign API call. JSON-RPC errors count as failed calls.
+pub fn record_foreign_call(
+	method: &str,
+	duration: Duration,
+	res: &Result<serde_json::Value, Error>,
+) {
+	let (method, failed) = match res {
+		Ok(r) if r["error"]["code"].as_i64() == Some(-32601) => (UNKNOWN_METHOD, true),
+		Ok(r) => (
+			method,
+			!r["error"].is_null() || !r["result"]["Err"].is_null(),
+		),
+		Err(_) => (method, true),
+	};
+	let mut calls = FOREIGN_API_CALLS.write().unwrap();
+	let stats = calls.entry(method.to_string()).or_default();
+	stats.count += 1;
+	if failed {
+		stats.errors += 1;
+	}
+	stats.seconds += duration.as_secs_f64();
+}
+
+// Writer of the Prometheus text format
+struct MetricsWriter {
+	out: String,
+}
+
+impl MetricsWriter {
+	fn new() -> Self {
+		MetricsWriter { out: String::new() }
+	}
+
+	fn family(&mut self, name: &str, kind: &str, help: &str) {
+		let _ = writeln!(self.out, "# HELP {} {}", name, help);
+		let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+	}
+
+	fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+		self.out.push_str(name);
+		if !labels.is_empty() {
+			let labels: Vec<String> = labels
+				.iter()
+				.map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+				.collect();
+			let _ = write!(self.out, "{{{}}}", labels.join(","));
+		}
+		let _ = writeln!(self.out, " {}", value);
+	}
+}
+
+fn escape_label(value: &str) -> String {
+	value
+		.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+}
+
+fn bool_value(value: bool) -> f64 {
+	if value {
+		1.0
+	} else {
+		0.0
+	}
+}
+
+// Balances, outputs and heights. Returns an error if the wallet is not open.
+fn write_wallet_metrics<L, C, K>(
+	out: &mut MetricsWriter,
+	wallet: &Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let mut w_lock = wallet.lock();
+	let lc = w_lock.lc_provider()?;
+	let w = lc.wallet_inst()?;
+
+	let accounts: Vec<_> = w.acct_path_iter().collect();
+	let mut balances = vec![];
+	for acct in accounts {
+		let info = updater::retrieve_info(&mut **w, &acct.path, METRICS_MIN_CONFIRMATIONS)?;
+		balances.push((acct.label, info));
+	}
+
+	let mut outputs: BTreeMap<String, u64> = [
+		OutputStatus::Unconfirmed,
+		OutputStatus::Unspent,
+		OutputStatus::Locked,
+		OutputStatus::Spent,
+	]
+	.iter()
+	.map(|s| (s.to_string(), 0))
+	.collect();
+	for o in w.iter() {
+		*outputs.entry(o.status.to_string()).or_insert(0) += 1;
+	}
+
+	let last_scanned_height = w.last_confirmed_height()?;
+	let client = w.w2n_client().clone();
+	// The node is queried without holding the wallet
+	drop(w_lock);
+	let node_tip = client.get_chain_tip().ok();
+
+	out.family(
+		"mwc_wallet_balance_nanomwc",
+		"gauge",
+		"Account balance by status, in nanoMWC",
+	);
+	for (account, info) in &balances {
+		for (status, amount) in &[
+			("total", info.total),
+			("awaiting_finalization", info.amount_awaiting_finalization),
+			("awaiting_confirmation", info.amount_awaiting_confirmation),
+			("immature", info.amount_immature),
+			("spendable", info.amount_currently_spendable),
+			("locked", info.amount_locked),
+		] {
+			out.sample(
+				"mwc_wallet_balance_nanomwc",
+				&[("account", account), ("status", status)],
+				*amount as f64,
+			);
+		}
+	}
+
+	out.family(
+		"mwc_wallet_outputs",
+		"gauge",
+		"Number of the outputs by status",
+	);
+	for (status, count) in &outputs {
+		out.sample("mwc_wallet_outputs", &[("status", status)], *count as f64);
+	}
+
+	out.family(
+		"mwc_wallet_last_scanned_height",
+		"gauge",
+		"Height of the last block that the wallet is synced to",
+	);
+	out.sample(
+		"mwc_wallet_last_scanned_height",
+		&[],
+		last_scanned_height as f64,
+	);
+
+	out.family(
+		"mwc_wallet_node_up",
+		"gauge",
+		"1 if the node responded to the chain tip request",
+	);
+	out.sample("mwc_wallet_node_up", &[], bool_value(node_tip.is_some()));
+	if let Some((height, _, _)) = node_tip {
+		out.family(
+			"mwc_wallet_node_tip_height",
+			"gauge",
+			"Chain tip height of the node",
+		);
+		out.sample("mwc_wallet_node_tip_height", &[], height as f64);
+	}
+	Ok(())
+}
+
+// Active swap trades by state. Reading the trades needs the keychain.
+fn write_swap_metrics<L, C, K>(
+	out: &mut MetricsWriter,
+	wallet: &Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let swaps = owner_swap::swap_list(wallet.clone(), keychain_mask, false)?;
+	let mut states: BTreeMap<String, u64> = BTreeMap::new();
+	for swap in swaps.iter().filter(|s| !s.state.is_final_state()) {
+		*states.entry(format!("{:?}", swap.state)).or_insert(0) += 1;
+	}
+
+	out.family(
+		"mwc_wallet_swaps_active",
+		"gauge",
+		"Number of the swap trades that are not finished, by state",
+	);
+	for (state, count) in &states {
+		out.sample(
+			"mwc_wallet_swaps_active",
+			&[("state", state)],
+			*count as f64,
+		);
+	}
+	Ok(())
+}
+
+fn write_process_metrics(out: &mut MetricsWriter) {
+	let updater = get_updater_stats();
+	out.family(
+		"mwc_wallet_updater_runs_total",
+		"counter",
+		"Number of the wallet updates by the updater loop",
+	);
+	out.sample("mwc_wallet_updater_runs_total", &[], updater.runs as f64);
+	out.family(
+		"mwc_wallet_updater_failures_total",
+		"counter",
+		"Number of the failed wallet updates",
+	);
+	out.sample(
+		"mwc_wallet_updater_failures_total",
+		&[],
+		updater.failures as f64,
+	);
+	out.family(
+		"mwc_wallet_updater_last_duration_seconds",
+		"gauge",
+		"Duration of the last wallet update",
+	);
+	out.sample(
+		"mwc_wallet_updater_last_duration_seconds",
+		&[],
+		updater.last_duration.as_secs_f64(),
+	);
+
+	{
+		let calls = FOREIGN_API_CALLS.read().unwrap();
+		out.family(
+			"mwc_wallet_foreign_api_requests_total",
+			"counter",
+			"Number of the foreign API requests by method",
+		);
+		for (method, stats) in calls.iter() {
+			out.sample(
+				"mwc_wallet_foreign_api_requests_total",
+				&[("method", method)],
+				stats.count as f64,
+			);
+		}
+		out.family(
+			"mwc_wallet_foreign_api_errors_total",
+			"counter",
+			"Number of the failed foreign API requests by method",
+		);
+		for (method, stats) in calls.iter() {
+			out.sample(
+				"mwc_wallet_foreign_api_errors_total",
+				&[("method", method)],
+				stats.errors as f64,
+			);
+		}
+		out.family(
+			"mwc_wallet_foreign_api_request_duration_seconds",
+			"summary",
+			"Latency of the foreign API requests by method",
+		);
+		for (method, stats) in calls.iter() {
+			out.sample(
+				"mwc_wallet_foreign_api_request_duration_seconds_sum",
+				&[("method", method)],
+				stats.seconds,
+			);
+			out.sample(
+				"mwc_wallet_foreign_api_request_duration_seconds_count",
+				&[("method", method)],
+				stats.count as f64,
+			);
+		}
+	}
+
+	out.family(
+		"mwc_wallet_mqs_listener_running",
+		"gauge",
+		"1 if the mwcmqs listener is running",
+	);
+	out.sample(
+		"mwc_wallet_mqs_listener_running",
+		&[],
+		bool_value(get_mwcmqs_brocker().is_some()),
+	);
+	out.family(
+		"mwc_wallet_mqs_connected",
+		"gauge",
+		"1 if the mwcmqs listener is connected to the server",
+	);
+	out.sample(
+		"mwc_wallet_mqs_connected",
+		&[],
+		bool_value(is_mwcmqs_connected()),
+	);
+}
+
+/// Render all wallet metrics
+pub fn render_metrics<L, C, K>(
+	wallet: &Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> String
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let mut out = MetricsWriter::new();
+
+	let mut wallet_out = MetricsWriter::new();
+	let wallet_open = match write_wallet_metrics(&mut wallet_out, wallet) {
+		Ok(_) => true,
+		Err(e) => {
+			debug!("Wallet metrics are not available, {}", e);
+			false
+		}
+	};
+	out.family("mwc_wallet_open", "gauge", "1 if the wallet is open");
+	out.sample("mwc_wallet_open", &[], bool_value(wallet_open));
+	if wallet_open {
+		out.out.push_str(&wallet_out.out);
+		let mut swap_out = MetricsWriter::new();
+		match write_swap_metrics(&mut swap_out, wallet, keychain_mask) {
+			Ok(_) => out.out.push_str(&swap_out.out),
+			Err(e) => debug!("Swap metrics are not available, {}", e),
+		}
+	}
+
+	write_process_metrics(&mut out);
+	out.out
+}
+
+/// Prometheus metrics handler of the owner listener
+pub struct MetricsHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Wallet instance
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	/// Keychain mask
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	/// API keys
+	access: Arc<OwnerApiAccess>,
+}
+
+impl<L, C, K> MetricsHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Create a new metrics handler
+	pub fn new(
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+		access: Arc<OwnerApiAccess>,
+	) -> MetricsHandler<L, C, K> {
+		MetricsHandler {
+			wallet,
+			keychain_mask,
+			access,
+		}
+	}
+}
+
+impl<L, C, K> api::Handler for MetricsHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		if self.access.authenticate(&req).is_none() {
+			return Box::pin(async {
+				Ok(Response::builder()
+					.status(StatusCode::UNAUTHORIZED)
+					.body(Body::empty())
+					.unwrap())
+			});
+		}
+		let wallet = self.wallet.clone();
+		let keychain_mask = self.keychain_mask.clone();
+
+		Box::pin(async move {
+			let res = run_blocking(move || {
+				let mask = keychain_mask.lock().clone();
+				Ok(serde_json::Value::String(render_metrics(
+					&wallet,
+					mask.as_ref(),
+				)))
+			})
+			.await;
+			let mut res = match res {
+				Ok(serde_json::Value::String(metrics)) => Response::new(Body::from(metrics)),
+				other => {
+					let e = match other {
+						Err(e) => format!("{}", e),
+						Ok(_) => "unexpected result".to_string(),
+					};
+					error!("Unable to render the metrics, {}", e);
+					let mut r =
+						Response::new(Body::from(format!("Unable to render the metrics, {}", e)));
+					*r.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+					r
+				}
+			};
+			res.headers_mut().insert(
+				CONTENT_TYPE,
+				HeaderValue::from_static("text/plain; version=0.0.4"),
+			);
+			Ok(res)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn text_format() {
+		let mut out = MetricsWriter::new();
+		out.family("mwc_test", "gauge", "Test metric");
+		out.sample("mwc_test", &[("account", "a\"b\\c")], 1.5);
+		out.sample("mwc_test", &[], 2.0);
+		assert_eq!(
+			out.out,
+			"# HELP mwc_test Test metric\n# TYPE mwc_test gauge\n\
+			 mwc_test{account=\"a\\\"b\\\\c\"} 1.5\nmwc_test 2\n"
+		);
+	}
+
+	#[test]
+	fn foreign_calls() {
+		let method = "metrics_test_method";
+		let ok = Ok(serde_json::json!({"result": {"Ok": null}}));
+		let failed = Ok(serde_json::json!({"result": {"Err": {"GenericError": "x"}}}));
+		let unknown = Ok(serde_json::json!({"error": {"code": -32601}}));
+		record_foreign_call(method, Duration::from_millis(500), &ok);
+		record_foreign_call(method, Duration::from_millis(500), &failed);
+		record_foreign_call("metrics_test_bogus", Duration::from_millis(1), &unknown);
+
+		let calls = FOREIGN_API_CALLS.read().unwrap();
+		let stats = calls.get(method).unwrap();
+		assert_eq!(stats.count, 2);
+		assert_eq!(stats.errors, 1);
+		assert!((stats.seconds - 1.0).abs() < 1e-9);
+		assert!(calls.get("metrics_test_bogus").is_none());
+		assert!(calls.get(UNKNOWN_METHOD).unwrap().errors >= 1);
+	}
+}
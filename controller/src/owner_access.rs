@@ -0,0 +1,480 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Access control for the owner API listener. Requests are authenticated with the
+//! named API keys, every call is checked against the key permission and the
+//! privileged calls are written into the audit log.
+
+use crate::config::{OwnerApiConfig, OwnerApiKey, OwnerApiPermission};
+use crate::util::to_base64;
+use chrono::Utc;
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Request};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::RwLock;
+
+/// Basic auth user name of the owner API secret
+pub const OWNER_API_SECRET_KEY_ID: &str = "mwc";
+
+/// JSON-RPC error code of the denied calls
+const ACCESS_DENIED_CODE: i64 = -32098;
+
+lazy_static! {
+	static ref OWNER_API_CONFIG: RwLock<Option<OwnerApiConfig>> = RwLock::new(None);
+}
+
+/// Access config applied to the owner API listeners started after this call
+pub fn set_owner_api_config(config: Option<OwnerApiConfig>) {
+	*OWNER_API_CONFIG.write().unwrap() = config;
+}
+
+/// Current owner API access config
+pub fn get_owner_api_config() -> Option<OwnerApiConfig> {
+	OWNER_API_CONFIG.read().unwrap().clone()
+}
+
+/// Permission required to call the owner API method. Unknown methods are admin only.
+pub fn method_permission(method: &str) -> OwnerApiPermission {
+	known_method_permission(method).unwrap_or(OwnerApiPermission::Admin)
+}
+
+// Every owner API method is listed here, so a new method doesn't get a permission by accident
+fn known_method_permission(method: &str) -> Option<OwnerApiPermission> {
+	let permission = match method {
+		"accounts"
+		| "get_stored_tx"
+		| "node_height"
+		| "retrieve_outputs"
+		| "retrieve_summary_info"
+		| "retrieve_txs"
+		| "retrieve_payment_proof"
+		| "verify_payment_proof"
+		| "export_payment_proof_document"
+		| "verify_payment_proof_document"
+		| "verify_slate_messages"
+		| "get_public_proof_address"
+		| "proof_address_from_onion_v3"
+		| "get_top_level_directory"
+		| "get_updater_messages"
+		| "retrieve_invoices"
+		| "retrieve_pending_slates"
+		| "retrieve_spend_policy"
+		| "retrieve_contacts"
+		| "get_contact"
+		| "retrieve_expired_txs"
+		| "estimate_fee"
+		| "scan_status"
+		| "init_secure_api"
+		| "subscribe"
+		| "unsubscribe" => OwnerApiPermission::Read,
+		// Parked slates are the incoming payments and the payments of the issued invoices
+		"issue_invoice_tx"
+		| "cancel_invoice"
+		| "approve_pending_slate"
+		| "reject_pending_slate" => OwnerApiPermission::Receive,
+		// Active account is the source of the payments, the updater changes the wallet data.
+		// Frozen outputs, labels, contacts and the running scan change what the payments do.
+		"init_send_tx" | "process_invoice_tx" | "tx_lock_outputs" | "finalize_tx" | "post_tx"
+		| "cancel_tx" | "transfer" | "set_active_account" | "start_updater" | "stop_updater"
+		| "freeze_output" | "unfreeze_output" | "label_output" | "label_tx" | "save_contact"
+		| "delete_contact" | "pause_scan" | "cancel_scan" => OwnerApiPermission::Spend,
+		// mnemonic, password, wallet lifecycle (open and close), scan and the wallet policies
+		"create_account_path"
+		| "scan"
+		| "create_config"
+		| "create_wallet"
+		| "open_wallet"
+		| "close_wallet"
+		| "get_mnemonic"
+		| "change_password"
+		| "delete_wallet"
+		| "set_top_level_directory"
+		| "set_tor_config"
+		| "set_receive_policy"
+		| "set_spend_policy"
+		| "clear_spend_policy"
+		| "set_auto_cancel_expired"
+		| "set_fee_policy" => OwnerApiPermission::Admin,
+		_ => return None,
+	};
+	Some(permission)
+}
+
+/// Key that authenticated the request
+#[derive(Debug, Clone)]
+pub struct AccessKey {
+	/// Key id
+	pub id: String,
+	/// Permission of the key
+	pub permission: OwnerApiPermission,
+	/// Maximum amount of a single transaction
+	pub spend_limit: Option<u64>,
+}
+
+/// Access control state of the owner API listener
+pub struct OwnerApiAccess {
+	// Expected 'Authorization' header values with the keys
+	keys: Vec<(String, AccessKey)>,
+	audit_log_path: Option<String>,
+}
+
+impl OwnerApiAccess {
+	/// Build the access control from the config and the owner API secret
+	pub fn new(config: Option<OwnerApiConfig>, api_secret: Option<String>) -> Self {
+		let config = config.unwrap_or(OwnerApiConfig {
+			audit_log_path: None,
+			keys: None,
+		});
+		let mut keys: Vec<(String, AccessKey)> = config
+			.keys
+			.unwrap_or(vec![])
+			.into_iter()
+			.map(|k: OwnerApiKey| {
+				(
+					Self::basic_auth(&k.id, &k.secret),
+					AccessKey {
+						id: k.id,
+						permission: k.permission,
+						spend_limit: k.spend_limit,
+					},
+				)
+			})
+			.collect();
+		if !keys.is_empty() {
+			if let Some(secret) = api_secret {
+				keys.push((
+					Self::basic_auth(OWNER_API_SECRET_KEY_ID, &secret),
+					Self::owner_secret_key(),
+				));
+			}
+		}
+		OwnerApiAccess {
+			keys,
+			audit_log_path: config.audit_log_path,
+		}
+	}
+
+	/// True if the listener authenticates with the named keys. Otherwise the basic
+	/// auth middleware checks the owner API secret and the caller is an admin.
+	pub fn keys_enabled(&self) -> bool {
+		!self.keys.is_empty()
+	}
+
+	/// Find the key of the request. Returns None if the request is not authorized.
+	pub fn authenticate(&self, req: &Request<Body>) -> Option<AccessKey> {
+		if !self.keys_enabled() {
+			return Some(Self::owner_secret_key());
+		}
+		let auth = req.headers().get(AUTHORIZATION)?.as_bytes();
+		self.keys
+			.iter()
+			.find(|(expected, _)| secure_eq(expected.as_bytes(), auth))
+			.map(|(_, key)| key.clone())
+	}
+
+	/// Check a JSON-RPC request against the key permission. Returns the JSON-RPC
+	/// error response for the denied requests.
+	pub fn check_request(
+		&self,
+		key: &AccessKey,
+		val: &serde_json::Value,
+	) -> Result<(), serde_json::Value> {
+		let method = val["method"].as_str().unwrap_or("");
+		let required = method_permission(method);
+		let denied = if key.permission < required {
+			Some(format!(
+				"API key '{}' doesn't have the {:?} permission required by '{}'",
+				key.id, required, method
+			))
+		} else if required == OwnerApiPermission::Spend {
+			match (key.spend_limit, request_amount(method, &val["params"])) {
+				(Some(limit), Some(amount)) if amount > limit => Some(format!(
+					"Amount {} is over the spend limit {} of the API key '{}'",
+					amount, limit, key.id
+				)),
+				(Some(_), None) if amount_param(method).is_some() => Some(format!(
+					"Amount of '{}' is not found, the API key '{}' has the spend limit",
+					method, key.id
+				)),
+				_ => None,
+			}
+		} else {
+			None
+		};
+
+		match denied {
+			Some(reason) => {
+				warn!("Owner API call denied, {}", reason);
+				self.audit(key, method, &format!("denied: {}", reason));
+				Err(serde_json::json!({
+					"jsonrpc": "2.0",
+					"id": val["id"],
+					"error": {
+						"message": reason,
+						"code": ACCESS_DENIED_CODE
+					}
+				}))
+			}
+			None => Ok(()),
+		}
+	}
+
+	/// Record the result of the call into the audit log. Read only calls are skipped.
+	pub fn audit_result(&self, key: &AccessKey, val: &serde_json::Value, was_error: bool) {
+		let method = val["method"].as_str().unwrap_or("");
+		if method_permission(method) > OwnerApiPermission::Read {
+			self.audit(key, method, if was_error { "error" } else { "ok" });
+		}
+	}
+
+	fn audit(&self, key: &AccessKey, method: &str, outcome: &str) {
+		let path = match &self.audit_log_path {
+			Some(p) => p,
+			None => return,
+		};
+		let record = serde_json::json!({
+			"time": Utc::now().to_rfc3339(),
+			"key_id": key.id,
+			"method": method,
+			"outcome": outcome,
+		});
+		let res = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.and_then(|mut f| writeln!(f, "{}", record));
+		if let Err(e) = res {
+			error!("Unable to write owner API audit log {}, {}", path, e);
+		}
+	}
+
+	fn owner_secret_key() -> AccessKey {
+		AccessKey {
+			id: OWNER_API_SECRET_KEY_ID.to_string(),
+			permission: OwnerApiPermission::Admin,
+			spend_limit: None,
+		}
+	}
+
+	fn basic_auth(id: &str, secret: &str) -> String {
+		"Basic ".to_string() + &to_base64(&format!("{}:{}", id, secret))
+	}
+}
+
+// Param that holds the amount of the spend call. Transfer keeps the funds in the wallet.
+fn amount_param(method: &str) -> Option<&'static str> {
+	match method {
+		"init_send_tx" => Some("args"),
+		"process_invoice_tx" | "tx_lock_outputs" | "finalize_tx" => Some("slate"),
+		_ => None,
+	}
+}
+
+fn amount_value(v: &serde_json::Value) -> Option<u64> {
+	match v {
+		serde_json::Value::Number(n) => n.as_u64(),
+		serde_json::Value::String(s) => s.parse::<u64>().ok(),
+		_ => None,
+	}
+}
+
+// Amount that the spend call moves. The param is found by its name, positional params by
+// their shape.
+fn request_amount(method: &str, params: &serde_json::Value) -> Option<u64> {
+	let name = amount_param(method)?;
+	let is_amount_param = |p: &&serde_json::Value| match name {
+		"slate" => p.get("version_info").is_some(),
+		_ => p.get("amount").is_some(),
+	};
+	let param = match params {
+		serde_json::Value::Object(m) => m.get(name),
+		serde_json::Value::Array(a) => a.iter().find(is_amount_param),
+		_ => None,
+	}?;
+	amount_value(&param["amount"])
+}
+
+// Compare the secrets in constant time
+fn secure_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len()
+		&& a.iter()
+			.zip(b.iter())
+			.fold(0u8, |acc, (x, y)| acc | (x ^ y))
+			== 0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn access() -> OwnerApiAccess {
+		OwnerApiAccess::new(
+			Some(OwnerApiConfig {
+				audit_log_path: None,
+				keys: Some(vec![
+					OwnerApiKey {
+						id: "viewer".to_string(),
+						secret: "s1".to_string(),
+						permission: OwnerApiPermission::Read,
+						spend_limit: None,
+					},
+					OwnerApiKey {
+						id: "payer".to_string(),
+						secret: "s2".to_string(),
+						permission: OwnerApiPermission::Spend,
+						spend_limit: Some(1_000),
+					},
+				]),
+			}),
+			Some("legacy".to_string()),
+		)
+	}
+
+	fn request(auth: Option<(&str, &str)>) -> Request<Body> {
+		let mut builder = Request::builder();
+		if let Some((id, secret)) = auth {
+			builder = builder.header(AUTHORIZATION, OwnerApiAccess::basic_auth(id, secret));
+		}
+		builder.body(Body::empty()).unwrap()
+	}
+
+	#[test]
+	fn authenticate_keys() {
+		let access = access();
+		assert!(access.keys_enabled());
+		assert_eq!(
+			access
+				.authenticate(&request(Some(("viewer", "s1"))))
+				.unwrap()
+				.id,
+			"viewer"
+		);
+		assert_eq!(
+			access
+				.authenticate(&request(Some(("mwc", "legacy"))))
+				.unwrap()
+				.permission,
+			OwnerApiPermission::Admin
+		);
+		assert!(access
+			.authenticate(&request(Some(("viewer", "s2"))))
+			.is_none());
+		assert!(access.authenticate(&request(None)).is_none());
+
+		let no_keys = OwnerApiAccess::new(None, Some("legacy".to_string()));
+		assert!(!no_keys.keys_enabled());
+		assert_eq!(no_keys.authenticate(&request(None)).unwrap().id, "mwc");
+	}
+
+	#[test]
+	fn check_permissions() {
+		let access = access();
+		let viewer = access
+			.authenticate(&request(Some(("viewer", "s1"))))
+			.unwrap();
+		let payer = access
+			.authenticate(&request(Some(("payer", "s2"))))
+			.unwrap();
+
+		let summary = serde_json::json!({"id": 1, "method": "retrieve_summary_info", "params": {}});
+		let mnemonic = serde_json::json!({"id": 1, "method": "get_mnemonic", "params": {}});
+		let send = |amount: &str| {
+			serde_json::json!({"id": 1, "method": "init_send_tx",
+				"params": {"token": null, "args": {"amount": amount}}})
+		};
+
+		assert!(access.check_request(&viewer, &summary).is_ok());
+		assert!(access.check_request(&viewer, &send("10")).is_err());
+		assert!(access.check_request(&payer, &send("1000")).is_ok());
+		assert!(access.check_request(&payer, &send("1001")).is_err());
+		assert!(access.check_request(&payer, &mnemonic).is_err());
+
+		let positional = serde_json::json!({"id": 1, "method": "init_send_tx",
+			"params": [{"amount": 5000}]});
+		let err = access.check_request(&payer, &positional).unwrap_err();
+		assert_eq!(err["error"]["code"], ACCESS_DENIED_CODE);
+	}
+
+	#[test]
+	fn check_method_permissions() {
+		let access = access();
+		let viewer = access
+			.authenticate(&request(Some(("viewer", "s1"))))
+			.unwrap();
+		let payer = access
+			.authenticate(&request(Some(("payer", "s2"))))
+			.unwrap();
+		let call = |method: &str| serde_json::json!({"id": 1, "method": method, "params": {}});
+
+		for method in &["set_active_account", "start_updater", "stop_updater"] {
+			assert!(access.check_request(&viewer, &call(method)).is_err());
+			assert!(access.check_request(&payer, &call(method)).is_ok());
+		}
+		for method in &["open_wallet", "close_wallet"] {
+			assert!(access.check_request(&payer, &call(method)).is_err());
+		}
+		for method in &["init_secure_api", "subscribe", "unsubscribe"] {
+			assert!(access.check_request(&viewer, &call(method)).is_ok());
+		}
+		assert!(access
+			.check_request(&payer, &call("send_batch_tx"))
+			.is_err());
+	}
+
+	// Method names of the owner API traits
+	fn trait_methods(src: &str) -> Vec<String> {
+		src.lines()
+			.skip_while(|l| !l.starts_with("pub trait "))
+			.take_while(|l| *l != "}")
+			.filter_map(|l| l.strip_prefix("\tfn "))
+			.map(|l| l.split('(').next().unwrap().trim().to_string())
+			.collect()
+	}
+
+	#[test]
+	fn owner_api_methods_mapped() {
+		let v2 = trait_methods(include_str!("../../api/src/owner_rpc.rs"));
+		let v3 = trait_methods(include_str!("../../api/src/owner_rpc_s.rs"));
+		assert!(v3.iter().any(|m| m == "init_send_tx"));
+		for method in v2.iter().chain(v3.iter()) {
+			assert!(
+				known_method_permission(method).is_some(),
+				"owner API method {} has no permission",
+				method
+			);
+		}
+	}
+
+	#[test]
+	fn check_spend_amounts() {
+		let access = access();
+		let payer = access
+			.authenticate(&request(Some(("payer", "s2"))))
+			.unwrap();
+
+		// Invoice amount comes from the slate, not from the args
+		let invoice = |amount: u64| {
+			serde_json::json!({"id": 1, "method": "process_invoice_tx",
+				"params": [null, {"version_info": {"version": 3}, "amount": amount.to_string()},
+					{"amount": 0}]})
+		};
+		assert!(access.check_request(&payer, &invoice(1000)).is_ok());
+		assert!(access.check_request(&payer, &invoice(1001)).is_err());
+
+		// Spend call without the amount is denied for the key with the limit
+		let no_amount = serde_json::json!({"id": 1, "method": "init_send_tx", "params": {}});
+		assert!(access.check_request(&payer, &no_amount).is_err());
+	}
+}
@@ -0,0 +1,386 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Owner API event stream over WebSocket. The client opens the connection at
+//! `/v3/owner/ws`, calls `init_secure_api` the same way as for the V3 API and then sends
+//! encrypted `subscribe` and `unsubscribe` requests with the list of the topics
+//! (`balance`, `updater`, `slate`, `swap`). The events of the subscribed topics are
+//! pushed as encrypted `event` notifications. Every request is checked against the
+//! permission of the API key.
+
+use crate::api::{self, ResponseFuture};
+use crate::apiwallet::{EncryptionErrorResponse, JsonId, Owner, OwnerRpcS};
+use crate::controller::OwnerV3Helpers;
+use crate::keychain::Keychain;
+use crate::libwallet::api_impl::events::{self, EventTopic, WalletEvent};
+use crate::libwallet::{NodeClient, WalletLCProvider};
+use crate::owner_access::{AccessKey, OwnerApiAccess};
+use crate::util::secp::key::SecretKey;
+use crate::util::Mutex;
+use easy_jsonrpc_mw::{Handler, MaybeReply};
+use futures::{SinkExt, StreamExt};
+use hyper::header::{self, HeaderValue};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Request, Response, StatusCode};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+// Magic string of the WebSocket handshake, RFC 6455
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// WebSocket handler for the owner API event stream
+pub struct OwnerAPIHandlerWs<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Owner API that handles `init_secure_api`
+	owner_api: Arc<Owner<L, C, K>>,
+	/// API keys and audit log
+	access: Arc<OwnerApiAccess>,
+}
+
+impl<L, C, K> OwnerAPIHandlerWs<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Create a new event stream handler
+	pub fn new(
+		owner_api: Arc<Owner<L, C, K>>,
+		access: Arc<OwnerApiAccess>,
+	) -> OwnerAPIHandlerWs<L, C, K> {
+		OwnerAPIHandlerWs { owner_api, access }
+	}
+}
+
+fn websocket_accept_key(key: &[u8]) -> String {
+	let mut data = key.to_vec();
+	data.extend_from_slice(WEBSOCKET_GUID.as_bytes());
+	let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, &data);
+	base64::encode(digest.as_ref())
+}
+
+fn bad_request(msg: &str) -> Response<Body> {
+	Response::builder()
+		.status(StatusCode::BAD_REQUEST)
+		.body(msg.to_string().into())
+		.unwrap()
+}
+
+fn rpc_result(id: &serde_json::Value, result: serde_json::Value) -> serde_json::Value {
+	serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": id,
+		"result": result,
+	})
+}
+
+fn rpc_error(id: &serde_json::Value, code: i32, message: &str) -> serde_json::Value {
+	serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": id,
+		"error": {
+			"code": code,
+			"message": message,
+		}
+	})
+}
+
+fn topics_value(topics: &HashSet<EventTopic>) -> serde_json::Value {
+	let mut topics: Vec<serde_json::Value> = topics
+		.iter()
+		.filter_map(|t| serde_json::to_value(t).ok())
+		.collect();
+	topics.sort_by_key(|t| t.as_str().unwrap_or("").to_string());
+	serde_json::Value::Array(topics)
+}
+
+// Handle the decrypted subscribe or unsubscribe request
+fn handle_request(
+	req: &serde_json::Value,
+	topics: &RwLock<HashSet<EventTopic>>,
+) -> serde_json::Value {
+	let id = &req["id"];
+	let method = req["method"].as_str().unwrap_or("");
+	if method != "subscribe" && method != "unsubscribe" {
+		return rpc_error(id, -32601, "Method not found");
+	}
+	let requested: Vec<EventTopic> = match serde_json::from_value(req["params"]["topics"].clone()) {
+		Ok(t) => t,
+		Err(e) => return rpc_error(id, -32602, &format!("Invalid topics, {}", e)),
+	};
+	let mut topics = topics.write().unwrap();
+	for t in requested {
+		if method == "subscribe" {
+			topics.insert(t);
+		} else {
+			topics.remove(&t);
+		}
+	}
+	rpc_result(id, serde_json::json!({ "Ok": topics_value(&topics) }))
+}
+
+// The shared key is set up by the owner API, the same way as for the V3 API
+fn init_secure_api<L, C, K>(
+	api: &Owner<L, C, K>,
+	key: &Arc<Mutex<Option<SecretKey>>>,
+	val: serde_json::Value,
+) -> serde_json::Value
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	match OwnerRpcS::handle_request(api, val) {
+		MaybeReply::Reply(r) => {
+			OwnerV3Helpers::update_owner_api_shared_key(
+				key.clone(),
+				&r,
+				api.shared_key.lock().clone(),
+			);
+			r
+		}
+		MaybeReply::DontReply => serde_json::Value::Null,
+	}
+}
+
+// Process the message from the client. Returns the reply.
+fn handle_message<L, C, K>(
+	text: &str,
+	api: &Owner<L, C, K>,
+	access: &OwnerApiAccess,
+	caller: &AccessKey,
+	key: &Arc<Mutex<Option<SecretKey>>>,
+	topics: &RwLock<HashSet<EventTopic>>,
+) -> serde_json::Value
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let val: serde_json::Value = match serde_json::from_str(text) {
+		Ok(v) => v,
+		Err(e) => {
+			return rpc_error(
+				&serde_json::Value::Null,
+				-32700,
+				&format!("Parse error, {}", e),
+			)
+		}
+	};
+
+	if OwnerV3Helpers::is_init_secure_api(&val) {
+		if let Err(e) = access.check_request(caller, &val) {
+			return e;
+		}
+		return init_secure_api(api, key, val);
+	}
+
+	if !OwnerV3Helpers::is_encrypted_request(&val) {
+		return EncryptionErrorResponse::new(
+			1,
+			-32002,
+			"Event stream accepts only init_secure_api and encrypted requests",
+		)
+		.as_json_value();
+	}
+	if let Err(v) = OwnerV3Helpers::check_encryption_started(key.clone()) {
+		return v;
+	}
+	let (id, req) = match OwnerV3Helpers::decrypt_request(key.clone(), &val) {
+		Ok(r) => r,
+		Err(e) => return e,
+	};
+	let res = match access.check_request(caller, &req) {
+		Ok(_) => handle_request(&req, topics),
+		Err(e) => e,
+	};
+	match OwnerV3Helpers::encrypt_response(key.clone(), &id, &res) {
+		Ok(v) => v,
+		Err(v) => v,
+	}
+}
+
+// Encrypted JSON-RPC notification with the event
+fn event_message(
+	key: &Arc<Mutex<Option<SecretKey>>>,
+	event: &WalletEvent,
+) -> Option<serde_json::Value> {
+	let notification = serde_json::json!({
+		"jsonrpc": "2.0",
+		"method": "event",
+		"params": event,
+	});
+	OwnerV3Helpers::encrypt_response(
+		key.clone(),
+		&JsonId::StrId("event".to_string()),
+		&notification,
+	)
+	.ok()
+}
+
+async fn serve_connection<L, C, K>(
+	upgraded: Upgraded,
+	api: Arc<Owner<L, C, K>>,
+	access: Arc<OwnerApiAccess>,
+	caller: AccessKey,
+) where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let ws = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+	let (mut sink, mut stream) = ws.split();
+
+	let key: Arc<Mutex<Option<SecretKey>>> = Arc::new(Mutex::new(None));
+	let topics: Arc<RwLock<HashSet<EventTopic>>> = Arc::new(RwLock::new(HashSet::new()));
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<WalletEvent>();
+	let subscribed = topics.clone();
+	let subscription = events::subscribe_events(Box::new(move |event| {
+		if subscribed.read().unwrap().contains(&event.topic) {
+			let _ = tx.send(event.clone());
+		}
+	}));
+
+	loop {
+		let reply = tokio::select! {
+			msg = stream.next() => match msg {
+				Some(Ok(Message::Text(text))) => {
+					Some(handle_message(&text, &api, &access, &caller, &key, &topics))
+				}
+				Some(Ok(Message::Close(_))) | None => break,
+				Some(Err(e)) => {
+					debug!("Owner API event stream of '{}' failed, {}", caller.id, e);
+					break;
+				}
+				Some(Ok(_)) => None,
+			},
+			event = rx.recv() => match event {
+				Some(event) => event_message(&key, &event),
+				None => break,
+			},
+		};
+		if let Some(reply) = reply {
+			if let Err(e) = sink.send(Message::Text(reply.to_string())).await {
+				debug!("Unable to send to the owner API event stream, {}", e);
+				break;
+			}
+		}
+	}
+
+	events::unsubscribe_events(subscription);
+	info!("Owner API event stream of '{}' is closed", caller.id);
+}
+
+impl<L, C, K> api::Handler for OwnerAPIHandlerWs<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let caller = match self.access.authenticate(&req) {
+			Some(k) => k,
+			None => {
+				return Box::pin(async {
+					Ok(Response::builder()
+						.status(StatusCode::UNAUTHORIZED)
+						.body(Body::empty())
+						.unwrap())
+				})
+			}
+		};
+
+		let is_upgrade = req
+			.headers()
+			.get(header::UPGRADE)
+			.and_then(|v| v.to_str().ok())
+			.map(|v| v.eq_ignore_ascii_case("websocket"))
+			.unwrap_or(false);
+		let accept_key = req
+			.headers()
+			.get(header::SEC_WEBSOCKET_KEY)
+			.map(|k| websocket_accept_key(k.as_bytes()));
+		let accept_key = match (is_upgrade, accept_key) {
+			(true, Some(k)) => k,
+			_ => return Box::pin(async { Ok(bad_request("Expected WebSocket upgrade request")) }),
+		};
+
+		info!("Owner API event stream is opened by '{}'", caller.id);
+		let api = self.owner_api.clone();
+		let access = self.access.clone();
+		tokio::spawn(async move {
+			match req.into_body().on_upgrade().await {
+				Ok(upgraded) => serve_connection(upgraded, api, access, caller).await,
+				Err(e) => error!("Owner API event stream upgrade failed, {}", e),
+			}
+		});
+
+		Box::pin(async move {
+			let mut res = Response::new(Body::empty());
+			*res.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+			let headers = res.headers_mut();
+			headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+			headers.insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
+			headers.insert(
+				header::SEC_WEBSOCKET_ACCEPT,
+				HeaderValue::from_str(&accept_key).unwrap(),
+			);
+			Ok(res)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn accept_key() {
+		// Example from RFC 6455
+		assert_eq!(
+			websocket_accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="),
+			"s3pPLMBiTxaE4xNZF+MhaWbR1pY="
+		);
+	}
+
+	#[test]
+	fn subscribe_topics() {
+		let topics = RwLock::new(HashSet::new());
+		let res = handle_request(
+			&serde_json::json!({"id": 1, "method": "subscribe", "params": {"topics": ["balance", "swap"]}}),
+			&topics,
+		);
+		assert_eq!(res["result"]["Ok"], serde_json::json!(["balance", "swap"]));
+		let res = handle_request(
+			&serde_json::json!({"id": 2, "method": "unsubscribe", "params": {"topics": ["swap"]}}),
+			&topics,
+		);
+		assert_eq!(res["result"]["Ok"], serde_json::json!(["balance"]));
+		let res = handle_request(
+			&serde_json::json!({"id": 3, "method": "subscribe", "params": {"topics": ["blocks"]}}),
+			&topics,
+		);
+		assert_eq!(res["error"]["code"], -32602);
+		let res = handle_request(&serde_json::json!({"id": 4, "method": "send"}), &topics);
+		assert_eq!(res["error"]["code"], -32601);
+	}
+}
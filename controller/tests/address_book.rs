@@ -0,0 +1,182 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the address book and the local transaction notes and tags
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{Contact, ErrorKind, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+macro_rules! send_to_dest {
+	($a:expr, $m: expr, $b:expr, $c:expr, $d:expr) => {
+		test_framework::send_to_dest($a, $m, $b, $c, $d, false, None, 1)
+	};
+}
+
+fn address_book_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	let contact_error = |res: Result<Contact, libwallet::Error>| match res {
+		Err(e) => match e.kind() {
+			ErrorKind::ContactError(_) => true,
+			_ => false,
+		},
+		Ok(_) => false,
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let bob = api.save_contact(
+			m,
+			Contact {
+				name: "@bob".to_string(),
+				address: "xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU".to_string(),
+				method: "mqs".to_string(),
+				slate_version: None,
+				proof_address: None,
+			},
+		)?;
+		assert_eq!(bob.name, "bob");
+		assert_eq!(bob.method, "mwcmqs");
+		let alice = api.save_contact(
+			m,
+			Contact {
+				name: "alice".to_string(),
+				address: "http://127.0.0.1:3415".to_string(),
+				method: "http".to_string(),
+				slate_version: Some(2),
+				proof_address: Some(
+					"xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU".to_string(),
+				),
+			},
+		)?;
+
+		let contacts = api.retrieve_contacts(m)?;
+		assert_eq!(contacts, vec![alice.clone(), bob]);
+		assert_eq!(api.get_contact(m, "@alice")?, alice);
+
+		// Invalid contacts are not stored
+		let mut invalid = alice.clone();
+		invalid.method = "file".to_string();
+		assert!(contact_error(api.save_contact(m, invalid)));
+		let mut invalid = alice.clone();
+		invalid.name = "alice smith".to_string();
+		assert!(contact_error(api.save_contact(m, invalid)));
+		let mut invalid = alice.clone();
+		invalid.proof_address = Some("not an address".to_string());
+		assert!(contact_error(api.save_contact(m, invalid)));
+
+		api.delete_contact(m, "bob")?;
+		assert_eq!(api.retrieve_contacts(m)?, vec![alice]);
+		assert!(contact_error(api.get_contact(m, "bob")));
+		Ok(())
+	})?;
+
+	send_to_dest!(wallet1.clone(), mask1, client1.clone(), "wallet2", reward)?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+		let sent = txs
+			.iter()
+			.find(|t| t.tx_type == TxLogEntryType::TxSent)
+			.unwrap();
+		let tx = api.label_tx(
+			m,
+			None,
+			sent.tx_slate_id,
+			Some("Rent for March".to_string()),
+			vec!["rent".to_string(), "home".to_string(), "rent".to_string()],
+		)?;
+		assert_eq!(tx.id, sent.id);
+
+		// Labels are stored and searchable
+		let (_, txs) = api.retrieve_txs(m, false, Some(sent.id), None)?;
+		assert_eq!(txs[0].note, Some("Rent for March".to_string()));
+		assert_eq!(txs[0].tags, vec!["home", "rent"]);
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		let found: Vec<u32> = txs
+			.iter()
+			.filter(|t| t.matches("MARCH"))
+			.map(|t| t.id)
+			.collect();
+		assert_eq!(found, vec![sent.id]);
+		assert_eq!(txs.iter().filter(|t| t.matches("home")).count(), 1);
+		assert_eq!(txs.iter().filter(|t| t.matches("vacation")).count(), 0);
+
+		let tx = api.label_tx(m, Some(sent.id), None, None, vec![])?;
+		assert_eq!(tx.note, None);
+		assert!(tx.tags.is_empty());
+		assert!(api.label_tx(m, Some(1000), None, None, vec![]).is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn address_book() {
+	let test_dir = "test_output/address_book";
+	setup(test_dir);
+	if let Err(e) = address_book_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
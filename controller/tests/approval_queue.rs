@@ -0,0 +1,224 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the manual approval queue of the foreign API
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_config::ReceivePolicyConfig;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{ErrorKind, InitTxArgs, PendingSlateStatus, Slate};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn approval_policy(timeout: u64) -> ReceivePolicyConfig {
+	ReceivePolicyConfig {
+		manual_approval: Some(true),
+		approval_timeout_secs: Some(timeout),
+		..ReceivePolicyConfig::default()
+	}
+}
+
+/// Test the slates parked in the approval queue
+fn approval_queue_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// Do some mining
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	// Receive policy of wallet 2
+	let set_receive_policy = |policy: Option<ReceivePolicyConfig>| {
+		wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, _| {
+			api.set_receive_policy(policy)?;
+			Ok(())
+		})
+	};
+	set_receive_policy(Some(approval_policy(3600)))?;
+
+	let amount = 2_000_000_000;
+	let init_slate = || -> Result<Slate, wallet::Error> {
+		let mut slate = Slate::blank(1);
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: amount,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				..Default::default()
+			};
+			slate = api.init_send_tx(m, args, 1)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			Ok(())
+		})?;
+		Ok(slate)
+	};
+
+	// Receive through the listener, returns the response or the error kind
+	let receive = |slate: &Slate| -> Result<Result<Slate, ErrorKind>, wallet::Error> {
+		let mut res = Err(ErrorKind::GenericError("not called".to_string()));
+		wallet::controller::foreign_single_use(wallet2.clone(), mask2_i.clone(), |api| {
+			api.approval_queue = true;
+			res = api
+				.receive_tx(
+					slate,
+					Some("mwcmqs://sender@mqs.mwc.mw".to_string()),
+					None,
+					None,
+				)
+				.map_err(|e| e.kind());
+			Ok(())
+		})?;
+		Ok(res)
+	};
+
+	let is_pending = |r: &Result<Slate, ErrorKind>| match r {
+		Err(ErrorKind::PendingApproval(_)) => true,
+		_ => false,
+	};
+	let is_queue_error = |r: &Result<Slate, ErrorKind>| match r {
+		Err(ErrorKind::ApprovalQueueError(_)) => true,
+		_ => false,
+	};
+
+	// Approved slate, the sender gets the response on the retry
+	let slate = init_slate()?;
+	assert!(is_pending(&receive(&slate)?));
+	assert!(is_pending(&receive(&slate)?));
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let pending = api.retrieve_pending_slates(m, None)?;
+		assert_eq!(pending.len(), 1);
+		assert_eq!(pending[0].id, slate.id);
+		assert_eq!(pending[0].status, PendingSlateStatus::Pending);
+		// Nothing is received until the approval
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+		assert!(txs.is_empty());
+		api.approve_pending_slate(m, slate.id)?;
+		let pending = api.retrieve_pending_slates(m, Some(slate.id))?;
+		assert_eq!(pending[0].status, PendingSlateStatus::Approved);
+		assert!(api.reject_pending_slate(m, slate.id).is_err());
+		Ok(())
+	})?;
+
+	let slate = receive(&slate)?.unwrap();
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, wallet2_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(wallet2_info.amount_currently_spendable, amount);
+		Ok(())
+	})?;
+
+	// Rejected slate
+	let slate = init_slate()?;
+	assert!(is_pending(&receive(&slate)?));
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.reject_pending_slate(m, slate.id)?;
+		assert!(api.approve_pending_slate(m, slate.id).is_err());
+		Ok(())
+	})?;
+	assert!(is_queue_error(&receive(&slate)?));
+
+	// Expired slate
+	set_receive_policy(Some(approval_policy(0)))?;
+	let slate = init_slate()?;
+	assert!(is_pending(&receive(&slate)?));
+	assert!(is_queue_error(&receive(&slate)?));
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let pending = api.retrieve_pending_slates(m, Some(slate.id))?;
+		assert_eq!(pending[0].status, PendingSlateStatus::Expired);
+		assert!(api.approve_pending_slate(m, slate.id).is_err());
+		Ok(())
+	})?;
+
+	// Only one slate from the sender can wait for the approval
+	set_receive_policy(Some(ReceivePolicyConfig {
+		max_pending_approvals_per_sender: Some(1),
+		..approval_policy(3600)
+	}))?;
+	let slate = init_slate()?;
+	assert!(is_pending(&receive(&slate)?));
+	let slate = init_slate()?;
+	match receive(&slate)? {
+		Err(ErrorKind::ReceivePolicyViolation { rule, .. }) => {
+			assert_eq!(rule, "max_pending_approvals_per_sender")
+		}
+		_ => panic!("the second slate must be rejected"),
+	}
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn approval_queue() {
+	let test_dir = "test_output/approval_queue";
+	setup(test_dir);
+	if let Err(e) = approval_queue_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,158 @@
+// Copyright 2019 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test a batch payout, aggregated into a single transaction
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_util::Mutex;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{BatchSendArgs, BatchSendEntry};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn batch_send_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// Wallet 2 receives the batch payments with its foreign api
+	let listener_wallet = wallet2.clone();
+	thread::spawn(move || {
+		if let Err(e) = wallet::controller::foreign_listener(
+			listener_wallet,
+			Arc::new(Mutex::new(None)),
+			"127.0.0.1:33415",
+			None,
+			false,
+		) {
+			error!("Foreign listener error: {}", e);
+		}
+	});
+	thread::sleep(Duration::from_millis(500));
+
+	// Mine into wallet 1
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	let entry = |dest: &str, amount: u64| BatchSendEntry {
+		method: "http".to_owned(),
+		dest: dest.to_owned(),
+		amount,
+		apisecret: None,
+		message: None,
+		payment_proof_recipient_address: None,
+	};
+	let args = BatchSendArgs {
+		src_acct_name: None,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		selection_strategy_is_use_all: false,
+		target_slate_version: None,
+		ttl_blocks: None,
+		fluff: true,
+		entries: vec![
+			entry("http://127.0.0.1:33415", 1_000_000_000),
+			// Not a valid http address, this recipient fails and must be rolled back
+			entry("wallet3", 2_000_000_000),
+			entry("http://127.0.0.1:33415", 3_000_000_000),
+		],
+	};
+
+	let mut results = vec![];
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		results = api.send_batch_tx(m, args)?;
+		Ok(())
+	})?;
+	assert_eq!(results.len(), 3);
+	assert!(results[0].error.is_none());
+	assert!(results[1].error.is_some());
+	assert!(results[2].error.is_none());
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, results[1].slate_id)?;
+		assert!(txs[0].is_cancelled());
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	// Both successful payments were confirmed by the single posted transaction
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, wallet_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(wallet_info.total, 4_000_000_000);
+		Ok(())
+	})?;
+
+	// Nothing stays locked after the rollback and confirmation
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, wallet_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(wallet_info.amount_locked, 0);
+		for i in [0, 2].iter() {
+			let (_, txs) = api.retrieve_txs(m, true, None, results[*i].slate_id)?;
+			assert!(txs[0].confirmed);
+		}
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn batch_send() {
+	let test_dir = "test_output/batch_send";
+	setup(test_dir);
+	if let Err(e) = batch_send_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,163 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests frozen and labeled outputs
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+use grin_wallet_util::grin_util as util;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{ErrorKind, InitTxArgs, OutputStatus, Slate};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn input_commits(slate: &Slate) -> Vec<String> {
+	slate
+		.tx
+		.inputs()
+		.iter()
+		.map(|i| util::to_hex(i.commit.0.to_vec()))
+		.collect()
+}
+
+fn coin_control_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let send_args = |outputs: Option<Vec<String>>| InitTxArgs {
+		src_acct_name: None,
+		amount: reward / 2,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: outputs.is_none(),
+		outputs,
+		..Default::default()
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, outputs) = api.retrieve_outputs(m, false, true, None)?;
+		assert!(refreshed);
+		let frozen_commit = outputs
+			.iter()
+			.find(|o| o.output.height == 1)
+			.unwrap()
+			.output
+			.commit
+			.clone()
+			.unwrap();
+
+		let output = api.freeze_output(m, &frozen_commit)?;
+		assert!(output.frozen);
+		assert_eq!(output.status, OutputStatus::Unspent);
+
+		// Frozen output is skipped even if all outputs are used
+		let slate = api.init_send_tx(m, send_args(None), 1)?;
+		let inputs = input_commits(&slate);
+		assert!(inputs.len() > 1);
+		assert!(!inputs.contains(&frozen_commit));
+
+		// Unless it is listed explicitly
+		let slate = api.init_send_tx(m, send_args(Some(vec![frozen_commit.clone()])), 1)?;
+		assert_eq!(input_commits(&slate), vec![frozen_commit.clone()]);
+
+		// Note and tags are stored with the output
+		api.label_output(
+			m,
+			&frozen_commit,
+			Some("cold storage".to_string()),
+			vec![
+				"savings".to_string(),
+				"cold".to_string(),
+				"savings".to_string(),
+				" ".to_string(),
+			],
+		)?;
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		let output = outputs
+			.iter()
+			.find(|o| o.output.commit.as_ref() == Some(&frozen_commit))
+			.unwrap();
+		assert!(output.output.frozen);
+		assert_eq!(output.output.note, Some("cold storage".to_string()));
+		assert_eq!(output.output.tags, vec!["cold", "savings"]);
+
+		let output = api.label_output(m, &frozen_commit, None, vec![])?;
+		assert_eq!(output.note, None);
+		assert!(output.tags.is_empty());
+
+		// Unfrozen output is selected again
+		let output = api.unfreeze_output(m, &frozen_commit)?;
+		assert!(!output.frozen);
+		let slate = api.init_send_tx(m, send_args(None), 1)?;
+		assert!(input_commits(&slate).contains(&frozen_commit));
+
+		let unknown = "08".to_string() + &"00".repeat(32);
+		match api.freeze_output(m, &unknown) {
+			Err(e) => match e.kind() {
+				ErrorKind::OutputNotFound(c) => assert_eq!(c, unknown),
+				_ => return Err(e.into()),
+			},
+			Ok(_) => panic!("unknown output is frozen"),
+		}
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn coin_control() {
+	let test_dir = "test_output/coin_control";
+	setup(test_dir);
+	if let Err(e) = coin_control_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,356 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the air-gapped signing with an offline cold wallet
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{
+	ColdSignRequest, ColdSignResponse, ColdSignStep, InitTxArgs, OutputData, OutputStatus, Slate,
+	TxLogEntryType,
+};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+// Requests and responses travel between the machines as files
+fn carry<T: serde::Serialize + serde::de::DeserializeOwned>(data: &T) -> T {
+	serde_json::from_str(&serde_json::to_string_pretty(data).unwrap()).unwrap()
+}
+
+fn cold_wallet_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client_cold,
+		wallet_cold,
+		mask_cold_i,
+		test_dir,
+		"cold",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask_cold = (&mask_cold_i).as_ref();
+	create_wallet_and_add!(
+		client_online,
+		wallet_online,
+		mask_online_i,
+		test_dir,
+		"online",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask_online = (&mask_online_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// The cold wallet is funded while it is online, then it exports its outputs
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet_cold.clone(), mask_cold, 10, false);
+	let mut exported: Vec<OutputData> = vec![];
+	wallet::controller::owner_single_use(Some(wallet_cold.clone()), mask_cold, None, |api, m| {
+		api.retrieve_summary_info(m, true, 1)?;
+		exported = carry(&api.export_cold_outputs(m)?);
+		Ok(())
+	})?;
+	assert!(!exported.is_empty());
+	let cold_total: u64 = exported.iter().map(|o| o.value).sum();
+
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			api.create_account_path(m, "cold")?;
+			api.set_active_account(m, "cold")?;
+			assert_eq!(
+				api.import_cold_outputs(m, exported.clone())?,
+				exported.len()
+			);
+			assert_eq!(api.import_cold_outputs(m, exported.clone())?, 0);
+
+			// The scan can't rewind the cold outputs, it keeps them
+			let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+			assert!(refreshed);
+			assert_eq!(info.total, cold_total);
+			Ok(())
+		},
+	)?;
+
+	// Online wallet selects the inputs, the offline one signs them
+	let amount = 2_000_000_000;
+	let send_request = || -> Result<ColdSignRequest, wallet::Error> {
+		let mut request = None;
+		wallet::controller::owner_single_use(
+			Some(wallet_online.clone()),
+			mask_online,
+			None,
+			|api, m| {
+				let args = InitTxArgs {
+					src_acct_name: None,
+					amount,
+					minimum_confirmations: 2,
+					max_outputs: 500,
+					num_change_outputs: 1,
+					selection_strategy_is_use_all: false,
+					message: Some("cold payment".to_string()),
+					..Default::default()
+				};
+				request = Some(carry(&api.init_cold_send_tx(m, args)?));
+				Ok(())
+			},
+		)?;
+		Ok(request.unwrap())
+	};
+	let request = send_request()?;
+	assert_eq!(request.step, ColdSignStep::Send);
+	assert!(!request.inputs.is_empty());
+
+	// Other wallet can't sign the outputs it doesn't own
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		assert!(api.cold_sign(m, &request).is_err());
+		Ok(())
+	})?;
+
+	let cold_sign = |request: &ColdSignRequest| -> Result<ColdSignResponse, wallet::Error> {
+		let mut response = None;
+		wallet::controller::owner_single_use(
+			Some(wallet_cold.clone()),
+			mask_cold,
+			None,
+			|api, m| {
+				response = Some(carry(&api.cold_sign(m, &carry(request))?));
+				Ok(())
+			},
+		)?;
+		Ok(response.unwrap())
+	};
+
+	let response = cold_sign(&request)?;
+	assert_eq!(response.outputs.len(), 1);
+	let change = response.outputs[0].value;
+
+	let mut slate = Slate::blank(2);
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			slate = api.process_cold_sign_response(m, &response)?;
+			let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+			let locked = outputs
+				.iter()
+				.filter(|o| o.output.status == OutputStatus::Locked)
+				.count();
+			assert_eq!(locked, request.inputs.len());
+			assert!(outputs
+				.iter()
+				.any(|o| o.output.status == OutputStatus::Unconfirmed && o.output.value == change));
+
+			// Sent transaction owns the locked inputs and the change
+			let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+			assert_eq!(txs.len(), 1);
+			assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+			assert_eq!(txs[0].amount_credited, change);
+			assert!(outputs
+				.iter()
+				.filter(|o| o.output.status != OutputStatus::Unspent)
+				.all(|o| o.output.tx_log_entry == Some(txs[0].id)));
+			assert!(api.process_cold_sign_response(m, &response).is_err());
+
+			// Receiver adds its output and signature, the offline wallet adds the sender one
+			slate = client_online.send_tx_slate_direct("wallet2", &slate)?;
+			let sign_request = api.cold_sign_request(ColdSignStep::Sign, &slate, None)?;
+			let sign_response = cold_sign(&sign_request)?;
+			assert!(sign_response.outputs.is_empty());
+			slate = api.process_cold_sign_response(m, &sign_response)?;
+
+			// The test node mines the block for the posting wallet, its reward stays out of
+			// the cold account
+			api.set_active_account(m, "default")?;
+			api.post_tx(m, &slate.tx, false)?;
+			api.set_active_account(m, "cold")?;
+			Ok(())
+		},
+	)?;
+	let fee = slate.fee;
+
+	// Offline wallet has nothing left for the slate
+	{
+		wallet_inst!(wallet_cold, w);
+		assert!(w
+			.get_private_context(mask_cold, slate.id.as_bytes(), 0)
+			.is_err());
+	}
+
+	// Mined coins of the receiver fund its payment to the cold wallet below
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet2.clone(), mask2, 5, false);
+
+	// Refresh finds the change and the spent inputs by their commitments
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+			assert_eq!(info.total, cold_total - amount - fee);
+			let (_, outputs) = api.retrieve_outputs(m, true, false, None)?;
+			let spent = outputs
+				.iter()
+				.filter(|o| o.output.status == OutputStatus::Spent)
+				.count();
+			assert_eq!(spent, request.inputs.len());
+			assert!(outputs.iter().all(|o| o.output.cold));
+			assert!(outputs
+				.iter()
+				.any(|o| o.output.status == OutputStatus::Unspent && o.output.value == change));
+			let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+			assert!(txs[0].confirmed);
+			assert_eq!(txs[0].fee, Some(fee));
+			Ok(())
+		},
+	)?;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].confirmed);
+		assert_eq!(txs[0].amount_credited, amount);
+		Ok(())
+	})?;
+
+	// Cold wallet receives, the online wallet returns the slate to the sender
+	let mut slate = Slate::blank(2);
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: false,
+			..Default::default()
+		};
+		slate = api.init_send_tx(m, args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		Ok(())
+	})?;
+	let mut receive_request = None;
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, _m| {
+			receive_request = Some(api.cold_sign_request(ColdSignStep::Receive, &slate, None)?);
+			Ok(())
+		},
+	)?;
+	let response = cold_sign(&receive_request.unwrap())?;
+	assert_eq!(response.outputs.len(), 1);
+	assert_eq!(response.outputs[0].value, amount);
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			slate = api.process_cold_sign_response(m, &response)?;
+			let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+			assert_eq!(txs.len(), 1);
+			assert_eq!(txs[0].tx_type, TxLogEntryType::TxReceived);
+			assert_eq!(txs[0].amount_credited, amount);
+			Ok(())
+		},
+	)?;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet2.clone(), mask2, 2, false);
+
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+			assert_eq!(info.total, cold_total - fee);
+			let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+			assert!(outputs
+				.iter()
+				.any(|o| o.output.status == OutputStatus::Unspent && o.output.value == amount));
+			Ok(())
+		},
+	)?;
+
+	// Cancelled cold transaction releases its inputs and drops its change
+	let request = send_request()?;
+	let response = cold_sign(&request)?;
+	wallet::controller::owner_single_use(
+		Some(wallet_online.clone()),
+		mask_online,
+		None,
+		|api, m| {
+			let slate = api.process_cold_sign_response(m, &response)?;
+			api.cancel_tx(m, None, Some(slate.id))?;
+			let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+			assert_eq!(txs[0].tx_type, TxLogEntryType::TxSentCancelled);
+			let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+			assert!(outputs
+				.iter()
+				.all(|o| o.output.status == OutputStatus::Unspent));
+			let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+			assert_eq!(info.total, cold_total - fee);
+			Ok(())
+		},
+	)?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn cold_wallet() {
+	let test_dir = "test_output/cold_wallet";
+	setup(test_dir);
+	if let Err(e) = cold_wallet_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,156 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the fee estimation and the fee policy
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_config::FeePolicyConfig;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{ErrorKind, InitTxArgs, OutputStatus};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn fee_policy_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	let amount = 2_000_000_000;
+	let args = |use_all: bool| InitTxArgs {
+		src_acct_name: None,
+		amount,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 2,
+		selection_strategy_is_use_all: use_all,
+		..Default::default()
+	};
+	let fee_policy = |fee_per_kweight, max_fee, max_fee_percent| {
+		Some(FeePolicyConfig {
+			fee_per_kweight,
+			max_fee,
+			max_fee_percent,
+		})
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// Estimate matches the transaction and doesn't lock anything
+		let estimate = api.estimate_fee(m, args(false))?;
+		assert_eq!(estimate.amount, amount);
+		assert_eq!(estimate.num_inputs, 1);
+		assert_eq!(estimate.num_outputs, 3);
+		assert_eq!(estimate.max_fee, None);
+		assert!(!estimate.exceeds_max_fee());
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status != OutputStatus::Locked));
+		let slate = api.init_send_tx(m, args(false), 1)?;
+		assert_eq!(slate.fee, estimate.fee);
+
+		// More inputs cost less, the strategies are estimated separately
+		let estimate_all = api.estimate_fee(m, args(true))?;
+		assert!(estimate_all.num_inputs > 1);
+		assert!(estimate_all.fee < estimate.fee);
+
+		// Rate below the network default pays the default fee
+		let default_fee = api.estimate_fee(m, args(false))?.fee;
+		api.set_fee_policy(fee_policy(Some(1), None, None))?;
+		assert_eq!(api.estimate_fee(m, args(false))?.fee, default_fee);
+
+		// Fee follows the configured rate
+		api.set_fee_policy(fee_policy(Some(2_000_000_000), None, None))?;
+		let rate1 = api.estimate_fee(m, args(false))?.fee;
+		api.set_fee_policy(fee_policy(Some(6_000_000_000), None, None))?;
+		let rate3 = api.estimate_fee(m, args(false))?;
+		assert_eq!(rate3.fee, rate1 * 3);
+		let slate = api.init_send_tx(m, args(false), 1)?;
+		assert_eq!(slate.fee, rate3.fee);
+
+		// Transactions over the limits are rejected before anything is locked
+		api.set_fee_policy(fee_policy(Some(6_000_000_000), Some(rate3.fee - 1), None))?;
+		let estimate = api.estimate_fee(m, args(false))?;
+		assert_eq!(estimate.max_fee, Some(rate3.fee - 1));
+		assert!(estimate.exceeds_max_fee());
+		match api.init_send_tx(m, args(false), 1) {
+			Err(e) => match e.kind() {
+				ErrorKind::FeeTooHigh { fee, max_fee, .. } => {
+					assert_eq!(fee, rate3.fee);
+					assert_eq!(max_fee, rate3.fee - 1);
+				}
+				_ => return Err(e.into()),
+			},
+			Ok(_) => panic!("transaction over the max fee is created"),
+		}
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status != OutputStatus::Locked));
+
+		let percent = rate3.fee * 100 / amount;
+		api.set_fee_policy(fee_policy(Some(6_000_000_000), None, Some(percent)))?;
+		assert!(api.estimate_fee(m, args(false))?.exceeds_max_fee());
+		assert!(api.init_send_tx(m, args(false), 1).is_err());
+		api.set_fee_policy(fee_policy(
+			Some(6_000_000_000),
+			Some(rate3.fee),
+			Some(percent + 1),
+		))?;
+		assert!(!api.estimate_fee(m, args(false))?.exceeds_max_fee());
+		api.init_send_tx(m, args(false), 1)?;
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn fee_policy() {
+	let test_dir = "test_output/fee_policy";
+	setup(test_dir);
+	if let Err(e) = fee_policy_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the verification of the node data against the header chain
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::core::hash::Hashed;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::internal::header_sync;
+use libwallet::NodeClient;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Sync the header chain during the wallet update and verify the headers
+fn header_sync_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, _m| {
+		api.set_header_verification(true, None)
+	})?;
+
+	// Do some mining
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (validated, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(validated);
+		assert_eq!(info.last_confirmed_height, bh);
+		Ok(())
+	})?;
+
+	{
+		wallet_inst!(wallet1, w);
+		let tip = header_sync::verified_tip(&**w).unwrap();
+		assert_eq!(tip.height, bh);
+		assert_eq!(tip.hash, chain.head().unwrap().last_block_h.to_hex());
+		assert!(header_sync::check_header_hash(&**w, bh, &tip.hash).is_ok());
+		assert!(header_sync::check_header_hash(&**w, bh, "00").is_err());
+		assert!(header_sync::check_heights(&tip, vec![1, bh]).is_ok());
+		assert!(header_sync::check_heights(&tip, vec![bh + 1]).is_err());
+
+		// Outputs and kernels must be in the MMR range of the block at their height
+		let first = w.get_verified_header(1)?.unwrap();
+		let out_size = first.output_mmr_size;
+		let kernel_size = first.kernel_mmr_size;
+		assert!(header_sync::check_outputs(&**w, &tip, vec![(1, out_size)]).is_ok());
+		assert!(header_sync::check_outputs(&**w, &tip, vec![(1, out_size + 1)]).is_err());
+		assert!(header_sync::check_outputs(&**w, &tip, vec![(2, out_size)]).is_err());
+		assert!(header_sync::check_kernels(&**w, &tip, vec![(1, kernel_size)]).is_ok());
+		assert!(header_sync::check_kernels(&**w, &tip, vec![(2, kernel_size)]).is_err());
+		assert!(header_sync::check_kernels(&**w, &tip, vec![(bh + 1, 1)]).is_err());
+
+		// Headers with the broken link, proof of work or scaling are rejected
+		let window: Vec<_> = (0..bh)
+			.map(|h| w.get_verified_header(h).unwrap().unwrap())
+			.collect();
+		let header = w.w2n_client().get_block_header(bh)?;
+		assert!(header_sync::verify_header(&header, &window).is_ok());
+
+		let mut bad_pow = header.clone();
+		bad_pow.pow.nonce += 1;
+		assert!(header_sync::verify_header(&bad_pow, &window).is_err());
+
+		let mut bad_link = header.clone();
+		bad_link.prev_hash = header.hash();
+		assert!(header_sync::verify_header(&bad_link, &window).is_err());
+
+		let mut bad_scaling = header.clone();
+		bad_scaling.pow.secondary_scaling += 1;
+		assert!(header_sync::verify_header(&bad_scaling, &window).is_err());
+	}
+
+	// More blocks extend the verified chain
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (validated, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(validated);
+		Ok(())
+	})?;
+	{
+		wallet_inst!(wallet1, w);
+		assert_eq!(header_sync::verified_tip(&**w).unwrap().height, bh + 5);
+	}
+
+	// The header chain of a new wallet must match the checkpoint
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.set_header_verification(true, Some((1, "00".to_string())))?;
+		assert!(api.retrieve_summary_info(m, true, 1).is_err());
+		Ok(())
+	})?;
+	let checkpoint = chain.get_header_by_height(1).unwrap().hash().to_hex();
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.set_header_verification(true, Some((1, checkpoint)))?;
+		let (validated, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(validated);
+		Ok(())
+	})?;
+	{
+		wallet_inst!(wallet2, w);
+		assert_eq!(header_sync::verified_tip(&**w).unwrap().height, bh + 5);
+		assert!(w.get_verified_header(0)?.is_some());
+	}
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn header_sync() {
+	let test_dir = "test_output/header_sync";
+	setup(test_dir);
+	if let Err(e) = header_sync_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
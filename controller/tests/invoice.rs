@@ -21,7 +21,7 @@ use grin_wallet_libwallet as libwallet;
 use grin_wallet_util::grin_core as core;
 
 use impls::test_framework::{self, LocalWalletClient};
-use libwallet::{InitTxArgs, IssueInvoiceTxArgs, Slate};
+use libwallet::{InitTxArgs, InvoiceStatus, IssueInvoiceTxArgs, Slate};
 use std::thread;
 use std::time::Duration;
 
@@ -99,6 +99,8 @@ fn invoice_tx_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
 		// Wallet 2 inititates an invoice transaction, requesting payment
 		let args = IssueInvoiceTxArgs {
 			amount: reward * 2,
+			reference: Some("order-1".to_owned()),
+			expiry_blocks: Some(100),
 			..Default::default()
 		};
 		slate = api.issue_invoice_tx(m, args)?;
@@ -167,6 +169,48 @@ fn invoice_tx_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
 		Ok(())
 	})?;
 
+	// Invoice for wallet 2 is matched by the finalized slate
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, invoices) = api.retrieve_invoices(m, true, None, Some("order-1".to_owned()))?;
+		assert_eq!(invoices.len(), 1);
+		assert_eq!(invoices[0].id, slate.id);
+		assert_eq!(invoices[0].status, InvoiceStatus::Paid);
+		assert!(invoices[0].paid_ts.is_some());
+		assert_eq!(invoices[0].expiry_height, slate.ttl_cutoff_height);
+
+		// Cancelled invoice can't be cancelled again, expired one is reported as expired
+		let args = IssueInvoiceTxArgs {
+			amount: reward,
+			reference: Some("order-2".to_owned()),
+			..Default::default()
+		};
+		let cancelled = api.issue_invoice_tx(m, args)?;
+		api.cancel_invoice(m, cancelled.id)?;
+		assert!(api.cancel_invoice(m, cancelled.id).is_err());
+		let args = IssueInvoiceTxArgs {
+			amount: reward,
+			reference: Some("order-3".to_owned()),
+			expiry_blocks: Some(1),
+			..Default::default()
+		};
+		api.issue_invoice_tx(m, args)?;
+		Ok(())
+	})?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, invoices) = api.retrieve_invoices(m, true, None, None)?;
+		let statuses: Vec<InvoiceStatus> = invoices.iter().map(|i| i.status).collect();
+		assert_eq!(
+			statuses,
+			vec![
+				InvoiceStatus::Paid,
+				InvoiceStatus::Cancelled,
+				InvoiceStatus::Expired
+			]
+		);
+		Ok(())
+	})?;
+
 	// Test self-sending
 	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
 		// Wallet 1 inititates an invoice transaction, requesting payment
@@ -0,0 +1,270 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test two wallets exchanging slates and swap messages through the self hosted MWCMQS broker
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+use grin_wallet_util::grin_keychain as keychain;
+use grin_wallet_util::grin_util::secp::key::SecretKey;
+use grin_wallet_util::grin_util::Mutex;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_mqs_broker::{start_broker, BrokerConfig};
+use impls::test_framework::{self, LocalWalletClient};
+use impls::{
+	Address, CloseReason, DefaultLCProvider, MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber,
+	Publisher, Subscriber, SubscriptionHandler,
+};
+use libwallet::proof::crypto;
+use libwallet::proof::proofaddress::{self, ProvableAddress};
+use libwallet::swap::message::{Message, SecondaryUpdate, Update};
+use libwallet::{InitTxArgs, Slate, WalletInst};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+use wallet::controller::Controller;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+type TestLCProvider = DefaultLCProvider<'static, LocalWalletClient, keychain::ExtKeychain>;
+type TestWallet = Arc<
+	Mutex<
+		Box<
+			dyn WalletInst<'static, TestLCProvider, LocalWalletClient, keychain::ExtKeychain>
+				+ 'static,
+		>,
+	>,
+>;
+
+/// Processes the slates with the wallet controller and records the swap messages
+struct TestHandler {
+	controller: Controller<TestLCProvider, LocalWalletClient, keychain::ExtKeychain>,
+	swap_messages: Arc<Mutex<Vec<Uuid>>>,
+}
+
+impl SubscriptionHandler for TestHandler {
+	fn on_open(&self) {
+		self.controller.on_open()
+	}
+	fn on_slate(&self, from: &dyn Address, slate: &mut Slate) {
+		self.controller.on_slate(from, slate)
+	}
+	fn on_close(&self, result: CloseReason) {
+		self.controller.on_close(result)
+	}
+	fn on_dropped(&self) {
+		self.controller.on_dropped()
+	}
+	fn on_reestablished(&self) {
+		self.controller.on_reestablished()
+	}
+	fn on_swap_message(&self, swap: Message) -> Option<Message> {
+		self.swap_messages.lock().push(swap.id);
+		None
+	}
+	fn set_notification_channels(&self, slate_id: &Uuid, slate_send_channel: Sender<Slate>) {
+		self.controller
+			.set_notification_channels(slate_id, slate_send_channel)
+	}
+	fn reset_notification_channels(&self, slate_id: &Uuid) {
+		self.controller.reset_notification_channels(slate_id)
+	}
+}
+
+/// Start the MQS listener of the wallet on the local broker
+fn start_listener(
+	name: &str,
+	wallet: TestWallet,
+	mask: Option<SecretKey>,
+	port: u16,
+) -> Result<
+	(
+		MWCMQPublisher,
+		MWCMQSubscriber,
+		MWCMQSAddress,
+		Arc<Mutex<Vec<Uuid>>>,
+	),
+	wallet::Error,
+> {
+	let secret_key = {
+		wallet_inst!(wallet, w);
+		proofaddress::payment_proof_address_secret(&w.keychain(mask.as_ref())?)?
+	};
+	let public_key = crypto::public_key_from_secret_key(&secret_key)?;
+	let address = MWCMQSAddress::new(
+		ProvableAddress::from_pub_key(&public_key),
+		Some("127.0.0.1".to_string()),
+		Some(port),
+	);
+
+	let controller = Controller::new(name, wallet, Arc::new(Mutex::new(mask)), None, true);
+	let swap_messages = Arc::new(Mutex::new(vec![]));
+	let publisher = MWCMQPublisher::new(
+		address.clone(),
+		&secret_key,
+		"127.0.0.1".to_string(),
+		port,
+		false,
+		false,
+		true,
+		Box::new(TestHandler {
+			controller: controller.clone(),
+			swap_messages: swap_messages.clone(),
+		}),
+	);
+	controller.set_publisher(Box::new(publisher.clone()));
+
+	let subscriber = MWCMQSubscriber::new(&publisher);
+	let mut cloned_subscriber = subscriber.clone();
+	thread::spawn(move || {
+		if let Err(e) = cloned_subscriber.start() {
+			error!("MQS subscriber error: {}", e);
+		}
+	});
+	// wait for the first poll
+	while !subscriber.is_running() {
+		thread::sleep(Duration::from_millis(100));
+	}
+	thread::sleep(Duration::from_millis(3000));
+	Ok((publisher, subscriber, address, swap_messages))
+}
+
+fn mwcmqs_broker_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	let mut broker = start_broker(BrokerConfig {
+		bind_addr: "127.0.0.1:0".parse().unwrap(),
+		poll_timeout: Duration::from_secs(5),
+		..BrokerConfig::default()
+	})
+	.unwrap();
+	let port = broker.local_addr().port();
+
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		true
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		true
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let (publisher1, mut subscriber1, _address1, swaps1) =
+		start_listener("wallet1", wallet1.clone(), mask1_i.clone(), port)?;
+	let (publisher2, mut subscriber2, address2, swaps2) =
+		start_listener("wallet2", wallet2.clone(), mask2_i.clone(), port)?;
+
+	// Send the slate to wallet2 and wait for the response
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: reward * 2,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			..Default::default()
+		};
+		let slate = api.init_send_tx(m, args, 1)?;
+		api.tx_lock_outputs(m, &slate, Some(address2.get_full_name()), 0)?;
+
+		let (tx, rx) = channel();
+		subscriber1.set_notification_channels(&slate.id, tx);
+		publisher1.post_slate(&slate, &address2)?;
+		let slate = rx
+			.recv_timeout(Duration::from_secs(30))
+			.expect("slate is not returned by wallet2");
+		subscriber1.reset_notification_channels(&slate.id);
+
+		let slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (wallet2_refreshed, wallet2_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(wallet2_refreshed);
+		assert_eq!(wallet2_info.amount_currently_spendable, reward * 2);
+		Ok(())
+	})?;
+
+	// Swap messages go both ways
+	let swap_id = Uuid::new_v4();
+	let message = Message::new(swap_id, Update::None, SecondaryUpdate::Empty);
+	let address1 = publisher1.get_publisher_address()?;
+	publisher1.post_take(&message, &address2)?;
+	publisher2.post_take(&message, address1.as_ref())?;
+	for _ in 0..100 {
+		if !swaps1.lock().is_empty() && !swaps2.lock().is_empty() {
+			break;
+		}
+		thread::sleep(Duration::from_millis(100));
+	}
+	assert_eq!(*swaps1.lock(), vec![swap_id]);
+	assert_eq!(*swaps2.lock(), vec![swap_id]);
+
+	subscriber1.stop();
+	subscriber2.stop();
+	broker.stop();
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn mwcmqs_broker() {
+	let test_dir = "test_output/mwcmqs_broker";
+	setup(test_dir);
+	if let Err(e) = mwcmqs_broker_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
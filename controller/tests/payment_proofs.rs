@@ -27,6 +27,9 @@ use std::time::Duration;
 #[macro_use]
 mod common;
 use common::{clean_output_dir, create_wallet_proxy, setup};
+use grin_wallet_libwallet::proof::payment_proof::{
+	verify_payment_proof_document, PaymentProofDocument,
+};
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
 
 /// Various tests on accounts within the same wallet
@@ -162,6 +165,22 @@ fn payment_proofs_test_impl(test_dir: &'static str) -> Result<(), wallet::Error>
 		pp.amount = 20;
 		let res = sender_api.verify_payment_proof(m, &pp);
 		assert!(res.is_err());
+
+		// Standalone document, verifiable offline and with the node
+		let doc = sender_api.export_payment_proof_document(m, true, None, Some(slate.id))?;
+		let doc = PaymentProofDocument::from_json(&doc.to_json()?)?;
+		verify_payment_proof_document(&doc)?;
+		sender_api.verify_payment_proof_document(m, &doc, true)?;
+
+		let mut bad_doc = doc.clone();
+		bad_doc.amount = 20;
+		assert!(verify_payment_proof_document(&bad_doc).is_err());
+		let mut bad_doc = doc.clone();
+		bad_doc.block_height += 1;
+		assert!(verify_payment_proof_document(&bad_doc).is_ok());
+		assert!(sender_api
+			.verify_payment_proof_document(m, &bad_doc, true)
+			.is_err());
 		Ok(())
 	})?;
 
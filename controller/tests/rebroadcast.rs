@@ -0,0 +1,193 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the rebroadcast of the posted transactions that are not on the chain
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use grin_wallet_config::RebroadcastConfig;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, Slate, StatusMessage, TxLogEntry, TxLogEntryType};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn rebroadcast_config(interval_secs: u64, max_retries: u32, cancel: bool) -> RebroadcastConfig {
+	RebroadcastConfig {
+		enabled: Some(true),
+		interval_secs: Some(interval_secs),
+		backoff_factor: Some(2),
+		max_retries: Some(max_retries),
+		cancel_after_retries: Some(cancel),
+	}
+}
+
+fn rebroadcast_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	// Finalized transaction that the node lost after the post
+	let lost_tx = || -> Result<Slate, wallet::Error> {
+		let mut slate = Slate::blank(2);
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: reward,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				..Default::default()
+			};
+			let slate_i = api.init_send_tx(m, args, 1)?;
+			slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			slate = api.finalize_tx(m, &slate)?;
+			Ok(())
+		})?;
+		wallet_inst!(wallet1, w);
+		libwallet::owner::track_posted_tx(&mut **w, mask1, &slate.tx)?;
+		Ok(slate)
+	};
+	let sent_tx = |slate: &Slate| -> Result<TxLogEntry, wallet::Error> {
+		let mut tx = None;
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+			tx = txs.into_iter().next();
+			Ok(())
+		})?;
+		Ok(tx.unwrap())
+	};
+	let set_rebroadcast_config = |config: Option<RebroadcastConfig>| {
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, _| {
+			api.set_rebroadcast_config(config)?;
+			Ok(())
+		})
+	};
+	let run_rebroadcast = || -> Result<Vec<StatusMessage>, wallet::Error> {
+		let (tx, rx) = channel();
+		libwallet::owner::rebroadcast_txs(wallet1.clone(), mask1, &Some(tx))?;
+		Ok(rx.try_iter().collect())
+	};
+
+	let slate = lost_tx()?;
+	let tx = sent_tx(&slate)?;
+	assert_eq!(tx.rebroadcast.as_ref().unwrap().retries, 0);
+
+	// Nothing happens by default, before the interval or when the rebroadcast is disabled
+	assert!(run_rebroadcast()?.is_empty());
+	set_rebroadcast_config(Some(rebroadcast_config(3600, 1, false)))?;
+	assert!(run_rebroadcast()?.is_empty());
+	set_rebroadcast_config(None)?;
+	assert!(run_rebroadcast()?.is_empty());
+	assert_eq!(sent_tx(&slate)?.rebroadcast, tx.rebroadcast);
+
+	// Missing transaction is posted again, the post mines it in the test chain
+	set_rebroadcast_config(Some(rebroadcast_config(0, 1, false)))?;
+	let messages = run_rebroadcast()?;
+	assert_eq!(messages.len(), 1);
+	match &messages[0] {
+		StatusMessage::Info(s) => assert!(s.contains("posted again, retry 1")),
+		m => panic!("unexpected message {:?}", m),
+	}
+	assert_eq!(sent_tx(&slate)?.rebroadcast.unwrap().retries, 1);
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+	let tx = sent_tx(&slate)?;
+	assert!(tx.confirmed);
+	// Confirmed transaction is not tracked any more
+	assert!(run_rebroadcast()?.is_empty());
+
+	// Transaction stays unconfirmed when the retries are exhausted
+	let slate = lost_tx()?;
+	set_rebroadcast_config(Some(rebroadcast_config(0, 0, false)))?;
+	let messages = run_rebroadcast()?;
+	assert_eq!(messages.len(), 1);
+	match &messages[0] {
+		StatusMessage::Warning(s) => assert!(s.contains("is not posted any more")),
+		m => panic!("unexpected message {:?}", m),
+	}
+	let tx = sent_tx(&slate)?;
+	assert_eq!(tx.tx_type, TxLogEntryType::TxSent);
+	assert!(tx.rebroadcast.is_none());
+	assert!(run_rebroadcast()?.is_empty());
+
+	// Transaction is cancelled when the config asks for it
+	let slate = lost_tx()?;
+	set_rebroadcast_config(Some(rebroadcast_config(0, 0, true)))?;
+	let messages = run_rebroadcast()?;
+	assert_eq!(messages.len(), 1);
+	match &messages[0] {
+		StatusMessage::Warning(s) => assert!(s.contains("is cancelled")),
+		m => panic!("unexpected message {:?}", m),
+	}
+	let tx = sent_tx(&slate)?;
+	assert_eq!(tx.tx_type, TxLogEntryType::TxSentCancelled);
+	assert!(tx.rebroadcast.is_none());
+	assert!(run_rebroadcast()?.is_empty());
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn rebroadcast() {
+	let test_dir = "test_output/rebroadcast";
+	setup(test_dir);
+	if let Err(e) = rebroadcast_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,263 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the receive policy of the foreign API
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_config::ReceivePolicyConfig;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::proof::proofaddress::ProvableAddress;
+use libwallet::{ErrorKind, InitTxArgs, Slate};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test the slates that are rejected by the receive policy
+fn receive_policy_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// Do some mining
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let mut address = None;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		address = Some(api.get_public_proof_address(m)?);
+		Ok(())
+	})?;
+	let address = ProvableAddress::from_pub_key(&address.unwrap());
+
+	// Receive policy of wallet 2
+	let set_receive_policy = |policy: Option<ReceivePolicyConfig>| {
+		wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, _| {
+			api.set_receive_policy(policy)?;
+			Ok(())
+		})
+	};
+	set_receive_policy(Some(ReceivePolicyConfig {
+		min_amount: Some(1_000_000_000),
+		max_pending_receives: Some(5),
+		sender_rate_limit: Some(1),
+		sender_rate_period_secs: Some(3600),
+		allowed_senders: None,
+		denied_senders: Some(vec!["mwcmqs://denied@mqs.mwc.mw".to_string()]),
+		require_payment_proof: Some(true),
+		manual_approval: None,
+		approval_timeout_secs: None,
+		max_pending_approvals: None,
+		max_pending_approvals_per_sender: None,
+	}))?;
+
+	// Slates are built by wallet1 and are sent to wallet2 from the different addresses
+	let init_slate = |amount: u64, proof: bool| -> Result<Slate, wallet::Error> {
+		let mut slate = Slate::blank(1);
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: amount,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				payment_proof_recipient_address: if proof { Some(address.clone()) } else { None },
+				..Default::default()
+			};
+			slate = api.init_send_tx(m, args, 1)?;
+			Ok(())
+		})?;
+		Ok(slate)
+	};
+
+	// Sender None is the slate from the http or tor listener without a payment proof
+	let receive = |slate: &Slate, from: Option<&str>| -> Result<Option<String>, wallet::Error> {
+		let mut rule = None;
+		wallet::controller::foreign_single_use(wallet2.clone(), mask2_i.clone(), |api| {
+			match api.receive_tx(slate, from.map(|f| f.to_string()), None, None) {
+				Ok(_) => (),
+				Err(e) => match e.kind() {
+					ErrorKind::ReceivePolicyViolation { rule: r, .. } => rule = Some(r),
+					_ => return Err(e.into()),
+				},
+			}
+			Ok(())
+		})?;
+		Ok(rule)
+	};
+
+	let slate = init_slate(2_000_000_000, false)?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://sender1@mqs.mwc.mw"))?,
+		Some("require_payment_proof".to_string())
+	);
+	// Slates from files are accepted by the wallet owner, the policy doesn't apply
+	assert_eq!(receive(&slate, Some("file"))?, None);
+
+	let slate = init_slate(500_000_000, true)?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://sender1@mqs.mwc.mw"))?,
+		Some("min_amount".to_string())
+	);
+
+	let slate = init_slate(2_000_000_000, true)?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://denied@mqs.mwc.mw:443"))?,
+		Some("denied_senders".to_string())
+	);
+	assert_eq!(receive(&slate, Some("mwcmqs://sender1@mqs.mwc.mw"))?, None);
+
+	let slate = init_slate(2_000_000_000, true)?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://sender1@mqs.mwc.mw:443"))?,
+		Some("sender_rate_limit".to_string())
+	);
+	assert_eq!(receive(&slate, Some("mwcmqs://sender2@mqs.mwc.mw"))?, None);
+
+	// Three received slates are pending, no more receives are accepted
+	set_receive_policy(Some(ReceivePolicyConfig {
+		min_amount: None,
+		max_pending_receives: Some(3),
+		sender_rate_limit: None,
+		sender_rate_period_secs: None,
+		allowed_senders: None,
+		denied_senders: None,
+		require_payment_proof: None,
+		manual_approval: None,
+		approval_timeout_secs: None,
+		max_pending_approvals: None,
+		max_pending_approvals_per_sender: None,
+	}))?;
+	let slate = init_slate(2_000_000_000, true)?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://sender3@mqs.mwc.mw"))?,
+		Some("max_pending_receives".to_string())
+	);
+
+	// Only allowed senders
+	set_receive_policy(Some(ReceivePolicyConfig {
+		min_amount: None,
+		max_pending_receives: None,
+		sender_rate_limit: None,
+		sender_rate_period_secs: None,
+		allowed_senders: Some(vec!["sender3".to_string()]),
+		denied_senders: None,
+		require_payment_proof: None,
+		manual_approval: None,
+		approval_timeout_secs: None,
+		max_pending_approvals: None,
+		max_pending_approvals_per_sender: None,
+	}))?;
+	assert_eq!(
+		receive(&slate, Some("mwcmqs://sender2@mqs.mwc.mw"))?,
+		Some("allowed_senders".to_string())
+	);
+	assert_eq!(receive(&slate, Some("mwcmqs://sender3@mqs.mwc.mw"))?, None);
+	// Sender without an address and a payment proof is not in the allowed list
+	let slate = init_slate(2_000_000_000, false)?;
+	assert_eq!(receive(&slate, None)?, Some("allowed_senders".to_string()));
+
+	// Senders without an address and a payment proof share one rate limit
+	set_receive_policy(Some(ReceivePolicyConfig {
+		min_amount: None,
+		max_pending_receives: None,
+		sender_rate_limit: Some(1),
+		sender_rate_period_secs: Some(3600),
+		allowed_senders: None,
+		denied_senders: None,
+		require_payment_proof: None,
+		manual_approval: None,
+		approval_timeout_secs: None,
+		max_pending_approvals: None,
+		max_pending_approvals_per_sender: None,
+	}))?;
+	assert_eq!(receive(&slate, None)?, None);
+	let slate = init_slate(2_000_000_000, false)?;
+	assert_eq!(
+		receive(&slate, None)?,
+		Some("sender_rate_limit".to_string())
+	);
+
+	// And can be denied together
+	set_receive_policy(Some(ReceivePolicyConfig {
+		min_amount: None,
+		max_pending_receives: None,
+		sender_rate_limit: None,
+		sender_rate_period_secs: None,
+		allowed_senders: None,
+		denied_senders: Some(vec!["anonymous".to_string()]),
+		require_payment_proof: None,
+		manual_approval: None,
+		approval_timeout_secs: None,
+		max_pending_approvals: None,
+		max_pending_approvals_per_sender: None,
+	}))?;
+	assert_eq!(receive(&slate, None)?, Some("denied_senders".to_string()));
+	assert_eq!(receive(&slate, Some("mwcmqs://sender4@mqs.mwc.mw"))?, None);
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn receive_policy() {
+	let test_dir = "test_output/receive_policy";
+	setup(test_dir);
+	if let Err(e) = receive_policy_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,270 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the rollback of the confirmations from the abandoned branch after a chain reorg
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{OutputStatus, StatusMessage, TxLogEntryType};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+macro_rules! send_to_dest {
+	($a:expr, $m: expr, $b:expr, $c:expr, $d:expr) => {
+		test_framework::send_to_dest($a, $m, $b, $c, $d, false, None, 1)
+	};
+}
+
+fn reorg_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+	create_wallet_and_add!(
+		miner_client,
+		miner,
+		miner_mask_i,
+		test_dir,
+		"miner",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let miner_mask = (&miner_mask_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let fork_height = 10u64;
+	let _ = test_framework::award_blocks_to_wallet(
+		&chain,
+		wallet1.clone(),
+		mask1,
+		fork_height as usize,
+		false,
+	);
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		Ok(())
+	})?;
+	let fork_header = chain.head_header().unwrap();
+
+	// Payment is confirmed at the branch that will be abandoned
+	send_to_dest!(wallet1.clone(), mask1, client1.clone(), "wallet2", reward)?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+	assert_eq!(chain.head().unwrap().height, fork_height + 3);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		let sent = txs
+			.iter()
+			.find(|t| t.tx_type == TxLogEntryType::TxSent)
+			.unwrap();
+		assert!(sent.confirmed);
+		assert_eq!(sent.output_height, fork_height + 1);
+		Ok(())
+	})?;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(info.amount_currently_spendable, reward);
+		Ok(())
+	})?;
+
+	// Longer fork without the payment replaces the branch
+	test_framework::award_fork_blocks_to_wallet(
+		&chain,
+		&fork_header,
+		miner.clone(),
+		miner_mask,
+		5,
+	)?;
+	assert_eq!(chain.head().unwrap().height, fork_height + 5);
+
+	let (tx, rx) = channel();
+	libwallet::owner::update_wallet_state(wallet1.clone(), mask1, &Some(tx))?;
+	let warnings: Vec<String> = rx
+		.try_iter()
+		.filter_map(|m| match m {
+			StatusMessage::Warning(s) => Some(s),
+			_ => None,
+		})
+		.collect();
+	assert!(warnings.iter().any(|w| w.contains(&format!(
+		"blocks after height {} were replaced",
+		fork_height
+	))));
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		let sent = txs
+			.iter()
+			.find(|t| t.tx_type == TxLogEntryType::TxSent)
+			.unwrap();
+		assert!(!sent.confirmed);
+		assert!(txs
+			.iter()
+			.filter(|t| t.confirmed)
+			.all(|t| t.output_height <= fork_height));
+
+		// Inputs of the payment are back in the UTXO set, the outputs from the abandoned
+		// blocks are not confirmed
+		let (_, outputs) = api.retrieve_outputs(m, true, false, None)?;
+		let count =
+			|status: OutputStatus| outputs.iter().filter(|o| o.output.status == status).count();
+		assert_eq!(count(OutputStatus::Locked), sent.num_inputs);
+		assert_eq!(count(OutputStatus::Spent), 0);
+		assert_eq!(count(OutputStatus::Unconfirmed), 4);
+		assert!(outputs
+			.iter()
+			.filter(|o| o.output.status == OutputStatus::Unspent)
+			.all(|o| o.output.height <= fork_height));
+		Ok(())
+	})?;
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(info.amount_currently_spendable, 0);
+		assert_eq!(info.amount_awaiting_confirmation, reward);
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		assert!(txs.iter().all(|t| !t.confirmed));
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+fn fresh_wallet_update_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		miner_client,
+		miner,
+		miner_mask_i,
+		test_dir,
+		"miner",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let miner_mask = (&miner_mask_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 5, false);
+
+	// Wallet without the scanned blocks does the full scan instead of skipping the update
+	{
+		wallet_inst!(wallet1, w);
+		assert!(w.last_scanned_blocks()?.is_empty());
+	}
+	assert!(libwallet::owner::update_wallet_state(
+		wallet1.clone(),
+		mask1,
+		&None
+	)?);
+	{
+		wallet_inst!(wallet1, w);
+		let blocks = w.last_scanned_blocks()?;
+		assert_eq!(
+			blocks.first().map(|b| b.height),
+			Some(chain.head().unwrap().height)
+		);
+	}
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn reorg() {
+	let test_dir = "test_output/reorg";
+	setup(test_dir);
+	if let Err(e) = reorg_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
+
+#[test]
+fn fresh_wallet_update() {
+	let test_dir = "test_output/fresh_wallet_update";
+	setup(test_dir);
+	if let Err(e) = fresh_wallet_update_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
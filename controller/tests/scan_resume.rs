@@ -0,0 +1,159 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests resuming the interrupted scan from the saved checkpoint
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use chrono::Utc;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::internal::scan;
+use libwallet::{NodeClient, ScanCheckpoint};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn scan_resume_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let bh = 20u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(info.total, bh * reward);
+		Ok(())
+	})?;
+
+	// Checkpoint of the scan that was interrupted in the middle of the PMMR range
+	let checkpoint = {
+		wallet_inst!(wallet1, w);
+		let client = w.w2n_client().clone();
+		let keychain = w.keychain(mask1)?;
+		let (tip_height, tip_hash, _) = client.get_chain_tip()?;
+		let pmmr_range = client.height_range_to_pmmr_indices(1, Some(tip_height))?;
+		let mid = pmmr_range.0 + (pmmr_range.1 - pmmr_range.0) / 2;
+		let first_half =
+			scan::collect_chain_outputs(&keychain, client, pmmr_range.0, Some(mid), &None, false)?;
+		assert!(first_half.len() > 3);
+
+		// The wallet lost two outputs, the checkpoint knows only the second one
+		let mut batch = w.batch(mask1)?;
+		batch.delete(&first_half[1].key_id, &None)?;
+		batch.delete(&first_half[2].key_id, &None)?;
+		for (i, out) in first_half.iter().enumerate() {
+			if i != 1 {
+				batch.save_scan_output(out.clone())?;
+			}
+		}
+		let checkpoint = ScanCheckpoint {
+			start_height: 1,
+			end_height: tip_height,
+			end_hash: tip_hash,
+			start_pmmr_index: pmmr_range.0,
+			end_pmmr_index: pmmr_range.1,
+			last_pmmr_index: mid,
+			outputs_found: first_half.len() as u64 - 1,
+			child_indexes: vec![],
+			updated: Utc::now(),
+		};
+		batch.save_scan_checkpoint(checkpoint.clone())?;
+		batch.commit()?;
+		checkpoint
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let status = api.scan_status(m)?;
+		assert!(!status.running);
+		assert_eq!(
+			status.checkpoint.unwrap().last_pmmr_index,
+			checkpoint.last_pmmr_index
+		);
+		assert!(status.percent_complete > 0 && status.percent_complete < 100);
+		assert!(!api.pause_scan());
+
+		// Scan continues after the checkpoint, so the output that it doesn't know is not restored
+		api.scan(m, Some(1), false)?;
+		let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+		assert_eq!(info.total, (bh - 1) * reward);
+		let status = api.scan_status(m)?;
+		assert!(!status.running);
+		assert!(status.checkpoint.is_none());
+
+		// Completed scan doesn't leave the checkpoint, the next one checks the whole range
+		api.scan(m, Some(1), false)?;
+		let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+		assert_eq!(info.total, bh * reward);
+		Ok(())
+	})?;
+
+	// Cancel drops the progress of the interrupted scan
+	{
+		wallet_inst!(wallet1, w);
+		let mut batch = w.batch(mask1)?;
+		batch.save_scan_checkpoint(checkpoint)?;
+		batch.commit()?;
+	}
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		assert!(api.scan_status(m)?.checkpoint.is_some());
+		api.cancel_scan(m)?;
+		assert!(api.scan_status(m)?.checkpoint.is_none());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn scan_resume() {
+	let test_dir = "test_output/scan_resume";
+	setup(test_dir);
+	if let Err(e) = scan_resume_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
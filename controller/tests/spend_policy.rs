@@ -0,0 +1,164 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the outbound spending policy of the accounts
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_util::ZeroingString;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{ErrorKind, InitTxArgs, SpendPolicyArgs};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test the sends that are rejected by the spending policy
+fn spend_policy_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// Do some mining
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let allowed = "mwcmqs://xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU@mqs.mwc.mw";
+
+	// Builds and locks the slate, the locked send counts for the rolling limits.
+	// Returns the violated rule.
+	let send = |amount: u64, dest: &str| -> Result<Option<String>, wallet::Error> {
+		let mut rule = None;
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: amount,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				address: Some(dest.to_string()),
+				..Default::default()
+			};
+			match api.init_send_tx(m, args, 1) {
+				Ok(slate) => api.tx_lock_outputs(m, &slate, Some(dest.to_string()), 0)?,
+				Err(e) => match e.kind() {
+					ErrorKind::SpendPolicyViolation { rule: r, .. } => rule = Some(r),
+					_ => return Err(e.into()),
+				},
+			}
+			Ok(())
+		})?;
+		Ok(rule)
+	};
+
+	let policy = SpendPolicyArgs {
+		src_acct_name: None,
+		max_per_tx: Some(1_000_000_000),
+		max_24h: Some(1_500_000_000),
+		max_7d: None,
+		allowed_destinations: Some(vec![allowed.to_string()]),
+		new_destination_cooldown_secs: None,
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// Wallet password is required to change the policy
+		assert!(api
+			.set_spend_policy(m, ZeroingString::from("wrong"), None, &policy)
+			.is_err());
+		assert!(api.retrieve_spend_policy(m, None)?.is_none());
+		api.set_spend_policy(m, ZeroingString::from(""), None, &policy)?;
+		Ok(())
+	})?;
+
+	assert_eq!(
+		send(1_200_000_000, allowed)?,
+		Some("max_per_tx".to_string())
+	);
+	assert_eq!(
+		send(500_000_000, "mwcmqs://other@mqs.mwc.mw")?,
+		Some("allowed_destinations".to_string())
+	);
+	assert_eq!(send(1_000_000_000, allowed)?, None);
+	assert_eq!(send(600_000_000, allowed)?, Some("max_24h".to_string()));
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let status = api.retrieve_spend_policy(m, None)?.unwrap();
+		assert_eq!(status.spent_24h, 1_000_000_000);
+		assert_eq!(status.spent_7d, 1_000_000_000);
+
+		// Newly allowed destination can't be paid until the cool-down is over
+		let policy = SpendPolicyArgs {
+			allowed_destinations: Some(vec![
+				allowed.to_string(),
+				"mwcmqs://other@mqs.mwc.mw".to_string(),
+			]),
+			new_destination_cooldown_secs: Some(3600),
+			..policy.clone()
+		};
+		api.set_spend_policy(m, ZeroingString::from(""), None, &policy)?;
+		Ok(())
+	})?;
+	assert_eq!(
+		send(100_000_000, "mwcmqs://other@mqs.mwc.mw")?,
+		Some("new_destination_cooldown_secs".to_string())
+	);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.clear_spend_policy(m, ZeroingString::from(""), None, None)?;
+		assert!(api.retrieve_spend_policy(m, None)?.is_none());
+		Ok(())
+	})?;
+	assert_eq!(send(1_200_000_000, "mwcmqs://other@mqs.mwc.mw")?, None);
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn spend_policy() {
+	let test_dir = "test_output/spend_policy";
+	setup(test_dir);
+	if let Err(e) = spend_policy_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
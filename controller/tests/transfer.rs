@@ -0,0 +1,148 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the transfer between the accounts of the wallet
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{ErrorKind, LinkedTx, OutputStatus, TransferArgs, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn transfer_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let amount = reward / 2;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	let transfer_args = |from: Option<&str>, to: &str| TransferArgs {
+		src_acct_name: from.map(|s| s.to_string()),
+		dest_acct_name: to.to_string(),
+		amount,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: false,
+		message: Some("savings".to_string()),
+		fluff: true,
+	};
+
+	let mut res = None;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.create_account_path(m, "savings")?;
+
+		// Unknown and same accounts are rejected before anything is locked
+		match api.transfer(m, transfer_args(None, "vacation")) {
+			Err(e) => match e.kind() {
+				ErrorKind::UnknownAccountLabel(l) => assert_eq!(l, "vacation"),
+				_ => return Err(e.into()),
+			},
+			Ok(_) => panic!("transfer to unknown account"),
+		}
+		assert!(api
+			.transfer(m, transfer_args(Some("default"), "default"))
+			.is_err());
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status != OutputStatus::Locked));
+
+		res = Some(api.transfer(m, transfer_args(None, "savings"))?);
+		Ok(())
+	})?;
+	let res = res.unwrap();
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	// Entries of both accounts reference each other
+	assert_eq!(res.src_tx.tx_type, TxLogEntryType::TxSent);
+	assert_eq!(res.dest_tx.tx_type, TxLogEntryType::TxReceived);
+	assert_eq!(res.src_tx.tx_slate_id, Some(res.slate_id));
+	assert_eq!(res.dest_tx.tx_slate_id, Some(res.slate_id));
+	assert_eq!(
+		res.src_tx.linked_tx,
+		Some(LinkedTx {
+			parent_key_id: res.dest_tx.parent_key_id.clone(),
+			id: res.dest_tx.id,
+		})
+	);
+	assert_eq!(
+		res.dest_tx.linked_tx,
+		Some(LinkedTx {
+			parent_key_id: res.src_tx.parent_key_id.clone(),
+			id: res.src_tx.id,
+		})
+	);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(res.slate_id))?;
+		assert_eq!(txs.len(), 1);
+		assert!(txs[0].confirmed);
+		assert_eq!(txs[0].linked_tx, res.src_tx.linked_tx);
+
+		api.set_active_account(m, "savings")?;
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(info.amount_currently_spendable, amount);
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(res.slate_id))?;
+		assert_eq!(txs.len(), 1);
+		assert!(txs[0].confirmed);
+		assert_eq!(txs[0].linked_tx, res.dest_tx.linked_tx);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn transfer() {
+	let test_dir = "test_output/transfer";
+	setup(test_dir);
+	if let Err(e) = transfer_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
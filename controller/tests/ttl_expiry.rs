@@ -0,0 +1,163 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the cancellation of the transactions with expired TTL
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, OutputStatus, Slate, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn ttl_expiry_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	let mut slate = Slate::blank(1);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.set_auto_cancel_expired(false)?;
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 2_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			ttl_blocks: Some(2),
+			..Default::default()
+		};
+		let slate_i = api.init_send_tx(m, args, 1)?;
+		slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+
+		// Nothing is expired before the cutoff height
+		assert!(api.retrieve_expired_txs(m)?.is_empty());
+		Ok(())
+	})?;
+
+	// Mine past the cutoff height, the disabled update leaves the transaction as it is
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+
+		// Dry run reports the changes without making them
+		let expired = api.retrieve_expired_txs(m)?;
+		assert_eq!(expired.len(), 1);
+		assert_eq!(expired[0].tx.tx_slate_id, Some(slate.id));
+		assert!(!expired[0].locked_inputs.is_empty());
+		assert_eq!(expired[0].unconfirmed_outputs.len(), 1);
+		assert!(expired[0]
+			.locked_inputs
+			.iter()
+			.all(|o| o.status == OutputStatus::Locked));
+		assert_eq!(api.retrieve_expired_txs(m)?.len(), 1);
+		Ok(())
+	})?;
+	{
+		wallet_inst!(wallet1, w);
+		assert!(w.get_private_context(mask1, slate.id.as_bytes(), 0).is_ok());
+	}
+
+	// Enabled update cancels the transaction and releases its inputs
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.set_auto_cancel_expired(true)?;
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSentCancelled);
+		assert_eq!(
+			txs[0].cancel_reason,
+			Some("TTL expired at height 12, chain tip 12".to_string())
+		);
+		assert!(api.retrieve_expired_txs(m)?.is_empty());
+
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status == OutputStatus::Unspent));
+		let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+		assert_eq!(info.amount_locked, 0);
+		assert_eq!(info.amount_awaiting_finalization, 0);
+		Ok(())
+	})?;
+	{
+		wallet_inst!(wallet1, w);
+		assert!(w
+			.get_private_context(mask1, slate.id.as_bytes(), 0)
+			.is_err());
+	}
+
+	// The receiver drops its unconfirmed output
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxReceivedCancelled);
+		assert!(txs[0].cancel_reason.is_some());
+		let (_, outputs) = api.retrieve_outputs(m, true, false, None)?;
+		assert!(outputs.is_empty());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn ttl_expiry() {
+	let test_dir = "test_output/ttl_expiry";
+	setup(test_dir);
+	if let Err(e) = ttl_expiry_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -17,6 +17,7 @@ use std::fs::File;
 use std::io::{Read, Write};
 
 use crate::error::{Error, ErrorKind};
+use crate::libwallet::slate_versions::binary::CompactSlate;
 use crate::libwallet::{Slate, SlateVersion, VersionedSlate};
 use crate::{SlateGetter, SlatePutter};
 use std::path::PathBuf;
@@ -24,6 +25,33 @@ use std::path::PathBuf;
 #[derive(Clone)]
 pub struct PathToSlate(pub PathBuf);
 
+/// Slate file in the compact binary encoding. Reading is done with PathToSlate,
+/// it accepts both encodings.
+#[derive(Clone)]
+pub struct CompactPathToSlate(pub PathBuf);
+
+impl PathToSlate {
+	fn read_content(&self) -> Result<String, Error> {
+		let file_name = self.0.to_str().unwrap_or("INVALID PATH");
+		let mut pub_tx_f = File::open(&self.0).map_err(|e| {
+			ErrorKind::IO(format!("Unable to open proof file {}, {}", file_name, e))
+		})?;
+		let mut content = String::new();
+		pub_tx_f.read_to_string(&mut content).map_err(|e| {
+			ErrorKind::IO(format!(
+				"Unable to read data from file {}, {}",
+				file_name, e
+			))
+		})?;
+		Ok(content)
+	}
+
+	/// Check if the file holds the slate in the compact binary encoding
+	pub fn is_compact(&self) -> Result<bool, Error> {
+		Ok(CompactSlate::is_armored(&self.read_content()?))
+	}
+}
+
 impl SlatePutter for PathToSlate {
 	fn put_tx(&self, slate: &Slate) -> Result<(), Error> {
 		let file_name = self.0.to_str().unwrap_or("INVALID PATH");
@@ -71,22 +99,38 @@ impl SlatePutter for PathToSlate {
 impl SlateGetter for PathToSlate {
 	fn get_tx(&self) -> Result<Slate, Error> {
 		let file_name = self.0.to_str().unwrap_or("INVALID PATH");
-		let mut pub_tx_f = File::open(&self.0).map_err(|e| {
-			ErrorKind::IO(format!("Unable to open proof file {}, {}", file_name, e))
+		let content = self.read_content()?;
+
+		Ok(Slate::deserialize_upgrade(&content).map_err(|e| {
+			ErrorKind::IO(format!(
+				"Unable to build slate from json, file {}, {}",
+				file_name, e
+			))
+		})?)
+	}
+}
+
+impl SlatePutter for CompactPathToSlate {
+	fn put_tx(&self, slate: &Slate) -> Result<(), Error> {
+		let file_name = self.0.to_str().unwrap_or("INVALID PATH");
+		let mut pub_tx = File::create(&self.0).map_err(|e| {
+			ErrorKind::IO(format!("Unable to create proof file {}, {}", file_name, e))
 		})?;
-		let mut content = String::new();
-		pub_tx_f.read_to_string(&mut content).map_err(|e| {
+		let out_slate = CompactSlate(slate.clone().into()).to_armored()?;
+		pub_tx.write_all(out_slate.as_bytes()).map_err(|e| {
 			ErrorKind::IO(format!(
-				"Unable to read data from file {}, {}",
+				"Unable to store data at proof file {}, {}",
 				file_name, e
 			))
 		})?;
 
-		Ok(Slate::deserialize_upgrade(&content).map_err(|e| {
+		pub_tx.sync_all().map_err(|e| {
 			ErrorKind::IO(format!(
-				"Unable to build slate from json, file {}, {}",
+				"Unable to store data at proof file {}, {}",
 				file_name, e
 			))
-		})?)
+		})?;
+
+		Ok(())
 	}
 }
@@ -15,7 +15,7 @@
 /// HTTP Wallet 'plugin' implementation
 use crate::client_utils::{Client, ClientError};
 use crate::error::{Error, ErrorKind};
-use crate::libwallet::slate_versions::{SlateVersion, VersionedSlate};
+use crate::libwallet::slate_versions::{SlateEncoding, SlateVersion, VersionedSlate};
 use crate::libwallet::swap::message::Message;
 use crate::libwallet::Slate;
 use crate::{SlateSender, SwapMessageSender};
@@ -79,12 +79,12 @@ impl HttpDataSender {
 		Ok(ret)
 	}
 
-	/// Check version of the listening wallet
+	/// Check version of the listening wallet. Returns the slate version and the encoding to use.
 	pub fn check_other_version(
 		&self,
 		url: &str,
 		timeout: Option<u128>,
-	) -> Result<SlateVersion, Error> {
+	) -> Result<(SlateVersion, SlateEncoding), Error> {
 		let res_str: String;
 		let start_time = std::time::Instant::now();
 		trace!("starting now check version");
@@ -166,6 +166,11 @@ impl HttpDataSender {
 			))
 		})?;
 
+		// Old wallets don't report encodings, they understand json only
+		let supported_slate_encodings: Vec<String> =
+			serde_json::from_value(resp_value["supported_slate_encodings"].clone())
+				.unwrap_or(vec![]);
+
 		// trivial tests for now, but will be expanded later
 		if foreign_api_version < 2 {
 			let report = "Other wallet reports unrecognized API format.".to_string();
@@ -174,14 +179,17 @@ impl HttpDataSender {
 		}
 
 		if supported_slate_versions.contains(&"V3B".to_owned()) {
-			return Ok(SlateVersion::V3B);
+			if supported_slate_encodings.contains(&"Compact".to_owned()) {
+				return Ok((SlateVersion::V3B, SlateEncoding::Compact));
+			}
+			return Ok((SlateVersion::V3B, SlateEncoding::Json));
 		}
 
 		if supported_slate_versions.contains(&"V3".to_owned()) {
-			return Ok(SlateVersion::V3);
+			return Ok((SlateVersion::V3, SlateEncoding::Json));
 		}
 		if supported_slate_versions.contains(&"V2".to_owned()) {
-			return Ok(SlateVersion::V2);
+			return Ok((SlateVersion::V2, SlateEncoding::Json));
 		}
 
 		let report = "Unable to negotiate slate format with other wallet.".to_string();
@@ -383,8 +391,13 @@ impl SlateSender for HttpDataSender {
 		let (url_str, _tor) = self.set_up_tor_send_process()?;
 
 		let slate_send = match self.check_other_version(&url_str, None)? {
-			SlateVersion::V3B => VersionedSlate::into_version(slate.clone(), SlateVersion::V3),
-			SlateVersion::V2 | SlateVersion::V3 => {
+			(SlateVersion::V3B, SlateEncoding::Compact) => {
+				VersionedSlate::into_compact(slate.clone())
+			}
+			(SlateVersion::V3B, SlateEncoding::Json) => {
+				VersionedSlate::into_version(slate.clone(), SlateVersion::V3)
+			}
+			(SlateVersion::V2, _) | (SlateVersion::V3, _) => {
 				let mut slate = slate.clone();
 				if slate.payment_proof.is_some() {
 					return Err(ErrorKind::ClientCallback("Payment proof requested, but other wallet does not support payment proofs or tor payment proof. Please urge other user to upgrade, or re-send tx without a payment proof".into()).into());
@@ -478,13 +491,19 @@ impl SlateSender for HttpDataSender {
 		{
 			res["result"]["Ok"]["ttl_cutoff_height"] = json!(u64::MAX);
 		}
-		let slate =
-			Slate::deserialize_upgrade(&serde_json::to_string(&slate_value).map_err(|e| {
+		// Compact slate comes back as an armored string
+		let slate_str = match slate_value.as_str() {
+			Some(s) => s.to_string(),
+			None => serde_json::to_string(&slate_value).map_err(|e| {
 				ErrorKind::GenericError(format!("Unable to build slate from values, {}", e))
-			})?)
-			.map_err(|e| {
-				ErrorKind::GenericError(format!("Unable to build slate from response {}, {}", res_str, e))
-			})?;
+			})?,
+		};
+		let slate = Slate::deserialize_upgrade(&slate_str).map_err(|e| {
+			ErrorKind::GenericError(format!(
+				"Unable to build slate from response {}, {}",
+				res_str, e
+			))
+		})?;
 
 		// //compare the listening wallet proof address retrieved earlier to the returned slate. If they don't match, return error
 		// if let Some(ref p) = slate.payment_proof {
@@ -17,7 +17,7 @@ pub mod http;
 mod mwcmq;
 mod types;
 
-pub use self::file::PathToSlate;
+pub use self::file::{CompactPathToSlate, PathToSlate};
 pub use self::http::HttpDataSender;
 
 use crate::config::{TorConfig, WalletConfig};
@@ -27,7 +27,8 @@ use crate::libwallet::Slate;
 use crate::tor::config::complete_tor_address;
 use crate::util::ZeroingString;
 pub use mwcmq::{
-	get_mwcmqs_brocker, init_mwcmqs_access_data, MWCMQPublisher, MWCMQSubscriber, MwcMqsChannel,
+	get_mwcmqs_brocker, init_mwcmqs_access_data, is_mwcmqs_connected, MWCMQPublisher,
+	MWCMQSubscriber, MwcMqsChannel,
 };
 pub use types::{
 	Address, AddressType, CloseReason, HttpsAddress, MWCMQSAddress, Publisher, Subscriber,
@@ -26,6 +26,7 @@ use grin_util::RwLock;
 use grin_wallet_libwallet::proof::message::EncryptedMessage;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
 use grin_wallet_libwallet::proof::tx_proof::{push_proof_for_slate, TxProof};
+use grin_wallet_libwallet::slate_versions::binary::CompactSlate;
 use grin_wallet_libwallet::swap::message::Message;
 use grin_wallet_libwallet::swap::message::SwapMessage;
 use grin_wallet_libwallet::{Slate, VersionedSlate};
@@ -51,6 +52,9 @@ lazy_static! {
 	static ref MWCMQS_BROKER: RwLock<Option<(MWCMQPublisher, MWCMQSubscriber)>> = RwLock::new(None);
 }
 
+// Connection state of the running listener
+static MWCMQS_CONNECTED: AtomicBool = AtomicBool::new(false);
+
 /// Init mwc mqs objects for the access.
 pub fn init_mwcmqs_access_data(publisher: MWCMQPublisher, subscriber: MWCMQSubscriber) {
 	MWCMQS_BROKER.write().replace((publisher, subscriber));
@@ -66,6 +70,11 @@ pub fn reset_mwcmqs_brocker() {
 	MWCMQS_BROKER.write().take();
 }
 
+/// True if the mwcmqs listener is connected to the server
+pub fn is_mwcmqs_connected() -> bool {
+	MWCMQS_CONNECTED.load(Ordering::Relaxed)
+}
+
 pub struct MwcMqsChannel {
 	des_address: String,
 }
@@ -188,12 +197,21 @@ impl MWCMQPublisher {
 		secret_key: &SecretKey,
 		mwcmqs_domain: String,
 		mwcmqs_port: u16,
+		use_tls: bool,
+		compact_slates: bool,
 		print_to_log: bool,
 		handler: Box<dyn SubscriptionHandler + Send>,
 	) -> Self {
 		Self {
 			address,
-			broker: MWCMQSBroker::new(mwcmqs_domain, mwcmqs_port, print_to_log, handler),
+			broker: MWCMQSBroker::new(
+				mwcmqs_domain,
+				mwcmqs_port,
+				use_tls,
+				compact_slates,
+				print_to_log,
+				handler,
+			),
 			secret_key: secret_key.clone(),
 		}
 	}
@@ -290,13 +308,17 @@ impl Subscriber for MWCMQSubscriber {
 			.timeout(Duration::from_secs(60))
 			.build()
 		{
+			let listener_auth = self.broker.listener_auth.lock().clone();
 			let mut params = HashMap::new();
 			params.insert("mapmessage", "nil");
+			if let Some((time_now, signature)) = &listener_auth {
+				params.insert("time_now", time_now);
+				params.insert("signature", signature);
+			}
 			let response = client
 				.post(&format!(
-					"https://{}:{}/sender?address={}",
-					self.broker.mwcmqs_domain,
-					self.broker.mwcmqs_port,
+					"{}/sender?address={}",
+					self.broker.base_url(),
 					str::replace(&self.address.get_stripped(), "@", "%40")
 				))
 				.form(&params)
@@ -336,14 +358,20 @@ struct MWCMQSBroker {
 	running: Arc<AtomicBool>,
 	pub mwcmqs_domain: String,
 	pub mwcmqs_port: u16,
+	pub use_tls: bool,
+	pub compact_slates: bool,
 	pub print_to_log: bool,
 	pub handler: Arc<Mutex<Box<dyn SubscriptionHandler + Send>>>,
+	// Challenge and signature of the listener session, the stop request is signed with them
+	listener_auth: Arc<Mutex<Option<(String, String)>>>,
 }
 
 impl MWCMQSBroker {
 	fn new(
 		mwcmqs_domain: String,
 		mwcmqs_port: u16,
+		use_tls: bool,
+		compact_slates: bool,
 		print_to_log: bool,
 		handler: Box<dyn SubscriptionHandler + Send>,
 	) -> Self {
@@ -351,9 +379,31 @@ impl MWCMQSBroker {
 			running: Arc::new(AtomicBool::new(false)),
 			mwcmqs_domain,
 			mwcmqs_port,
+			use_tls,
+			compact_slates,
 			print_to_log,
 			handler: Arc::new(Mutex::new(handler)),
+			listener_auth: Arc::new(Mutex::new(None)),
+		}
+	}
+
+	// Plain http is used by the self hosted brokers without TLS
+	fn base_url(&self) -> String {
+		let scheme = if self.use_tls { "https" } else { "http" };
+		format!("{}://{}:{}", scheme, self.mwcmqs_domain, self.mwcmqs_port)
+	}
+
+	// Slate is sent as json or as an armored compact slate
+	fn slate_to_string(&self, slate: &Slate) -> Result<String, Error> {
+		if self.compact_slates {
+			return Ok(CompactSlate(slate.clone().into()).to_armored()?);
 		}
+		let version = slate.lowest_version();
+		let slate = VersionedSlate::into_version(slate.clone(), version);
+		let slate_str = serde_json::to_string(&slate).map_err(|e| {
+			ErrorKind::MqsGenericError(format!("Unable convert Slate to Json, {}", e))
+		})?;
+		Ok(slate_str)
 	}
 
 	fn encrypt_slate(
@@ -365,13 +415,9 @@ impl MWCMQSBroker {
 	) -> Result<String, Error> {
 		let pkey = to.address.public_key()?;
 		let skey = secret_key.clone();
-		let version = slate.lowest_version();
-		let slate = VersionedSlate::into_version(slate.clone(), version);
-		let serde_json = serde_json::to_string(&slate).map_err(|e| {
-			ErrorKind::MqsGenericError(format!("Unable convert Slate to Json, {}", e))
-		})?;
+		let slate_str = self.slate_to_string(slate)?;
 
-		let message = EncryptedMessage::new(serde_json, &to.address, &pkey, &skey)
+		let message = EncryptedMessage::new(slate_str, &to.address, &pkey, &skey)
 			.map_err(|e| ErrorKind::GenericError(format!("Unable encrypt slate, {}", e)))?;
 
 		let message_ser = &serde_json::to_string(&message).map_err(|e| {
@@ -411,18 +457,10 @@ impl MWCMQSBroker {
 		}
 		let pkey = to.address.public_key()?;
 		let skey = secret_key.clone();
-		let version = slate.lowest_version();
-		let slate = VersionedSlate::into_version(slate.clone(), version);
 
-		let message = EncryptedMessage::new(
-			serde_json::to_string(&slate).map_err(|e| {
-				ErrorKind::MqsGenericError(format!("Unable convert Slate to Json, {}", e))
-			})?,
-			&to.address,
-			&pkey,
-			&skey,
-		)
-		.map_err(|e| ErrorKind::GenericError(format!("Unable encrypt slate, {}", e)))?;
+		let message =
+			EncryptedMessage::new(self.slate_to_string(slate)?, &to.address, &pkey, &skey)
+				.map_err(|e| ErrorKind::GenericError(format!("Unable encrypt slate, {}", e)))?;
 
 		let message_ser = &serde_json::to_string(&message).map_err(|e| {
 			ErrorKind::MqsGenericError(format!("Unable convert Message to Json, {}", e))
@@ -447,9 +485,8 @@ impl MWCMQSBroker {
 		params.insert("signature", &signature);
 
 		let url = format!(
-			"https://{}:{}/sender?address={}",
-			self.mwcmqs_domain,
-			self.mwcmqs_port,
+			"{}/sender?address={}",
+			self.base_url(),
 			&str::replace(&to.get_stripped(), "@", "%40")
 		);
 		let response = client.post(&url).form(&params).send();
@@ -537,9 +574,8 @@ impl MWCMQSBroker {
 		params.insert("signature", &signature);
 
 		let url = format!(
-			"https://{}:{}/sender?address={}",
-			self.mwcmqs_domain,
-			self.mwcmqs_port,
+			"{}/sender?address={}",
+			self.base_url(),
 			&str::replace(&to.get_stripped(), "@", "%40")
 		);
 		let response = client.post(&url).form(&params).send();
@@ -644,9 +680,8 @@ impl MWCMQSBroker {
 			let client = cl.unwrap();
 			let resp_result = client
 				.get(&format!(
-					"https://{}:{}/timenow?address={}",
-					self.mwcmqs_domain,
-					self.mwcmqs_port,
+					"{}/timenow?address={}",
+					self.base_url(),
 					str::replace(&cloned_address.get_stripped(), "@", "%40"),
 				))
 				.send();
@@ -676,11 +711,11 @@ impl MWCMQSBroker {
 		if time_now_signature.is_empty() {
 			is_error = true;
 		}
+		*self.listener_auth.lock() = Some((time_now.to_string(), time_now_signature.clone()));
 
 		let mut url = String::from(&format!(
-			"https://{}:{}/listener?address={}&delTo={}&time_now={}&signature={}",
-			self.mwcmqs_domain,
-			self.mwcmqs_port,
+			"{}/listener?address={}&delTo={}&time_now={}&signature={}",
+			self.base_url(),
 			str::replace(&cloned_address.get_stripped(), "@", "%40"),
 			"nil".to_string(),
 			time_now,
@@ -688,9 +723,8 @@ impl MWCMQSBroker {
 		));
 
 		let first_url = String::from(&format!(
-			"https://{}:{}/listener?address={}&delTo={}&time_now={}&signature={}&first=true",
-			self.mwcmqs_domain,
-			self.mwcmqs_port,
+			"{}/listener?address={}&delTo={}&time_now={}&signature={}&first=true",
+			self.base_url(),
 			str::replace(&cloned_address.get_stripped(), "@", "%40"),
 			"nil".to_string(),
 			time_now,
@@ -717,6 +751,7 @@ impl MWCMQSBroker {
 				if !cloned_running.load(Ordering::SeqCst) {
 					break;
 				}
+				MWCMQS_CONNECTED.store(connected, Ordering::Relaxed);
 
 				let secs = if !connected { 2 } else { 120 };
 				let cl = reqwest::Client::builder()
@@ -802,7 +837,7 @@ impl MWCMQSBroker {
 					if !read_resp.is_ok() {
 						// read error occured. Sleep and try again in 5 seconds
 						self.do_log_info(format!("io error occured while trying to connect to {}. Will sleep for 5 second and will reconnect.",
-												 &format!("https://{}:{}", self.mwcmqs_domain, self.mwcmqs_port)));
+												 &self.base_url()));
 						self.do_log_error(format!("Error: {:?}", read_resp));
 						let second = time::Duration::from_millis(5000);
 						thread::sleep(second);
@@ -828,9 +863,8 @@ impl MWCMQSBroker {
 									}
 
 									url = String::from(format!(
-										"https://{}:{}/listener?address={}&delTo={}&time_now={}&signature={}",
-										self.mwcmqs_domain,
-										self.mwcmqs_port,
+										"{}/listener?address={}&delTo={}&time_now={}&signature={}",
+										self.base_url(),
 										str::replace(&cloned_address.get_stripped(), "@", "%40"),
 										&last_message_id,
 										time_now,
@@ -865,9 +899,8 @@ impl MWCMQSBroker {
 							}
 
 							url = String::from(format!(
-								"https://{}:{}/listener?address={}&delTo={}&time_now={}&signature={}",
-								self.mwcmqs_domain,
-								self.mwcmqs_port,
+								"{}/listener?address={}&delTo={}&time_now={}&signature={}",
+								self.base_url(),
 								str::replace(&cloned_address.get_stripped(), "@", "%40"),
 								&last_message_id,
 								time_now,
@@ -1106,6 +1139,7 @@ impl MWCMQSBroker {
 			));
 		}
 
+		MWCMQS_CONNECTED.store(false, Ordering::Relaxed);
 		cloned_running.store(false, Ordering::SeqCst);
 	}
 
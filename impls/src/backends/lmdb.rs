@@ -31,8 +31,9 @@ use crate::store::{self, option_to_not_found, to_key, to_key_u64, u64_to_key};
 use crate::core::core::Transaction;
 use crate::core::ser;
 use crate::libwallet::{
-	AcctPathMapping, Context, Error, ErrorKind, NodeClient, OutputData, ScannedBlockInfo,
-	TxLogEntry, TxProof, WalletBackend, WalletOutputBatch,
+	AcctPathMapping, Contact, Context, Error, ErrorKind, Invoice, NodeClient, OutputData,
+	OutputResult, PendingSlate, ScanCheckpoint, ScannedBlockInfo, SpendPolicy, TxLogEntry, TxProof,
+	VerifiedHeader, WalletBackend, WalletOutputBatch, WalletSettings,
 };
 use crate::util::secp::constants::SECRET_KEY_SIZE;
 use crate::util::secp::key::SecretKey;
@@ -51,6 +52,13 @@ const PRIVATE_TX_CONTEXT_PREFIX: u8 = b'p';
 const TX_LOG_ENTRY_PREFIX: u8 = b't';
 const TX_LOG_ID_PREFIX: u8 = b'i';
 const ACCOUNT_PATH_MAPPING_PREFIX: u8 = b'a';
+const INVOICE_PREFIX: u8 = b'v';
+const PENDING_SLATE_PREFIX: u8 = b'q';
+const SPEND_POLICY_PREFIX: u8 = b's';
+const VERIFIED_HEADER_PREFIX: u8 = b'h';
+const SCAN_CHECKPOINT_PREFIX: u8 = b'k';
+const SCAN_OUTPUT_PREFIX: u8 = b'u';
+const CONTACT_PREFIX: u8 = b'b';
 const LAST_SCANNED_BLOCK: u8 = b'm'; // pre v3.0 was l
 
 /// test to see if database files exist in the current directory. If so,
@@ -108,6 +116,8 @@ where
 	parent_key_id: Identifier,
 	/// wallet to node client
 	w2n_client: C,
+	/// Settings from the wallet configuration
+	settings: WalletSettings,
 	///phantom
 	_phantom: &'ck PhantomData<C>,
 }
@@ -154,6 +164,7 @@ where
 			master_checksum: Box::new(None),
 			parent_key_id: LMDBBackend::<C, K>::default_path(),
 			w2n_client: n_client,
+			settings: WalletSettings::default(),
 			_phantom: &PhantomData,
 		};
 		Ok(res)
@@ -184,6 +195,14 @@ where
 		&self.data_file_dir
 	}
 
+	fn settings(&self) -> &WalletSettings {
+		&self.settings
+	}
+
+	fn set_settings(&mut self, settings: WalletSettings) {
+		self.settings = settings;
+	}
+
 	/// Set the keychain, which should already have been opened
 	fn set_keychain(
 		&mut self,
@@ -359,6 +378,66 @@ where
 		self.db.get_ser(&acct_key).map_err(|e| e.into())
 	}
 
+	fn get_invoice(&self, id: &Uuid) -> Result<Option<Invoice>, Error> {
+		let key = to_key(INVOICE_PREFIX, &mut id.as_bytes().to_vec());
+		self.db.get_ser(&key).map_err(|e| e.into())
+	}
+
+	fn invoice_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Invoice> + 'a> {
+		Box::new(self.db.iter(&[INVOICE_PREFIX]).unwrap().map(|o| o.1))
+	}
+
+	fn get_pending_slate(&self, id: &Uuid) -> Result<Option<PendingSlate>, Error> {
+		let key = to_key(PENDING_SLATE_PREFIX, &mut id.as_bytes().to_vec());
+		self.db.get_ser(&key).map_err(|e| e.into())
+	}
+
+	fn pending_slate_iter<'a>(&'a self) -> Box<dyn Iterator<Item = PendingSlate> + 'a> {
+		Box::new(self.db.iter(&[PENDING_SLATE_PREFIX]).unwrap().map(|o| o.1))
+	}
+
+	fn get_spend_policy(&self, parent_key_id: &Identifier) -> Result<Option<SpendPolicy>, Error> {
+		let key = to_key(SPEND_POLICY_PREFIX, &mut parent_key_id.to_bytes().to_vec());
+		self.db.get_ser(&key).map_err(|e| e.into())
+	}
+
+	fn spend_policy_iter<'a>(&'a self) -> Box<dyn Iterator<Item = SpendPolicy> + 'a> {
+		Box::new(self.db.iter(&[SPEND_POLICY_PREFIX]).unwrap().map(|o| o.1))
+	}
+
+	fn get_contact(&self, name: &str) -> Result<Option<Contact>, Error> {
+		let key = to_key(CONTACT_PREFIX, &mut name.as_bytes().to_vec());
+		self.db.get_ser(&key).map_err(|e| e.into())
+	}
+
+	fn contact_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Contact> + 'a> {
+		Box::new(self.db.iter(&[CONTACT_PREFIX]).unwrap().map(|o| o.1))
+	}
+
+	fn get_verified_header(&self, height: u64) -> Result<Option<VerifiedHeader>, Error> {
+		let key = u64_to_key(VERIFIED_HEADER_PREFIX, height);
+		self.db.get_ser(&key).map_err(|e| e.into())
+	}
+
+	fn verified_header_iter<'a>(&'a self) -> Box<dyn Iterator<Item = VerifiedHeader> + 'a> {
+		Box::new(
+			self.db
+				.iter(&[VERIFIED_HEADER_PREFIX])
+				.unwrap()
+				.map(|o| o.1),
+		)
+	}
+
+	fn get_scan_checkpoint(&self) -> Result<Option<ScanCheckpoint>, Error> {
+		self.db
+			.get_ser(&[SCAN_CHECKPOINT_PREFIX])
+			.map_err(|e| e.into())
+	}
+
+	fn scan_output_iter<'a>(&'a self) -> Box<dyn Iterator<Item = OutputResult> + 'a> {
+		Box::new(self.db.iter(&[SCAN_OUTPUT_PREFIX]).unwrap().map(|o| o.1))
+	}
+
 	fn store_tx(&self, uuid: &str, tx: &Transaction) -> Result<(), Error> {
 		let filename = format!("{}.mwctx", uuid);
 		let path = path::Path::new(&self.data_file_dir)
@@ -763,6 +842,112 @@ where
 		)
 	}
 
+	fn save_invoice(&mut self, invoice: Invoice) -> Result<(), Error> {
+		let key = to_key(INVOICE_PREFIX, &mut invoice.id.as_bytes().to_vec());
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &invoice)?;
+		Ok(())
+	}
+
+	fn save_pending_slate(&mut self, pending: PendingSlate) -> Result<(), Error> {
+		let key = to_key(PENDING_SLATE_PREFIX, &mut pending.id.as_bytes().to_vec());
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &pending)?;
+		Ok(())
+	}
+
+	fn delete_pending_slate(&mut self, id: &Uuid) -> Result<(), Error> {
+		let key = to_key(PENDING_SLATE_PREFIX, &mut id.as_bytes().to_vec());
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&key)
+			.map_err(|e| e.into())
+	}
+
+	fn save_spend_policy(&mut self, policy: SpendPolicy) -> Result<(), Error> {
+		let key = to_key(
+			SPEND_POLICY_PREFIX,
+			&mut policy.parent_key_id.to_bytes().to_vec(),
+		);
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &policy)?;
+		Ok(())
+	}
+
+	fn delete_spend_policy(&mut self, parent_key_id: &Identifier) -> Result<(), Error> {
+		let key = to_key(SPEND_POLICY_PREFIX, &mut parent_key_id.to_bytes().to_vec());
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&key)
+			.map_err(|e| e.into())
+	}
+
+	fn save_contact(&mut self, contact: Contact) -> Result<(), Error> {
+		let key = to_key(CONTACT_PREFIX, &mut contact.name.as_bytes().to_vec());
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &contact)?;
+		Ok(())
+	}
+
+	fn delete_contact(&mut self, name: &str) -> Result<(), Error> {
+		let key = to_key(CONTACT_PREFIX, &mut name.as_bytes().to_vec());
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&key)
+			.map_err(|e| e.into())
+	}
+
+	fn save_verified_header(&mut self, header: VerifiedHeader) -> Result<(), Error> {
+		let key = u64_to_key(VERIFIED_HEADER_PREFIX, header.height);
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &header)?;
+		Ok(())
+	}
+
+	fn delete_verified_header(&mut self, height: u64) -> Result<(), Error> {
+		let key = u64_to_key(VERIFIED_HEADER_PREFIX, height);
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&key)
+			.map_err(|e| e.into())
+	}
+
+	fn save_scan_checkpoint(&mut self, checkpoint: ScanCheckpoint) -> Result<(), Error> {
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.put_ser(&[SCAN_CHECKPOINT_PREFIX], &checkpoint)?;
+		Ok(())
+	}
+
+	fn save_scan_output(&mut self, output: OutputResult) -> Result<(), Error> {
+		let key = u64_to_key(SCAN_OUTPUT_PREFIX, output.mmr_index);
+		self.db.borrow().as_ref().unwrap().put_ser(&key, &output)?;
+		Ok(())
+	}
+
+	fn delete_scan_checkpoint(&mut self) -> Result<(), Error> {
+		let br = self.db.borrow();
+		let db = br.as_ref().unwrap();
+		let keys: Vec<Vec<u8>> = db
+			.iter(&[SCAN_OUTPUT_PREFIX])
+			.unwrap()
+			.map(|o: (Vec<u8>, OutputResult)| o.0)
+			.collect();
+		for key in keys {
+			db.delete(&key)?;
+		}
+		let checkpoint: Option<ScanCheckpoint> = db.get_ser(&[SCAN_CHECKPOINT_PREFIX])?;
+		if checkpoint.is_some() {
+			db.delete(&[SCAN_CHECKPOINT_PREFIX])?;
+		}
+		Ok(())
+	}
+
 	fn lock_output(&mut self, out: &mut OutputData) -> Result<(), Error> {
 		out.lock();
 		self.save(out.clone())
@@ -46,13 +46,10 @@ pub mod test_framework;
 pub mod tor;
 
 pub use crate::adapters::{
-	create_sender,
-	get_mwcmqs_brocker, init_mwcmqs_access_data,
-	Address, AddressType, CloseReason,
-	HttpDataSender, HttpsAddress,
-	MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, MwcMqsChannel, PathToSlate, Publisher,
-	SlateGetter, SlatePutter, SlateReceiver, SlateSender, Subscriber, SubscriptionHandler,
-	SwapMessageSender,
+	create_sender, get_mwcmqs_brocker, init_mwcmqs_access_data, is_mwcmqs_connected, Address,
+	AddressType, CloseReason, CompactPathToSlate, HttpDataSender, HttpsAddress, MWCMQPublisher,
+	MWCMQSAddress, MWCMQSubscriber, MwcMqsChannel, PathToSlate, Publisher, SlateGetter,
+	SlatePutter, SlateReceiver, SlateSender, Subscriber, SubscriptionHandler, SwapMessageSender,
 };
 pub use crate::backends::{wallet_db_exists, LMDBBackend};
 pub use crate::error::{Error, ErrorKind};
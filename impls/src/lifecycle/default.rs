@@ -20,7 +20,9 @@ use crate::config::{
 };
 use crate::core::global;
 use crate::keychain::Keychain;
-use crate::libwallet::{Error, ErrorKind, NodeClient, WalletBackend, WalletLCProvider};
+use crate::libwallet::{
+	Error, ErrorKind, NodeClient, WalletBackend, WalletLCProvider, WalletSettings,
+};
 use crate::lifecycle::seed::WalletSeed;
 use crate::util::secp::key::SecretKey;
 use crate::util::ZeroingString;
@@ -37,6 +39,7 @@ where
 	data_dir: String,
 	node_client: C,
 	backend: Option<Box<dyn WalletBackend<'a, C, K> + 'a>>,
+	settings: WalletSettings,
 }
 
 impl<'a, C, K> DefaultLCProvider<'a, C, K>
@@ -50,6 +53,7 @@ where
 			node_client,
 			data_dir: "default".to_owned(),
 			backend: None,
+			settings: WalletSettings::default(),
 		}
 	}
 }
@@ -117,6 +121,26 @@ where
 				wallet,
 				tor,
 				mqs,
+				receive_policy: default_config
+					.members
+					.as_ref()
+					.and_then(|m| m.receive_policy.clone()),
+				owner_api: default_config
+					.members
+					.as_ref()
+					.and_then(|m| m.owner_api.clone()),
+				node_failover: default_config
+					.members
+					.as_ref()
+					.and_then(|m| m.node_failover.clone()),
+				rebroadcast: default_config
+					.members
+					.as_ref()
+					.and_then(|m| m.rebroadcast.clone()),
+				fee_policy: default_config
+					.members
+					.as_ref()
+					.and_then(|m| m.fee_policy.clone()),
 				logging,
 			}),
 			..default_config
@@ -262,6 +286,7 @@ where
 			.map_err(|e| ErrorKind::Lifecycle(format!("Error deriving keychain, {}", e)))?;
 
 		let mask = wallet.set_keychain(Box::new(keychain), create_mask, use_test_rng)?;
+		wallet.set_settings(self.settings.clone());
 		self.backend = Some(Box::new(wallet));
 		Ok(mask)
 	}
@@ -411,4 +436,15 @@ where
 			Some(w) => Ok(w),
 		}
 	}
+
+	fn wallet_settings(&self) -> WalletSettings {
+		self.settings.clone()
+	}
+
+	fn set_wallet_settings(&mut self, settings: WalletSettings) {
+		if let Some(b) = self.backend.as_mut() {
+			b.set_settings(settings.clone());
+		}
+		self.settings = settings;
+	}
 }
@@ -15,9 +15,14 @@
 //! Client functions, implementations of the NodeClient trait
 
 use crate::api::{self, LocatedTxKernel, OutputListing, OutputPrintable};
-use crate::core::core::{Transaction, TxKernel};
+use crate::config::NodeFailoverConfig;
+use crate::core::core::hash::{Hash, Hashed};
+use crate::core::core::{BlockHeader, HeaderVersion, Transaction, TxKernel};
+use crate::core::pow::{Difficulty, Proof, ProofOfWork};
+use crate::keychain::BlindingFactor;
 use crate::libwallet::HeaderInfo;
 use crate::libwallet::{NodeClient, NodeVersionInfo};
+use chrono::{DateTime, Utc};
 use crossbeam_utils::thread::scope;
 use futures::stream::FuturesUnordered;
 use futures::TryStreamExt;
@@ -32,8 +37,8 @@ use crate::util::{self, to_hex};
 
 use super::resp_types::*;
 use crate::client_utils::json_rpc::*;
-use std::time::{Duration, Instant};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 const ENDPOINT: &str = "/v2/foreign";
 const CACHE_VALID_TIME_MS: u128 = 5000; // 2 seconds for cache should be enough for our purpose
@@ -48,12 +53,13 @@ lazy_static! {
 // cashed values are stored by the key K
 #[derive(Clone)]
 struct CachedValue<K, T> {
-	data: Arc<RwLock<HashMap<K, (T,Instant)>>>,
+	data: Arc<RwLock<HashMap<K, (T, Instant)>>>,
 }
 
-impl<K,T> CachedValue<K,T>
-	where K: std::cmp::Eq + std::hash::Hash,
-		  T: Clone
+impl<K, T> CachedValue<K, T>
+where
+	K: std::cmp::Eq + std::hash::Hash,
+	T: Clone,
 {
 	fn new() -> Self {
 		CachedValue {
@@ -64,18 +70,21 @@ impl<K,T> CachedValue<K,T>
 	// Return none is cached value not set of epired
 	fn get_value(&self, key: &K) -> Option<T> {
 		match self.data.write().unwrap().get(key) {
-			Some( (data, time) ) => {
+			Some((data, time)) => {
 				if time.elapsed().as_millis() > CACHE_VALID_TIME_MS {
 					return None;
 				}
-				Some( (*data).clone())
-			},
-			None => None
+				Some((*data).clone())
+			}
+			None => None,
 		}
 	}
 
 	fn set_value(&self, key: K, value: T) {
-		self.data.write().unwrap().insert(key, (value, Instant::now()) );
+		self.data
+			.write()
+			.unwrap()
+			.insert(key, (value, Instant::now()));
 	}
 
 	fn clean(&self) {
@@ -83,6 +92,28 @@ impl<K,T> CachedValue<K,T>
 	}
 }
 
+// Node that the client can fail over to
+#[derive(Clone, Debug)]
+struct NodeEndpoint {
+	url: String,
+	priority: u32,
+	// Call to the node failed, it is skipped until the next health check
+	failed: bool,
+}
+
+// Failover state, shared by all clones of the client
+struct NodeFailover {
+	// Additional nodes, check_node_api_http_addr is not included
+	nodes: Vec<NodeEndpoint>,
+	// Node that is used now, None for check_node_api_http_addr
+	active: Option<String>,
+	max_blocks_behind: u64,
+	check_interval: Duration,
+	broadcast_post_tx: bool,
+	last_check: Option<Instant>,
+	primary_failed: bool,
+}
+
 #[derive(Clone)]
 pub struct HTTPNodeClient {
 	node_url: String,
@@ -91,9 +122,11 @@ pub struct HTTPNodeClient {
 	client: Client,
 
 	// cache for the data
-	chain_tip:   CachedValue<u8,(u64, String, u64)>,
+	chain_tip: CachedValue<u8, (u64, String, u64)>,
 	header_info: CachedValue<u64, HeaderInfo>,
-	block_info:  CachedValue<u64, api::BlockPrintable>,
+	block_info: CachedValue<u64, api::BlockPrintable>,
+
+	failover: Arc<RwLock<Option<NodeFailover>>>,
 }
 
 impl HTTPNodeClient {
@@ -107,12 +140,197 @@ impl HTTPNodeClient {
 			node_api_secret: node_api_secret,
 			node_version_info: None,
 			client,
-			chain_tip: 	 CachedValue::new(),
+			chain_tip: CachedValue::new(),
 			header_info: CachedValue::new(),
-			block_info:  CachedValue::new(),
+			block_info: CachedValue::new(),
+			failover: Arc::new(RwLock::new(None)),
 		})
 	}
 
+	/// Set the additional nodes the client fails over to when the node is down or lagging.
+	/// All nodes use the same node API secret.
+	pub fn set_failover(&mut self, config: Option<NodeFailoverConfig>) {
+		let failover = match config {
+			Some(c) => {
+				let mut nodes: Vec<NodeEndpoint> = c
+					.nodes
+					.unwrap_or(vec![])
+					.into_iter()
+					.map(|n| NodeEndpoint {
+						url: n.url,
+						priority: n.priority.unwrap_or(1),
+						failed: false,
+					})
+					.collect();
+				nodes.sort_by_key(|n| n.priority);
+				if nodes.is_empty() {
+					None
+				} else {
+					Some(NodeFailover {
+						nodes,
+						active: None,
+						max_blocks_behind: c.max_blocks_behind.unwrap_or(3),
+						check_interval: Duration::from_secs(
+							c.health_check_interval_secs.unwrap_or(60),
+						),
+						broadcast_post_tx: c.broadcast_post_tx.unwrap_or(false),
+						last_check: None,
+						primary_failed: false,
+					})
+				}
+			}
+			None => None,
+		};
+		*self.failover.write().unwrap() = failover;
+	}
+
+	// All nodes with the priorities, check_node_api_http_addr has priority 0.
+	fn failover_nodes(&self, failover: &NodeFailover) -> Vec<NodeEndpoint> {
+		let mut nodes = vec![NodeEndpoint {
+			url: self.node_url.clone(),
+			priority: 0,
+			failed: failover.primary_failed,
+		}];
+		nodes.extend(failover.nodes.iter().cloned());
+		nodes.sort_by_key(|n| n.priority);
+		nodes
+	}
+
+	// Node that is used now, without the health check
+	fn current_node(&self) -> String {
+		match self.failover.read().unwrap().as_ref() {
+			Some(f) => f.active.clone().unwrap_or(self.node_url.clone()),
+			None => self.node_url.clone(),
+		}
+	}
+
+	// Node for the next call. If the health check is due, it is started in the background,
+	// the call doesn't wait for it and uses the current node.
+	fn active_node(&self) -> String {
+		let check_due = match self.failover.write().unwrap().as_mut() {
+			Some(f) => {
+				let due = match f.last_check {
+					Some(t) => t.elapsed() > f.check_interval,
+					None => true,
+				};
+				if due {
+					// only one check is running at a time
+					f.last_check = Some(Instant::now());
+				}
+				due
+			}
+			None => return self.node_url.clone(),
+		};
+		if check_due {
+			let client = self.clone();
+			let res = thread::Builder::new()
+				.name("node_health_check".to_string())
+				.spawn(move || client.check_nodes());
+			if let Err(e) = res {
+				warn!("Unable to start the node health check, {}", e);
+			}
+		}
+		self.current_node()
+	}
+
+	// Node height if the node is healthy
+	fn node_health(&self, url: &str) -> Result<u64, libwallet::Error> {
+		let null = serde_json::Value::Null;
+		self.send_json_request_to::<GetVersionResp>(url, "get_version", &null, 0, false)?;
+		let tip = self.send_json_request_to::<GetTipResp>(url, "get_tip", &null, 0, false)?;
+		Ok(tip.height)
+	}
+
+	// Query all nodes and switch to the preferred one that is not behind the others
+	fn check_nodes(&self) {
+		let (nodes, max_blocks_behind) = {
+			let mut failover = self.failover.write().unwrap();
+			let f = match failover.as_mut() {
+				Some(f) => f,
+				None => return,
+			};
+			// other threads keep using the current node while this check is running
+			f.last_check = Some(Instant::now());
+			(self.failover_nodes(f), f.max_blocks_behind)
+		};
+
+		let heights: Vec<(String, Option<u64>)> = nodes
+			.iter()
+			.map(|n| {
+				let height = match self.node_health(&n.url) {
+					Ok(h) => Some(h),
+					Err(e) => {
+						warn!("Node {} failed the health check, {}", n.url, e);
+						None
+					}
+				};
+				(n.url.clone(), height)
+			})
+			.collect();
+		let best = heights.iter().filter_map(|h| h.1).max();
+
+		let mut failover = self.failover.write().unwrap();
+		let f = match failover.as_mut() {
+			Some(f) => f,
+			None => return,
+		};
+		f.primary_failed = heights
+			.iter()
+			.any(|h| h.0 == self.node_url && h.1.is_none());
+		for n in f.nodes.iter_mut() {
+			n.failed = heights.iter().any(|h| h.0 == n.url && h.1.is_none());
+		}
+		let selected = match best {
+			Some(best) => heights
+				.iter()
+				.find(|h| match h.1 {
+					Some(height) => height + max_blocks_behind >= best,
+					None => false,
+				})
+				.map(|h| h.0.clone()),
+			// Nothing is reachable, keep the current node
+			None => None,
+		};
+		if let Some(url) = selected {
+			let current = f.active.clone().unwrap_or(self.node_url.clone());
+			if url != current {
+				warn!("Switching node from {} to {}", current, url);
+				f.active = Some(url);
+				self.reset_cache();
+			}
+		}
+	}
+
+	// Call to the node failed after the retries. Switch to the next node by priority,
+	// returns false if there is no other node to try.
+	fn fail_over(&self, failed_url: &str) -> bool {
+		let mut failover = self.failover.write().unwrap();
+		let f = match failover.as_mut() {
+			Some(f) => f,
+			None => return false,
+		};
+		if failed_url == self.node_url {
+			f.primary_failed = true;
+		}
+		for n in f.nodes.iter_mut() {
+			if n.url == failed_url {
+				n.failed = true;
+			}
+		}
+		match self.failover_nodes(f).into_iter().find(|n| !n.failed) {
+			Some(n) => {
+				warn!(
+					"Node {} is not available, switching to {}",
+					failed_url, n.url
+				);
+				f.active = Some(n.url);
+				self.reset_cache();
+				true
+			}
+			None => false,
+		}
+	}
+
 	/// Allow returning the chain height without needing a wallet instantiated
 	pub fn chain_height(&self) -> Result<(u64, String, u64), libwallet::Error> {
 		self.get_chain_tip()
@@ -122,18 +340,44 @@ impl HTTPNodeClient {
 		&self,
 		method: &str,
 		params: &serde_json::Value,
-		counter: i32
+		counter: i32,
+	) -> Result<D, libwallet::Error> {
+		let node_url = self.active_node();
+		self.send_json_request_to(&node_url, method, params, counter, true)
+	}
+
+	fn send_json_request_to<D: serde::de::DeserializeOwned>(
+		&self,
+		node_url: &str,
+		method: &str,
+		params: &serde_json::Value,
+		counter: i32,
+		failover: bool,
 	) -> Result<D, libwallet::Error> {
-		let url = format!("{}{}", self.node_url(), ENDPOINT);
+		let url = format!("{}{}", node_url, ENDPOINT);
 		let req = build_request(method, params);
-		let res = self.client.post::<Request, Response>(url.as_str(), self.node_api_secret(), &req);
+		let res = self
+			.client
+			.post::<Request, Response>(url.as_str(), self.node_api_secret(), &req);
 
 		match res {
 			Err(e) => {
-				if counter>0 {
+				if counter > 0 {
 					debug!("Retrying to call Node API method {}: {}", method, e);
-					thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-					return self.send_json_request(method, params, counter-1);
+					thread::sleep(Duration::from_millis(
+						NODE_CALL_DELAY[(counter - 1) as usize],
+					));
+					return self.send_json_request_to(
+						node_url,
+						method,
+						params,
+						counter - 1,
+						failover,
+					);
+				}
+				// Node is not reachable, the other nodes might be
+				if failover && self.fail_over(node_url) {
+					return self.send_json_request(method, params, NODE_CALL_RETRY);
 				}
 				let report = format!("Error calling {}: {}", method, e);
 				error!("{}", report);
@@ -142,10 +386,18 @@ impl HTTPNodeClient {
 			Ok(inner) => match inner.clone().into_result() {
 				Ok(r) => Ok(r),
 				Err(e) => {
-					if counter>0 {
+					if counter > 0 {
 						debug!("Retrying to call Node API method {}: {}", method, e);
-						thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-						return self.send_json_request(method, params, counter-1);
+						thread::sleep(Duration::from_millis(
+							NODE_CALL_DELAY[(counter - 1) as usize],
+						));
+						return self.send_json_request_to(
+							node_url,
+							method,
+							params,
+							counter - 1,
+							failover,
+						);
 					}
 					error!("{:?}", inner);
 					// error message is likely what user want to see...
@@ -158,20 +410,32 @@ impl HTTPNodeClient {
 	}
 
 	/// Return Connected peers
-	fn get_connected_peer_info_impls(&self, counter: i32) -> Result<Vec<grin_p2p::types::PeerInfoDisplayLegacy>, libwallet::Error> {
+	fn get_connected_peer_info_impls(
+		&self,
+		counter: i32,
+	) -> Result<Vec<grin_p2p::types::PeerInfoDisplayLegacy>, libwallet::Error> {
 		// There is no v2 API with connected peers. Keep using v1 for that
-		let addr = self.node_url();
+		let addr = self.active_node();
 		let url = format!("{}/v1/peers/connected", addr);
 
-		let res = self.client
-			.get::<Vec<grin_p2p::types::PeerInfoDisplayLegacy>>(url.as_str(), self.node_api_secret());
+		let res = self
+			.client
+			.get::<Vec<grin_p2p::types::PeerInfoDisplayLegacy>>(
+				url.as_str(),
+				self.node_api_secret(),
+			);
 		match res {
 			Err(e) => {
 				// Do retry
-				if counter>0 {
+				if counter > 0 {
 					debug!("Retry to call connected peers API {}, {}", url, e);
-					thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-					return self.get_connected_peer_info_impls(counter-1);
+					thread::sleep(Duration::from_millis(
+						NODE_CALL_DELAY[(counter - 1) as usize],
+					));
+					return self.get_connected_peer_info_impls(counter - 1);
+				}
+				if self.fail_over(&addr) {
+					return self.get_connected_peer_info_impls(NODE_CALL_RETRY);
 				}
 				let report = format!("Get connected peers error {}, {}", url, e);
 				error!("{}", report);
@@ -192,16 +456,24 @@ impl HTTPNodeClient {
 		let method = "get_kernel";
 		let params = json!([to_hex(excess.0.to_vec()), min_height, max_height]);
 		// have to handle this manually since the error needs to be parsed
-		let url = format!("{}{}", self.node_url(), ENDPOINT);
+		let node_url = self.active_node();
+		let url = format!("{}{}", node_url, ENDPOINT);
 		let req = build_request(method, &params);
-		let res = self.client.post::<Request, Response>(url.as_str(), self.node_api_secret(), &req);
+		let res = self
+			.client
+			.post::<Request, Response>(url.as_str(), self.node_api_secret(), &req);
 
 		match res {
 			Err(e) => {
-				if counter>0 {
+				if counter > 0 {
 					debug!("Retry to call API get_kernel, {}", e);
-					thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-					return self.get_kernel_impl(excess, min_height, max_height, counter-1);
+					thread::sleep(Duration::from_millis(
+						NODE_CALL_DELAY[(counter - 1) as usize],
+					));
+					return self.get_kernel_impl(excess, min_height, max_height, counter - 1);
+				}
+				if self.fail_over(&node_url) {
+					return self.get_kernel_impl(excess, min_height, max_height, NODE_CALL_RETRY);
 				}
 				let report = format!("Error calling {}: {}", method, e);
 				error!("{}", report);
@@ -264,7 +536,8 @@ impl HTTPNodeClient {
 
 		trace!("Output query chunk size is: {}", chunk_size);
 
-		let url = format!("{}{}", self.node_url(), ENDPOINT);
+		let node_url = self.active_node();
+		let url = format!("{}{}", node_url, ENDPOINT);
 
 		let task = async move {
 			let params: Vec<_> = query_params
@@ -311,10 +584,13 @@ impl HTTPNodeClient {
 					match r.into_result::<Vec<OutputPrintable>>() {
 						Ok(mut r) => results.append(&mut r),
 						Err(e) => {
-							if counter>0 {
+							if counter > 0 {
 								debug!("Retry to call API get_outputs, {}", e);
-								thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-								return self.get_outputs_from_node_impl(wallet_outputs,counter-1);
+								thread::sleep(Duration::from_millis(
+									NODE_CALL_DELAY[(counter - 1) as usize],
+								));
+								return self
+									.get_outputs_from_node_impl(wallet_outputs, counter - 1);
 							}
 
 							let report = format!("Unable to parse response for get_outputs: {}", e);
@@ -326,10 +602,15 @@ impl HTTPNodeClient {
 				results
 			}
 			Err(e) => {
-				if counter>0 {
+				if counter > 0 {
 					debug!("Retry to call API get_outputs, {}", e);
-					thread::sleep(Duration::from_millis(NODE_CALL_DELAY[(counter-1) as usize]));
-					return self.get_outputs_from_node_impl(wallet_outputs,counter-1);
+					thread::sleep(Duration::from_millis(
+						NODE_CALL_DELAY[(counter - 1) as usize],
+					));
+					return self.get_outputs_from_node_impl(wallet_outputs, counter - 1);
+				}
+				if self.fail_over(&node_url) {
+					return self.get_outputs_from_node_impl(wallet_outputs, NODE_CALL_RETRY);
 				}
 				let report = format!("Outputs by id failed: {}", e);
 				error!("{}", report);
@@ -361,6 +642,9 @@ impl NodeClient for HTTPNodeClient {
 	fn node_url(&self) -> &str {
 		&self.node_url
 	}
+	fn active_node_url(&self) -> String {
+		self.current_node()
+	}
 	fn node_api_secret(&self) -> Option<String> {
 		self.node_api_secret.clone()
 	}
@@ -383,9 +667,11 @@ impl NodeClient for HTTPNodeClient {
 		if let Some(v) = self.node_version_info.as_ref() {
 			return Some(v.clone());
 		}
-		let retval = match self
-			.send_json_request::<GetVersionResp>("get_version", &serde_json::Value::Null, 1)
-		{
+		let retval = match self.send_json_request::<GetVersionResp>(
+			"get_version",
+			&serde_json::Value::Null,
+			1,
+		) {
 			Ok(n) => NodeVersionInfo {
 				node_version: n.node_version,
 				block_header_version: n.block_header_version,
@@ -404,7 +690,8 @@ impl NodeClient for HTTPNodeClient {
 				} else {
 					error!(
 						"Unable to contact Node to get version info: {}, {}",
-						self.node_url, e
+						self.current_node(),
+						e
 					);
 					return None;
 				}
@@ -414,10 +701,47 @@ impl NodeClient for HTTPNodeClient {
 		Some(retval)
 	}
 
-
 	/// Posts a transaction to a grin node
 	fn post_tx(&self, tx: &Transaction, fluff: bool) -> Result<(), libwallet::Error> {
 		let params = json!([tx, fluff]);
+
+		// Broadcast to all healthy nodes, the transaction is posted if any of them accepts it
+		let broadcast_nodes = match self.failover.read().unwrap().as_ref() {
+			Some(f) if f.broadcast_post_tx => Some(
+				self.failover_nodes(f)
+					.into_iter()
+					.filter(|n| !n.failed)
+					.map(|n| n.url)
+					.collect::<Vec<String>>(),
+			),
+			_ => None,
+		};
+		if let Some(nodes) = broadcast_nodes {
+			let mut res = Err(libwallet::ErrorKind::ClientCallback(
+				"No healthy nodes to post the transaction".to_string(),
+			)
+			.into());
+			for url in nodes {
+				let res_node = self.send_json_request_to::<serde_json::Value>(
+					&url,
+					"push_transaction",
+					&params,
+					NODE_CALL_RETRY,
+					false,
+				);
+				match res_node {
+					Ok(_) => res = Ok(()),
+					Err(e) => {
+						warn!("Unable to post the transaction to node {}, {}", url, e);
+						if res.is_err() {
+							res = Err(e);
+						}
+					}
+				}
+			}
+			return res;
+		}
+
 		self.send_json_request::<serde_json::Value>("push_transaction", &params, NODE_CALL_RETRY)?;
 		Ok(())
 	}
@@ -428,7 +752,8 @@ impl NodeClient for HTTPNodeClient {
 			return Ok(tip);
 		}
 
-		let result = self.send_json_request::<GetTipResp>("get_tip", &serde_json::Value::Null, 1)?;
+		let result =
+			self.send_json_request::<GetTipResp>("get_tip", &serde_json::Value::Null, 1)?;
 		let res = (
 			result.height,
 			result.last_block_pushed,
@@ -445,7 +770,11 @@ impl NodeClient for HTTPNodeClient {
 		}
 
 		let params = json!([Some(height), None::<Option<String>>, None::<Option<String>>]);
-		let r = self.send_json_request::<api::BlockHeaderPrintable>("get_header", &params, NODE_CALL_RETRY)?;
+		let r = self.send_json_request::<api::BlockHeaderPrintable>(
+			"get_header",
+			&params,
+			NODE_CALL_RETRY,
+		)?;
 
 		assert!(r.height == height);
 		let hdr = HeaderInfo {
@@ -460,6 +789,17 @@ impl NodeClient for HTTPNodeClient {
 		Ok(hdr)
 	}
 
+	/// Return the full block header by height
+	fn get_block_header(&self, height: u64) -> Result<BlockHeader, libwallet::Error> {
+		let params = json!([Some(height), None::<Option<String>>, None::<Option<String>>]);
+		let r = self.send_json_request::<api::BlockHeaderPrintable>(
+			"get_header",
+			&params,
+			NODE_CALL_RETRY,
+		)?;
+		header_from_printable(r)
+	}
+
 	/// Return Connected peers
 	fn get_connected_peer_info(
 		&self,
@@ -474,7 +814,6 @@ impl NodeClient for HTTPNodeClient {
 		min_height: Option<u64>,
 		max_height: Option<u64>,
 	) -> Result<Option<(TxKernel, u64, u64)>, libwallet::Error> {
-
 		self.get_kernel_impl(excess, min_height, max_height, NODE_CALL_RETRY)
 	}
 
@@ -505,7 +844,11 @@ impl NodeClient for HTTPNodeClient {
 			Vec::new();
 
 		let params = json!([start_index, end_index, max_outputs, Some(true)]);
-		let res = self.send_json_request::<OutputListing>("get_unspent_outputs", &params, NODE_CALL_RETRY)?;
+		let res = self.send_json_request::<OutputListing>(
+			"get_unspent_outputs",
+			&params,
+			NODE_CALL_RETRY,
+		)?;
 		for out in res.outputs {
 			if out.spent {
 				continue;
@@ -554,7 +897,8 @@ impl NodeClient for HTTPNodeClient {
 		end_height: Option<u64>,
 	) -> Result<(u64, u64), libwallet::Error> {
 		let params = json!([start_height, end_height]);
-		let res = self.send_json_request::<OutputListing>("get_pmmr_indices", &params, NODE_CALL_RETRY)?;
+		let res =
+			self.send_json_request::<OutputListing>("get_pmmr_indices", &params, NODE_CALL_RETRY)?;
 
 		Ok((res.last_retrieved_index, res.highest_index))
 	}
@@ -589,11 +933,15 @@ impl NodeClient for HTTPNodeClient {
 			while tasks.len() < threads_number && height <= end_height {
 				if let Some(b) = self.block_info.get_value(&height) {
 					result_blocks.push(b); // using cache
-				}
-				else {
-					let params = json!([Some(height), None::<Option<String>>, None::<Option<String>>]);
+				} else {
+					let params =
+						json!([Some(height), None::<Option<String>>, None::<Option<String>>]);
 					tasks.push(async move {
-						self.send_json_request::<api::BlockPrintable>("get_block", &params, NODE_CALL_RETRY)
+						self.send_json_request::<api::BlockPrintable>(
+							"get_block",
+							&params,
+							NODE_CALL_RETRY,
+						)
 					});
 				}
 				height += 1;
@@ -611,11 +959,12 @@ impl NodeClient for HTTPNodeClient {
 							self.block_info.set_value(b.header.height, b.clone());
 						}
 						result_blocks.extend(blocks)
-					},
+					}
 					Err(e) => {
 						let report = format!(
 							"get_blocks_by_height: error calling api 'get_block' at {}. Error: {}",
-							self.node_url, e
+							self.current_node(),
+							e
 						);
 						error!("{}", report);
 						return Err(libwallet::ErrorKind::ClientCallback(report).into());
@@ -629,3 +978,267 @@ impl NodeClient for HTTPNodeClient {
 		Ok(result_blocks)
 	}
 }
+
+// Restore the block header from the node API data. The header hash is recalculated, so
+// any field that doesn't match the node data is detected.
+fn header_from_printable(r: api::BlockHeaderPrintable) -> Result<BlockHeader, libwallet::Error> {
+	let parse_err = |field: &str, e: String| -> libwallet::Error {
+		libwallet::ErrorKind::ClientCallback(format!(
+			"Unable to parse header {} at height {}, {}",
+			field, r.height, e
+		))
+		.into()
+	};
+	let hash = |field: &str, hex: &str| -> Result<Hash, libwallet::Error> {
+		Hash::from_hex(hex).map_err(|e| parse_err(field, format!("{:?}", e)))
+	};
+
+	let header = BlockHeader {
+		version: HeaderVersion(r.version),
+		height: r.height,
+		prev_hash: hash("previous", &r.previous)?,
+		prev_root: hash("prev_root", &r.prev_root)?,
+		timestamp: DateTime::parse_from_rfc3339(&r.timestamp)
+			.map_err(|e| parse_err("timestamp", format!("{}", e)))?
+			.with_timezone(&Utc),
+		output_root: hash("output_root", &r.output_root)?,
+		range_proof_root: hash("range_proof_root", &r.range_proof_root)?,
+		kernel_root: hash("kernel_root", &r.kernel_root)?,
+		total_kernel_offset: BlindingFactor::from_hex(&r.total_kernel_offset)
+			.map_err(|e| parse_err("total_kernel_offset", format!("{}", e)))?,
+		output_mmr_size: r.output_mmr_size,
+		kernel_mmr_size: r.kernel_mmr_size,
+		pow: ProofOfWork {
+			total_difficulty: Difficulty::from_num(r.total_difficulty),
+			secondary_scaling: r.secondary_scaling,
+			nonce: r.nonce,
+			proof: Proof {
+				edge_bits: r.edge_bits,
+				nonces: r.cuckoo_solution.clone(),
+			},
+		},
+	};
+
+	if header.hash().to_hex() != r.hash {
+		return Err(libwallet::ErrorKind::ClientCallback(format!(
+			"Header at height {} doesn't match its hash {}",
+			r.height, r.hash
+		))
+		.into());
+	}
+	Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::NodeEndpointConfig;
+	use crate::core::global::{self, ChainTypes};
+	use std::io::{BufRead, BufReader, Read, Write};
+	use std::net::{TcpListener, TcpStream};
+	use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+	// Node foreign API that answers the health check and the transaction post
+	struct MockNode {
+		url: String,
+		height: Arc<AtomicU64>,
+		posted: Arc<AtomicUsize>,
+	}
+
+	impl MockNode {
+		fn start(height: u64) -> MockNode {
+			let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+			let node = MockNode {
+				url: format!("http://{}", listener.local_addr().unwrap()),
+				height: Arc::new(AtomicU64::new(height)),
+				posted: Arc::new(AtomicUsize::new(0)),
+			};
+			let (height, posted) = (node.height.clone(), node.posted.clone());
+			thread::spawn(move || {
+				for stream in listener.incoming() {
+					if let Ok(s) = stream {
+						let _ = mock_response(s, &height, &posted);
+					}
+				}
+			});
+			node
+		}
+	}
+
+	fn mock_response(
+		mut stream: TcpStream,
+		height: &AtomicU64,
+		posted: &AtomicUsize,
+	) -> std::io::Result<()> {
+		let mut reader = BufReader::new(stream.try_clone()?);
+		let mut content_len = 0;
+		loop {
+			let mut line = String::new();
+			reader.read_line(&mut line)?;
+			let line = line.trim_end().to_lowercase();
+			if line.is_empty() {
+				break;
+			}
+			if line.starts_with("content-length:") {
+				content_len = line[15..].trim().parse().unwrap_or(0);
+			}
+		}
+		let mut body = vec![0u8; content_len];
+		reader.read_exact(&mut body)?;
+		let req: serde_json::Value = serde_json::from_slice(&body)?;
+
+		let result = match req["method"].as_str().unwrap_or("") {
+			"get_version" => json!({"node_version": "4.0.0", "block_header_version": 2}),
+			"get_tip" => json!({
+				"height": height.load(Ordering::SeqCst),
+				"last_block_pushed": "00",
+				"prev_block_to_last": "00",
+				"total_difficulty": 1
+			}),
+			"push_transaction" => {
+				posted.fetch_add(1, Ordering::SeqCst);
+				serde_json::Value::Null
+			}
+			_ => json!({"Err": "unsupported"}),
+		};
+		let resp = json!({"jsonrpc": "2.0", "id": req["id"], "result": {"Ok": result}}).to_string();
+		write!(
+			stream,
+			"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+			 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			resp.len(),
+			resp
+		)?;
+		stream.flush()
+	}
+
+	// Address where nothing is listening
+	fn down_url() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		format!("http://{}", listener.local_addr().unwrap())
+	}
+
+	fn failover_client(primary: &str, nodes: Vec<(&str, u32)>, broadcast: bool) -> HTTPNodeClient {
+		global::set_mining_mode(ChainTypes::AutomatedTesting);
+		let mut client = HTTPNodeClient::new(primary, None).unwrap();
+		client.set_failover(Some(NodeFailoverConfig {
+			max_blocks_behind: Some(3),
+			// checks are triggered by the tests
+			health_check_interval_secs: Some(3600),
+			broadcast_post_tx: Some(broadcast),
+			nodes: Some(
+				nodes
+					.into_iter()
+					.map(|(url, priority)| NodeEndpointConfig {
+						url: url.to_string(),
+						priority: Some(priority),
+					})
+					.collect(),
+			),
+		}));
+		// the first health check is done
+		client
+			.failover
+			.write()
+			.unwrap()
+			.as_mut()
+			.unwrap()
+			.last_check = Some(Instant::now());
+		client
+	}
+
+	#[test]
+	fn set_failover() {
+		let nodes = vec![("http://b", 5), ("http://a", 2)];
+		let mut client = failover_client("http://primary", nodes, false);
+		{
+			let failover = client.failover.read().unwrap();
+			let urls: Vec<String> = client
+				.failover_nodes(failover.as_ref().unwrap())
+				.into_iter()
+				.map(|n| n.url)
+				.collect();
+			assert_eq!(urls, vec!["http://primary", "http://a", "http://b"]);
+		}
+		assert_eq!(client.active_node(), "http://primary");
+
+		// Without the additional nodes there is no failover
+		client.set_failover(Some(NodeFailoverConfig::default()));
+		assert!(client.failover.read().unwrap().is_none());
+		client.set_failover(None);
+		assert!(!client.fail_over("http://primary"));
+		assert_eq!(client.active_node(), "http://primary");
+	}
+
+	#[test]
+	fn fail_over() {
+		let backup = MockNode::start(100);
+		let primary = down_url();
+		let client = failover_client(&primary, vec![(backup.url.as_str(), 1)], false);
+
+		// The call to the primary node fails and is repeated on the backup
+		let (height, _, _) = client.get_chain_tip().unwrap();
+		assert_eq!(height, 100);
+		assert_eq!(client.current_node(), backup.url);
+
+		// No more nodes to try
+		assert!(!client.fail_over(&backup.url));
+	}
+
+	#[test]
+	fn lagging_node() {
+		let primary = MockNode::start(100);
+		let backup = MockNode::start(110);
+		let client = failover_client(primary.url.as_str(), vec![(backup.url.as_str(), 1)], false);
+
+		// Primary is behind by more than 3 blocks
+		client.check_nodes();
+		assert_eq!(client.current_node(), backup.url);
+
+		// Primary caught up, it is preferred again
+		primary.height.store(108, Ordering::SeqCst);
+		client.check_nodes();
+		assert_eq!(client.current_node(), primary.url);
+
+		// The check that is due runs in the background, the call uses the current node
+		backup.height.store(120, Ordering::SeqCst);
+		client
+			.failover
+			.write()
+			.unwrap()
+			.as_mut()
+			.unwrap()
+			.last_check = None;
+		assert_eq!(client.active_node(), primary.url);
+		let start = Instant::now();
+		while client.current_node() != backup.url {
+			assert!(start.elapsed() < Duration::from_secs(10));
+			thread::sleep(Duration::from_millis(50));
+		}
+	}
+
+	#[test]
+	fn broadcast_post_tx() {
+		let primary = MockNode::start(100);
+		let backup1 = MockNode::start(100);
+		let backup2 = MockNode::start(100);
+		let nodes = vec![(backup1.url.as_str(), 1), (backup2.url.as_str(), 2)];
+
+		let client = failover_client(primary.url.as_str(), nodes.clone(), true);
+		client.post_tx(&Transaction::empty(), false).unwrap();
+		for n in &[&primary, &backup1, &backup2] {
+			assert_eq!(n.posted.load(Ordering::SeqCst), 1);
+		}
+
+		// Without the broadcast only the active node gets the transaction
+		let client = failover_client(primary.url.as_str(), nodes, false);
+		client.post_tx(&Transaction::empty(), false).unwrap();
+		assert_eq!(primary.posted.load(Ordering::SeqCst), 2);
+		assert_eq!(backup1.posted.load(Ordering::SeqCst), 1);
+
+		// Down node doesn't fail the broadcast
+		let client = failover_client(&down_url(), vec![(backup1.url.as_str(), 1)], true);
+		client.post_tx(&Transaction::empty(), false).unwrap();
+		assert_eq!(backup1.posted.load(Ordering::SeqCst), 2);
+	}
+}
@@ -16,7 +16,9 @@ use crate::api;
 use crate::chain;
 use crate::chain::Chain;
 use crate::core;
-use crate::core::core::{Output, OutputFeatures, OutputIdentifier, Transaction, TxKernel};
+use crate::core::core::{
+	BlockHeader, Output, OutputFeatures, OutputIdentifier, Transaction, TxKernel,
+};
 use crate::core::{consensus, global, pow};
 use crate::keychain;
 use crate::libwallet;
@@ -133,9 +135,21 @@ pub fn add_block_with_reward(
 	reward_kernel: TxKernel,
 ) {
 	let prev = chain.head_header().unwrap();
+	add_block_with_reward_at(chain, &prev, txs, reward_output, reward_kernel);
+}
+
+/// Adds a block on top of `prev` and mines it. If `prev` is not the head, the block
+/// starts or extends a fork. Returns the header of the new block.
+pub fn add_block_with_reward_at(
+	chain: &Chain,
+	prev: &BlockHeader,
+	txs: Vec<&Transaction>,
+	reward_output: Output,
+	reward_kernel: TxKernel,
+) -> BlockHeader {
 	let next_header_info = consensus::next_difficulty(1, chain.difficulty_iter().unwrap());
 	let mut b = core::core::Block::new(
-		&prev,
+		prev,
 		txs.into_iter().cloned().collect(),
 		next_header_info.clone().difficulty,
 		(reward_output, reward_kernel),
@@ -151,10 +165,12 @@ pub fn add_block_with_reward(
 		global::min_edge_bits(),
 	)
 	.unwrap();
+	let header = b.header.clone();
 	chain
 		.process_block(b, chain::Options::MINE, Vec::<Hash>::new())
 		.unwrap();
 	chain.validate(false).unwrap();
+	header
 }
 
 /// adds a reward output to a wallet, includes that reward in a block, mines
@@ -211,6 +227,38 @@ where
 	Ok(())
 }
 
+/// Award `number` blocks to a wallet on a fork that starts after `fork_header`. The fork
+/// becomes the main chain once it has more work than the current one.
+pub fn award_fork_blocks_to_wallet<'a, L, C, K>(
+	chain: &Chain,
+	fork_header: &BlockHeader,
+	wallet: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K> + 'a>>>,
+	keychain_mask: Option<&SecretKey>,
+	number: usize,
+) -> Result<(), libwallet::Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: keychain::Keychain + 'a,
+{
+	let mut prev = fork_header.clone();
+	for _ in 0..number {
+		let block_fees = BlockFees {
+			fees: 0,
+			key_id: None,
+			height: prev.height + 1,
+		};
+		let coinbase_tx = {
+			let mut w_lock = wallet.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			foreign::build_coinbase(&mut **w, keychain_mask, &block_fees, false)?
+		};
+		prev =
+			add_block_with_reward_at(chain, &prev, vec![], coinbase_tx.output, coinbase_tx.kernel);
+	}
+	Ok(())
+}
+
 /// send an amount to a destination
 pub fn send_to_dest<'a, L, C, K>(
 	wallet: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
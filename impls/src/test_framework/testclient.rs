@@ -23,6 +23,7 @@ use crate::core::core::verifier_cache::LruVerifierCache;
 use crate::core::core::{Transaction, TxKernel};
 use crate::core::global::{set_mining_mode, ChainTypes};
 use crate::core::pow;
+use crate::core::ser;
 use crate::keychain::Keychain;
 use crate::libwallet;
 use crate::libwallet::api_impl::foreign;
@@ -148,6 +149,7 @@ where
 			let resp = match m.method.as_ref() {
 				"get_chain_tip" => self.get_chain_tip(m)?,
 				"get_header_info" => self.get_header_info(m)?,
+				"get_block_header" => self.get_block_header(m)?,
 				"get_outputs_from_node" => self.get_outputs_from_node(m)?,
 				"get_outputs_by_pmmr_index" => self.get_outputs_by_pmmr_index(m)?,
 				"height_range_to_pmmr_indices" => self.height_range_to_pmmr_indices(m)?,
@@ -291,6 +293,23 @@ where
 		})
 	}
 
+	/// get full header, binary serialized
+	fn get_block_header(
+		&mut self,
+		m: WalletProxyMessage,
+	) -> Result<WalletProxyMessage, libwallet::Error> {
+		let height = m.body.parse::<u64>().unwrap();
+		let hdr: BlockHeader = self.chain.get_header_by_height(height).unwrap();
+		let hdr_bin = ser::ser_vec(&hdr, ser::ProtocolVersion(1)).unwrap();
+
+		Ok(WalletProxyMessage {
+			sender_id: "node".to_owned(),
+			dest: m.sender_id,
+			method: m.method,
+			body: util::to_hex(hdr_bin),
+		})
+	}
+
 	/// get api outputs
 	/// Result value: Commit, Height, MMR
 	fn get_outputs_from_node(
@@ -564,6 +583,39 @@ impl NodeClient for LocalWalletClient {
 		})
 	}
 
+	/// Return the full block header by height
+	fn get_block_header(&self, height: u64) -> Result<BlockHeader, libwallet::Error> {
+		let m = WalletProxyMessage {
+			sender_id: self.id.clone(),
+			dest: self.node_url().to_owned(),
+			method: "get_block_header".to_owned(),
+			body: format!("{}", height),
+		};
+		{
+			let p = self.proxy_tx.lock();
+			p.send(m).map_err(|e| {
+				libwallet::ErrorKind::ClientCallback(format!("Get block header send, {}", e))
+			})?;
+		}
+		let r = self.rx.lock();
+		let m = r.recv().unwrap();
+		trace!("Received get_block_header response: {:?}", m.clone());
+		let hdr_bin = util::from_hex(&m.body).map_err(|e| {
+			libwallet::ErrorKind::ClientCallback(format!(
+				"Parsing get_block_header response, {}",
+				e
+			))
+		})?;
+		let hdr = ser::deserialize::<BlockHeader>(&mut &hdr_bin[..], ser::ProtocolVersion(1))
+			.map_err(|e| {
+				libwallet::ErrorKind::ClientCallback(format!(
+					"Parsing get_block_header response, {}",
+					e
+				))
+			})?;
+		Ok(hdr)
+	}
+
 	/// Return Connected peers
 	fn get_connected_peer_info(
 		&self,
@@ -0,0 +1,90 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Range proof rewinding speed of the chain scan on a synthetic UTXO set.
+//! Run with `cargo bench -p grin_wallet_libwallet --bench scan_rewind`.
+//! The set size can be changed with the SCAN_BENCH_OUTPUTS environment variable.
+
+use grin_wallet_libwallet::internal::scan::identify_utxo_outputs;
+use grin_wallet_util::grin_core::libtx::proof;
+use grin_wallet_util::grin_keychain::{
+	ExtKeychain, ExtKeychainPath, Keychain, SwitchCommitmentType,
+};
+use grin_wallet_util::grin_util::secp::pedersen;
+use std::env;
+use std::time::{Duration, Instant};
+
+// Every OWNED_EVERY output of the set belongs to the scanning wallet
+const OWNED_EVERY: usize = 50;
+
+fn build_outputs(
+	wallet: &ExtKeychain,
+	size: usize,
+) -> Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)> {
+	let other = ExtKeychain::from_random_seed(false).unwrap();
+	let switch = SwitchCommitmentType::Regular;
+	(0..size)
+		.map(|i| {
+			let keychain = if i % OWNED_EVERY == 0 { wallet } else { &other };
+			let builder = proof::ProofBuilder::new(keychain);
+			let key_id = ExtKeychainPath::new(3, 0, 0, i as u32, 0).to_identifier();
+			let value = 1_000_000 + i as u64;
+			let commit = keychain.commit(value, &key_id, switch).unwrap();
+			let proof =
+				proof::create(keychain, &builder, value, &key_id, switch, commit, None).unwrap();
+			// Heights far above the hard fork, only the current proof format is checked
+			(commit, proof, false, 1_000_000 + i as u64, i as u64 + 1)
+		})
+		.collect()
+}
+
+fn run(
+	wallet: &ExtKeychain,
+	outputs: &Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)>,
+	threads: usize,
+) -> Duration {
+	let start = Instant::now();
+	let found = identify_utxo_outputs(wallet, outputs.clone(), threads).unwrap();
+	let elapsed = start.elapsed();
+	assert_eq!(found.len(), (outputs.len() + OWNED_EVERY - 1) / OWNED_EVERY);
+	elapsed
+}
+
+fn main() {
+	let size = env::var("SCAN_BENCH_OUTPUTS")
+		.ok()
+		.and_then(|s| s.parse::<usize>().ok())
+		.unwrap_or(5000);
+
+	let wallet = ExtKeychain::from_random_seed(false).unwrap();
+	println!("Building {} synthetic outputs...", size);
+	let outputs = build_outputs(&wallet, size);
+
+	let serial = run(&wallet, &outputs, 1);
+	println!(
+		"threads: 1, time: {:?}, outputs/s: {:.0}",
+		serial,
+		size as f64 / serial.as_secs_f64()
+	);
+	for threads in &[2, 4, 8] {
+		let elapsed = run(&wallet, &outputs, *threads);
+		println!(
+			"threads: {}, time: {:?}, outputs/s: {:.0}, speedup: {:.2}x",
+			threads,
+			elapsed,
+			size as f64 / elapsed.as_secs_f64(),
+			serial.as_secs_f64() / elapsed.as_secs_f64()
+		);
+	}
+}
@@ -21,6 +21,7 @@
 #![deny(unused_mut)]
 #![warn(missing_docs)]
 
+pub mod events;
 pub mod foreign;
 pub mod owner;
 pub mod owner_swap;
@@ -0,0 +1,129 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallet events for the streaming API clients. The updater, the foreign API and the
+//! swap processing publish the events, subscribers receive them through a callback.
+
+use crate::grin_keychain::Identifier;
+use crate::swap::fsm::state::StateId;
+use crate::types::WalletInfo;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Topic of a wallet event
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+	/// Balance of an account changed
+	Balance,
+	/// Updater status message
+	Updater,
+	/// Slate was received or queued for approval by the foreign API
+	Slate,
+	/// Swap trade moved to another state
+	Swap,
+}
+
+/// Event that is pushed to the subscribers
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WalletEvent {
+	/// Event topic
+	pub topic: EventTopic,
+	/// Event data, the format depends on the topic
+	pub data: serde_json::Value,
+}
+
+/// Subscriber callback. It is called from the publishing thread, so it must not block.
+pub type EventCallback = Box<dyn Fn(&WalletEvent) + Send + Sync>;
+
+static NEXT_SUBSCRIBER_ID: AtomicU64 = AtomicU64::new(1);
+
+lazy_static! {
+	static ref SUBSCRIBERS: RwLock<HashMap<u64, EventCallback>> = RwLock::new(HashMap::new());
+	// Last published balance by account and minimum confirmations
+	static ref LAST_BALANCE: RwLock<HashMap<(Identifier, u64), WalletInfo>> =
+		RwLock::new(HashMap::new());
+	// Last published state by swap id
+	static ref LAST_SWAP_STATE: RwLock<HashMap<String, StateId>> = RwLock::new(HashMap::new());
+}
+
+/// Subscribe to the wallet events. Returns the subscription id.
+pub fn subscribe_events(callback: EventCallback) -> u64 {
+	let id = NEXT_SUBSCRIBER_ID.fetch_add(1, Ordering::Relaxed);
+	SUBSCRIBERS.write().unwrap().insert(id, callback);
+	id
+}
+
+/// Cancel the subscription
+pub fn unsubscribe_events(id: u64) {
+	SUBSCRIBERS.write().unwrap().remove(&id);
+}
+
+/// Push the event to all subscribers
+pub fn publish_event(topic: EventTopic, data: serde_json::Value) {
+	let subscribers = SUBSCRIBERS.read().unwrap();
+	if subscribers.is_empty() {
+		return;
+	}
+	let event = WalletEvent { topic, data };
+	for callback in subscribers.values() {
+		callback(&event);
+	}
+}
+
+/// Publish the account balance if it changed since the last time. Height changes alone
+/// are not published.
+pub fn publish_balance(parent_key_id: &Identifier, info: &WalletInfo) {
+	let key = (parent_key_id.clone(), info.minimum_confirmations);
+	{
+		let mut last = LAST_BALANCE.write().unwrap();
+		if let Some(prev) = last.get(&key) {
+			let prev = WalletInfo {
+				last_confirmed_height: info.last_confirmed_height,
+				..prev.clone()
+			};
+			if prev == *info {
+				return;
+			}
+		}
+		last.insert(key, info.clone());
+	}
+	publish_event(
+		EventTopic::Balance,
+		serde_json::json!({
+			"account_path": parent_key_id,
+			"info": info,
+		}),
+	);
+}
+
+/// Publish the swap state if it changed since the last time
+pub fn publish_swap_state(swap_id: &str, state: &StateId) {
+	let prev = LAST_SWAP_STATE
+		.write()
+		.unwrap()
+		.insert(swap_id.to_string(), state.clone());
+	if prev.as_ref() == Some(state) {
+		return;
+	}
+	publish_event(
+		EventTopic::Swap,
+		serde_json::json!({
+			"swap_id": swap_id,
+			"prev_state": prev,
+			"state": state,
+		}),
+	);
+}
@@ -13,8 +13,10 @@
 // limitations under the License.
 
 //! Generic implementation of owner API functions
-use crate::api_impl::owner::check_ttl;
+use crate::api_impl::events::{self, EventTopic};
+use crate::api_impl::owner::{check_lock_height, check_ttl};
 use crate::api_impl::owner_swap;
+use crate::config::ReceivePolicyConfig;
 use crate::grin_keychain::Keychain;
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::Mutex;
@@ -22,11 +24,15 @@ use crate::internal::{tx, updater};
 use crate::proof::proofaddress;
 use crate::proof::proofaddress::ProofAddressType;
 use crate::proof::proofaddress::ProvableAddress;
-use crate::slate_versions::SlateVersion;
+use crate::slate_versions::v3::SlateV3;
+use crate::slate_versions::{SlateEncoding, SlateVersion};
 use crate::{
-	BlockFees, CbData, Error, ErrorKind, NodeClient, Slate, TxLogEntryType, VersionInfo,
+	BlockFees, CbData, Error, ErrorKind, InvoiceStatus, NodeClient, PendingSlate,
+	PendingSlateRequest, PendingSlateStatus, Slate, TxLogEntry, TxLogEntryType, VersionInfo,
 	WalletBackend, WalletInst, WalletLCProvider,
 };
+use chrono::prelude::*;
+use chrono::Duration;
 use grin_core::core::amount_to_hr_string;
 use grin_wallet_util::OnionV3Address;
 use std::sync::Arc;
@@ -35,6 +41,9 @@ use strum::IntoEnumIterator;
 
 const FOREIGN_API_VERSION: u16 = 2;
 const USER_MESSAGE_MAX_LEN: usize = 256;
+/// Time the finished slates stay in the approval queue after they expire, so the senders
+/// that retry still get the final status
+const PENDING_SLATE_KEEP_SECS: i64 = 24 * 3600;
 
 lazy_static! {
 	/// Recieve account can be specified separately and must be allpy to ALL receive operations
@@ -72,11 +81,406 @@ pub fn set_receive_account(account: String) {
 	RECV_ACCOUNT.write().unwrap().replace(account.to_string());
 }
 
+// Sender address without the scheme, mwcmqs domain and onion suffix, so the same sender
+// matches in any of its forms
+pub(crate) fn normalize_sender(addr: &str) -> String {
+	let addr = addr.trim();
+	let addr = match addr.find("://") {
+		Some(i) => &addr[i + 3..],
+		None => addr,
+	};
+	let addr = addr
+		.split(|c: char| c == '@' || c == '/')
+		.next()
+		.unwrap_or(addr);
+	addr.trim_end_matches(".onion").to_string()
+}
+
+// Sender id of the slates without the sender address and the payment proof, such senders
+// share one rate limit and can be denied together
+const ANONYMOUS_SENDER: &str = "anonymous";
+
+fn sender_ids(address: &Option<String>, proof_address: Option<&str>) -> Vec<String> {
+	let mut ids = vec![];
+	if let Some(a) = address {
+		ids.push(normalize_sender(a));
+	}
+	if let Some(p) = proof_address {
+		ids.push(normalize_sender(p));
+	}
+	if ids.is_empty() {
+		ids.push(ANONYMOUS_SENDER.to_string());
+	}
+	ids
+}
+
+fn slate_sender_ids(slate: &Slate, address: &Option<String>) -> Vec<String> {
+	sender_ids(
+		address,
+		slate
+			.payment_proof
+			.as_ref()
+			.map(|p| p.sender_address.public_key.as_str()),
+	)
+}
+
+fn tx_sender_ids(tx: &TxLogEntry) -> Vec<String> {
+	// Receives without the sender address are logged with the "http" address
+	let address = tx.address.clone().filter(|a| a != "http");
+	sender_ids(
+		&address,
+		tx.payment_proof
+			.as_ref()
+			.map(|p| p.sender_address.public_key.as_str()),
+	)
+}
+
+/// Check the incoming slate against the receive policy. Slates from files and self sends
+/// are initiated by the wallet owner, the policy doesn't apply to them.
+pub fn check_receive_policy<'a, T: ?Sized, C, K>(
+	w: &T,
+	slate: &Slate,
+	address: &Option<String>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let policy = match w.settings().receive_policy.clone() {
+		Some(p) => p,
+		None => return Ok(()),
+	};
+	match address.as_ref().map(|a| a.as_str()) {
+		Some("self") | Some("file") => return Ok(()),
+		_ => (),
+	}
+
+	let reject = |rule: &str, reason: String| -> Error {
+		warn!(
+			"slate [{}] from {:?} is rejected by the receive policy ({}), {}",
+			slate.id, address, rule, reason
+		);
+		ErrorKind::ReceivePolicyViolation {
+			rule: rule.to_string(),
+			reason,
+		}
+		.into()
+	};
+
+	let senders = slate_sender_ids(slate, address);
+
+	if let Some(denied) = &policy.denied_senders {
+		if let Some(s) = senders
+			.iter()
+			.find(|s| denied.iter().any(|d| normalize_sender(d) == **s))
+		{
+			return Err(reject("denied_senders", format!("sender {} is denied", s)));
+		}
+	}
+
+	if let Some(allowed) = &policy.allowed_senders {
+		if senders.iter().any(|s| s == ANONYMOUS_SENDER) {
+			return Err(reject(
+				"allowed_senders",
+				"sender without an address or a payment proof is not allowed".to_string(),
+			));
+		}
+		if !senders
+			.iter()
+			.any(|s| allowed.iter().any(|a| normalize_sender(a) == *s))
+		{
+			return Err(reject(
+				"allowed_senders",
+				"sender is not in the allowed list".to_string(),
+			));
+		}
+	}
+
+	if policy.require_payment_proof.unwrap_or(false) && slate.payment_proof.is_none() {
+		return Err(reject(
+			"require_payment_proof",
+			"payment proof is required".to_string(),
+		));
+	}
+
+	if let Some(min_amount) = policy.min_amount {
+		if slate.amount < min_amount {
+			return Err(reject(
+				"min_amount",
+				format!(
+					"amount {} MWC is below the minimum {} MWC",
+					amount_to_hr_string(slate.amount, false),
+					amount_to_hr_string(min_amount, false)
+				),
+			));
+		}
+	}
+
+	if policy.max_pending_receives.is_none() && policy.sender_rate_limit.is_none() {
+		return Ok(());
+	}
+
+	let rate_start =
+		Utc::now() - Duration::seconds(policy.sender_rate_period_secs.unwrap_or(3600) as i64);
+	let mut pending = 0;
+	let mut recent = 0;
+	for tx in w.tx_log_iter() {
+		match tx.tx_type {
+			TxLogEntryType::TxReceived => {
+				if !tx.confirmed {
+					pending += 1;
+				}
+			}
+			TxLogEntryType::TxReceivedCancelled => (),
+			_ => continue,
+		}
+		if tx.creation_ts >= rate_start && tx_sender_ids(&tx).iter().any(|s| senders.contains(s)) {
+			recent += 1;
+		}
+	}
+
+	if let Some(max_pending) = policy.max_pending_receives {
+		if pending >= max_pending {
+			return Err(reject(
+				"max_pending_receives",
+				format!(
+					"wallet has {} unconfirmed received transactions, the limit is {}",
+					pending, max_pending
+				),
+			));
+		}
+	}
+
+	if let Some(limit) = policy.sender_rate_limit {
+		if recent >= limit {
+			return Err(reject(
+				"sender_rate_limit",
+				format!(
+					"sender reached the limit of {} slates per {} seconds",
+					limit,
+					policy.sender_rate_period_secs.unwrap_or(3600)
+				),
+			));
+		}
+	}
+
+	Ok(())
+}
+
+// Remove the rejected, expired and delivered slates from the approval queue
+fn prune_pending_slates<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	now: DateTime<Utc>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let keep_until = now - Duration::seconds(PENDING_SLATE_KEEP_SECS);
+	let stale: Vec<PendingSlate> = w
+		.pending_slate_iter()
+		.filter(|p| p.status != PendingSlateStatus::Approved || p.delivered)
+		.filter(|p| p.is_expired_at(keep_until))
+		.collect();
+	if stale.is_empty() {
+		return Ok(());
+	}
+	let mut batch = w.batch(keychain_mask)?;
+	for p in &stale {
+		batch.delete_pending_slate(&p.id)?;
+	}
+	batch.commit()?;
+	debug!(
+		"{} finished slates are removed from the approval queue",
+		stale.len()
+	);
+	Ok(())
+}
+
+// Reject the new slate if the approval queue is full, globally or for the sender
+fn check_pending_approvals<'a, T: ?Sized, C, K>(
+	w: &T,
+	policy: &ReceivePolicyConfig,
+	slate: &Slate,
+	address: &Option<String>,
+	now: DateTime<Utc>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let sender = address.as_ref().map(|a| normalize_sender(a));
+	let mut pending = 0;
+	let mut from_sender = 0;
+	for p in w.pending_slate_iter() {
+		if p.status != PendingSlateStatus::Pending || p.is_expired_at(now) {
+			continue;
+		}
+		pending += 1;
+		if sender.is_some() && p.address.as_ref().map(|a| normalize_sender(a)) == sender {
+			from_sender += 1;
+		}
+	}
+
+	let (rule, reason) = match (
+		policy.max_pending_approvals,
+		policy.max_pending_approvals_per_sender,
+	) {
+		(Some(max), _) if pending >= max => (
+			"max_pending_approvals",
+			format!(
+				"{} slates are waiting for the approval, the limit is {}",
+				pending, max
+			),
+		),
+		(_, Some(max)) if sender.is_some() && from_sender >= max => (
+			"max_pending_approvals_per_sender",
+			format!(
+				"{} slates from the sender are waiting for the approval, the limit is {}",
+				from_sender, max
+			),
+		),
+		_ => return Ok(()),
+	};
+	warn!(
+		"slate [{}] from {:?} is rejected by the receive policy ({}), {}",
+		slate.id, address, rule, reason
+	);
+	Err(ErrorKind::ReceivePolicyViolation {
+		rule: rule.to_string(),
+		reason,
+	}
+	.into())
+}
+
+/// Park the incoming slate in the approval queue if the receive policy requires the manual
+/// approval. Returns the response slate if the wallet owner already approved the slate,
+/// None if the request doesn't need the approval. Until the slate is approved the caller
+/// gets the PendingApproval error.
+pub fn approval_queue_check<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &Slate,
+	request: PendingSlateRequest,
+	address: Option<String>,
+	dest_acct_name: Option<&str>,
+	message: Option<String>,
+) -> Result<Option<Slate>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let policy = match w.settings().receive_policy.clone() {
+		Some(p) if p.manual_approval.unwrap_or(false) => p,
+		_ => return Ok(None),
+	};
+	match address.as_ref().map(|a| a.as_str()) {
+		Some("self") | Some("file") => return Ok(None),
+		_ => (),
+	}
+
+	let now = Utc::now();
+	let pending = match w.get_pending_slate(&slate.id)? {
+		Some(p) => p,
+		None => {
+			prune_pending_slates(w, keychain_mask, now)?;
+			check_pending_approvals(w, &policy, slate, &address, now)?;
+			let timeout = policy.approval_timeout_secs.unwrap_or(3600);
+			let pending = PendingSlate {
+				id: slate.id,
+				request,
+				slate: SlateV3::from(slate),
+				address: address.clone(),
+				dest_acct_name: dest_acct_name.map(|s| s.to_string()),
+				message,
+				creation_ts: now,
+				expiry_ts: now + Duration::seconds(timeout as i64),
+				status: PendingSlateStatus::Pending,
+				response: None,
+				delivered: false,
+			};
+			let mut batch = w.batch(keychain_mask)?;
+			batch.save_pending_slate(pending)?;
+			batch.commit()?;
+			info!(
+				"slate [{}] from {:?} is parked until the wallet owner approves it",
+				slate.id, address
+			);
+			events::publish_event(
+				EventTopic::Slate,
+				serde_json::json!({
+					"slate_id": slate.id,
+					"amount": slate.amount,
+					"address": address,
+					"status": "pending_approval",
+				}),
+			);
+			return Err(ErrorKind::PendingApproval(slate.id.to_string()).into());
+		}
+	};
+
+	if pending.request != request {
+		return Err(ErrorKind::ApprovalQueueError(format!(
+			"Slate {} is already parked for {}",
+			slate.id, pending.request
+		))
+		.into());
+	}
+
+	match pending.status {
+		PendingSlateStatus::Pending => {
+			if pending.is_expired_at(now) {
+				let mut pending = pending;
+				pending.status = PendingSlateStatus::Expired;
+				let mut batch = w.batch(keychain_mask)?;
+				batch.save_pending_slate(pending)?;
+				batch.commit()?;
+				Err(ErrorKind::ApprovalQueueError(format!(
+					"Slate {} was not approved in time",
+					slate.id
+				))
+				.into())
+			} else {
+				Err(ErrorKind::PendingApproval(slate.id.to_string()).into())
+			}
+		}
+		PendingSlateStatus::Approved => match pending.response.clone() {
+			Some(response) => {
+				if !pending.delivered {
+					let mut pending = pending;
+					pending.delivered = true;
+					let mut batch = w.batch(keychain_mask)?;
+					batch.save_pending_slate(pending)?;
+					batch.commit()?;
+				}
+				Ok(Some(Slate::from(response)))
+			}
+			None => Err(ErrorKind::PendingApproval(slate.id.to_string()).into()),
+		},
+		PendingSlateStatus::Rejected => Err(ErrorKind::ApprovalQueueError(format!(
+			"Slate {} was rejected by the wallet owner",
+			slate.id
+		))
+		.into()),
+		PendingSlateStatus::Expired => Err(ErrorKind::ApprovalQueueError(format!(
+			"Slate {} was not approved in time",
+			slate.id
+		))
+		.into()),
+	}
+}
+
 /// Return the version info
 pub fn check_version() -> VersionInfo {
 	VersionInfo {
 		foreign_api_version: FOREIGN_API_VERSION,
 		supported_slate_versions: SlateVersion::iter().collect(),
+		supported_slate_encodings: SlateEncoding::iter().collect(),
 	}
 }
 
@@ -124,6 +528,14 @@ where
 	let mut address_for_logging = address.clone();
 
 	check_ttl(w, &slate, refresh_from_node)?;
+	check_lock_height(&slate)?;
+	check_receive_policy(w, &slate, &address)?;
+	if slate.lock_height > 0 {
+		info!(
+			"slate [{}] is height locked, the transaction is not valid before height {}",
+			slate.id, slate.lock_height
+		);
+	}
 
 	if address.is_none() {
 		// that means it's not mqs so need to print it
@@ -248,6 +660,16 @@ where
 		p.receiver_signature = Some(sig);
 	}
 
+	events::publish_event(
+		EventTopic::Slate,
+		serde_json::json!({
+			"slate_id": ret_slate.id,
+			"amount": ret_slate.amount,
+			"address": address,
+			"status": "received",
+		}),
+	);
+
 	Ok(ret_slate)
 }
 
@@ -265,6 +687,29 @@ where
 {
 	let mut sl = slate.clone();
 	check_ttl(w, &sl, refresh_from_node)?;
+
+	// Invoice record is matched by the slate id. Only open invoices with the same amount can be paid.
+	let invoice = w.get_invoice(&sl.id)?;
+	if let Some(i) = &invoice {
+		if i.status != InvoiceStatus::Open {
+			return Err(
+				ErrorKind::InvoiceError(format!("Invoice {} is {}", i.id, i.status)).into(),
+			);
+		}
+		if i.is_expired_at(w.last_confirmed_height()?) {
+			return Err(ErrorKind::InvoiceError(format!("Invoice {} is expired", i.id)).into());
+		}
+		if i.amount != sl.amount {
+			return Err(ErrorKind::InvoiceError(format!(
+				"Invoice {} amount is {}, slate amount is {}",
+				i.id,
+				amount_to_hr_string(i.amount, true),
+				amount_to_hr_string(sl.amount, true)
+			))
+			.into());
+		}
+	}
+
 	// Participant id 0 for mwc713 compatibility
 	let context = w.get_private_context(keychain_mask, sl.id.as_bytes(), 0)?;
 	// Participant id 0 for mwc713 compatibility
@@ -275,6 +720,11 @@ where
 		let mut batch = w.batch(keychain_mask)?;
 		// Participant id 0 for mwc713 compatibility
 		batch.delete_private_context(sl.id.as_bytes(), 0)?;
+		if let Some(mut i) = invoice {
+			i.status = InvoiceStatus::Paid;
+			i.paid_ts = Some(Utc::now());
+			batch.save_invoice(i)?;
+		}
 		batch.commit()?;
 	}
 	Ok(sl)
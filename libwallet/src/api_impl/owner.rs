@@ -17,24 +17,38 @@
 use uuid::Uuid;
 
 use crate::grin_core::core::hash::Hashed;
-use crate::grin_core::core::Transaction;
+use crate::grin_core::core::{KernelFeatures, Transaction};
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::Mutex;
 
+use crate::api_impl::events;
+use crate::api_impl::foreign;
 use crate::api_impl::owner_updater::StatusMessage;
 use crate::grin_keychain::{Identifier, Keychain};
 use crate::grin_util::secp::key::PublicKey;
 
-use crate::internal::{keys, scan, selection, tx, updater};
+use crate::internal::rebroadcast::{self, RebroadcastAction};
+use crate::internal::{
+	cold, expiry, fee, header_sync, keys, reorg, scan, selection, spend_policy, tx, updater,
+};
 use crate::slate::{PaymentInfo, Slate};
-use crate::types::{AcctPathMapping, Context, NodeClient, TxLogEntry, WalletBackend, WalletInfo};
+use crate::slate_versions::v3::SlateV3;
+use crate::types::{
+	AcctPathMapping, Contact, Context, Invoice, InvoiceStatus, LinkedTx, NodeClient, OutputData,
+	PendingSlate, PendingSlateRequest, PendingSlateStatus, SpendPolicy, SpendPolicyDestination,
+	TxLogEntry, WalletBackend, WalletInfo,
+};
 use crate::{
-	wallet_lock, InitTxArgs, IssueInvoiceTxArgs, NodeHeightResult, OutputCommitMapping,
-	PaymentProof, ScannedBlockInfo, TxLogEntryType, WalletInst, WalletLCProvider,
+	wallet_lock, BatchSendArgs, BatchSendEntry, ColdSignRequest, ColdSignResponse, ColdSignStep,
+	ExpiredTx, FeeEstimate, InitTxArgs, IssueInvoiceTxArgs, NodeHeightResult, OutputCommitMapping,
+	PaymentProof, ScanStatus, ScannedBlockInfo, SpendPolicyArgs, SpendPolicyStatus, TransferArgs,
+	TransferResult, TxLogEntryType, WalletInst, WalletLCProvider,
 };
 use crate::{Error, ErrorKind};
 
 use crate::proof::tx_proof::{pop_proof_for_slate, TxProof};
+use chrono::prelude::*;
+use chrono::Duration;
 use std::cmp;
 use std::fs::File;
 use std::io::Write;
@@ -43,7 +57,11 @@ use std::sync::Arc;
 
 const USER_MESSAGE_MAX_LEN: usize = 256;
 use crate::proof::crypto;
-use crate::proof::proofaddress;
+use crate::proof::crypto::Hex;
+use crate::proof::payment_proof::{
+	PaymentProofDocument, PAYMENT_PROOF_DOCUMENT_TYPE, PAYMENT_PROOF_DOCUMENT_VERSION,
+};
+use crate::proof::proofaddress::{self, ProvableAddress};
 use grin_core::global;
 
 /// List of accounts
@@ -166,6 +184,249 @@ where
 	))
 }
 
+// Apply the change to the wallet outputs with the commit
+fn update_output<'a, L, C, K, F>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	commit: &str,
+	update: F,
+) -> Result<OutputData, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+	F: Fn(&mut OutputData),
+{
+	wallet_lock!(wallet_inst, w);
+	// The same commit can be stored for several mmr indexes
+	let mut outputs: Vec<OutputData> = w
+		.iter()
+		.filter(|o| o.commit.as_ref().map(|c| c.as_str()) == Some(commit))
+		.collect();
+	if outputs.is_empty() {
+		return Err(ErrorKind::OutputNotFound(commit.to_string()).into());
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	for out in outputs.iter_mut() {
+		update(out);
+		batch.save(out.clone())?;
+	}
+	batch.commit()?;
+	Ok(outputs.remove(0))
+}
+
+/// Freeze or unfreeze the output. Frozen outputs are not selected for the transactions
+/// unless they are listed in the send outputs.
+pub fn set_output_frozen<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	commit: &str,
+	frozen: bool,
+) -> Result<OutputData, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	update_output(wallet_inst, keychain_mask, commit, |out| {
+		out.frozen = frozen
+	})
+}
+
+/// Set the note and the tags of the output. They replace the current ones.
+pub fn label_output<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	commit: &str,
+	note: Option<String>,
+	tags: Vec<String>,
+) -> Result<OutputData, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (note, tags) = clean_labels(note, tags);
+	update_output(wallet_inst, keychain_mask, commit, |out| {
+		out.note = note.clone();
+		out.tags = tags.clone();
+	})
+}
+
+// Empty note and tags are dropped, tags are sorted and deduplicated
+fn clean_labels(note: Option<String>, tags: Vec<String>) -> (Option<String>, Vec<String>) {
+	let note = note.filter(|n| !n.trim().is_empty());
+	let mut tags: Vec<String> = tags
+		.into_iter()
+		.map(|t| t.trim().to_string())
+		.filter(|t| !t.is_empty())
+		.collect();
+	tags.sort();
+	tags.dedup();
+	(note, tags)
+}
+
+/// Set the note and the tags of the transaction. They replace the current ones and are
+/// never sent to the other party.
+pub fn label_tx<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	tx_id: Option<u32>,
+	tx_slate_id: Option<Uuid>,
+	note: Option<String>,
+	tags: Vec<String>,
+) -> Result<TxLogEntry, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let tx_id_string = match (tx_id, tx_slate_id) {
+		(Some(id), _) => id.to_string(),
+		(None, Some(slate_id)) => slate_id.to_string(),
+		(None, None) => {
+			return Err(ErrorKind::GenericError(
+				"Transaction id or slate id is required".to_string(),
+			)
+			.into())
+		}
+	};
+	let mut txs = updater::retrieve_txs(
+		&mut **w,
+		keychain_mask,
+		tx_id,
+		tx_slate_id,
+		Some(&parent_key_id),
+		false,
+		None,
+		None,
+	)?;
+	if txs.len() != 1 {
+		return Err(ErrorKind::TransactionDoesntExist(tx_id_string).into());
+	}
+	let mut tx = txs.remove(0);
+	let (note, tags) = clean_labels(note, tags);
+	tx.note = note;
+	tx.tags = tags;
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_tx_log_entry(tx.clone(), &parent_key_id)?;
+	batch.commit()?;
+	Ok(tx)
+}
+
+/// List the address book contacts
+pub fn retrieve_contacts<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<Vec<Contact>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let _ = w.keychain(keychain_mask)?;
+	Ok(w.contact_iter().collect())
+}
+
+/// Get the address book contact by name, the name can have the '@' prefix
+pub fn get_contact<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	name: &str,
+) -> Result<Contact, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let _ = w.keychain(keychain_mask)?;
+	let name = name.trim_start_matches('@');
+	w.get_contact(name)?
+		.ok_or(ErrorKind::ContactError(format!("Contact @{} is not found", name)).into())
+}
+
+/// Add the contact to the address book or replace the contact with the same name.
+/// Method `mqs` is stored as `mwcmqs`, the send method name.
+pub fn save_contact<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	contact: Contact,
+) -> Result<Contact, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut contact = contact;
+	contact.name = contact.name.trim().trim_start_matches('@').to_string();
+	if contact.name.is_empty() || contact.name.contains(char::is_whitespace) {
+		return Err(ErrorKind::ContactError(format!(
+			"Invalid contact name '{}', it can't be empty or contain spaces",
+			contact.name
+		))
+		.into());
+	}
+	contact.address = contact.address.trim().to_string();
+	if contact.address.is_empty() {
+		return Err(ErrorKind::ContactError(format!(
+			"Address of the contact @{} is empty",
+			contact.name
+		))
+		.into());
+	}
+	contact.method = match contact.method.to_lowercase().as_str() {
+		"http" => "http".to_string(),
+		"tor" => "tor".to_string(),
+		"mqs" | "mwcmqs" => "mwcmqs".to_string(),
+		m => {
+			return Err(ErrorKind::ContactError(format!(
+				"Method {} is not supported, expected http, tor or mqs",
+				m
+			))
+			.into())
+		}
+	};
+	if let Some(addr) = &contact.proof_address {
+		ProvableAddress::from_str(&proofaddress::address_to_pubkey(addr.clone())).map_err(|e| {
+			ErrorKind::ContactError(format!("Invalid proof address {}, {}", addr, e))
+		})?;
+	}
+
+	wallet_lock!(wallet_inst, w);
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_contact(contact.clone())?;
+	batch.commit()?;
+	Ok(contact)
+}
+
+/// Remove the contact from the address book
+pub fn delete_contact<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	name: &str,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let name = name.trim_start_matches('@');
+	if w.get_contact(name)?.is_none() {
+		return Err(ErrorKind::ContactError(format!("Contact @{} is not found", name)).into());
+	}
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_contact(name)?;
+	batch.commit()?;
+	Ok(())
+}
+
 /// Retrieve txs
 pub fn retrieve_txs<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -224,6 +485,7 @@ where
 	wallet_lock!(wallet_inst, w);
 	let parent_key_id = w.parent_key_id();
 	let wallet_info = updater::retrieve_info(&mut **w, &parent_key_id, minimum_confirmations)?;
+	events::publish_balance(&parent_key_id, &wallet_info);
 	Ok((validated, wallet_info))
 }
 
@@ -319,6 +581,93 @@ where
 		sender_sig: s_sig,
 	})
 }
+
+/// Build the standalone payment proof document for a confirmed transaction.
+/// The document can be verified without a wallet, see proof::payment_proof
+pub fn export_payment_proof_document<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	refresh_from_node: bool,
+	tx_id: Option<u32>,
+	tx_slate_id: Option<Uuid>,
+) -> Result<PaymentProofDocument, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let proof = retrieve_payment_proof(
+		wallet_inst.clone(),
+		keychain_mask,
+		status_send_channel,
+		refresh_from_node,
+		tx_id,
+		tx_slate_id,
+	)?;
+
+	let (client, sender_secret) = {
+		wallet_lock!(wallet_inst, w);
+		let txs = updater::retrieve_txs(
+			&mut **w,
+			keychain_mask,
+			tx_id,
+			tx_slate_id,
+			None,
+			false,
+			None,
+			None,
+		)?;
+		let sender_address_path = match txs.get(0).and_then(|tx| tx.payment_proof.clone()) {
+			Some(p) => p.sender_address_path,
+			None => {
+				return Err(ErrorKind::PaymentProofRetrieval(
+					"Transaction does not contain a payment proof".into(),
+				)
+				.into());
+			}
+		};
+		let keychain = w.keychain(keychain_mask)?;
+		(
+			w.w2n_client().clone(),
+			proofaddress::payment_proof_address_secret_from_index(&keychain, sender_address_path)?,
+		)
+	};
+
+	let (kernel, height, _) = match client.get_kernel(&proof.excess, None, None)? {
+		Some(k) => k,
+		None => {
+			return Err(ErrorKind::PaymentProofRetrieval(
+				"Transaction kernel is not found on chain, please wait for the confirmation".into(),
+			)
+			.into());
+		}
+	};
+	let header = client.get_header_info(height)?;
+
+	// Stored sender signature type depends on the recipient address. Document is signed with
+	// the sender MQS key, so anybody can verify it with the sender address.
+	let msg = tx::payment_proof_message(
+		proof.amount,
+		&proof.excess,
+		proof.sender_address.public_key.clone(),
+	)?;
+	let sender_sig = crypto::sign_challenge(&msg, &sender_secret)?.to_hex();
+
+	Ok(PaymentProofDocument {
+		proof_type: PAYMENT_PROOF_DOCUMENT_TYPE.to_string(),
+		version: PAYMENT_PROOF_DOCUMENT_VERSION,
+		amount: proof.amount,
+		kernel,
+		sender_address: proof.sender_address,
+		sender_sig,
+		recipient_address: proof.recipient_address,
+		recipient_sig: proof.recipient_sig,
+		block_height: height,
+		block_hash: header.hash,
+	})
+}
+
 ///get stored tx proof file.
 pub fn get_stored_tx_proof<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -362,6 +711,41 @@ where
 	return Ok(proof);
 }
 
+/// Estimate the fee of the transaction that init_send_tx would build with the same
+/// arguments. Nothing is locked or stored.
+/// Caller is responsible for wallet refresh
+pub fn estimate_fee<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &InitTxArgs,
+	routputs: usize, // Number of resulting outputs. Normally it is 1
+) -> Result<FeeEstimate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = match &args.src_acct_name {
+		Some(d) => match w.get_acct_path(d.clone())? {
+			Some(p) => p.path,
+			None => w.parent_key_id(),
+		},
+		None => w.parent_key_id(),
+	};
+	tx::estimate_send_tx(
+		&mut *w,
+		args.amount,
+		args.minimum_confirmations,
+		args.max_outputs as usize,
+		args.num_change_outputs as usize,
+		args.selection_strategy_is_use_all,
+		&parent_key_id,
+		&args.outputs,
+		routputs,
+		args.exclude_change_outputs.unwrap_or(false),
+		args.minimum_confirmations_change_outputs,
+	)
+}
+
 /// Initiate tx as sender
 /// Caller is responsible for wallet refresh
 pub fn init_send_tx<'a, T: ?Sized, C, K>(
@@ -397,10 +781,23 @@ where
 
 	let mut slate = tx::new_tx_slate(&mut *w, args.amount, 2, use_test_rng, args.ttl_blocks)?;
 
+	// Kernel features are built from the slate lock height, so it must be set before
+	// the inputs are added
+	if let Some(lock_height) = args.lock_height {
+		if lock_height <= slate.height {
+			return Err(ErrorKind::GenericError(format!(
+				"lock_height {} must be above the current height {}",
+				lock_height, slate.height
+			))
+			.into());
+		}
+		slate.lock_height = lock_height;
+	}
+
 	// if we just want to estimate, don't save a context, just send the results
 	// back
 	if let Some(true) = args.estimate_only {
-		let (total, fee) = tx::estimate_send_tx(
+		let estimate = tx::estimate_send_tx(
 			&mut *w,
 			args.amount,
 			args.minimum_confirmations,
@@ -413,11 +810,22 @@ where
 			args.exclude_change_outputs.unwrap_or(false),
 			args.minimum_confirmations_change_outputs,
 		)?;
-		slate.amount = total;
-		slate.fee = fee;
+		slate.amount = estimate.total;
+		slate.fee = estimate.fee;
 		return Ok(slate);
 	}
 
+	let mut destinations = vec![];
+	if let Some(a) = &args.address {
+		if a != "file_proof" {
+			destinations.push(a.clone());
+		}
+	}
+	if let Some(a) = &args.payment_proof_recipient_address {
+		destinations.push(a.public_key.clone());
+	}
+	spend_policy::check_spend_policy(&*w, &parent_key_id, args.amount, &destinations)?;
+
 	let mut context = tx::add_inputs_to_slate(
 		&mut *w,
 		keychain_mask,
@@ -437,6 +845,34 @@ where
 		args.minimum_confirmations_change_outputs,
 	)?;
 
+	add_send_proof_and_save_context(
+		&mut *w,
+		keychain_mask,
+		&mut slate,
+		&mut context,
+		args.address,
+		args.payment_proof_recipient_address,
+		args.target_slate_version,
+	)?;
+	Ok(slate)
+}
+
+// Add the payment proof addresses to the slate of the sender and save the context,
+// the slate is ready to be sent
+fn add_send_proof_and_save_context<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &mut Slate,
+	context: &mut Context,
+	address: Option<String>,
+	payment_proof_recipient_address: Option<ProvableAddress>,
+	target_slate_version: Option<u16>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
 	// Payment Proof, add addresses to slate and save address
 	// TODO: Note we only use single derivation path for now,
 	// probably want to allow sender to specify which one
@@ -444,7 +880,7 @@ where
 	let k = w.keychain(keychain_mask)?;
 	let sender_a = proofaddress::payment_proof_address(&k, proofaddress::ProofAddressType::MQS)?;
 
-	if let Some(a) = args.address {
+	if let Some(a) = address {
 		if a.eq("file_proof") {
 			debug!("doing file proof");
 			//in file proof, we are putting the same address both both sender_address and receiver_address
@@ -458,7 +894,7 @@ where
 		}
 	}
 
-	if let Some(a) = args.payment_proof_recipient_address {
+	if let Some(a) = payment_proof_recipient_address {
 		slate.payment_proof = Some(PaymentInfo {
 			sender_address: sender_a,
 			receiver_address: a,
@@ -486,7 +922,7 @@ where
 		batch.save_private_context(slate.id.as_bytes(), 0, &context)?;
 		batch.commit()?;
 	}
-	if let Some(v) = args.target_slate_version {
+	if let Some(v) = target_slate_version {
 		slate.version_info.orig_version = v;
 	}
 
@@ -497,71 +933,779 @@ where
 		slate.network_type = Some("mainnet".to_string());
 	}
 
-	Ok(slate)
+	Ok(())
+}
+
+// Account of the batch payout, the active account by default
+fn batch_parent_key_id<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &BatchSendArgs,
+) -> Result<Identifier, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	Ok(match &args.src_acct_name {
+		Some(d) => match w.get_acct_path(d.clone())? {
+			Some(p) => p.path,
+			None => w.parent_key_id(),
+		},
+		None => w.parent_key_id(),
+	})
+}
+
+/// Select the inputs of a batch payout. The coins cover the amounts and the fees of all
+/// recipients. The first recipient spends them, every next one spends the change of the
+/// previous recipient, so the inputs are selected and locked once and the aggregated
+/// transaction keeps a single change output after the cut-through.
+/// Caller is responsible for wallet refresh
+pub fn select_batch_inputs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &BatchSendArgs,
+) -> Result<Vec<OutputData>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if args.entries.is_empty() {
+		return Err(
+			ErrorKind::GenericError("Batch doesn't have any recipients".to_string()).into(),
+		);
+	}
+	let parent_key_id = batch_parent_key_id(w, args)?;
+	let fee_policy = w.settings().fee_policy.clone();
+
+	// Fee of the first recipient is added by the selection, the others spend a single change
+	let next_fees =
+		fee::tx_fee(fee_policy.fee_per_kweight, 1, 2, 1) * (args.entries.len() as u64 - 1);
+	let amount = args.entries.iter().map(|e| e.amount).sum::<u64>() + next_fees;
+	let current_height = w.w2n_client().get_chain_tip()?.0;
+
+	let (coins, _, _, _) = selection::select_coins_and_fee(
+		w,
+		amount,
+		current_height,
+		args.minimum_confirmations,
+		args.max_outputs as usize,
+		1,
+		args.selection_strategy_is_use_all,
+		&parent_key_id,
+		&None,
+		1,
+		false,
+		1,
+		fee_policy.fee_per_kweight,
+	)?;
+	Ok(coins)
+}
+
+/// Build and lock the slate of a single batch payout recipient. The slate spends `inputs`,
+/// the selected batch inputs or the change of the previous recipient.
+/// Returns the slate and its change output that funds the next recipient.
+/// Caller is responsible for wallet refresh
+pub fn init_batch_send_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: &BatchSendArgs,
+	entry: &BatchSendEntry,
+	inputs: &[OutputData],
+	use_test_rng: bool,
+) -> Result<(Slate, Option<OutputData>), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = batch_parent_key_id(w, args)?;
+
+	let mut destinations = vec![entry.dest.clone()];
+	if let Some(a) = &entry.payment_proof_recipient_address {
+		destinations.push(a.public_key.clone());
+	}
+	spend_policy::check_spend_policy(&*w, &parent_key_id, entry.amount, &destinations)?;
+
+	let message = match entry.message.clone() {
+		Some(mut m) => {
+			m.truncate(USER_MESSAGE_MAX_LEN);
+			Some(m)
+		}
+		None => None,
+	};
+
+	let mut slate = tx::new_tx_slate(&mut *w, entry.amount, 2, use_test_rng, args.ttl_blocks)?;
+	let keychain = w.keychain(keychain_mask)?;
+	let mut context = selection::build_send_tx_from_coins(
+		&mut *w,
+		&keychain,
+		keychain_mask,
+		&mut slate,
+		inputs,
+		parent_key_id,
+		use_test_rng,
+	)?;
+	slate.fill_round_1(
+		&keychain,
+		&mut context.sec_key,
+		&context.sec_nonce,
+		0,
+		message,
+		use_test_rng,
+	)?;
+	add_send_proof_and_save_context(
+		&mut *w,
+		keychain_mask,
+		&mut slate,
+		&mut context,
+		Some(entry.dest.clone()),
+		entry.payment_proof_recipient_address.clone(),
+		args.target_slate_version,
+	)?;
+	selection::lock_tx_context(
+		&mut *w,
+		keychain_mask,
+		&slate,
+		&context,
+		Some(entry.dest.clone()),
+	)?;
+
+	let change = match context.get_outputs().first() {
+		Some((id, mmr_index, _)) => Some(w.get(id, mmr_index)?),
+		None => None,
+	};
+	Ok((slate, change))
+}
+
+/// Roll back the slate of a batch payout recipient that failed. The slate is cancelled
+/// and its `inputs` get back the state they had before the slate locked them, the change
+/// of the previous recipient stays unconfirmed.
+pub fn cancel_batch_send_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: &BatchSendArgs,
+	slate_id: &Uuid,
+	inputs: &[OutputData],
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = batch_parent_key_id(w, args)?;
+	tx::cancel_tx(
+		&mut *w,
+		keychain_mask,
+		&parent_key_id,
+		None,
+		Some(*slate_id),
+	)?;
+	let mut batch = w.batch(keychain_mask)?;
+	for input in inputs {
+		batch.save(input.clone())?;
+	}
+	batch.commit()?;
+	Ok(())
+}
+
+/// Store the aggregated transaction of the batch payout for the slates of the batch, so
+/// the repost and the rebroadcast post the whole batch.
+pub fn store_batch_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	slate_ids: &[Uuid],
+	tx: &Transaction,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	for id in slate_ids {
+		w.store_tx(&format!("{}", id), tx)?;
+	}
+	Ok(())
+}
+
+// Paths of the transfer accounts, the active account is the source by default
+fn transfer_accounts<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &TransferArgs,
+) -> Result<(Identifier, Identifier), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let src_key_id = match &args.src_acct_name {
+		Some(name) => match w.get_acct_path(name.clone())? {
+			Some(m) => m.path,
+			None => return Err(ErrorKind::UnknownAccountLabel(name.clone()).into()),
+		},
+		None => w.parent_key_id(),
+	};
+	let dest_key_id = match w.get_acct_path(args.dest_acct_name.clone())? {
+		Some(m) => m.path,
+		None => return Err(ErrorKind::UnknownAccountLabel(args.dest_acct_name.clone()).into()),
+	};
+	if src_key_id == dest_key_id {
+		return Err(ErrorKind::GenericError(format!(
+			"Transfer source and destination are the same account '{}'",
+			args.dest_acct_name
+		))
+		.into());
+	}
+	Ok((src_key_id, dest_key_id))
+}
+
+// Tx log entries of the transfer in the source and destination accounts
+fn transfer_entries<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &TransferArgs,
+	slate_id: &Uuid,
+) -> Result<(TxLogEntry, TxLogEntry), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (src_key_id, dest_key_id) = transfer_accounts(&mut *w, args)?;
+	let find = |w: &mut T, parent_key_id: &Identifier| {
+		w.tx_log_iter()
+			.find(|t| t.tx_slate_id == Some(slate_id.clone()) && &t.parent_key_id == parent_key_id)
+			.ok_or(ErrorKind::TransactionDoesntExist(slate_id.to_string()))
+	};
+	let src_tx = find(&mut *w, &src_key_id)?;
+	let dest_tx = find(&mut *w, &dest_key_id)?;
+	Ok((src_tx, dest_tx))
+}
+
+/// Build the transfer between two accounts of the wallet. The slate is sent to self,
+/// received by the destination account and the tx log entries of both accounts are
+/// linked to each other. The returned slate is ready to be finalized and posted.
+/// Caller is responsible for wallet refresh
+pub fn init_transfer_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: &TransferArgs,
+	use_test_rng: bool,
+) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let _ = transfer_accounts(&mut *w, args)?;
+	let slate = init_send_tx(&mut *w, keychain_mask, args.init_tx_args(), use_test_rng, 1)?;
+	let res = tx_lock_outputs(&mut *w, keychain_mask, &slate, Some("self".to_string()), 0)
+		.and_then(|_| {
+			foreign::receive_tx(
+				&mut *w,
+				keychain_mask,
+				&slate,
+				Some("self".to_string()),
+				None,
+				None,
+				Some(&args.dest_acct_name),
+				args.message.clone(),
+				use_test_rng,
+				false,
+			)
+		})
+		.and_then(|slate| {
+			let (mut src_tx, mut dest_tx) = transfer_entries(&mut *w, args, &slate.id)?;
+			src_tx.linked_tx = Some(LinkedTx {
+				parent_key_id: dest_tx.parent_key_id.clone(),
+				id: dest_tx.id,
+			});
+			dest_tx.linked_tx = Some(LinkedTx {
+				parent_key_id: src_tx.parent_key_id.clone(),
+				id: src_tx.id,
+			});
+			let mut batch = w.batch(keychain_mask)?;
+			let parent_key_id = src_tx.parent_key_id.clone();
+			batch.save_tx_log_entry(src_tx, &parent_key_id)?;
+			let parent_key_id = dest_tx.parent_key_id.clone();
+			batch.save_tx_log_entry(dest_tx, &parent_key_id)?;
+			batch.commit()?;
+			Ok(slate)
+		});
+	if res.is_err() {
+		let _ = cancel_transfer_tx(&mut *w, keychain_mask, args, &slate.id);
+	}
+	res
+}
+
+/// Roll back the transfer that wasn't posted. Both accounts cancel their side, the
+/// locked inputs of the source account are released.
+pub fn cancel_transfer_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: &TransferArgs,
+	slate_id: &Uuid,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (src_key_id, dest_key_id) = transfer_accounts(&mut *w, args)?;
+	// Receiving side might not exist if the transfer failed before the receive
+	let _ = tx::cancel_tx(
+		&mut *w,
+		keychain_mask,
+		&dest_key_id,
+		None,
+		Some(slate_id.clone()),
+	);
+	tx::cancel_tx(
+		&mut *w,
+		keychain_mask,
+		&src_key_id,
+		None,
+		Some(slate_id.clone()),
+	)
+}
+
+/// Linked tx log entries of the posted transfer
+pub fn transfer_txs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: &TransferArgs,
+	slate_id: &Uuid,
+) -> Result<TransferResult, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (src_tx, dest_tx) = transfer_entries(&mut *w, args, slate_id)?;
+	Ok(TransferResult {
+		slate_id: slate_id.clone(),
+		src_tx,
+		dest_tx,
+	})
+}
+
+/// Initiate a transaction as the recipient (invoicing)
+pub fn issue_invoice_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: IssueInvoiceTxArgs,
+	use_test_rng: bool,
+	num_outputs: usize, // Number of outputs for this transaction. Normally it is 1
+) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = match args.dest_acct_name {
+		Some(d) => {
+			let pm = w.get_acct_path(d)?;
+			match pm {
+				Some(p) => p.path,
+				None => w.parent_key_id(),
+			}
+		}
+		None => w.parent_key_id(),
+	};
+
+	let message = match args.message {
+		Some(mut m) => {
+			m.truncate(USER_MESSAGE_MAX_LEN);
+			Some(m)
+		}
+		None => None,
+	};
+
+	let mut slate = tx::new_tx_slate(&mut *w, args.amount, 2, use_test_rng, args.expiry_blocks)?;
+	let context = tx::add_output_to_slate(
+		&mut *w,
+		keychain_mask,
+		&mut slate,
+		args.address.clone(),
+		None,
+		None,
+		&parent_key_id,
+		0, // Participant 0 for mwc713 compatibility
+		message.clone(),
+		true,
+		use_test_rng,
+		num_outputs,
+	)?;
+
+	let invoice = Invoice {
+		id: slate.id,
+		parent_key_id: parent_key_id.clone(),
+		amount: args.amount,
+		memo: message,
+		reference: args.reference,
+		creation_ts: Utc::now(),
+		expiry_height: slate.ttl_cutoff_height,
+		status: InvoiceStatus::Open,
+		paid_ts: None,
+	};
+
+	// Save the aggsig context in our DB for when we
+	// recieve the transaction back
+	{
+		let mut batch = w.batch(keychain_mask)?;
+		// Participant id is 0 for mwc713 compatibility
+		batch.save_private_context(slate.id.as_bytes(), 0, &context)?;
+		batch.save_invoice(invoice)?;
+		batch.commit()?;
+	}
+
+	if let Some(v) = args.target_slate_version {
+		slate.version_info.orig_version = v;
+	}
+
+	Ok(slate)
+}
+
+/// Retrieve invoices issued by the wallet, optionally filtered by the invoice slate id
+/// or by the external reference. Open invoices that passed the expiry height are
+/// marked as expired.
+pub fn retrieve_invoices<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	refresh_from_node: bool,
+	invoice_id: Option<Uuid>,
+	reference: Option<String>,
+) -> Result<(bool, Vec<Invoice>), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut validated = false;
+	if refresh_from_node {
+		validated =
+			perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)?;
+	}
+
+	wallet_lock!(wallet_inst, w);
+	let height = w.last_confirmed_height()?;
+	let mut invoices: Vec<Invoice> = w
+		.invoice_iter()
+		.filter(|i| invoice_id.map(|id| i.id == id).unwrap_or(true))
+		.filter(|i| reference.is_none() || i.reference == reference)
+		.collect();
+
+	let mut expired = vec![];
+	for i in invoices.iter_mut() {
+		if i.status == InvoiceStatus::Open && i.is_expired_at(height) {
+			i.status = InvoiceStatus::Expired;
+			expired.push(i.clone());
+		}
+	}
+	if !expired.is_empty() {
+		let mut batch = w.batch(keychain_mask)?;
+		for i in expired {
+			batch.save_invoice(i)?;
+		}
+		batch.commit()?;
+	}
+
+	invoices.sort_by_key(|i| i.creation_ts);
+	Ok((validated, invoices))
+}
+
+/// Cancel an open invoice. The invoice transaction is cancelled as well, so the
+/// invoice can't be finalized any more.
+pub fn cancel_invoice<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	invoice_id: Uuid,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut invoice = {
+		wallet_lock!(wallet_inst, w);
+		let invoice = w
+			.get_invoice(&invoice_id)?
+			.ok_or(ErrorKind::InvoiceError(format!(
+				"Invoice {} not found",
+				invoice_id
+			)))?;
+		if invoice.status != InvoiceStatus::Open {
+			return Err(ErrorKind::InvoiceError(format!(
+				"Invoice {} is {}, only open invoices can be cancelled",
+				invoice_id, invoice.status
+			))
+			.into());
+		}
+		invoice
+	};
+
+	cancel_tx(
+		wallet_inst.clone(),
+		keychain_mask,
+		status_send_channel,
+		None,
+		Some(invoice_id),
+	)?;
+
+	wallet_lock!(wallet_inst, w);
+	invoice.status = InvoiceStatus::Cancelled;
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_invoice(invoice)?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Retrieve the slates parked in the approval queue, optionally filtered by the slate id.
+/// Pending slates that passed the approval timeout are marked as expired.
+pub fn retrieve_pending_slates<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	slate_id: Option<Uuid>,
+) -> Result<Vec<PendingSlate>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let now = Utc::now();
+	let mut pending: Vec<PendingSlate> = w
+		.pending_slate_iter()
+		.filter(|p| slate_id.map(|id| p.id == id).unwrap_or(true))
+		.collect();
+
+	let mut expired = vec![];
+	for p in pending.iter_mut() {
+		if p.status == PendingSlateStatus::Pending && p.is_expired_at(now) {
+			p.status = PendingSlateStatus::Expired;
+			expired.push(p.clone());
+		}
+	}
+	if !expired.is_empty() {
+		let mut batch = w.batch(keychain_mask)?;
+		for p in expired {
+			batch.save_pending_slate(p)?;
+		}
+		batch.commit()?;
+	}
+
+	pending.sort_by_key(|p| p.creation_ts);
+	Ok(pending)
+}
+
+// Read the parked slate that is still waiting for the owner decision
+fn get_pending_for_decision<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate_id: Uuid,
+) -> Result<PendingSlate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut pending = w
+		.get_pending_slate(&slate_id)?
+		.ok_or(ErrorKind::ApprovalQueueError(format!(
+			"Slate {} is not found in the approval queue",
+			slate_id
+		)))?;
+	if pending.status == PendingSlateStatus::Pending && pending.is_expired_at(Utc::now()) {
+		pending.status = PendingSlateStatus::Expired;
+		let mut batch = w.batch(keychain_mask)?;
+		batch.save_pending_slate(pending.clone())?;
+		batch.commit()?;
+	}
+	if pending.status != PendingSlateStatus::Pending {
+		return Err(ErrorKind::ApprovalQueueError(format!(
+			"Slate {} is {}, only pending slates can be approved or rejected",
+			slate_id, pending.status
+		))
+		.into());
+	}
+	Ok(pending)
+}
+
+/// Approve the parked slate. The slate is processed the same way the foreign API would do it,
+/// the response is stored for the sender.
+pub fn approve_pending_slate<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	slate_id: Uuid,
+) -> Result<Slate, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let mut pending = get_pending_for_decision(&mut **w, keychain_mask, slate_id)?;
+	let slate = Slate::from(pending.slate.clone());
+
+	let response = match pending.request {
+		PendingSlateRequest::ReceiveTx => foreign::receive_tx(
+			&mut **w,
+			keychain_mask,
+			&slate,
+			pending.address.clone(),
+			None,
+			None,
+			pending.dest_acct_name.as_ref().map(|s| s.as_str()),
+			pending.message.clone(),
+			false,
+			false,
+		)?,
+		PendingSlateRequest::FinalizeInvoiceTx => {
+			foreign::finalize_invoice_tx(&mut **w, keychain_mask, &slate, false)?
+		}
+	};
+
+	pending.status = PendingSlateStatus::Approved;
+	pending.response = Some(SlateV3::from(&response));
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_pending_slate(pending)?;
+	batch.commit()?;
+	Ok(response)
+}
+
+/// Reject the parked slate, the sender gets the error on the next retry.
+pub fn reject_pending_slate<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	slate_id: Uuid,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let mut pending = get_pending_for_decision(&mut **w, keychain_mask, slate_id)?;
+	pending.status = PendingSlateStatus::Rejected;
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_pending_slate(pending)?;
+	batch.commit()?;
+	Ok(())
+}
+
+// Account name and path for the spending policy calls, active account by default
+fn spend_policy_account<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	acct_name: &Option<String>,
+) -> Result<(String, Identifier), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	match acct_name {
+		Some(name) => match w.get_acct_path(name.clone())? {
+			Some(m) => Ok((m.label, m.path)),
+			None => Err(ErrorKind::UnknownAccountLabel(name.clone()).into()),
+		},
+		None => {
+			let parent_key_id = w.parent_key_id();
+			let label = w
+				.acct_path_iter()
+				.find(|m| m.path == parent_key_id)
+				.map(|m| m.label)
+				.unwrap_or(parent_key_id.to_hex());
+			Ok((label, parent_key_id))
+		}
+	}
+}
+
+/// Set the spending policy of an account. Destinations that are already allowed keep
+/// their time, so updating the policy doesn't restart their cool-down.
+pub fn set_spend_policy<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	args: &SpendPolicyArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let (_, parent_key_id) = spend_policy_account(&mut **w, &args.src_acct_name)?;
+	let now = Utc::now();
+	let current = w
+		.get_spend_policy(&parent_key_id)?
+		.and_then(|p| p.allowed_destinations)
+		.unwrap_or(vec![]);
+	let allowed_destinations = args.allowed_destinations.as_ref().map(|list| {
+		list.iter()
+			.map(|address| SpendPolicyDestination {
+				address: address.clone(),
+				added_ts: current
+					.iter()
+					.find(|d| d.address == *address)
+					.map(|d| d.added_ts)
+					.unwrap_or(now),
+			})
+			.collect()
+	});
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_spend_policy(SpendPolicy {
+		parent_key_id,
+		max_per_tx: args.max_per_tx,
+		max_24h: args.max_24h,
+		max_7d: args.max_7d,
+		allowed_destinations,
+		new_destination_cooldown_secs: args.new_destination_cooldown_secs,
+		update_ts: now,
+	})?;
+	batch.commit()?;
+	Ok(())
 }
 
-/// Initiate a transaction as the recipient (invoicing)
-pub fn issue_invoice_tx<'a, T: ?Sized, C, K>(
-	w: &mut T,
+/// Remove the spending policy of an account
+pub fn clear_spend_policy<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
-	args: IssueInvoiceTxArgs,
-	use_test_rng: bool,
-	num_outputs: usize, // Number of outputs for this transaction. Normally it is 1
-) -> Result<Slate, Error>
+	acct_name: &Option<String>,
+) -> Result<(), Error>
 where
-	T: WalletBackend<'a, C, K>,
+	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	let parent_key_id = match args.dest_acct_name {
-		Some(d) => {
-			let pm = w.get_acct_path(d)?;
-			match pm {
-				Some(p) => p.path,
-				None => w.parent_key_id(),
-			}
-		}
-		None => w.parent_key_id(),
-	};
+	wallet_lock!(wallet_inst, w);
+	let (_, parent_key_id) = spend_policy_account(&mut **w, acct_name)?;
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_spend_policy(&parent_key_id)?;
+	batch.commit()?;
+	Ok(())
+}
 
-	let message = match args.message {
-		Some(mut m) => {
-			m.truncate(USER_MESSAGE_MAX_LEN);
-			Some(m)
-		}
-		None => None,
+/// Spending policy of an account with the amounts sent during the policy windows
+pub fn retrieve_spend_policy<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	acct_name: &Option<String>,
+) -> Result<Option<SpendPolicyStatus>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let (acct_name, parent_key_id) = spend_policy_account(&mut **w, acct_name)?;
+	let policy = match w.get_spend_policy(&parent_key_id)? {
+		Some(p) => p,
+		None => return Ok(None),
 	};
-
-	let mut slate = tx::new_tx_slate(&mut *w, args.amount, 2, use_test_rng, None)?;
-	let context = tx::add_output_to_slate(
-		&mut *w,
-		keychain_mask,
-		&mut slate,
-		args.address.clone(),
-		None,
-		None,
-		&parent_key_id,
-		0, // Participant 0 for mwc713 compatibility
-		message,
-		true,
-		use_test_rng,
-		num_outputs,
-	)?;
-
-	// Save the aggsig context in our DB for when we
-	// recieve the transaction back
-	{
-		let mut batch = w.batch(keychain_mask)?;
-		// Participant id is 0 for mwc713 compatibility
-		batch.save_private_context(slate.id.as_bytes(), 0, &context)?;
-		batch.commit()?;
-	}
-
-	if let Some(v) = args.target_slate_version {
-		slate.version_info.orig_version = v;
-	}
-
-	Ok(slate)
+	let now = Utc::now();
+	Ok(Some(SpendPolicyStatus {
+		acct_name,
+		policy,
+		spent_24h: spend_policy::sent_since(&**w, &parent_key_id, now - Duration::hours(24)),
+		spent_7d: spend_policy::sent_since(&**w, &parent_key_id, now - Duration::days(7)),
+	}))
 }
 
 /// Receive an invoice tx, essentially adding inputs to whatever
@@ -582,6 +1726,13 @@ where
 {
 	let mut ret_slate = slate.clone();
 	check_ttl(w, &ret_slate, refresh_from_node)?;
+	if args.lock_height.is_some() {
+		return Err(ErrorKind::GenericError(
+			"lock_height is not supported for the invoice payments".to_string(),
+		)
+		.into());
+	}
+	check_lock_height(&ret_slate)?;
 	let parent_key_id = match args.src_acct_name {
 		Some(d) => {
 			let pm = w.get_acct_path(d)?;
@@ -609,6 +1760,16 @@ where
 		}
 	}
 
+	// Invoice issuer is the recipient of the payment
+	let mut destinations = vec![];
+	if let Some(a) = &args.address {
+		destinations.push(a.clone());
+	}
+	if let Some(p) = &ret_slate.payment_proof {
+		destinations.push(p.receiver_address.public_key.clone());
+	}
+	spend_policy::check_spend_policy(&*w, &parent_key_id, ret_slate.amount, &destinations)?;
+
 	let message = match args.message {
 		Some(mut m) => {
 			m.truncate(USER_MESSAGE_MAX_LEN);
@@ -786,6 +1947,234 @@ where
 	}
 }
 
+/// Track the posted transaction, it is posted again if it doesn't get to the chain
+pub fn track_posted_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	tx: &Transaction,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	rebroadcast::track_posted_tx(w, keychain_mask, tx)
+}
+
+/// Repost the tracked transactions that are missing from the chain, or cancel them when
+/// the retries are exhausted. Does nothing if the rebroadcast is disabled.
+pub fn rebroadcast_txs<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let now = Utc::now();
+	let (client, config, due) = {
+		wallet_lock!(wallet_inst, w);
+		let config = match rebroadcast::rebroadcast_config(w.settings()) {
+			Some(c) => c,
+			None => return Ok(()),
+		};
+		let _ = w.keychain(keychain_mask)?;
+		let due = rebroadcast::due_txs(&**w, &config, now);
+		(w.w2n_client().clone(), config, due)
+	};
+
+	for (tx, due_tx) in due {
+		let action = match rebroadcast::repost_tx(&client, &config, &tx, &due_tx) {
+			Ok(Some(action)) => action,
+			Ok(None) => continue,
+			Err(e) => {
+				warn!(
+					"Unable to check the kernel of the transaction {}, {}",
+					tx.id, e
+				);
+				break;
+			}
+		};
+		{
+			wallet_lock!(wallet_inst, w);
+			rebroadcast::record_rebroadcast(&mut **w, keychain_mask, &tx, &action, now)?;
+		}
+		let msg = match action {
+			RebroadcastAction::Reposted(retry) => StatusMessage::Info(format!(
+				"Transaction {} is not found on the chain, it is posted again, retry {}",
+				tx.id, retry
+			)),
+			RebroadcastAction::Cancelled => StatusMessage::Warning(format!(
+				"Transaction {} didn't get to the chain after the rebroadcasts and is cancelled",
+				tx.id
+			)),
+			RebroadcastAction::GaveUp => StatusMessage::Warning(format!(
+				"Transaction {} didn't get to the chain after the rebroadcasts, it is not posted any more",
+				tx.id
+			)),
+		};
+		if let Some(ref s) = status_send_channel {
+			let _ = s.send(msg);
+		}
+	}
+	Ok(())
+}
+
+/// Unconfirmed transactions whose TTL is expired at the current node height, with the
+/// inputs and outputs that the wallet update changes when it cancels them.
+pub fn retrieve_expired_txs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<Vec<ExpiredTx>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let tip_height = w.w2n_client().get_chain_tip()?.0;
+	expiry::expired_txs(w, keychain_mask, tip_height)
+}
+
+/// Unspent outputs of the active account for the online wallet to track. Called on the
+/// offline cold wallet.
+pub fn export_cold_outputs<'a, T: ?Sized, C, K>(w: &mut T) -> Result<Vec<OutputData>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = w.parent_key_id();
+	Ok(cold::export_outputs(&*w, &parent_key_id))
+}
+
+/// Track the outputs of the cold wallet in the active account. The account must not
+/// hold the outputs of this wallet.
+pub fn import_cold_outputs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	outputs: Vec<OutputData>,
+) -> Result<usize, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = w.parent_key_id();
+	cold::import_outputs(w, keychain_mask, &parent_key_id, outputs)
+}
+
+/// Initiate tx as sender with the cold wallet outputs. Returns the unsigned request
+/// for the offline wallet.
+/// Caller is responsible for wallet refresh
+pub fn init_cold_send_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	args: InitTxArgs,
+	use_test_rng: bool,
+) -> Result<ColdSignRequest, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = match &args.src_acct_name {
+		Some(d) => match w.get_acct_path(d.clone())? {
+			Some(p) => p.path,
+			None => w.parent_key_id(),
+		},
+		None => w.parent_key_id(),
+	};
+	if args.payment_proof_recipient_address.is_some() {
+		return Err(ErrorKind::ColdWallet(
+			"payment proofs are not supported for the cold wallet transactions".to_string(),
+		)
+		.into());
+	}
+	let message = args.message.clone().map(|mut m| {
+		m.truncate(USER_MESSAGE_MAX_LEN);
+		m
+	});
+
+	let mut slate = tx::new_tx_slate(&mut *w, args.amount, 2, use_test_rng, args.ttl_blocks)?;
+	if let Some(lock_height) = args.lock_height {
+		if lock_height <= slate.height {
+			return Err(ErrorKind::GenericError(format!(
+				"lock_height {} must be above the current height {}",
+				lock_height, slate.height
+			))
+			.into());
+		}
+		slate.lock_height = lock_height;
+	}
+
+	let destinations: Vec<String> = args
+		.address
+		.iter()
+		.filter(|a| *a != "file_proof")
+		.cloned()
+		.collect();
+	spend_policy::check_spend_policy(&*w, &parent_key_id, args.amount, &destinations)?;
+
+	cold::send_request(&mut *w, &mut slate, &args, &parent_key_id, message)
+}
+
+/// Request for the offline wallet to receive the slate or to sign the slate that the
+/// other party returned
+pub fn cold_sign_request(
+	step: ColdSignStep,
+	slate: &Slate,
+	message: Option<String>,
+) -> Result<ColdSignRequest, Error> {
+	if step == ColdSignStep::Send {
+		return Err(ErrorKind::ColdWallet(
+			"send request is built by the transaction initiation".to_string(),
+		)
+		.into());
+	}
+	Ok(ColdSignRequest {
+		step,
+		slate: SlateV3::from(slate),
+		inputs: vec![],
+		num_change_outputs: 0,
+		message: message.map(|mut m| {
+			m.truncate(USER_MESSAGE_MAX_LEN);
+			m
+		}),
+	})
+}
+
+/// Sign the request with the keychain. Called on the offline cold wallet.
+pub fn cold_sign<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	request: &ColdSignRequest,
+	use_test_rng: bool,
+) -> Result<ColdSignResponse, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	cold::sign(w, keychain_mask, request, use_test_rng)
+}
+
+/// Record the response of the offline wallet in the active account. Returns the slate
+/// for the other party, or the finalized slate after the sign step.
+pub fn process_cold_sign_response<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	response: &ColdSignResponse,
+) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let parent_key_id = w.parent_key_id();
+	cold::process_response(w, keychain_mask, &parent_key_id, response)
+}
+
 /// verify slate messages
 pub fn verify_slate_messages(slate: &Slate) -> Result<(), Error> {
 	slate.verify_messages()
@@ -874,6 +2263,60 @@ where
 	Ok(())
 }
 
+/// Status of the running scan, or of the interrupted one if there is nothing running
+pub fn scan_status<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+) -> Result<ScanStatus, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (running, checkpoint) = match scan::running_scan(&wallet_inst) {
+		Some(cp) => (true, Some(cp)),
+		None => {
+			wallet_lock!(wallet_inst, w);
+			(false, w.get_scan_checkpoint()?)
+		}
+	};
+	Ok(ScanStatus {
+		running,
+		percent_complete: checkpoint.as_ref().map(|cp| cp.progress()).unwrap_or(0),
+		checkpoint,
+	})
+}
+
+/// Pause the running scan of the wallet instance. The next scan of the same range resumes
+/// from the saved progress. Returns false if no scan is running.
+pub fn pause_scan<'a, L, C, K>(wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>) -> bool
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	scan::pause_scan(&wallet_inst)
+}
+
+/// Cancel the running scan of the wallet instance, or drop the progress of the interrupted one
+pub fn cancel_scan<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if scan::cancel_scan(&wallet_inst) {
+		return Ok(());
+	}
+	wallet_lock!(wallet_inst, w);
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_scan_checkpoint()?;
+	batch.commit()?;
+	Ok(())
+}
+
 /// node height
 pub fn node_height<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -981,6 +2424,15 @@ where
 		}
 	}
 
+	write_info(
+		String::from("Address Book:"),
+		file.as_mut(),
+		status_send_channel,
+	);
+	for contact in w.contact_iter() {
+		write_info(format!("{:?}", contact), file.as_mut(), status_send_channel);
+	}
+
 	if let Some(f) = fn_copy {
 		let _ = status_send_channel.send(StatusMessage::Info(format!(
 			"Wallet dump is stored at  {}",
@@ -1038,20 +2490,39 @@ where
 		return Ok((0, String::new(), ScannedBlockInfo::empty(), false));
 	}
 
-	let mut last_scanned_block = ScannedBlockInfo::empty();
 	let head_height = blocks.first().map(|b| b.height).unwrap_or(0);
-	for bl in blocks {
-		// check if that block is not changed
-		if let Ok(hdr_info) = w.w2n_client().get_header_info(bl.height) {
-			if hdr_info.hash == bl.hash {
-				last_scanned_block = bl;
-				break;
+	let last_scanned_block = match reorg::find_fork_point(w.w2n_client(), &blocks) {
+		Ok(b) => b,
+		Err(e) => {
+			if let Some(ref s) = status_send_channel {
+				let _ = s.send(StatusMessage::Warning(format!(
+					"Unable to get block headers from mwc-node, {}",
+					e
+				)));
 			}
+			return Ok((0, String::new(), ScannedBlockInfo::empty(), false));
 		}
-	}
+	};
 
 	let has_reorg = last_scanned_block.height != head_height;
 
+	// Confirmations from the abandoned branch are reverted, the scan from the fork point
+	// confirms again what made it to the new branch
+	if has_reorg {
+		let stats =
+			reorg::rollback_confirmations(&mut **w, keychain_mask, last_scanned_block.height)?;
+		warn!(
+			"Chain reorg is detected, fork point is at height {}, abandoned head at height {}. Reverted {} outputs and {} transactions",
+			last_scanned_block.height, head_height, stats.outputs, stats.transactions
+		);
+		if let Some(ref s) = status_send_channel {
+			let _ = s.send(StatusMessage::Warning(format!(
+				"Chain reorganisation is detected, blocks after height {} were replaced. Confirmations of {} outputs and {} transactions are reverted, the wallet will be rescanned from height {}",
+				last_scanned_block.height, stats.outputs, stats.transactions, last_scanned_block.height
+			)));
+		}
+	}
+
 	Ok((tip_height, tip_hash, last_scanned_block, has_reorg))
 }
 
@@ -1140,6 +2611,13 @@ where
 		wallet_lock!(wallet_inst, w);
 
 		if let Ok((after_tip_height, after_tip_hash, _)) = w.w2n_client().get_chain_tip() {
+			// Block hashes are used for the reorg detection, they must be on the verified chain
+			if w.settings().header_verification {
+				for b in &blocks {
+					header_sync::check_header_hash(&**w, b.height, &b.hash)?;
+				}
+			}
+
 			// Since we are still online, we can save the scan status
 			{
 				let mut batch = w.batch(keychain_mask)?;
@@ -1190,6 +2668,35 @@ where
 	Ok(())
 }
 
+/// Check that the kernel of the slate transaction matches the slate lock height.
+/// Otherwise the signatures are done for the wrong kernel features.
+pub fn check_lock_height(slate: &Slate) -> Result<(), Error> {
+	if slate.lock_height == 0 {
+		return Ok(());
+	}
+	if slate.lock_height <= slate.height {
+		return Err(ErrorKind::SlateValidation(format!(
+			"lock_height {} is not above the slate height {}",
+			slate.lock_height, slate.height
+		))
+		.into());
+	}
+	for k in slate.tx.kernels() {
+		match k.features {
+			KernelFeatures::HeightLocked { lock_height, .. }
+				if lock_height == slate.lock_height => {}
+			_ => {
+				return Err(ErrorKind::SlateValidation(format!(
+					"kernel features {:?} don't match the slate lock_height {}",
+					k.features, slate.lock_height
+				))
+				.into())
+			}
+		}
+	}
+	Ok(())
+}
+
 /// Verify/validate arbitrary payment proof
 /// Returns (whether this wallet is the sender, whether this wallet is the recipient)
 pub fn verify_payment_proof<'a, L, C, K>(
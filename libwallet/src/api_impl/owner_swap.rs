@@ -150,6 +150,7 @@ where
 		1,              // Number of resulting outputs. Normally it is 1
 		false,
 		0,
+		None, // Both parties of the swap expect the default fee
 	)?;
 
 	let context = create_context(
@@ -1061,6 +1062,10 @@ where
 		lock_height: slate.lock_height,
 		is_coinbase: false,
 		tx_log_entry: Some(log_id),
+		frozen: false,
+		note: None,
+		tags: vec![],
+		cold: false,
 	})?;
 	batch.commit()?;
 	Ok(())
@@ -15,14 +15,15 @@
 //! A threaded persistent Updater that can be controlled by a grin wallet
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::grin_keychain::Keychain;
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::Mutex;
 
+use crate::api_impl::events::{self, EventTopic};
 use crate::api_impl::owner;
 use crate::types::NodeClient;
 use crate::Error;
@@ -31,6 +32,35 @@ use std::thread::JoinHandle;
 
 const MESSAGE_QUEUE_MAX_LEN: usize = 10_000;
 
+lazy_static! {
+	static ref UPDATER_STATS: RwLock<UpdaterStats> = RwLock::new(UpdaterStats::default());
+}
+
+/// Statistics of the updater loop, reported by the metrics endpoint
+#[derive(Clone, Debug, Default)]
+pub struct UpdaterStats {
+	/// Number of the wallet updates
+	pub runs: u64,
+	/// Number of the failed wallet updates
+	pub failures: u64,
+	/// Duration of the last wallet update
+	pub last_duration: Duration,
+}
+
+/// Updater loop statistics since the wallet start
+pub fn get_updater_stats() -> UpdaterStats {
+	UPDATER_STATS.read().unwrap().clone()
+}
+
+fn record_updater_run(duration: Duration, success: bool) {
+	let mut stats = UPDATER_STATS.write().unwrap();
+	stats.runs += 1;
+	if !success {
+		stats.failures += 1;
+	}
+	stats.last_duration = duration;
+}
+
 /// Update status messages which can be returned to listening clients
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StatusMessage {
@@ -62,6 +92,9 @@ pub fn start_updater_log_thread(
 		.spawn(move || loop {
 			let running = running_state.load(Ordering::Relaxed);
 			while let Ok(m) = rx.try_recv() {
+				if let Ok(data) = serde_json::to_value(&m) {
+					events::publish_event(EventTopic::Updater, data);
+				}
 				// save to our message queue to be read by other consumers
 				{
 					let mut q = queue.lock();
@@ -176,6 +209,23 @@ where
 		}
 	}
 
+	// Publish the balance with the minimum confirmations from the wallet settings
+	fn publish_summary(&self, keychain_mask: Option<&SecretKey>) -> Result<(), Error> {
+		let minimum_confirmations = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			w.settings().minimum_confirmations
+		};
+		owner::retrieve_summary_info(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&None,
+			false,
+			minimum_confirmations,
+		)?;
+		Ok(())
+	}
+
 	/// Start the updater at the given frequency
 	pub fn run(
 		&self,
@@ -192,11 +242,27 @@ where
 			};
 			if wallet_opened {
 				// Business goes here
-				owner::update_wallet_state(
+				let started = Instant::now();
+				let res = owner::update_wallet_state(
+					self.wallet_inst.clone(),
+					(&keychain_mask).as_ref(),
+					status_send_channel,
+				);
+				record_updater_run(started.elapsed(), res.is_ok());
+				res?;
+				// Failed rebroadcast is retried at the next update
+				if let Err(e) = owner::rebroadcast_txs(
 					self.wallet_inst.clone(),
 					(&keychain_mask).as_ref(),
 					status_send_channel,
-				)?;
+				) {
+					warn!("Unable to rebroadcast the transactions, {}", e);
+				}
+				// Streaming clients get the balance changes. The update is done, so a
+				// failure here doesn't stop the updater.
+				if let Err(e) = self.publish_summary((&keychain_mask).as_ref()) {
+					warn!("Unable to publish the wallet balance, {}", e);
+				}
 			}
 
 			let sec = frequency.as_secs();
@@ -19,8 +19,10 @@ use crate::grin_keychain::Identifier;
 use crate::grin_util::secp::pedersen;
 use crate::proof::proofaddress;
 use crate::proof::proofaddress::ProvableAddress;
-use crate::slate_versions::SlateVersion;
-use crate::types::OutputData;
+use crate::slate_versions::v3::SlateV3;
+use crate::slate_versions::{SlateEncoding, SlateVersion};
+use crate::types::{OutputData, ScanCheckpoint, SpendPolicy, TxLogEntry};
+use uuid::Uuid;
 
 /// Send TX API Args
 // TODO: This is here to ensure the legacy V1 API remains intact
@@ -100,6 +102,11 @@ pub struct InitTxArgs {
 	#[serde(with = "secp_ser::opt_string_or_u64")]
 	#[serde(default)]
 	pub ttl_blocks: Option<u64>,
+	/// If set, the transaction kernel is height locked and the transaction can't be
+	/// included into a block below this height.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub lock_height: Option<u64>,
 	/// If set, require a payment proof for the particular recipient
 	#[serde(
 		serialize_with = "proofaddress::option_as_string",
@@ -168,6 +175,7 @@ impl Default for InitTxArgs {
 			message: None,
 			target_slate_version: None,
 			ttl_blocks: None,
+			lock_height: None,
 			estimate_only: Some(false),
 			payment_proof_recipient_address: None,
 			address: None,
@@ -231,6 +239,15 @@ pub struct IssueInvoiceTxArgs {
 	/// recipient address
 	#[serde(default)]
 	pub address: Option<String>,
+	/// Optional external reference, for example the order id. It is stored with the
+	/// invoice record only and never sent to the payer.
+	#[serde(default)]
+	pub reference: Option<String>,
+	/// Number of blocks from the current height after which the invoice expires.
+	/// The slate TTL is set to the same height, so the payer can't pay it later.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub expiry_blocks: Option<u64>,
 }
 
 impl Default for IssueInvoiceTxArgs {
@@ -241,6 +258,8 @@ impl Default for IssueInvoiceTxArgs {
 			message: None,
 			target_slate_version: None,
 			address: None,
+			reference: None,
+			expiry_blocks: None,
 		}
 	}
 }
@@ -297,6 +316,9 @@ pub struct VersionInfo {
 	pub foreign_api_version: u16,
 	/// Slate version
 	pub supported_slate_versions: Vec<SlateVersion>,
+	/// Slate encodings. Older wallets don't report it, they accept json only
+	#[serde(default)]
+	pub supported_slate_encodings: Vec<SlateEncoding>,
 }
 
 /// Packaged Payment Proof
@@ -357,3 +379,288 @@ pub struct SwapStartArgs {
 	/// Dry run flag. Use true if you want to validate config
 	pub dry_run: bool,
 }
+
+/// Single recipient of a batch payout
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BatchSendEntry {
+	/// The transaction method. Can currently be 'http' or 'mwcmqs'.
+	pub method: String,
+	/// The destination, contents will depend on the particular method
+	pub dest: String,
+	/// The amount to send, in nanogrins. (`1 G = 1_000_000_000nG`)
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// receiver wallet apisecret. Applicable to http/https address only
+	#[serde(default)]
+	pub apisecret: Option<String>,
+	/// Optional participant message for this recipient
+	#[serde(default)]
+	pub message: Option<String>,
+	/// If set, require a payment proof from this recipient
+	#[serde(
+		serialize_with = "proofaddress::option_as_string",
+		deserialize_with = "proofaddress::option_proof_address_from_string"
+	)]
+	#[serde(default)]
+	pub payment_proof_recipient_address: Option<ProvableAddress>,
+}
+
+/// Batch payout arguments. Selection settings are shared by all recipients.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BatchSendArgs {
+	/// The human readable account name from which to draw outputs
+	#[serde(default)]
+	pub src_acct_name: Option<String>,
+	/// The minimum number of confirmations an output
+	/// should have in order to be included in the transaction.
+	#[serde(with = "secp_ser::string_or_u64")]
+	#[serde(default = "InitTxArgs::default_minimum_confirmations")]
+	pub minimum_confirmations: u64,
+	/// Soft limit for the number of inputs of the batch, see `InitTxArgs`
+	#[serde(default = "InitTxArgs::default_max_outputs")]
+	pub max_outputs: u32,
+	/// Coin selection strategy, see `InitTxArgs`
+	#[serde(default = "InitTxArgs::default_selection_strategy_is_use_all")]
+	pub selection_strategy_is_use_all: bool,
+	/// Optionally set the output target slate version
+	#[serde(default)]
+	pub target_slate_version: Option<u16>,
+	/// Number of blocks from current after which TX should be ignored
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub ttl_blocks: Option<u64>,
+	/// Whether to use dandelion when posting. If false, skip the dandelion relay
+	#[serde(default = "InitTxSendArgs::default_fluff")]
+	pub fluff: bool,
+	/// Recipients of the payout
+	pub entries: Vec<BatchSendEntry>,
+}
+
+/// Outcome of a batch payout for a single recipient
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BatchSendResult {
+	/// The destination of the recipient
+	pub dest: String,
+	/// The amount requested for the recipient
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Slate Id, if the slate for the recipient was created
+	pub slate_id: Option<Uuid>,
+	/// Fee paid for this recipient
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub fee: u64,
+	/// Failure reason. None if the payment was posted
+	pub error: Option<String>,
+}
+
+impl BatchSendResult {
+	/// Empty result for the batch entry
+	pub fn new(entry: &BatchSendEntry) -> Self {
+		BatchSendResult {
+			dest: entry.dest.clone(),
+			amount: entry.amount,
+			slate_id: None,
+			fee: 0,
+			error: None,
+		}
+	}
+
+	/// True if the payment to this recipient was posted
+	pub fn is_ok(&self) -> bool {
+		self.error.is_none()
+	}
+}
+
+/// Transfer of funds between two accounts of the wallet
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TransferArgs {
+	/// Account to draw the outputs from. If None, the active account
+	#[serde(default)]
+	pub src_acct_name: Option<String>,
+	/// Account that receives the funds
+	pub dest_acct_name: String,
+	/// Amount to transfer
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// The minimum number of confirmations an output
+	/// should have in order to be included in the transaction.
+	#[serde(with = "secp_ser::string_or_u64")]
+	#[serde(default = "InitTxArgs::default_minimum_confirmations")]
+	pub minimum_confirmations: u64,
+	/// Soft limit for the number of inputs, see `InitTxArgs`
+	#[serde(default = "InitTxArgs::default_max_outputs")]
+	pub max_outputs: u32,
+	/// The target number of change outputs
+	#[serde(default = "InitTxArgs::default_num_change_outputs")]
+	pub num_change_outputs: u32,
+	/// Coin selection strategy, see `InitTxArgs`
+	#[serde(default = "InitTxArgs::default_selection_strategy_is_use_all")]
+	pub selection_strategy_is_use_all: bool,
+	/// Message that is stored with both transactions
+	#[serde(default)]
+	pub message: Option<String>,
+	/// Whether to use dandelion when posting. If false, skip the dandelion relay
+	#[serde(default = "InitTxSendArgs::default_fluff")]
+	pub fluff: bool,
+}
+
+impl TransferArgs {
+	/// Init tx arguments of the sending account
+	pub fn init_tx_args(&self) -> InitTxArgs {
+		InitTxArgs {
+			src_acct_name: self.src_acct_name.clone(),
+			amount: self.amount,
+			minimum_confirmations: self.minimum_confirmations,
+			max_outputs: self.max_outputs,
+			num_change_outputs: self.num_change_outputs,
+			selection_strategy_is_use_all: self.selection_strategy_is_use_all,
+			message: self.message.clone(),
+			address: Some("self".to_string()),
+			..Default::default()
+		}
+	}
+}
+
+/// Posted transfer between accounts, the transactions of both accounts are linked
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransferResult {
+	/// Slate Id of the transfer
+	pub slate_id: Uuid,
+	/// Transaction in the sending account
+	pub src_tx: TxLogEntry,
+	/// Transaction in the receiving account
+	pub dest_tx: TxLogEntry,
+}
+
+/// Fee of the transaction that the wallet would build, estimated without locking anything
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct FeeEstimate {
+	/// Amount to send
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Total value of the selected inputs, the amount that would be locked
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub total: u64,
+	/// Transaction fee
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub fee: u64,
+	/// Number of the selected inputs
+	pub num_inputs: usize,
+	/// Number of the outputs, including the change
+	pub num_outputs: usize,
+	/// Largest fee that the fee policy allows for the amount, None if there is no limit
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub max_fee: Option<u64>,
+}
+
+impl FeeEstimate {
+	/// True if the transaction would be rejected because of the fee policy limit
+	pub fn exceeds_max_fee(&self) -> bool {
+		self.max_fee.map(|m| self.fee > m) == Some(true)
+	}
+}
+
+/// Unconfirmed transaction whose TTL expired, with the changes its cancellation makes
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ExpiredTx {
+	/// The expired transaction
+	pub tx: TxLogEntry,
+	/// Locked inputs that become spendable again
+	pub locked_inputs: Vec<OutputData>,
+	/// Unconfirmed outputs that are deleted
+	pub unconfirmed_outputs: Vec<OutputData>,
+}
+
+/// Part of the transaction that the offline cold wallet signs
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ColdSignStep {
+	/// Sender adds the inputs, the change outputs and the public data of the first round
+	Send,
+	/// Receiver adds the output and the partial signature
+	Receive,
+	/// Sender adds the partial signature, the online wallet finalizes the transaction
+	Sign,
+}
+
+/// Unsigned request that the online wallet exports for the offline cold wallet
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ColdSignRequest {
+	/// What the offline wallet is asked to do
+	pub step: ColdSignStep,
+	/// Slate to sign
+	pub slate: SlateV3,
+	/// Outputs selected for spending, the offline wallet builds the inputs from their
+	/// key ids and values. Send step only.
+	#[serde(default)]
+	pub inputs: Vec<OutputData>,
+	/// Number of the change outputs to build. Send step only.
+	#[serde(default)]
+	pub num_change_outputs: usize,
+	/// Optional message for the participant data
+	#[serde(default)]
+	pub message: Option<String>,
+}
+
+/// Response of the offline cold wallet that the online wallet imports
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ColdSignResponse {
+	/// Step of the request
+	pub step: ColdSignStep,
+	/// Signed slate
+	pub slate: SlateV3,
+	/// New outputs of the cold wallet, the change or the received output. The online
+	/// wallet tracks them by the commitment.
+	#[serde(default)]
+	pub outputs: Vec<OutputData>,
+}
+
+/// Spending policy settings of an account. The new policy replaces the current one.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct SpendPolicyArgs {
+	/// Account name. If None, the active account
+	pub src_acct_name: Option<String>,
+	/// Maximum amount of a single transaction
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_per_tx: Option<u64>,
+	/// Maximum amount sent during the last 24 hours
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_24h: Option<u64>,
+	/// Maximum amount sent during the last 7 days
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_7d: Option<u64>,
+	/// If set, payments are allowed only to these destinations
+	#[serde(default)]
+	pub allowed_destinations: Option<Vec<String>>,
+	/// Time in seconds before a newly allowed destination can be paid
+	#[serde(default)]
+	pub new_destination_cooldown_secs: Option<u64>,
+}
+
+/// Spending policy of an account with the amounts already spent
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SpendPolicyStatus {
+	/// Account name
+	pub acct_name: String,
+	/// Current policy
+	pub policy: SpendPolicy,
+	/// Amount sent during the last 24 hours
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub spent_24h: u64,
+	/// Amount sent during the last 7 days
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub spent_7d: u64,
+}
+
+/// Status of the chain scan
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScanStatus {
+	/// Whether a scan is collecting the chain outputs now
+	pub running: bool,
+	/// Progress of the running scan, or of the interrupted one that the next scan resumes
+	pub checkpoint: Option<ScanCheckpoint>,
+	/// Scanned part of the checkpoint range, in percents
+	pub percent_complete: u8,
+}
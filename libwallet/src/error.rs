@@ -63,6 +63,22 @@ pub enum ErrorKind {
 	#[fail(display = "Fee Error: {}", _0)]
 	Fee(String),
 
+	/// Fee is over the limit of the fee policy
+	#[fail(
+		display = "Transaction fee {} is more than the fee policy limit {}",
+		fee_disp, max_fee_disp
+	)]
+	FeeTooHigh {
+		/// Transaction fee
+		fee: u64,
+		/// Display friendly
+		fee_disp: String,
+		/// Largest allowed fee
+		max_fee: u64,
+		/// Display friendly
+		max_fee_disp: String,
+	},
+
 	/// LibTX Error
 	#[fail(display = "LibTx Error, {}", _0)]
 	LibTX(grin_core::libtx::ErrorKind),
@@ -336,6 +352,66 @@ pub enum ErrorKind {
 	/// Swap error
 	#[fail(display = "Swap Error , {}", _0)]
 	SwapError(String),
+
+	/// Invoice error
+	#[fail(display = "Invoice error, {}", _0)]
+	InvoiceError(String),
+
+	/// Incoming slate is rejected by the receive policy
+	#[fail(display = "Rejected by the receive policy ({}), {}", rule, reason)]
+	ReceivePolicyViolation {
+		/// Policy rule, the config parameter name
+		rule: String,
+		/// Rejection details
+		reason: String,
+	},
+
+	/// Incoming slate is parked in the approval queue
+	#[fail(
+		display = "Slate {} is pending approval by the wallet owner, please retry later",
+		_0
+	)]
+	PendingApproval(String),
+
+	/// Approval queue error
+	#[fail(display = "Approval queue error, {}", _0)]
+	ApprovalQueueError(String),
+
+	/// Outbound transaction is rejected by the spending policy of the account
+	#[fail(display = "Rejected by the spending policy ({}), {}", rule, reason)]
+	SpendPolicyViolation {
+		/// Policy rule: max_per_tx, max_24h, max_7d or allowed_destinations
+		rule: String,
+		/// Rejection details
+		reason: String,
+	},
+
+	/// Node data doesn't match the verified header chain
+	#[fail(display = "Node data failed the header chain verification, {}", _0)]
+	HeaderVerification(String),
+
+	/// Scan is paused by the owner. Next scan of the same range resumes from the checkpoint
+	#[fail(
+		display = "Scan is paused at PMMR index {}, the next scan will resume from this point",
+		_0
+	)]
+	ScanPaused(u64),
+
+	/// Scan is cancelled by the owner, the scan progress is discarded
+	#[fail(display = "Scan is cancelled")]
+	ScanCancelled,
+
+	/// Wallet doesn't have an output with this commitment
+	#[fail(display = "Output {} is not found", _0)]
+	OutputNotFound(String),
+
+	/// Address book error
+	#[fail(display = "Address book error, {}", _0)]
+	ContactError(String),
+
+	/// Cold wallet signing error
+	#[fail(display = "Cold wallet error, {}", _0)]
+	ColdWallet(String),
 }
 
 impl Display for Error {
@@ -21,8 +21,15 @@
 #![deny(unused_mut)]
 #![warn(missing_docs)]
 
+pub mod cold;
+pub mod expiry;
+pub mod fee;
+pub mod header_sync;
 pub mod keys;
+pub mod rebroadcast;
+pub mod reorg;
 pub mod scan;
 pub mod selection;
+pub mod spend_policy;
 pub mod tx;
 pub mod updater;
@@ -0,0 +1,621 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Air-gapped signing with an offline cold wallet. The online wallet tracks the cold
+//! outputs by their commitments, selects the inputs and exchanges the slates with the
+//! other party. The offline wallet has the keychain, it builds the inputs, the outputs
+//! with their range proofs and the partial signatures. Requests and responses are
+//! carried between the machines as files. The online wallet keeps the transaction log
+//! entries, so the cold transactions expire and cancel the way the regular ones do.
+
+use crate::api_impl::types::{ColdSignRequest, ColdSignResponse, ColdSignStep, InitTxArgs};
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::libtx::proof::ProofBuilder;
+use crate::grin_keychain::{Identifier, Keychain};
+use crate::grin_util::secp::key::{SecretKey, ZERO_KEY};
+use crate::grin_util::secp::pedersen;
+use crate::internal::{fee, selection, tx};
+use crate::slate::Slate;
+use crate::slate_versions::v3::SlateV3;
+use crate::types::{
+	Context, NodeClient, OutputData, OutputStatus, TxLogEntry, TxLogEntryType, WalletBackend,
+};
+use grin_wallet_util::grin_util as util;
+use std::collections::HashSet;
+
+fn parse_commit(commit: &str) -> Result<pedersen::Commitment, Error> {
+	let bytes = util::from_hex(commit).map_err(|e| {
+		ErrorKind::GenericError(format!("Unable to parse HEX commit {}, {}", commit, e))
+	})?;
+	Ok(pedersen::Commitment::from_vec(bytes))
+}
+
+fn tx_commits(commits: Vec<pedersen::Commitment>) -> HashSet<String> {
+	commits.iter().map(|c| util::to_hex(c.0.to_vec())).collect()
+}
+
+// Output of the cold wallet as the online wallet sees it
+fn cold_output<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	parent_key_id: &Identifier,
+	key_id: &Identifier,
+	value: u64,
+	height: u64,
+) -> Result<OutputData, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	Ok(OutputData {
+		root_key_id: parent_key_id.clone(),
+		key_id: key_id.clone(),
+		n_child: key_id.to_path().last_path_index(),
+		commit: w.calc_commit_for_cache(keychain_mask, value, key_id)?,
+		mmr_index: None,
+		value,
+		status: OutputStatus::Unconfirmed,
+		height,
+		lock_height: 0,
+		is_coinbase: false,
+		tx_log_entry: None,
+		frozen: false,
+		note: None,
+		tags: vec![],
+		cold: true,
+	})
+}
+
+/// Unspent outputs of the account, exported by the offline wallet for the online one
+pub fn export_outputs<'a, T: ?Sized, C, K>(w: &T, parent_key_id: &Identifier) -> Vec<OutputData>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	w.iter()
+		.filter(|o| {
+			o.root_key_id == *parent_key_id
+				&& o.status == OutputStatus::Unspent
+				&& o.commit.is_some()
+		})
+		.map(|mut o| {
+			o.tx_log_entry = None;
+			o.cold = true;
+			o
+		})
+		.collect()
+}
+
+/// Start tracking the outputs of the cold wallet in the account. Outputs that are
+/// tracked already are skipped. Returns the number of the new outputs.
+pub fn import_outputs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	parent_key_id: &Identifier,
+	outputs: Vec<OutputData>,
+) -> Result<usize, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let known: HashSet<String> = w.iter().filter_map(|o| o.commit).collect();
+	let mut new_outputs = vec![];
+	for mut o in outputs {
+		let commit = o.commit.clone().ok_or_else(|| {
+			ErrorKind::ColdWallet(format!("output {} has no commitment", o.key_id))
+		})?;
+		parse_commit(&commit)?;
+		if known.contains(&commit) {
+			continue;
+		}
+		// The storage key of the own output can't be reused
+		if let Ok(existing) = w.get(&o.key_id, &o.mmr_index) {
+			if !existing.cold {
+				return Err(ErrorKind::ColdWallet(format!(
+					"output {} collides with the output {} of this wallet",
+					commit, o.key_id
+				))
+				.into());
+			}
+		}
+		o.root_key_id = parent_key_id.clone();
+		o.tx_log_entry = None;
+		o.cold = true;
+		new_outputs.push(o);
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	for o in &new_outputs {
+		batch.save(o.clone())?;
+	}
+	batch.commit()?;
+	Ok(new_outputs.len())
+}
+
+/// Select the cold outputs for the slate and set the fee. Returns the unsigned request
+/// for the offline wallet, nothing is locked until its response is processed.
+pub fn send_request<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	slate: &mut Slate,
+	args: &InitTxArgs,
+	parent_key_id: &Identifier,
+	message: Option<String>,
+) -> Result<ColdSignRequest, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let fee_policy = w.settings().fee_policy.clone();
+	let (coins, _total, amount, fee) = selection::select_coins_and_fee(
+		w,
+		slate.amount,
+		slate.height,
+		args.minimum_confirmations,
+		args.max_outputs as usize,
+		args.num_change_outputs as usize,
+		args.selection_strategy_is_use_all,
+		parent_key_id,
+		&args.outputs,
+		1,
+		args.exclude_change_outputs.unwrap_or(false),
+		args.minimum_confirmations_change_outputs,
+		fee_policy.fee_per_kweight,
+	)?;
+	fee::check_max_fee(&fee_policy, amount, fee)?;
+	if let Some(o) = coins.iter().find(|o| !o.cold) {
+		return Err(ErrorKind::ColdWallet(format!(
+			"output {} is not an output of the cold wallet",
+			o.key_id
+		))
+		.into());
+	}
+
+	slate.amount = amount;
+	slate.fee = fee;
+	Ok(ColdSignRequest {
+		step: ColdSignStep::Send,
+		slate: SlateV3::from(&*slate),
+		inputs: coins,
+		num_change_outputs: args.num_change_outputs as usize,
+		message,
+	})
+}
+
+/// Sign the request with the keychain of the offline wallet
+pub fn sign<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	request: &ColdSignRequest,
+	use_test_rng: bool,
+) -> Result<ColdSignResponse, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut slate = Slate::from(request.slate.clone());
+	let outputs = match request.step {
+		ColdSignStep::Send => sign_send(w, keychain_mask, &mut slate, request, use_test_rng)?,
+		ColdSignStep::Receive => sign_receive(w, keychain_mask, &mut slate, request, use_test_rng)?,
+		ColdSignStep::Sign => {
+			sign_round_2(w, keychain_mask, &mut slate)?;
+			vec![]
+		}
+	};
+	Ok(ColdSignResponse {
+		step: request.step.clone(),
+		slate: SlateV3::from(&slate),
+		outputs,
+	})
+}
+
+// Sender inputs and change, the context is kept for the second round
+fn sign_send<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &mut Slate,
+	request: &ColdSignRequest,
+	use_test_rng: bool,
+) -> Result<Vec<OutputData>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if !slate.participant_data.is_empty() {
+		return Err(ErrorKind::ColdWallet(format!("slate {} is signed already", slate.id)).into());
+	}
+	let total: u64 = request.inputs.iter().map(|o| o.value).sum();
+	if request.inputs.is_empty() || total < slate.amount + slate.fee {
+		return Err(ErrorKind::ColdWallet(format!(
+			"inputs of slate {} don't cover the amount and the fee",
+			slate.id
+		))
+		.into());
+	}
+	if total > slate.amount + slate.fee && request.num_change_outputs == 0 {
+		return Err(ErrorKind::ColdWallet(format!(
+			"slate {} needs at least one change output",
+			slate.id
+		))
+		.into());
+	}
+	// Inputs that the keychain can't open are not ours
+	for input in &request.inputs {
+		let commit = w.calc_commit_for_cache(keychain_mask, input.value, &input.key_id)?;
+		if commit.is_none() || commit != input.commit {
+			return Err(ErrorKind::ColdWallet(format!(
+				"input {} doesn't belong to this wallet",
+				input.key_id
+			))
+			.into());
+		}
+	}
+
+	let keychain = w.keychain(keychain_mask)?;
+	let parent_key_id = w.parent_key_id();
+	let (parts, change_amounts_derivations) = selection::inputs_and_change(
+		&request.inputs,
+		w,
+		keychain_mask,
+		slate.amount,
+		slate.fee,
+		request.num_change_outputs,
+	)?;
+	let blinding =
+		slate.add_transaction_elements(&keychain, &ProofBuilder::new(&keychain), parts)?;
+
+	let mut context = Context::new(
+		keychain.secp(),
+		blinding.secret_key(&keychain.secp())?,
+		&parent_key_id,
+		use_test_rng,
+		0,
+	);
+	context.amount = slate.amount;
+	context.fee = slate.fee;
+	for input in &request.inputs {
+		context.add_input(&input.key_id, &input.mmr_index, input.value);
+	}
+	let mut outputs = vec![];
+	for (change_amount, id, mmr_index) in &change_amounts_derivations {
+		context.add_output(&id, &mmr_index, *change_amount);
+		outputs.push(cold_output(
+			w,
+			keychain_mask,
+			&parent_key_id,
+			id,
+			*change_amount,
+			slate.height,
+		)?);
+	}
+
+	slate.fill_round_1(
+		&keychain,
+		&mut context.sec_key,
+		&context.sec_nonce,
+		0,
+		request.message.clone(),
+		use_test_rng,
+	)?;
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_private_context(slate.id.as_bytes(), 0, &context)?;
+	batch.commit()?;
+	Ok(outputs)
+}
+
+// Receiver output with both rounds, the way a regular receiver does it
+fn sign_receive<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &mut Slate,
+	request: &ColdSignRequest,
+	use_test_rng: bool,
+) -> Result<Vec<OutputData>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if w.tx_log_iter().any(|t| t.tx_slate_id == Some(slate.id)) {
+		return Err(
+			ErrorKind::ColdWallet(format!("slate {} is received already", slate.id)).into(),
+		);
+	}
+	let parent_key_id = w.parent_key_id();
+	let context = tx::add_output_to_slate(
+		w,
+		keychain_mask,
+		slate,
+		None,
+		None,
+		None,
+		&parent_key_id,
+		1,
+		request.message.clone(),
+		false,
+		use_test_rng,
+		1,
+	)?;
+	let mut outputs = vec![];
+	for (id, _, amount) in context.get_outputs() {
+		outputs.push(cold_output(
+			w,
+			keychain_mask,
+			&parent_key_id,
+			&id,
+			amount,
+			slate.height,
+		)?);
+	}
+	Ok(outputs)
+}
+
+// Sender partial signature from the context of the first round
+fn sign_round_2<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &mut Slate,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let context = w.get_private_context(keychain_mask, slate.id.as_bytes(), 0)?;
+	// The online wallet must not change what the first round agreed to
+	if slate.amount != context.amount || slate.fee != context.fee {
+		return Err(ErrorKind::ColdWallet(format!(
+			"amount or fee of slate {} doesn't match the signed one",
+			slate.id
+		))
+		.into());
+	}
+	slate.fill_round_2(
+		&w.keychain(keychain_mask)?,
+		&context.sec_key,
+		&context.sec_nonce,
+		0,
+	)?;
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_private_context(slate.id.as_bytes(), 0)?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Record the response of the offline wallet. The send step locks the spent cold
+/// outputs with a sent transaction, the receive step creates a received transaction,
+/// the new outputs of both are tracked as unconfirmed. The signed slate of the last
+/// step is finalized and stored. Returns the slate for the other party or for posting.
+pub fn process_response<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	parent_key_id: &Identifier,
+	response: &ColdSignResponse,
+) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut slate = Slate::from(response.slate.clone());
+	if response.step == ColdSignStep::Sign {
+		// Finalization needs the public data only, no keys of the cold wallet
+		slate.finalize(&w.keychain(keychain_mask)?)?;
+		// The online wallet doesn't sign, its context only names the account
+		let context =
+			Context::from_send_slate(&slate, ZERO_KEY, vec![], vec![], parent_key_id.clone(), 0)?;
+		tx::update_stored_tx(&mut *w, keychain_mask, &context, &slate, false)?;
+		tx::update_message(&mut *w, keychain_mask, &slate)?;
+		return Ok(slate);
+	}
+	let tx_types = match response.step {
+		ColdSignStep::Send => [TxLogEntryType::TxSent, TxLogEntryType::TxSentCancelled],
+		_ => [
+			TxLogEntryType::TxReceived,
+			TxLogEntryType::TxReceivedCancelled,
+		],
+	};
+	if w.tx_log_iter().any(|t| {
+		t.tx_slate_id == Some(slate.id)
+			&& t.parent_key_id == *parent_key_id
+			&& tx_types.contains(&t.tx_type)
+	}) {
+		return Err(
+			ErrorKind::ColdWallet(format!("slate {} is processed already", slate.id)).into(),
+		);
+	}
+
+	let input_commits = tx_commits(slate.tx.inputs().iter().map(|i| i.commitment()).collect());
+	let output_commits = tx_commits(slate.tx.outputs().iter().map(|o| o.commitment()).collect());
+
+	let mut locked = vec![];
+	if response.step == ColdSignStep::Send {
+		locked = w
+			.iter()
+			.filter(|o| {
+				o.cold
+					&& o.status == OutputStatus::Unspent
+					&& o.commit
+						.as_ref()
+						.map(|c| input_commits.contains(c))
+						.unwrap_or(false)
+			})
+			.collect();
+		if locked.len() != input_commits.len() {
+			return Err(ErrorKind::ColdWallet(format!(
+				"slate {} spends outputs that are not tracked as unspent",
+				slate.id
+			))
+			.into());
+		}
+	}
+
+	let mut outputs = vec![];
+	for o in &response.outputs {
+		let commit = o.commit.clone().unwrap_or_default();
+		if !output_commits.contains(&commit) {
+			return Err(ErrorKind::ColdWallet(format!(
+				"output {} is not in slate {}",
+				commit, slate.id
+			))
+			.into());
+		}
+		let mut o = o.clone();
+		o.root_key_id = parent_key_id.clone();
+		o.status = OutputStatus::Unconfirmed;
+		o.mmr_index = None;
+		o.tx_log_entry = None;
+		o.cold = true;
+		outputs.push(o);
+	}
+
+	let log_id = match response.step {
+		ColdSignStep::Send => {
+			let context = Context::from_send_slate(
+				&slate,
+				ZERO_KEY,
+				locked
+					.iter()
+					.map(|o| (o.key_id.clone(), o.mmr_index, o.value))
+					.collect(),
+				outputs
+					.iter()
+					.map(|o| (o.key_id.clone(), None, o.value))
+					.collect(),
+				parent_key_id.clone(),
+				0,
+			)?;
+			selection::lock_tx_context(&mut *w, keychain_mask, &slate, &context, None)?;
+			w.tx_log_iter()
+				.find(|t| {
+					t.tx_slate_id == Some(slate.id)
+						&& t.parent_key_id == *parent_key_id
+						&& t.tx_type == TxLogEntryType::TxSent
+				})
+				.map(|t| t.id)
+				.ok_or_else(|| ErrorKind::TransactionDoesntExist(slate.id.to_string()))?
+		}
+		_ => save_received_tx(&mut *w, keychain_mask, parent_key_id, &slate, &outputs)?,
+	};
+
+	// The commitments of the cold wallet replace the ones that the online keychain
+	// calculated for the change
+	let mut batch = w.batch(keychain_mask)?;
+	for mut o in outputs {
+		o.tx_log_entry = Some(log_id);
+		batch.save(o)?;
+	}
+	batch.commit()?;
+	Ok(slate)
+}
+
+// Received transaction of the cold outputs, the way the regular receiver records it
+fn save_received_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	parent_key_id: &Identifier,
+	slate: &Slate,
+	outputs: &[OutputData],
+) -> Result<u32, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let keychain = w.keychain(keychain_mask)?;
+	let mut output_commits = vec![];
+	for o in outputs {
+		if let Some(commit) = &o.commit {
+			output_commits.push(parse_commit(commit)?);
+		}
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	let log_id = batch.next_tx_log_id(parent_key_id)?;
+	let mut t = TxLogEntry::new(parent_key_id.clone(), TxLogEntryType::TxReceived, log_id);
+	t.tx_slate_id = Some(slate.id);
+	t.amount_credited = outputs.iter().map(|o| o.value).sum();
+	t.num_outputs = outputs.len();
+	t.output_commits = output_commits;
+	t.messages = Some(slate.participant_messages());
+	t.ttl_cutoff_height = slate.ttl_cutoff_height;
+	if slate.lock_height > 0 {
+		t.lock_height = Some(slate.lock_height);
+	}
+	if let Ok(e) = slate.calc_excess(&keychain) {
+		t.kernel_excess = Some(e)
+	}
+	t.kernel_lookup_min_height = Some(slate.height);
+	batch.save_tx_log_entry(t, parent_key_id)?;
+	batch.commit()?;
+	Ok(log_id)
+}
+
+/// Update the cold outputs from the node. The scan can't rewind them, they are looked
+/// up by the commitment. Returns the number of the changed outputs.
+pub fn refresh_outputs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<usize, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let outputs: Vec<OutputData> = w
+		.iter()
+		.filter(|o| o.cold && o.status != OutputStatus::Spent && o.commit.is_some())
+		.collect();
+	if outputs.is_empty() {
+		return Ok(0);
+	}
+	let mut commits = vec![];
+	for o in &outputs {
+		commits.push(parse_commit(o.commit.as_ref().unwrap())?);
+	}
+	let found = w.w2n_client().get_outputs_from_node(&commits)?;
+
+	let mut changed = 0;
+	let mut batch = w.batch(keychain_mask)?;
+	for (o, commit) in outputs.into_iter().zip(commits.iter()) {
+		match (found.get(commit), o.status.clone()) {
+			(Some((_, height, mmr_index)), OutputStatus::Unconfirmed) => {
+				// Output is stored by the mmr index too, the old record goes away
+				batch.delete(&o.key_id, &o.mmr_index)?;
+				let mut o = o;
+				o.status = OutputStatus::Unspent;
+				o.height = *height;
+				o.mmr_index = Some(*mmr_index);
+				batch.save(o)?;
+				changed += 1;
+			}
+			(None, OutputStatus::Unspent) | (None, OutputStatus::Locked) => {
+				let mut o = o;
+				o.status = OutputStatus::Spent;
+				batch.save(o)?;
+				changed += 1;
+			}
+			_ => {}
+		}
+	}
+	batch.commit()?;
+	Ok(changed)
+}
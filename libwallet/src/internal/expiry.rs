@@ -0,0 +1,124 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cancellation of the transactions with expired TTL. A transaction that is not on the
+//! chain by its TTL cutoff height is not going to be finalized by the other party, so
+//! the wallet releases its inputs, drops its unconfirmed outputs and the slate contexts.
+
+use crate::api_impl::types::ExpiredTx;
+use crate::error::Error;
+use crate::grin_keychain::Keychain;
+use crate::grin_util::secp::key::SecretKey;
+use crate::internal::updater;
+use crate::types::{NodeClient, OutputStatus, TxLogEntry, TxLogEntryType, WalletBackend};
+
+fn is_expired(t: &TxLogEntry, tip_height: u64) -> bool {
+	!t.confirmed
+		&& (t.tx_type == TxLogEntryType::TxSent || t.tx_type == TxLogEntryType::TxReceived)
+		&& t.ttl_cutoff_height.map(|h| tip_height >= h) == Some(true)
+}
+
+/// Unconfirmed transactions of all accounts whose TTL cutoff height is reached at the
+/// given chain height, with the outputs their cancellation changes.
+pub fn expired_txs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	tip_height: u64,
+) -> Result<Vec<ExpiredTx>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let txs: Vec<TxLogEntry> = w
+		.tx_log_iter()
+		.filter(|t| is_expired(t, tip_height))
+		.collect();
+
+	let mut res = vec![];
+	for tx in txs {
+		let outputs = updater::retrieve_outputs(
+			&mut *w,
+			keychain_mask,
+			false,
+			Some(&tx),
+			&tx.parent_key_id,
+			None,
+			None,
+		)?;
+		let (locked_inputs, unconfirmed_outputs) = outputs
+			.into_iter()
+			.map(|o| o.output)
+			.filter(|o| o.status == OutputStatus::Locked || o.status == OutputStatus::Unconfirmed)
+			.partition(|o| o.status == OutputStatus::Locked);
+		res.push(ExpiredTx {
+			tx,
+			locked_inputs,
+			unconfirmed_outputs,
+		});
+	}
+	Ok(res)
+}
+
+/// Cancel the expired transaction. Its locked inputs become spendable, the unconfirmed
+/// outputs and the private contexts of the slate are deleted. The reason is recorded at
+/// the cancelled tx log entry.
+pub fn cancel_expired_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	expired: &ExpiredTx,
+	tip_height: u64,
+) -> Result<TxLogEntry, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// The sender context is stored as participant 0, the receiver one as participant 1
+	let mut contexts = vec![];
+	if let Some(slate_id) = expired.tx.tx_slate_id.as_ref() {
+		for participant_id in 0..2 {
+			if w.get_private_context(keychain_mask, slate_id.as_bytes(), participant_id)
+				.is_ok()
+			{
+				contexts.push((slate_id.clone(), participant_id));
+			}
+		}
+	}
+
+	let mut tx = expired.tx.clone();
+	tx.cancel();
+	tx.cancel_reason = Some(format!(
+		"TTL expired at height {}, chain tip {}",
+		expired.tx.ttl_cutoff_height.unwrap_or(0),
+		tip_height
+	));
+	tx.rebroadcast = None;
+
+	let mut batch = w.batch(keychain_mask)?;
+	for o in &expired.locked_inputs {
+		let mut o = o.clone();
+		o.status = OutputStatus::Unspent;
+		batch.save(o)?;
+	}
+	for o in &expired.unconfirmed_outputs {
+		batch.delete(&o.key_id, &o.mmr_index)?;
+	}
+	for (slate_id, participant_id) in contexts {
+		batch.delete_private_context(slate_id.as_bytes(), participant_id)?;
+	}
+	batch.save_tx_log_entry(tx.clone(), &expired.tx.parent_key_id)?;
+	batch.commit()?;
+	Ok(tx)
+}
@@ -0,0 +1,70 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fee policy of the transactions that the wallet sends. The fee rate replaces the
+//! network default base fee, but never goes below it. The limits reject the
+//! transactions that pay too much.
+
+use crate::config::FeePolicyConfig;
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::amount_to_hr_string;
+use crate::grin_core::libtx;
+use std::cmp;
+
+/// Fee of the transaction with the given number of inputs, outputs and kernels.
+/// None fee rate is the network default base fee. The fee of a lower rate is raised to
+/// the default fee, the nodes don't accept the transactions that pay less.
+pub fn tx_fee(
+	fee_per_kweight: Option<u64>,
+	input_len: usize,
+	output_len: usize,
+	kernel_len: usize,
+) -> u64 {
+	match fee_per_kweight {
+		Some(rate) => {
+			// Base fee of 1 gives the transaction weight
+			let weight = libtx::tx_fee(input_len, output_len, kernel_len, Some(1));
+			cmp::max(
+				weight.saturating_mul(rate) / 1000,
+				libtx::tx_fee(input_len, output_len, kernel_len, None),
+			)
+		}
+		None => libtx::tx_fee(input_len, output_len, kernel_len, None),
+	}
+}
+
+/// Largest fee that the policy allows for sending the amount, None if there is no limit
+pub fn max_fee(policy: &FeePolicyConfig, amount: u64) -> Option<u64> {
+	let percent_limit = policy
+		.max_fee_percent
+		.map(|p| (amount as u128 * p as u128 / 100) as u64);
+	match (policy.max_fee, percent_limit) {
+		(Some(a), Some(b)) => Some(cmp::min(a, b)),
+		(a, b) => a.or(b),
+	}
+}
+
+/// Reject the fee that is over the limit of the policy
+pub fn check_max_fee(policy: &FeePolicyConfig, amount: u64, fee: u64) -> Result<(), Error> {
+	match max_fee(policy, amount) {
+		Some(max_fee) if fee > max_fee => Err(ErrorKind::FeeTooHigh {
+			fee,
+			fee_disp: amount_to_hr_string(fee, true),
+			max_fee,
+			max_fee_disp: amount_to_hr_string(max_fee, true),
+		}
+		.into()),
+		_ => Ok(()),
+	}
+}
@@ -0,0 +1,514 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light header sync. The wallet keeps a chain of the recent block headers that it
+//! verified itself (hash links, proof of work, difficulty adjustment and secondary
+//! scaling) and checks the heights, hashes and MMR positions reported by the node
+//! against it, so a node can't fake confirmations.
+//!
+//! The chain is anchored at the configured checkpoint. Without a checkpoint the header
+//! `ANCHOR_DEPTH` blocks below the node tip is trusted on the first sync. The difficulty
+//! window below the anchor is linked to the anchor by the header hashes. Every later
+//! header has to extend the verified chain, a reorg is followed only if the new branch
+//! has more work.
+//!
+//! A sync doesn't fetch the headers more than `KEEP_HEADERS` blocks below the node tip.
+//! A checkpoint or a verified chain that is further below is not used, the chain is
+//! anchored again as if there were no checkpoint.
+
+use crate::api_impl::owner_updater::StatusMessage;
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::consensus::{self, DIFFICULTY_ADJUST_WINDOW};
+use crate::grin_core::core::hash::{Hash, Hashed, ZERO_HASH};
+use crate::grin_core::core::BlockHeader;
+use crate::grin_core::pow::{self, Difficulty};
+use crate::grin_keychain::Keychain;
+use crate::grin_util::secp::key::SecretKey;
+use crate::types::{NodeClient, VerifiedHeader, WalletBackend};
+use std::sync::mpsc::Sender;
+
+/// Depth below the node tip where the header chain is anchored by the first sync
+/// if no checkpoint is configured
+const ANCHOR_DEPTH: u64 = 1440;
+/// Number of the verified headers that are kept below the tip
+const KEEP_HEADERS: u64 = 10080;
+/// Number of the previous headers that the difficulty of the next header depends on.
+/// One more header is needed to get the difficulty of the oldest one.
+const DIFFICULTY_WINDOW: u64 = DIFFICULTY_ADJUST_WINDOW + 2;
+/// Number of the fetched headers between the progress messages
+const PROGRESS_HEADERS: u64 = 1000;
+
+fn verification_error(reason: String) -> Error {
+	error!("Header chain verification failed, {}", reason);
+	ErrorKind::HeaderVerification(reason).into()
+}
+
+/// Tip of the verified header chain
+pub fn verified_tip<'a, T: ?Sized, C, K>(w: &T) -> Option<VerifiedHeader>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	w.verified_header_iter().max_by_key(|h| h.height)
+}
+
+fn fetch_header<C: NodeClient>(client: &C, height: u64) -> Result<BlockHeader, Error> {
+	let header = client.get_block_header(height)?;
+	if header.height != height {
+		return Err(verification_error(format!(
+			"node returned header at height {} instead of {}",
+			header.height, height
+		)));
+	}
+	Ok(header)
+}
+
+// Difficulty data of the window for the consensus difficulty adjustment, the newest
+// header first. The window is ordered by height.
+fn difficulty_data(window: &[VerifiedHeader]) -> Vec<consensus::HeaderInfo> {
+	window
+		.iter()
+		.enumerate()
+		.rev()
+		.filter_map(|(i, h)| {
+			let difficulty = if h.height == 0 {
+				h.total_difficulty
+			} else if i > 0 {
+				h.total_difficulty - window[i - 1].total_difficulty
+			} else {
+				return None;
+			};
+			Some(consensus::HeaderInfo::new(
+				Hash::from_hex(&h.hash).unwrap_or(ZERO_HASH),
+				h.timestamp.timestamp() as u64,
+				Difficulty::from_num(difficulty),
+				h.secondary_scaling,
+				h.is_secondary,
+			))
+		})
+		.collect()
+}
+
+/// Check that the header extends the verified headers: hash link, timestamp, difficulty
+/// adjustment, secondary scaling and proof of work. The window is ordered by height and
+/// ends with the previous header. It must have `DIFFICULTY_ADJUST_WINDOW + 2` headers,
+/// fewer only at the start of the chain.
+pub fn verify_header(header: &BlockHeader, window: &[VerifiedHeader]) -> Result<(), Error> {
+	let prev = match window.last() {
+		Some(prev) => prev,
+		None => {
+			return Err(verification_error(format!(
+				"no verified header below the height {}",
+				header.height
+			)))
+		}
+	};
+	if window.len() < DIFFICULTY_WINDOW as usize && window[0].height != 0 {
+		return Err(verification_error(format!(
+			"difficulty window of the header at height {} is incomplete",
+			header.height
+		)));
+	}
+	if header.height != prev.height + 1 || header.prev_hash.to_hex() != prev.hash {
+		return Err(verification_error(format!(
+			"header {} at height {} doesn't extend header {} at height {}",
+			header.hash().to_hex(),
+			header.height,
+			prev.hash,
+			prev.height
+		)));
+	}
+	if header.timestamp <= prev.timestamp {
+		return Err(verification_error(format!(
+			"header at height {} has invalid timestamp {}",
+			header.height, header.timestamp
+		)));
+	}
+	let total_difficulty = header.total_difficulty().to_num();
+	if total_difficulty <= prev.total_difficulty {
+		return Err(verification_error(format!(
+			"total difficulty of the header at height {} doesn't grow",
+			header.height
+		)));
+	}
+	let target_difficulty = total_difficulty - prev.total_difficulty;
+	let next = consensus::next_difficulty(header.height, difficulty_data(window));
+	if target_difficulty != next.difficulty.to_num() {
+		return Err(verification_error(format!(
+			"difficulty {} of the header at height {} doesn't match the expected {}",
+			target_difficulty,
+			header.height,
+			next.difficulty.to_num()
+		)));
+	}
+	if header.pow.secondary_scaling != next.secondary_scaling {
+		return Err(verification_error(format!(
+			"secondary scaling {} of the header at height {} doesn't match the expected {}",
+			header.pow.secondary_scaling, header.height, next.secondary_scaling
+		)));
+	}
+	if header.pow.to_difficulty(header.height).to_num() < target_difficulty {
+		return Err(verification_error(format!(
+			"proof of work of the header at height {} is below the difficulty {}",
+			header.height, target_difficulty
+		)));
+	}
+	pow::verify_size(header).map_err(|e| {
+		verification_error(format!(
+			"invalid proof of work of the header at height {}, {}",
+			header.height, e
+		))
+	})
+}
+
+// Highest verified header that the node chain has. Returns an error if the node chain
+// diverged below the kept headers.
+fn find_fork_point<'a, T: ?Sized, C, K>(
+	w: &T,
+	client: &C,
+	tip: &VerifiedHeader,
+	node_tip_height: u64,
+) -> Result<VerifiedHeader, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut height = std::cmp::min(tip.height, node_tip_height);
+	loop {
+		let local = match w.get_verified_header(height)? {
+			Some(h) => h,
+			None => {
+				return Err(verification_error(format!(
+					"node chain doesn't connect to the verified headers above height {}",
+					height
+				)))
+			}
+		};
+		if fetch_header(client, height)?.hash().to_hex() == local.hash {
+			return Ok(local);
+		}
+		if height == 0 {
+			return Err(verification_error(
+				"node chain has a different genesis".to_string(),
+			));
+		}
+		height -= 1;
+	}
+}
+
+// Anchor the header chain at the checkpoint, or at `ANCHOR_DEPTH` below the node tip.
+// A checkpoint more than `KEEP_HEADERS` below the node tip is not used. Returns the
+// anchor with the difficulty window below it, ordered by height. The window headers are
+// linked to the anchor by the hashes.
+fn anchor_headers<C: NodeClient>(
+	client: &C,
+	checkpoint: &Option<(u64, String)>,
+	node_tip_height: u64,
+) -> Result<Vec<VerifiedHeader>, Error> {
+	let keep_from = node_tip_height.saturating_sub(KEEP_HEADERS);
+	let checkpoint = match checkpoint {
+		Some((height, _)) if *height < keep_from => {
+			warn!(
+				"Header checkpoint at height {} is more than {} blocks below the node tip {}, it is not used",
+				height, KEEP_HEADERS, node_tip_height
+			);
+			None
+		}
+		checkpoint => checkpoint.as_ref(),
+	};
+	let anchor_height = match checkpoint {
+		Some((height, _)) => *height,
+		None => node_tip_height.saturating_sub(ANCHOR_DEPTH),
+	};
+	if anchor_height > node_tip_height {
+		return Err(verification_error(format!(
+			"header checkpoint at height {} is above the node tip {}",
+			anchor_height, node_tip_height
+		)));
+	}
+	let anchor = fetch_header(client, anchor_height)?;
+	match checkpoint {
+		Some((_, hash)) => {
+			if anchor.hash().to_hex() != *hash {
+				return Err(verification_error(format!(
+					"node header {} at the checkpoint height {} doesn't match the checkpoint {}",
+					anchor.hash().to_hex(),
+					anchor_height,
+					hash
+				)));
+			}
+			info!(
+				"Header chain is anchored at the checkpoint height {}, hash {}",
+				anchor_height, hash
+			);
+		}
+		None => warn!(
+			"Header chain is anchored at height {}, hash {} without a checkpoint, the node header is trusted",
+			anchor_height,
+			anchor.hash().to_hex()
+		),
+	}
+
+	let mut headers = vec![VerifiedHeader::from_header(&anchor)];
+	let mut next = anchor;
+	let window_start = anchor_height.saturating_sub(DIFFICULTY_WINDOW - 1);
+	for height in (window_start..anchor_height).rev() {
+		let header = fetch_header(client, height)?;
+		if header.hash() != next.prev_hash {
+			return Err(verification_error(format!(
+				"header at height {} doesn't link to the header at height {}",
+				height, next.height
+			)));
+		}
+		headers.push(VerifiedHeader::from_header(&header));
+		next = header;
+	}
+	headers.reverse();
+	Ok(headers)
+}
+
+// Verified headers of the difficulty window that ends at the header, ordered by height
+fn difficulty_window<'a, T: ?Sized, C, K>(
+	w: &T,
+	header: &VerifiedHeader,
+) -> Result<Vec<VerifiedHeader>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut window = vec![];
+	for height in header.height.saturating_sub(DIFFICULTY_WINDOW - 1)..header.height {
+		match w.get_verified_header(height)? {
+			Some(h) => window.push(h),
+			None => {
+				return Err(verification_error(format!(
+					"verified header at height {} of the difficulty window is missing",
+					height
+				)))
+			}
+		}
+	}
+	window.push(header.clone());
+	Ok(window)
+}
+
+/// Sync the verified header chain with the node up to the node tip height. Returns the
+/// verified header at that height.
+pub fn sync_headers<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	node_tip_height: u64,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	show_progress: bool,
+) -> Result<VerifiedHeader, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let client = w.w2n_client().clone();
+	let keep_from = node_tip_height.saturating_sub(KEEP_HEADERS);
+
+	// The verified chain that ends below the kept headers is anchored again, the headers
+	// in between are not fetched
+	let mut stale: Vec<u64> = vec![];
+	let old_tip = match verified_tip(w) {
+		Some(tip) if tip.height < keep_from => {
+			warn!(
+				"Verified header chain ends at height {}, more than {} blocks below the node tip {}, anchoring it again",
+				tip.height, KEEP_HEADERS, node_tip_height
+			);
+			stale = w.verified_header_iter().map(|h| h.height).collect();
+			None
+		}
+		tip => tip,
+	};
+
+	let mut new_headers: Vec<VerifiedHeader> = vec![];
+	let mut window = match &old_tip {
+		Some(tip) => {
+			let fork = find_fork_point(w, &client, tip, node_tip_height)?;
+			difficulty_window(w, &fork)?
+		}
+		None => {
+			let anchor = anchor_headers(&client, &w.settings().header_checkpoint, node_tip_height)?;
+			new_headers.extend(anchor.iter().cloned());
+			anchor
+		}
+	};
+	let fork = window.last().unwrap().clone();
+
+	let mut prev = fork.clone();
+	for height in (fork.height + 1)..=node_tip_height {
+		if let Some(ref s) = status_send_channel {
+			if (height - fork.height - 1) % PROGRESS_HEADERS == 0 {
+				let _ = s.send(StatusMessage::Scanning(
+					show_progress,
+					format!(
+						"Verifying block headers, Height: {} - {}",
+						height, node_tip_height
+					),
+					99,
+				));
+			}
+		}
+		let header = fetch_header(&client, height)?;
+		verify_header(&header, &window)?;
+		prev = VerifiedHeader::from_header(&header);
+		new_headers.push(prev.clone());
+		window.push(prev.clone());
+		if window.len() > DIFFICULTY_WINDOW as usize {
+			window.remove(0);
+		}
+	}
+
+	// Reorg, the headers above the fork point are replaced
+	let mut replaced: Vec<u64> = vec![];
+	if let Some(tip) = &old_tip {
+		if fork.height < tip.height && !new_headers.is_empty() {
+			if prev.total_difficulty <= tip.total_difficulty {
+				return Err(verification_error(format!(
+					"node chain forks at height {} and has less work than the verified chain",
+					fork.height
+				)));
+			}
+			warn!(
+				"Header chain reorg at height {}, new tip {} at height {}",
+				fork.height, prev.hash, prev.height
+			);
+			replaced = ((fork.height + 1)..=tip.height).collect();
+		}
+	}
+
+	let pruned: Vec<u64> = w
+		.verified_header_iter()
+		.map(|h| h.height)
+		.filter(|h| *h < keep_from && *h <= fork.height)
+		.collect();
+
+	if !new_headers.is_empty() || !pruned.is_empty() {
+		let mut batch = w.batch(keychain_mask)?;
+		for height in replaced.iter().chain(pruned.iter()).chain(stale.iter()) {
+			batch.delete_verified_header(*height)?;
+		}
+		for header in new_headers {
+			batch.save_verified_header(header)?;
+		}
+		batch.commit()?;
+	}
+
+	Ok(prev)
+}
+
+/// Check the header hash reported by the node against the verified chain. Heights
+/// outside of the verified chain are not checked.
+pub fn check_header_hash<'a, T: ?Sized, C, K>(w: &T, height: u64, hash: &str) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	match w.get_verified_header(height)? {
+		Some(h) if h.hash != hash => Err(verification_error(format!(
+			"node reports header {} at height {}, verified header is {}",
+			hash, height, h.hash
+		))),
+		_ => Ok(()),
+	}
+}
+
+// Check that the MMR positions reported by the node belong to the blocks at their
+// heights: a block adds the positions above the MMR size of the previous header up to
+// the MMR size of its own header. Heights below the kept headers are not checked.
+fn check_mmr_positions<'a, T: ?Sized, C, K, I>(
+	w: &T,
+	tip: &VerifiedHeader,
+	positions: I,
+	mmr: &str,
+	mmr_size: fn(&VerifiedHeader) -> u64,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+	I: IntoIterator<Item = (u64, u64)>,
+{
+	for (height, position) in positions {
+		check_heights(tip, vec![height])?;
+		let header = match w.get_verified_header(height)? {
+			Some(h) => h,
+			None => continue,
+		};
+		let lower = match height {
+			0 => Some(0),
+			_ => w.get_verified_header(height - 1)?.map(|h| mmr_size(&h)),
+		};
+		if position > mmr_size(&header) || lower.map(|l| position <= l).unwrap_or(false) {
+			return Err(verification_error(format!(
+				"node reports {} MMR position {} at height {} that the block header doesn't have",
+				mmr, position, height
+			)));
+		}
+	}
+	Ok(())
+}
+
+/// Check the outputs reported by the node, as (height, output MMR index), against the
+/// verified chain
+pub fn check_outputs<'a, T: ?Sized, C, K, I>(
+	w: &T,
+	tip: &VerifiedHeader,
+	outputs: I,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+	I: IntoIterator<Item = (u64, u64)>,
+{
+	check_mmr_positions(w, tip, outputs, "output", |h| h.output_mmr_size)
+}
+
+/// Check the kernels reported by the node, as (height, kernel MMR index), against the
+/// verified chain
+pub fn check_kernels<'a, T: ?Sized, C, K, I>(
+	w: &T,
+	tip: &VerifiedHeader,
+	kernels: I,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+	I: IntoIterator<Item = (u64, u64)>,
+{
+	check_mmr_positions(w, tip, kernels, "kernel", |h| h.kernel_mmr_size)
+}
+
+/// Check that the heights reported by the node are not above the verified tip
+pub fn check_heights<I>(tip: &VerifiedHeader, heights: I) -> Result<(), Error>
+where
+	I: IntoIterator<Item = u64>,
+{
+	match heights.into_iter().find(|h| *h > tip.height) {
+		Some(h) => Err(verification_error(format!(
+			"node reports data at height {} above the verified tip {}",
+			h, tip.height
+		))),
+		None => Ok(()),
+	}
+}
@@ -0,0 +1,242 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rebroadcast of the posted transactions. Node mempools are not persistent, a posted
+//! transaction can be lost on a node restart and stay unconfirmed until it expires.
+//! The updater looks up the kernels of the tracked transactions and posts the missing
+//! ones again, waiting longer after every retry. When the retries are exhausted the
+//! transaction is cancelled, so its inputs can be spent again. If the config opts out of
+//! the cancellation, the wallet only stops tracking the transaction.
+
+use crate::config::RebroadcastConfig;
+use crate::error::Error;
+use crate::grin_core::core::Transaction;
+use crate::grin_keychain::Keychain;
+use crate::grin_util::secp::key::SecretKey;
+use crate::internal::tx;
+use crate::types::{
+	NodeClient, RebroadcastState, TxLogEntry, TxLogEntryType, WalletBackend, WalletSettings,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::cmp;
+
+// Longest wait between the retries, keeps the large backoff within the chrono range
+const MAX_RETRY_DELAY_SECS: u64 = 365 * 24 * 3600;
+
+/// Rebroadcast settings of the wallet instance, None if the rebroadcast is disabled
+pub fn rebroadcast_config(settings: &WalletSettings) -> Option<RebroadcastConfig> {
+	settings
+		.rebroadcast
+		.clone()
+		.filter(|c| c.enabled.unwrap_or(true))
+}
+
+/// Transaction that is due for the rebroadcast
+#[derive(Debug, Clone)]
+pub enum DueTx {
+	/// Stored transaction that is posted again
+	Repost(Transaction),
+	/// Retries are exhausted
+	Exhausted,
+	/// Transaction data is not stored, the transaction can't be posted again
+	Missing,
+}
+
+/// What the rebroadcast did with the transaction
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebroadcastAction {
+	/// Transaction is posted again, with the number of the retry
+	Reposted(u32),
+	/// Retries are exhausted and the transaction is cancelled
+	Cancelled,
+	/// Transaction is not posted any more and not tracked, it stays unconfirmed
+	GaveUp,
+}
+
+// Only the sent transactions that are still waiting for the chain are tracked. The
+// inputs are ours, so the sender is the one who has to get them to the chain.
+fn is_pending(t: &TxLogEntry) -> bool {
+	!t.confirmed && t.tx_type == TxLogEntryType::TxSent
+}
+
+/// Time of the next rebroadcast. The interval is multiplied by the backoff factor
+/// after every retry.
+pub fn next_retry_ts(config: &RebroadcastConfig, state: &RebroadcastState) -> DateTime<Utc> {
+	let interval = config.interval_secs.unwrap_or(600);
+	let factor = cmp::max(config.backoff_factor.unwrap_or(2), 1) as u64;
+	let delay = interval.saturating_mul(factor.saturating_pow(state.retries));
+	state.last_post_ts + Duration::seconds(cmp::min(delay, MAX_RETRY_DELAY_SECS) as i64)
+}
+
+/// Start tracking the wallet transactions whose kernels are in the posted transaction.
+/// The retries of the transactions that are tracked already are kept, the next one is
+/// counted from this post.
+pub fn track_posted_tx<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	tx: &Transaction,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let excesses: Vec<_> = tx.kernels().iter().map(|k| k.excess).collect();
+	let entries: Vec<TxLogEntry> = w
+		.tx_log_iter()
+		.filter(|t| {
+			is_pending(t) && t.kernel_excess.as_ref().map(|e| excesses.contains(e)) == Some(true)
+		})
+		.collect();
+	if entries.is_empty() {
+		return Ok(());
+	}
+
+	let now = Utc::now();
+	let mut batch = w.batch(keychain_mask)?;
+	for mut t in entries {
+		t.rebroadcast = Some(RebroadcastState {
+			retries: t.rebroadcast.as_ref().map(|r| r.retries).unwrap_or(0),
+			last_post_ts: now,
+		});
+		let parent_key_id = t.parent_key_id.clone();
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+	}
+	batch.commit()?;
+	Ok(())
+}
+
+/// Tracked transactions that are due for the rebroadcast. The transactions whose stored
+/// data can't be read are skipped, they are checked again at the next update.
+pub fn due_txs<'a, T: ?Sized, C, K>(
+	w: &T,
+	config: &RebroadcastConfig,
+	now: DateTime<Utc>,
+) -> Vec<(TxLogEntry, DueTx)>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let max_retries = config.max_retries.unwrap_or(5);
+	w.tx_log_iter()
+		.filter(|t| {
+			is_pending(t)
+				&& t.kernel_excess.is_some()
+				&& t.rebroadcast
+					.as_ref()
+					.map(|r| next_retry_ts(config, r) <= now)
+					== Some(true)
+		})
+		.filter_map(|t| {
+			let retries = t.rebroadcast.as_ref().map(|r| r.retries).unwrap_or(0);
+			if retries >= max_retries {
+				return Some((t, DueTx::Exhausted));
+			}
+			match w.get_stored_tx(&t) {
+				Ok(Some(stored_tx)) => Some((t, DueTx::Repost(stored_tx))),
+				Ok(None) => Some((t, DueTx::Missing)),
+				Err(e) => {
+					warn!("Unable to read the stored transaction {}, {}", t.id, e);
+					None
+				}
+			}
+		})
+		.collect()
+}
+
+/// Post the due transaction again if its kernel is not on the chain. Returns None if the
+/// kernel is found, the wallet update confirms the transaction then. Node errors are
+/// returned, the transaction is checked again at the next update.
+/// The wallet must not be locked, the node client might need it.
+pub fn repost_tx<C>(
+	client: &C,
+	config: &RebroadcastConfig,
+	entry: &TxLogEntry,
+	due: &DueTx,
+) -> Result<Option<RebroadcastAction>, Error>
+where
+	C: NodeClient,
+{
+	let excess = match entry.kernel_excess.as_ref() {
+		Some(e) => e,
+		None => return Ok(None),
+	};
+	if client
+		.get_kernel(excess, entry.kernel_lookup_min_height, None)?
+		.is_some()
+	{
+		return Ok(None);
+	}
+
+	let action = match due {
+		DueTx::Repost(stored_tx) => {
+			// Rejected post is a retry too, the retries get exhausted in the end
+			if let Err(e) = client.post_tx(stored_tx, false) {
+				warn!("Unable to repost the transaction {}, {}", entry.id, e);
+			}
+			let retries = entry.rebroadcast.as_ref().map(|r| r.retries).unwrap_or(0);
+			RebroadcastAction::Reposted(retries + 1)
+		}
+		DueTx::Exhausted => {
+			if config.cancel_after_retries.unwrap_or(true) {
+				RebroadcastAction::Cancelled
+			} else {
+				RebroadcastAction::GaveUp
+			}
+		}
+		DueTx::Missing => {
+			warn!(
+				"Transaction {} is not stored, it can't be posted again",
+				entry.id
+			);
+			RebroadcastAction::GaveUp
+		}
+	};
+	Ok(Some(action))
+}
+
+/// Record the rebroadcast of the transaction. The cancelled transaction releases its
+/// inputs, the transaction that the wallet gave up on is not tracked any more.
+pub fn record_rebroadcast<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	entry: &TxLogEntry,
+	action: &RebroadcastAction,
+	now: DateTime<Utc>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut t = entry.clone();
+	t.rebroadcast = match action {
+		RebroadcastAction::Reposted(retries) => Some(RebroadcastState {
+			retries: *retries,
+			last_post_ts: now,
+		}),
+		RebroadcastAction::Cancelled | RebroadcastAction::GaveUp => None,
+	};
+	let parent_key_id = t.parent_key_id.clone();
+	{
+		let mut batch = w.batch(keychain_mask)?;
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+		batch.commit()?;
+	}
+	if *action == RebroadcastAction::Cancelled {
+		tx::cancel_tx(&mut *w, keychain_mask, &parent_key_id, Some(entry.id), None)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,154 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chain reorganisation handling. The wallet keeps the hashes of some recently scanned
+//! blocks, at every update they are compared with the node headers. The highest block that
+//! is still on the node chain is the fork point. Everything the wallet confirmed above it
+//! came from the abandoned branch and is reverted before the rescan from the fork point.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_keychain::Keychain;
+use crate::grin_util::secp::key::SecretKey;
+use crate::grin_util::to_hex;
+use crate::types::{NodeClient, OutputStatus, ScannedBlockInfo, WalletBackend};
+use std::collections::HashSet;
+
+/// Confirmations that were reverted by the rollback
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollbackStats {
+	/// Outputs that are unconfirmed or unspent again
+	pub outputs: usize,
+	/// Transactions that are unconfirmed again
+	pub transactions: usize,
+}
+
+/// Highest of the stored blocks that is still on the node chain. `blocks` are ordered from
+/// the highest one. The empty block is returned if there are no stored blocks, the wallet
+/// is scanned from the start then. The blocks that the node doesn't return are skipped. If
+/// none of the checked blocks match, the fork is below the stored blocks and the node block
+/// below the oldest one is returned, so the wallet is rescanned from the oldest stored
+/// block. Returns an error if the node returned none of the headers.
+pub fn find_fork_point<C>(
+	client: &C,
+	blocks: &[ScannedBlockInfo],
+) -> Result<ScannedBlockInfo, Error>
+where
+	C: NodeClient,
+{
+	if blocks.is_empty() {
+		return Ok(ScannedBlockInfo::empty());
+	}
+	let mut checked = false;
+	for bl in blocks {
+		let hdr_info = match client.get_header_info(bl.height) {
+			Ok(h) => h,
+			Err(e) => {
+				debug!("Unable to get the header at height {}, {}", bl.height, e);
+				continue;
+			}
+		};
+		checked = true;
+		if hdr_info.hash == bl.hash {
+			return Ok(bl.clone());
+		}
+		debug!(
+			"Block {} at height {} is not on the node chain, node has {}",
+			bl.hash, bl.height, hdr_info.hash
+		);
+	}
+	match blocks.last() {
+		Some(oldest) if checked => {
+			warn!(
+				"None of the stored blocks is on the node chain, the wallet is rescanned from height {}",
+				oldest.height
+			);
+			let height = oldest.height.saturating_sub(1);
+			let hash = client
+				.get_header_info(height)
+				.map(|h| h.hash)
+				.unwrap_or_default();
+			Ok(ScannedBlockInfo::new(height, hash))
+		}
+		_ => Err(ErrorKind::ClientCallback(
+			"Unable to get the headers of the scanned blocks".to_string(),
+		)
+		.into()),
+	}
+}
+
+/// Revert the confirmations that the wallet recorded above `fork_height`. Transactions are
+/// marked unconfirmed and their spent inputs are locked again, outputs that were confirmed
+/// above the fork point are unconfirmed and lose their mmr index from the abandoned branch.
+/// The following full scan confirms again everything that made it to the new branch.
+pub fn rollback_confirmations<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	fork_height: u64,
+) -> Result<RollbackStats, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let txs: Vec<_> = w
+		.tx_log_iter()
+		.filter(|t| t.confirmed && t.output_height > fork_height)
+		.collect();
+	let inputs: HashSet<String> = txs
+		.iter()
+		.flat_map(|t| t.input_commits.iter().map(|c| to_hex(c.0.to_vec())))
+		.collect();
+
+	let mut outputs = vec![];
+	let mut unconfirmed = vec![];
+	for mut out in w.iter() {
+		let spent_by_reverted = out.status == OutputStatus::Spent
+			&& out.commit.as_ref().map(|c| inputs.contains(c)) == Some(true);
+		if spent_by_reverted {
+			out.status = OutputStatus::Locked;
+			outputs.push(out);
+		} else if out.height > fork_height && out.status == OutputStatus::Unspent {
+			unconfirmed.push(out);
+		}
+	}
+
+	let stats = RollbackStats {
+		outputs: outputs.len() + unconfirmed.len(),
+		transactions: txs.len(),
+	};
+
+	let mut batch = w.batch(keychain_mask)?;
+	for out in outputs {
+		batch.save(out)?;
+	}
+	for mut out in unconfirmed {
+		// mmr_index is a part of the record key, the output is stored under the new key
+		// without the index until the scan finds it on the new branch
+		batch.delete(&out.key_id, &out.mmr_index)?;
+		out.status = OutputStatus::Unconfirmed;
+		out.mmr_index = None;
+		out.height = fork_height;
+		batch.save(out)?;
+	}
+	for mut t in txs {
+		t.confirmed = false;
+		t.confirmation_ts = None;
+		t.output_height = 0;
+		let parent_key_id = t.parent_key_id.clone();
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+	}
+	batch.commit()?;
+
+	Ok(stats)
+}
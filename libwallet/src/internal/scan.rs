@@ -17,22 +17,26 @@ use crate::api_impl::owner_updater::StatusMessage;
 use crate::grin_core::consensus::{valid_header_version, WEEK_HEIGHT};
 use crate::grin_core::core::HeaderVersion;
 use crate::grin_core::global;
-use crate::grin_core::libtx::proof;
+use crate::grin_core::libtx::{proof, secp_ser};
+use crate::grin_core::ser;
 use crate::grin_keychain::{Identifier, Keychain, SwitchCommitmentType};
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::secp::pedersen;
 use crate::grin_util::static_secp_instance;
 use crate::grin_util::Mutex;
+use crate::internal::cold;
+use crate::internal::expiry;
+use crate::internal::header_sync;
 use crate::internal::keys;
-use crate::internal::tx;
 use crate::types::*;
 use crate::{wallet_lock, Error, ErrorKind};
+use chrono::Utc;
 use grin_core::core::Transaction;
 use grin_wallet_util::grin_util as util;
 use std::cmp;
 use std::collections::{HashMap, HashSet};
-use std::sync::mpsc::Sender;
-use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
 // Wallet - node sync up strategy. We can request blocks from the node and analyze them. 1 week of blocks can be requested in theory.
@@ -48,10 +52,139 @@ const OUTPUT_TO_BLOCK: usize = 100;
 // of the node load. 4 is a reasonable number
 const SYNC_BLOCKS_THREADS: usize = 4;
 
+// Number of outputs that are requested from the node in one call
+const OUTPUTS_BATCH_SIZE: u64 = 1000;
+
+// How many batches of outputs can be downloaded ahead of the range proofs rewinding
+const OUTPUTS_BATCH_PREFETCH: usize = 2;
+
+// Owner request to the running scan
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScanRequest {
+	None,
+	Pause,
+	Cancel,
+}
+
+// Chain outputs collection of a wallet instance that is in progress
+struct ScanControl {
+	running: Option<ScanCheckpoint>,
+	request: ScanRequest,
+}
+
+/// Default number of threads that rewind the range proofs during the chain scan
+pub const DEFAULT_SCAN_THREADS: usize = 4;
+
+lazy_static! {
+	// Running scans by the wallet instance. The scan holds the wallet lock, so its control
+	// can't be stored in the wallet.
+	static ref SCAN_CONTROL: RwLock<HashMap<usize, ScanControl>> = RwLock::new(HashMap::new());
+}
+
+// Key of the wallet instance in SCAN_CONTROL
+fn instance_key<'a, L, C, K>(wallet_inst: &Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>) -> usize
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	Arc::as_ptr(wallet_inst) as *const () as usize
+}
+
+/// Progress of the running scan of the wallet instance, None if no scan is collecting
+/// the chain outputs now
+pub fn running_scan<'a, L, C, K>(
+	wallet_inst: &Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+) -> Option<ScanCheckpoint>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	SCAN_CONTROL
+		.read()
+		.unwrap()
+		.get(&instance_key(wallet_inst))
+		.and_then(|control| control.running.clone())
+}
+
+/// Ask the running scan of the wallet instance to stop after the current batch of outputs.
+/// The checkpoint is kept, so the next scan of the same range resumes from it. Returns
+/// false if no scan is running.
+pub fn pause_scan<'a, L, C, K>(wallet_inst: &Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>) -> bool
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	request_scan(instance_key(wallet_inst), ScanRequest::Pause)
+}
+
+/// Ask the running scan of the wallet instance to stop after the current batch of outputs
+/// and drop its checkpoint. Returns false if no scan is running.
+pub fn cancel_scan<'a, L, C, K>(wallet_inst: &Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>) -> bool
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	request_scan(instance_key(wallet_inst), ScanRequest::Cancel)
+}
+
+fn request_scan(key: usize, request: ScanRequest) -> bool {
+	match SCAN_CONTROL.write().unwrap().get_mut(&key) {
+		Some(control) if control.running.is_some() => {
+			control.request = request;
+			true
+		}
+		_ => false,
+	}
+}
+
+// Marks the scan of the wallet instance as running while the chain outputs are collected
+struct RunningScan {
+	key: usize,
+}
+
+impl RunningScan {
+	fn start(key: usize, checkpoint: &ScanCheckpoint) -> Self {
+		SCAN_CONTROL.write().unwrap().insert(
+			key,
+			ScanControl {
+				running: Some(checkpoint.clone()),
+				request: ScanRequest::None,
+			},
+		);
+		RunningScan { key }
+	}
+
+	// Publish the progress and return the pending owner request
+	fn update(&self, checkpoint: &ScanCheckpoint) -> ScanRequest {
+		let mut controls = SCAN_CONTROL.write().unwrap();
+		match controls.get_mut(&self.key) {
+			Some(control) => {
+				control.running = Some(checkpoint.clone());
+				control.request
+			}
+			None => ScanRequest::None,
+		}
+	}
+}
+
+impl Drop for RunningScan {
+	fn drop(&mut self) {
+		SCAN_CONTROL.write().unwrap().remove(&self.key);
+	}
+}
+
 /// Utility struct for return values from below
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OutputResult {
 	///
+	#[serde(
+		serialize_with = "secp_ser::as_hex",
+		deserialize_with = "secp_ser::commitment_from_hex"
+	)]
 	pub commit: pedersen::Commitment,
 	///
 	pub key_id: Identifier,
@@ -93,6 +226,24 @@ impl OutputResult {
 	}
 }
 
+impl ser::Writeable for OutputResult {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("OutputResult to json conversion failed, {}", e))
+		})?;
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for OutputResult {
+	fn read(reader: &mut dyn ser::Reader) -> Result<OutputResult, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to OutputResult conversion failed, {}", e))
+		})
+	}
+}
+
 #[derive(Debug, Clone)]
 /// Collect stats in case we want to just output a single tx log entry
 /// for restored non-coinbase outputs
@@ -107,9 +258,51 @@ pub struct RestoredTxStats {
 	pub output_height: u64,
 }
 
-fn identify_utxo_outputs<'a, K>(
+/// Rewind the range proofs of the chain outputs with the `threads` workers and return the
+/// outputs that belong to the wallet. The result keeps the order of `outputs`.
+pub fn identify_utxo_outputs<'a, K>(
 	keychain: &K,
 	outputs: Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)>,
+	threads: usize,
+) -> Result<Vec<OutputResult>, Error>
+where
+	K: Keychain + 'a,
+{
+	// Small batches are not worth the threads
+	if threads <= 1 || outputs.len() < threads * 10 {
+		return rewind_outputs(keychain, &outputs);
+	}
+
+	let chunk_size = (outputs.len() + threads - 1) / threads;
+	let results = crossbeam_utils::thread::scope(|s| {
+		let handles: Vec<_> = outputs
+			.chunks(chunk_size)
+			.map(|chunk| s.spawn(move |_| rewind_outputs(keychain, chunk)))
+			.collect();
+		handles
+			.into_iter()
+			.map(|h| {
+				h.join().unwrap_or_else(|_| {
+					Err(
+						ErrorKind::GenericError("Range proof rewind thread panicked".to_string())
+							.into(),
+					)
+				})
+			})
+			.collect::<Vec<_>>()
+	})
+	.map_err(|_| ErrorKind::GenericError("Range proof rewind thread panicked".to_string()))?;
+
+	let mut wallet_outputs = Vec::new();
+	for res in results {
+		wallet_outputs.extend(res?);
+	}
+	Ok(wallet_outputs)
+}
+
+fn rewind_outputs<'a, K>(
+	keychain: &K,
+	outputs: &[(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)],
 ) -> Result<Vec<OutputResult>, Error>
 where
 	K: Keychain + 'a,
@@ -178,6 +371,9 @@ where
 }
 
 /// Scanning chain for the outputs. Shared with mwc713
+/// The next batches of outputs are downloaded from the node while the current batch
+/// is rewinding, so the node round trips are hidden behind the range proof checks.
+/// `DEFAULT_SCAN_THREADS` threads rewind the range proofs.
 pub fn collect_chain_outputs<'a, C, K>(
 	keychain: &K,
 	client: C,
@@ -190,36 +386,94 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	let batch_size = 1000;
-	let start_index_stat = start_index;
-	let mut start_index = start_index;
-	let mut result_vec: Vec<OutputResult> = vec![];
-	loop {
-		let (highest_index, last_retrieved_index, outputs) =
-			client.get_outputs_by_pmmr_index(start_index, end_index, batch_size)?;
-
-		let range = highest_index as f64 - start_index_stat as f64;
-		let progress = last_retrieved_index as f64 - start_index_stat as f64;
-		let perc_complete = cmp::min(((progress / range) * 100.0) as u8, 99);
-
-		let msg = format!(
-			"Checking {} outputs, up to index {}. (Highest index: {})",
-			outputs.len(),
-			highest_index,
-			last_retrieved_index,
-		);
-		if let Some(ref s) = status_send_channel {
-			let _ = s.send(StatusMessage::Scanning(show_progress, msg, perc_complete));
-		}
+	collect_chain_outputs_from(
+		keychain,
+		client,
+		start_index,
+		start_index,
+		end_index,
+		DEFAULT_SCAN_THREADS,
+		status_send_channel,
+		show_progress,
+		|_, _| Ok(()),
+	)
+}
 
-		result_vec.append(&mut identify_utxo_outputs(keychain, outputs)?);
+// Scanning chain for the outputs from `start_index` with `threads` rewinding threads.
+// `start_index_stat` is the start of the whole range, it is used for the progress report.
+// `on_batch` is called with the last processed index and the wallet outputs after every
+// batch.
+fn collect_chain_outputs_from<'a, C, K, F>(
+	keychain: &K,
+	client: C,
+	start_index_stat: u64,
+	start_index: u64,
+	end_index: Option<u64>,
+	threads: usize,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	show_progress: bool,
+	mut on_batch: F,
+) -> Result<Vec<OutputResult>, Error>
+where
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+	F: FnMut(u64, &[OutputResult]) -> Result<(), Error>,
+{
+	let threads = cmp::max(threads, 1);
+	let (batch_tx, batch_rx) = mpsc::sync_channel(OUTPUTS_BATCH_PREFETCH);
+
+	crossbeam_utils::thread::scope(|s| {
+		// Receiver is dropped on the early return, so the fetcher doesn't block on the send
+		let batch_rx = batch_rx;
+		s.spawn(move |_| {
+			let mut start_index = start_index;
+			loop {
+				let res =
+					client.get_outputs_by_pmmr_index(start_index, end_index, OUTPUTS_BATCH_SIZE);
+				let next_index = match &res {
+					Ok((highest_index, last_retrieved_index, _))
+						if highest_index > last_retrieved_index =>
+					{
+						Some(last_retrieved_index + 1)
+					}
+					_ => None,
+				};
+				// Send fails if the scan was interrupted by an error
+				if batch_tx.send(res).is_err() {
+					break;
+				}
+				match next_index {
+					Some(i) => start_index = i,
+					None => break,
+				}
+			}
+		});
 
-		if highest_index <= last_retrieved_index {
-			break;
+		let mut result_vec: Vec<OutputResult> = vec![];
+		for batch in batch_rx.iter() {
+			let (highest_index, last_retrieved_index, outputs) = batch?;
+
+			let range = highest_index as f64 - start_index_stat as f64;
+			let progress = last_retrieved_index as f64 - start_index_stat as f64;
+			let perc_complete = cmp::min(((progress / range) * 100.0) as u8, 99);
+
+			let msg = format!(
+				"Checking {} outputs, up to index {}. (Highest index: {})",
+				outputs.len(),
+				highest_index,
+				last_retrieved_index,
+			);
+			if let Some(ref s) = status_send_channel {
+				let _ = s.send(StatusMessage::Scanning(show_progress, msg, perc_complete));
+			}
+
+			let mut found = identify_utxo_outputs(keychain, outputs, threads)?;
+			on_batch(last_retrieved_index, &found)?;
+			result_vec.append(&mut found);
 		}
-		start_index = last_retrieved_index + 1;
-	}
-	Ok(result_vec)
+		Ok(result_vec)
+	})
+	.map_err(|_| ErrorKind::GenericError("Chain scan thread panicked".to_string()))?
 }
 
 /// Respore missing outputs. Shared with mwc713
@@ -287,6 +541,10 @@ where
 		lock_height: output.lock_height,
 		is_coinbase: output.is_coinbase,
 		tx_log_entry: Some(log_id),
+		frozen: false,
+		note: None,
+		tags: vec![],
+		cold: false,
 	});
 
 	let max_child_index = *found_parents.get(&parent_key_id).unwrap_or(&0);
@@ -345,6 +603,7 @@ struct WalletTxInfo {
 	input_commit: HashSet<String>,   // Commits from input (if found)
 	output_commit: HashSet<String>,  // Commits from output (if found)
 	kernel_validation: Option<bool>, // Kernel validation flag. None - mean not validated because of height
+	kernel_mmr_index: Option<u64>,   // Kernel MMR index, checked against the verified header chain
 }
 
 impl WalletTxInfo {
@@ -364,6 +623,7 @@ impl WalletTxInfo {
 				.collect(),
 			tx_log,
 			kernel_validation: None,
+			kernel_mmr_index: None,
 		}
 	}
 
@@ -439,8 +699,9 @@ where
 	// Collecting Outputs with known commits only.
 	// Really hard to say why Output can be without commit. Probably same non complete or failed data.
 	// In any case we can't use it for recovering.
+	// Cold wallet outputs can't be rewound, they are refreshed by the commitment.
 	let mut last_output = String::new();
-	for w_out in w.iter().filter(|w| w.commit.is_some()) {
+	for w_out in w.iter().filter(|w| w.commit.is_some() && !w.cold) {
 		outputs.insert(
 			w_out.commit.clone().unwrap(),
 			WalletOutputInfo::new(w_out.clone()),
@@ -712,16 +973,101 @@ where
 
 		// Retrieve the actual PMMR index range we're looking for
 		let pmmr_range = client.height_range_to_pmmr_indices(start_height, Some(end_height))?;
+		let end_hash = client.get_header_info(end_height)?.hash;
+
+		// Continue the interrupted scan of the same range if its blocks are still on the chain
+		let (mut checkpoint, mut found_outs) = match w.get_scan_checkpoint()? {
+			Some(cp)
+				if cp.start_height == start_height
+					&& cp.start_pmmr_index == pmmr_range.0
+					&& cp.end_height <= end_height
+					&& client.get_header_info(cp.end_height)?.hash == cp.end_hash =>
+			{
+				if let Some(ref s) = status_send_channel {
+					let _ = s.send(StatusMessage::Scanning(
+						show_progress,
+						format!(
+							"Resuming the interrupted scan from PMMR index {}",
+							cp.last_pmmr_index + 1
+						),
+						cp.progress(),
+					));
+				}
+				let outs: Vec<OutputResult> = w.scan_output_iter().collect();
+				(cp, outs)
+			}
+			_ => {
+				let mut batch = w.batch(keychain_mask)?;
+				batch.delete_scan_checkpoint()?;
+				batch.commit()?;
+				let cp = ScanCheckpoint {
+					start_height,
+					end_height,
+					end_hash: end_hash.clone(),
+					start_pmmr_index: pmmr_range.0,
+					end_pmmr_index: pmmr_range.1,
+					last_pmmr_index: pmmr_range.0.saturating_sub(1),
+					outputs_found: 0,
+					child_indexes: vec![],
+					updated: Utc::now(),
+				};
+				(cp, vec![])
+			}
+		};
+		checkpoint.end_height = end_height;
+		checkpoint.end_hash = end_hash;
+		checkpoint.end_pmmr_index = pmmr_range.1;
+
+		// Getting outputs that are published on the chain. The progress is saved after
+		// every batch, so the scan can be paused or survive the wallet restart.
+		let running = RunningScan::start(instance_key(&wallet_inst), &checkpoint);
+		let next_index = checkpoint.last_pmmr_index + 1;
+		if next_index <= pmmr_range.1 {
+			let threads = w.settings().scan_threads;
+			let mut new_outs = collect_chain_outputs_from(
+				&keychain,
+				client,
+				pmmr_range.0,
+				next_index,
+				Some(pmmr_range.1),
+				threads,
+				status_send_channel,
+				show_progress,
+				|last_index, outputs| {
+					checkpoint.add_outputs(last_index, outputs);
+					let mut batch = w.batch(keychain_mask)?;
+					for out in outputs {
+						batch.save_scan_output(out.clone())?;
+					}
+					batch.save_scan_checkpoint(checkpoint.clone())?;
+					batch.commit()?;
+
+					// Found keys must not be reused if the wallet is used before the scan resumes
+					for (parent_key_id, max_child_index) in &checkpoint.child_indexes {
+						if *max_child_index >= w.current_child_index(parent_key_id)? {
+							let mut batch = w.batch(keychain_mask)?;
+							batch.save_child_index(parent_key_id, max_child_index + 1)?;
+							batch.commit()?;
+						}
+					}
 
-		// Getting outputs that are published on the chain.
-		chain_outs = collect_chain_outputs(
-			&keychain,
-			client,
-			pmmr_range.0,
-			Some(pmmr_range.1),
-			status_send_channel,
-			show_progress,
-		)?;
+					match running.update(&checkpoint) {
+						ScanRequest::None => Ok(()),
+						ScanRequest::Pause => {
+							Err(ErrorKind::ScanPaused(checkpoint.last_pmmr_index).into())
+						}
+						ScanRequest::Cancel => {
+							let mut batch = w.batch(keychain_mask)?;
+							batch.delete_scan_checkpoint()?;
+							batch.commit()?;
+							Err(ErrorKind::ScanCancelled.into())
+						}
+					}
+				},
+			)?;
+			found_outs.append(&mut new_outs);
+		}
+		chain_outs = found_outs;
 
 		// Reporting user what outputs we found
 		if let Some(ref s) = status_send_channel {
@@ -771,8 +1117,9 @@ where
 					)?;
 
 					match res {
-						Some((txkernel, height, _mmr_index)) => {
+						Some((txkernel, height, mmr_index)) => {
 							tx.kernel_validation = Some(true);
+							tx.kernel_mmr_index = Some(mmr_index);
 							assert!(txkernel.excess == *kernel);
 							tx.tx_log.output_height = height; // Height must come from kernel and will match heights of outputs
 							tx.updated = true;
@@ -948,6 +1295,32 @@ where
 		do_full_outputs_refresh,
 	)?;
 
+	// Outputs and kernels are confirmed only in the blocks that the header chain verified
+	{
+		wallet_lock!(wallet_inst, w);
+		if w.settings().header_verification {
+			let tip = header_sync::sync_headers(
+				&mut **w,
+				keychain_mask,
+				tip_height,
+				status_send_channel,
+				show_progress,
+			)?;
+			header_sync::check_outputs(
+				&**w,
+				&tip,
+				chain_outs.iter().map(|o| (o.height, o.mmr_index)),
+			)?;
+			header_sync::check_kernels(
+				&**w,
+				&tip,
+				transactions
+					.values()
+					.filter_map(|t| t.kernel_mmr_index.map(|i| (t.tx_log.output_height, i))),
+			)?;
+		}
+	}
+
 	// Printing values for debug...
 	/*	{
 		println!("Chain range: Heights: {} to {}", start_height, tip_height );
@@ -1013,36 +1386,6 @@ where
 	// Here we are done with all state changes of Outputs and transactions. Now we need to save them at the DB
 	// Note, unknown new outputs are not here because we handle them in the beginning by 'restore'.
 
-	// Cancel any cancellable transactions with an expired TTL
-	for tx in transactions
-		.values()
-		.filter(|tx| !(tx.tx_log.confirmed || tx.tx_log.is_cancelled()))
-	{
-		if let Some(h) = tx.tx_log.ttl_cutoff_height {
-			if tip_height >= h {
-				wallet_lock!(wallet_inst, w);
-				match tx::cancel_tx(
-					&mut **w,
-					keychain_mask,
-					&tx.tx_log.parent_key_id,
-					Some(tx.tx_log.id),
-					None,
-				) {
-					Err(e) => {
-						if let Some(ref s) = status_send_channel {
-							let _ = s.send(StatusMessage::Warning(format!(
-								"Unable to cancel TTL expired transaction {} because of error: {}",
-								tx.tx_uuid.split('/').next().unwrap_or("????"),
-								e
-							)));
-						}
-					}
-					_ => (),
-				}
-			}
-		}
-	}
-
 	// Apply last data updates and saving the data into DB.
 	{
 		store_transactions_outputs(
@@ -1066,6 +1409,56 @@ where
 		)?;
 	}
 
+	// Outputs of the offline cold wallet are looked up by the commitment
+	{
+		wallet_lock!(wallet_inst, w);
+		let changed = cold::refresh_outputs(&mut **w, keychain_mask)?;
+		if changed > 0 {
+			if let Some(ref s) = status_send_channel {
+				let _ = s.send(StatusMessage::Info(format!(
+					"{} cold wallet outputs are updated",
+					changed
+				)));
+			}
+		}
+	}
+
+	// Cancel the transactions with an expired TTL, they are not going to be on the chain
+	let auto_cancel_expired = {
+		wallet_lock!(wallet_inst, w);
+		w.settings().auto_cancel_expired
+	};
+	if auto_cancel_expired {
+		wallet_lock!(wallet_inst, w);
+		for expired in expiry::expired_txs(&mut **w, keychain_mask, tip_height)? {
+			let tx_id = expired
+				.tx
+				.tx_slate_id
+				.as_ref()
+				.map(|id| id.to_string())
+				.unwrap_or(expired.tx.id.to_string());
+			match expiry::cancel_expired_tx(&mut **w, keychain_mask, &expired, tip_height) {
+				Ok(_) => {
+					if let Some(ref s) = status_send_channel {
+						let _ = s.send(StatusMessage::Info(format!(
+							"TTL expired transaction {} is cancelled, {} inputs are unlocked",
+							tx_id,
+							expired.locked_inputs.len()
+						)));
+					}
+				}
+				Err(e) => {
+					if let Some(ref s) = status_send_channel {
+						let _ = s.send(StatusMessage::Warning(format!(
+							"Unable to cancel TTL expired transaction {} because of error: {}",
+							tx_id, e
+						)));
+					}
+				}
+			}
+		}
+	}
+
 	// Updating confirmed height record. The height at what we finish updating the data
 	// Updating 'done' job for all accounts that was involved. Update was done for all accounts- let's update that
 	{
@@ -1077,6 +1470,8 @@ where
 		for par_id in &accounts {
 			batch.save_last_confirmed_height(par_id, tip_height)?;
 		}
+		// Scan is complete, nothing to resume
+		batch.delete_scan_checkpoint()?;
 		batch.commit()?;
 	}
 
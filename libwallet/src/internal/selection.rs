@@ -19,12 +19,11 @@ use crate::grin_core::core::amount_to_hr_string;
 use crate::grin_core::libtx::{
 	build,
 	proof::{ProofBuild, ProofBuilder},
-	tx_fee,
 };
 use crate::grin_keychain::{Identifier, Keychain};
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::secp::pedersen::Commitment;
-use crate::internal::keys;
+use crate::internal::{fee, keys};
 use crate::proof::proofaddress;
 use crate::slate::Slate;
 use crate::types::*;
@@ -109,6 +108,68 @@ where
 	Ok(context)
 }
 
+/// Initialize a transaction on the sender side that spends all the given `coins` with
+/// a single change output. The coins are not selected, the caller already owns them, so
+/// they might be unconfirmed. Used by the batch payout, where every recipient spends
+/// the change of the previous one.
+pub fn build_send_tx_from_coins<'a, T: ?Sized, C, K>(
+	wallet: &mut T,
+	keychain: &K,
+	keychain_mask: Option<&SecretKey>,
+	slate: &mut Slate,
+	coins: &[OutputData],
+	parent_key_id: Identifier,
+	use_test_nonce: bool,
+) -> Result<Context, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let fee_policy = wallet.settings().fee_policy.clone();
+	let total: u64 = coins.iter().map(|c| c.value).sum();
+	let mut fee = fee::tx_fee(fee_policy.fee_per_kweight, coins.len(), 2, 1);
+	if total < slate.amount + fee {
+		// The exact amount is spent without the change output
+		let fee_no_change = fee::tx_fee(fee_policy.fee_per_kweight, coins.len(), 1, 1);
+		if total != slate.amount + fee_no_change {
+			return Err(ErrorKind::NotEnoughFunds {
+				available: total,
+				available_disp: amount_to_hr_string(total, true),
+				needed: slate.amount + fee,
+				needed_disp: amount_to_hr_string(slate.amount + fee, true),
+			})?;
+		}
+		fee = fee_no_change;
+	}
+	fee::check_max_fee(&fee_policy, slate.amount, fee)?;
+
+	let (elems, change_amounts_derivations) =
+		inputs_and_change(coins, wallet, keychain_mask, slate.amount, fee, 1)?;
+	slate.fee = fee;
+
+	let blinding = slate.add_transaction_elements(keychain, &ProofBuilder::new(keychain), elems)?;
+
+	let mut context = Context::new(
+		keychain.secp(),
+		blinding.secret_key(&keychain.secp())?,
+		&parent_key_id,
+		use_test_nonce,
+		0,
+	);
+
+	context.amount = slate.amount;
+	context.fee = fee;
+	for input in coins {
+		context.add_input(&input.key_id, &input.mmr_index, input.value);
+	}
+	for (change_amount, id, mmr_index) in &change_amounts_derivations {
+		context.add_output(&id, &mmr_index, *change_amount);
+	}
+
+	Ok(context)
+}
+
 /// Locks all corresponding outputs in the context, creates
 /// change outputs and tx log entry
 pub fn lock_tx_context<'a, T: ?Sized, C, K>(
@@ -155,6 +216,9 @@ where
 		t.stored_tx = Some(filename);
 		t.fee = Some(slate.fee);
 		t.ttl_cutoff_height = slate.ttl_cutoff_height;
+		if slate.lock_height > 0 {
+			t.lock_height = Some(slate.lock_height);
+		}
 
 		t.address = address;
 
@@ -220,6 +284,10 @@ where
 				lock_height: 0,
 				is_coinbase: false,
 				tx_log_entry: Some(log_id),
+				frozen: false,
+				note: None,
+				tags: vec![],
+				cold: false,
 			})?;
 		}
 		batch.save_tx_log_entry(t.clone(), &parent_key_id)?;
@@ -355,6 +423,9 @@ where
 	t.output_commits = commit_ped;
 	t.messages = messages;
 	t.ttl_cutoff_height = slate.ttl_cutoff_height;
+	if slate.lock_height > 0 {
+		t.lock_height = Some(slate.lock_height);
+	}
 	// when invoicing, this will be invalid
 	if let Ok(e) = slate.calc_excess(&keychain) {
 		t.kernel_excess = Some(e)
@@ -376,6 +447,10 @@ where
 			lock_height: 0,
 			is_coinbase: false,
 			tx_log_entry: Some(log_id),
+			frozen: false,
+			note: None,
+			tags: vec![],
+			cold: false,
 		})?;
 		i = i + 1;
 	}
@@ -418,6 +493,7 @@ where
 	K: Keychain + 'a,
 	B: ProofBuild,
 {
+	let fee_policy = wallet.settings().fee_policy.clone();
 	let (coins, _total, amount, fee) = select_coins_and_fee(
 		wallet,
 		amount,
@@ -431,7 +507,9 @@ where
 		routputs, // Number of resulting outputs. Normally it is 1
 		exclude_change_outputs,
 		change_output_minimum_confirmations,
+		fee_policy.fee_per_kweight,
 	)?;
+	fee::check_max_fee(&fee_policy, amount, fee)?;
 
 	// build transaction skeleton with inputs and change
 	let (parts, change_amounts_derivations) =
@@ -454,6 +532,7 @@ pub fn select_coins_and_fee<'a, T: ?Sized, C, K>(
 	routputs: usize,               // Number of resulting outputs. Normally it is 1
 	exclude_change_outputs: bool,
 	change_output_minimum_confirmations: u64,
+	fee_per_kweight: Option<u64>, // None for the network default fee
 ) -> Result<
 	(
 		Vec<OutputData>,
@@ -496,7 +575,7 @@ where
 	// First attempt to spend without change
 	assert!(routputs >= 1); // Normally it is 1
 
-	let mut fee = tx_fee(coins.len(), routputs, 1, None);
+	let mut fee = fee::tx_fee(fee_per_kweight, coins.len(), routputs, 1);
 	let mut total: u64 = coins.iter().map(|c| c.value).sum();
 	let mut amount_with_fee = amount + fee;
 
@@ -506,7 +585,7 @@ where
 
 	// We need to add a change address or amount with fee is more than total
 	if total != amount_with_fee {
-		fee = tx_fee(coins.len(), num_outputs, 1, None);
+		fee = fee::tx_fee(fee_per_kweight, coins.len(), num_outputs, 1);
 		amount_with_fee = amount + fee;
 
 		// Here check if we have enough outputs for the amount including fee otherwise
@@ -530,7 +609,7 @@ where
 				change_output_minimum_confirmations,
 			)
 			.1;
-			fee = tx_fee(coins.len(), num_outputs, 1, None);
+			fee = fee::tx_fee(fee_per_kweight, coins.len(), num_outputs, 1);
 			total = coins.iter().map(|c| c.value).sum();
 			amount_with_fee = amount + fee;
 
@@ -667,10 +746,14 @@ where
 		"exclude_change_outputs = {}, change_output_minimum_confirmations = {}",
 		exclude_change_outputs, change_output_minimum_confirmations
 	);
-	// first find all eligible outputs based on number of confirmations
+	// first find all eligible outputs based on number of confirmations.
+	// Frozen outputs are used only if the caller lists the outputs to spend.
 	let mut eligible = wallet
 		.iter()
 		.filter(|out| {
+			if out.frozen && outputs.is_none() {
+				return false;
+			}
 			if out.commit.is_some() && change_outputs.contains_key(out.commit.as_ref().unwrap()) {
 				out.root_key_id == *parent_key_id
 					&& out.eligible_to_spend(current_height, change_output_minimum_confirmations)
@@ -0,0 +1,151 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Outbound spending policy of the accounts. The policy is checked before a send or
+//! an invoice payment selects the inputs.
+
+use crate::api_impl::foreign::normalize_sender;
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::amount_to_hr_string;
+use crate::grin_keychain::{Identifier, Keychain};
+use crate::types::{NodeClient, SpendPolicy, TxLogEntryType, WalletBackend};
+use chrono::{DateTime, Duration, Utc};
+
+/// Amount sent from the account since the given time. Cancelled sends don't count.
+pub fn sent_since<'a, T: ?Sized, C, K>(
+	w: &T,
+	parent_key_id: &Identifier,
+	since: DateTime<Utc>,
+) -> u64
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	w.tx_log_iter()
+		.filter(|t| {
+			t.parent_key_id == *parent_key_id
+				&& t.tx_type == TxLogEntryType::TxSent
+				&& t.creation_ts >= since
+		})
+		.map(|t| {
+			t.amount_debited
+				.saturating_sub(t.amount_credited)
+				.saturating_sub(t.fee.unwrap_or(0))
+		})
+		.sum()
+}
+
+fn violation(rule: &str, reason: String) -> Error {
+	warn!(
+		"Transaction is rejected by the spending policy ({}), {}",
+		rule, reason
+	);
+	ErrorKind::SpendPolicyViolation {
+		rule: rule.to_string(),
+		reason,
+	}
+	.into()
+}
+
+/// Check the outbound transaction against the spending policy of the account.
+/// `destinations` are the known addresses of the recipient.
+pub fn check_spend_policy<'a, T: ?Sized, C, K>(
+	w: &T,
+	parent_key_id: &Identifier,
+	amount: u64,
+	destinations: &[String],
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let policy: SpendPolicy = match w.get_spend_policy(parent_key_id)? {
+		Some(p) => p,
+		None => return Ok(()),
+	};
+	let now = Utc::now();
+
+	if let Some(allowed) = &policy.allowed_destinations {
+		let destinations: Vec<String> = destinations.iter().map(|d| normalize_sender(d)).collect();
+		let dest = allowed
+			.iter()
+			.find(|a| destinations.contains(&normalize_sender(&a.address)));
+		match dest {
+			None => {
+				return Err(violation(
+					"allowed_destinations",
+					format!(
+						"destination {} is not in the allowed list",
+						if destinations.is_empty() {
+							"unknown".to_string()
+						} else {
+							destinations.join(", ")
+						}
+					),
+				))
+			}
+			Some(d) => {
+				let cooldown = policy.new_destination_cooldown_secs.unwrap_or(0);
+				let usable_ts = d.added_ts + Duration::seconds(cooldown as i64);
+				if usable_ts > now {
+					return Err(violation(
+						"new_destination_cooldown_secs",
+						format!(
+							"destination {} is new, it can be paid after {}",
+							d.address,
+							usable_ts.to_rfc3339()
+						),
+					));
+				}
+			}
+		}
+	}
+
+	if let Some(max) = policy.max_per_tx {
+		if amount > max {
+			return Err(violation(
+				"max_per_tx",
+				format!(
+					"amount {} MWC is over the limit {} MWC",
+					amount_to_hr_string(amount, false),
+					amount_to_hr_string(max, false)
+				),
+			));
+		}
+	}
+
+	for (rule, max, window) in &[
+		("max_24h", policy.max_24h, Duration::hours(24)),
+		("max_7d", policy.max_7d, Duration::days(7)),
+	] {
+		if let Some(max) = max {
+			let sent = sent_since(w, parent_key_id, now - *window);
+			if sent + amount > *max {
+				return Err(violation(
+					rule,
+					format!(
+						"{} MWC is already sent, amount {} MWC is over the limit {} MWC",
+						amount_to_hr_string(sent, false),
+						amount_to_hr_string(amount, false),
+						amount_to_hr_string(*max, false)
+					),
+				));
+			}
+		}
+	}
+
+	Ok(())
+}
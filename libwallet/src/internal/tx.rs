@@ -23,7 +23,7 @@ use crate::grin_util as util;
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::secp::{pedersen, Secp256k1, Signature};
 use crate::grin_util::Mutex;
-use crate::internal::{selection, updater};
+use crate::internal::{fee, selection, updater};
 use crate::proof::crypto;
 use crate::proof::crypto::Hex;
 use crate::proof::proofaddress;
@@ -32,7 +32,7 @@ use crate::proof::tx_proof::{push_proof_for_slate, TxProof};
 use crate::signature::Signature as otherSignature;
 use crate::slate::Slate;
 use crate::types::{Context, NodeClient, StoredProofInfo, TxLogEntryType, WalletBackend};
-use crate::{Error, ErrorKind};
+use crate::{Error, ErrorKind, FeeEstimate};
 use ed25519_dalek::Keypair as DalekKeypair;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use ed25519_dalek::SecretKey as DalekSecretKey;
@@ -94,7 +94,8 @@ where
 	Ok(slate)
 }
 
-/// Estimates locked amount and fee for the transaction without creating one
+/// Estimates locked amount and fee for the transaction without creating one, with the
+/// fee policy applied
 /// Caller is responsible for data refresh!!!!
 pub fn estimate_send_tx<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
@@ -108,13 +109,7 @@ pub fn estimate_send_tx<'a, T: ?Sized, C, K>(
 	routputs: usize,               // Number of resulting outputs. Normally it is 1
 	exclude_change_outputs: bool,
 	change_output_minimum_confirmations: u64,
-) -> Result<
-	(
-		u64, // total
-		u64, // fee
-	),
-	Error,
->
+) -> Result<FeeEstimate, Error>
 where
 	T: WalletBackend<'a, C, K>,
 	C: NodeClient + 'a,
@@ -131,7 +126,8 @@ where
 	// according to plan
 	// This function is just a big helper to do all of that, in theory
 	// this process can be split up in any way
-	let (_coins, total, _amount, fee) = selection::select_coins_and_fee(
+	let fee_policy = wallet.settings().fee_policy.clone();
+	let (coins, total, amount, fee) = selection::select_coins_and_fee(
 		wallet,
 		amount,
 		current_height,
@@ -144,8 +140,22 @@ where
 		routputs,
 		exclude_change_outputs,
 		change_output_minimum_confirmations,
+		fee_policy.fee_per_kweight,
 	)?;
-	Ok((total, fee))
+	// No change is built if the inputs match the amount with the fee exactly
+	let num_outputs = if total == amount + fee {
+		routputs
+	} else {
+		routputs + num_change_outputs
+	};
+	Ok(FeeEstimate {
+		amount,
+		total,
+		fee,
+		num_inputs: coins.len(),
+		num_outputs,
+		max_fee: fee::max_fee(&fee_policy, amount),
+	})
 }
 
 /// Add inputs to the slate (effectively becoming the sender)
@@ -392,6 +392,10 @@ where
 			lock_height: lock_height,
 			is_coinbase: true,
 			tx_log_entry: None,
+			frozen: false,
+			note: None,
+			tags: vec![],
+			cold: false,
 		})?;
 		batch.commit()?;
 	}
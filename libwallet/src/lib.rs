@@ -69,25 +69,30 @@ pub use bitcoin::Address as BitcoinAddress;
 pub use crate::error::{Error, ErrorKind};
 pub use crate::slate::{ParticipantData, ParticipantMessageData, ParticipantMessages, Slate};
 pub use crate::slate_versions::{
-	SlateVersion, VersionedCoinbase, VersionedSlate, CURRENT_SLATE_VERSION,
+	SlateEncoding, SlateVersion, VersionedCoinbase, VersionedSlate, CURRENT_SLATE_VERSION,
 	GRIN_BLOCK_HEADER_VERSION,
 };
 pub use api_impl::foreign;
 pub use api_impl::owner;
 pub use api_impl::owner_swap;
-pub use api_impl::owner_updater::StatusMessage;
+pub use api_impl::owner_updater::{get_updater_stats, StatusMessage, UpdaterStats};
 pub use api_impl::types::{
-	BlockFees, InitTxArgs, InitTxSendArgs, IssueInvoiceTxArgs, NodeHeightResult,
-	OutputCommitMapping, PaymentProof, SendTXArgs, SwapStartArgs, VersionInfo,
+	BatchSendArgs, BatchSendEntry, BatchSendResult, BlockFees, ColdSignRequest, ColdSignResponse,
+	ColdSignStep, ExpiredTx, FeeEstimate, InitTxArgs, InitTxSendArgs, IssueInvoiceTxArgs,
+	NodeHeightResult, OutputCommitMapping, PaymentProof, ScanStatus, SendTXArgs, SpendPolicyArgs,
+	SpendPolicyStatus, SwapStartArgs, TransferArgs, TransferResult, VersionInfo,
 };
-pub use internal::scan::scan;
+pub use internal::scan::{scan, OutputResult};
 pub use proof::tx_proof::TxProof;
 pub use proof::tx_proof::{proof_ok, verify_tx_proof_wrapper};
 pub use slate_versions::ser as dalek_ser;
 pub use types::{
-	AcctPathMapping, BlockIdentifier, CbData, Context, HeaderInfo, NodeClient, NodeVersionInfo,
-	OutputData, OutputStatus, ScannedBlockInfo, StoredProofInfo, TxLogEntry, TxLogEntryType,
-	WalletBackend, WalletInfo, WalletInst, WalletLCProvider, WalletOutputBatch,
+	AcctPathMapping, BlockIdentifier, CbData, Contact, Context, HeaderInfo, Invoice, InvoiceStatus,
+	LinkedTx, NodeClient, NodeVersionInfo, OutputData, OutputStatus, PendingSlate,
+	PendingSlateRequest, PendingSlateStatus, RebroadcastState, ScanCheckpoint, ScannedBlockInfo,
+	SpendPolicy, SpendPolicyDestination, StoredProofInfo, TxLogEntry, TxLogEntryType,
+	VerifiedHeader, WalletBackend, WalletInfo, WalletInst, WalletLCProvider, WalletOutputBatch,
+	WalletSettings,
 };
 
 pub use api_impl::foreign::{get_receive_account, set_receive_account};
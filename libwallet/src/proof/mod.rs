@@ -27,6 +27,8 @@ pub mod crypto;
 pub mod hasher;
 /// Proff messages
 pub mod message;
+/// Standalone payment proof document and its verification
+pub mod payment_proof;
 /// Addresses
 pub mod proofaddress;
 /// Proofs that come froom mwc713. Expected that they will be used for all transports
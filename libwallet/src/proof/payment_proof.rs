@@ -0,0 +1,202 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Standalone payment proof document. The document contains everything that is needed
+//! to verify the payment: the kernel, amount, sender and recipient addresses with their
+//! signatures and the block where the kernel was mined. Verification doesn't need a wallet.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::hash::Hashed;
+use crate::grin_core::core::TxKernel;
+use crate::grin_core::libtx::secp_ser;
+use crate::grin_util as util;
+use crate::internal::tx;
+use crate::proof::crypto;
+use crate::proof::proofaddress::{self, ProvableAddress};
+use crate::types::NodeClient;
+use ed25519_dalek::Signature as DalekSignature;
+use ed25519_dalek::Verifier;
+
+/// Type of the document, so the proof file describes itself
+pub const PAYMENT_PROOF_DOCUMENT_TYPE: &str = "mwc_payment_proof";
+
+/// Current version of the payment proof document
+pub const PAYMENT_PROOF_DOCUMENT_VERSION: u16 = 1;
+
+/// Standalone payment proof.
+/// Both signatures are done for the message `kernel_excess|sender_address|amount`,
+/// the same message that is used by the payment proofs in the slate.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentProofDocument {
+	/// Document type, PAYMENT_PROOF_DOCUMENT_TYPE
+	pub proof_type: String,
+	/// Document version
+	pub version: u16,
+	/// Amount that was paid
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Transaction kernel
+	pub kernel: TxKernel,
+	/// Sender address
+	#[serde(
+		serialize_with = "proofaddress::as_string",
+		deserialize_with = "proofaddress::proof_address_from_string"
+	)]
+	pub sender_address: ProvableAddress,
+	/// Sender signature
+	pub sender_sig: String,
+	/// Recipient address
+	#[serde(
+		serialize_with = "proofaddress::as_string",
+		deserialize_with = "proofaddress::proof_address_from_string"
+	)]
+	pub recipient_address: ProvableAddress,
+	/// Recipient signature
+	pub recipient_sig: String,
+	/// Height of the block with the kernel
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub block_height: u64,
+	/// Hash of the block with the kernel
+	pub block_hash: String,
+}
+
+impl PaymentProofDocument {
+	/// Read the document from json. Returns error if json is not a payment proof document
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		let doc: PaymentProofDocument = serde_json::from_str(json).map_err(|e| {
+			ErrorKind::PaymentProofParsing(format!("Unable to read payment proof document, {}", e))
+		})?;
+		if doc.proof_type != PAYMENT_PROOF_DOCUMENT_TYPE {
+			return Err(ErrorKind::PaymentProofParsing(format!(
+				"Unexpected document type {}",
+				doc.proof_type
+			))
+			.into());
+		}
+		Ok(doc)
+	}
+
+	/// Write the document to json
+	pub fn to_json(&self) -> Result<String, Error> {
+		serde_json::to_string_pretty(self).map_err(|e| {
+			ErrorKind::PaymentProofParsing(format!("Unable to build payment proof document, {}", e))
+				.into()
+		})
+	}
+}
+
+/// Verify the payment proof document. It is a pure check, no wallet or node is needed:
+/// the kernel signature and both sender and recipient signatures are validated.
+/// Note, it doesn't prove that the kernel is on the chain, use
+/// verify_payment_proof_document_on_chain for that.
+pub fn verify_payment_proof_document(doc: &PaymentProofDocument) -> Result<(), Error> {
+	if doc.proof_type != PAYMENT_PROOF_DOCUMENT_TYPE {
+		return Err(ErrorKind::PaymentProofParsing(format!(
+			"Unexpected document type {}",
+			doc.proof_type
+		))
+		.into());
+	}
+	if doc.version != PAYMENT_PROOF_DOCUMENT_VERSION {
+		return Err(ErrorKind::PaymentProofParsing(format!(
+			"Unsupported payment proof document version {}",
+			doc.version
+		))
+		.into());
+	}
+
+	doc.kernel.verify().map_err(|e| {
+		ErrorKind::PaymentProof(format!("Invalid transaction kernel signature, {:?}", e))
+	})?;
+
+	let msg = tx::payment_proof_message(
+		doc.amount,
+		&doc.kernel.excess,
+		doc.sender_address.public_key.clone(),
+	)?;
+
+	verify_address_signature(&msg, &doc.recipient_sig, &doc.recipient_address)
+		.map_err(|e| ErrorKind::PaymentProof(format!("Invalid recipient signature, {}", e)))?;
+	verify_address_signature(&msg, &doc.sender_sig, &doc.sender_address)
+		.map_err(|e| ErrorKind::PaymentProof(format!("Invalid sender signature, {}", e)))?;
+
+	Ok(())
+}
+
+/// Verify the payment proof document and check with the node that the kernel is
+/// mined at the block from the document.
+pub fn verify_payment_proof_document_on_chain<C: NodeClient>(
+	doc: &PaymentProofDocument,
+	client: &C,
+) -> Result<(), Error> {
+	verify_payment_proof_document(doc)?;
+
+	let (kernel, height, _mmr_index) = match client.get_kernel(&doc.kernel.excess, None, None)? {
+		Some(k) => k,
+		None => {
+			return Err(ErrorKind::PaymentProof(format!(
+				"Transaction kernel with excess {} not found on chain",
+				util::to_hex(doc.kernel.excess.0.to_vec())
+			))
+			.into());
+		}
+	};
+	if kernel.hash() != doc.kernel.hash() {
+		return Err(ErrorKind::PaymentProof(
+			"Transaction kernel on chain doesn't match the proof kernel".to_string(),
+		)
+		.into());
+	}
+	if height != doc.block_height {
+		return Err(ErrorKind::PaymentProof(format!(
+			"Transaction kernel is mined at height {}, proof expects {}",
+			height, doc.block_height
+		))
+		.into());
+	}
+	let header = client.get_header_info(height)?;
+	if header.hash != doc.block_hash {
+		return Err(ErrorKind::PaymentProof(format!(
+			"Block hash at height {} is {}, proof expects {}",
+			height, header.hash, doc.block_hash
+		))
+		.into());
+	}
+	Ok(())
+}
+
+// MQS addresses sign with secp256k1, tor addresses with ed25519
+fn verify_address_signature(
+	msg: &str,
+	signature: &str,
+	address: &ProvableAddress,
+) -> Result<(), Error> {
+	if address.public_key.len() == 56 {
+		let sig_bytes = util::from_hex(signature).map_err(|e| {
+			ErrorKind::TxProofGenericError(format!("Unable to read signature HEX, {}", e))
+		})?;
+		let sig = DalekSignature::from_bytes(&sig_bytes).map_err(|e| {
+			ErrorKind::TxProofGenericError(format!("Unable to read signature, {}", e))
+		})?;
+		address
+			.tor_public_key()?
+			.verify(msg.as_bytes(), &sig)
+			.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+	} else {
+		let sig = crypto::signature_from_string(signature)?;
+		crypto::verify_signature(msg, &sig, &address.public_key()?)
+			.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+	}
+	Ok(())
+}
@@ -38,6 +38,7 @@ use std::fmt;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::slate_versions::binary::CompactSlate;
 use crate::slate_versions::v2::SlateV2;
 use crate::slate_versions::v2::SlateV2ParseTTL;
 
@@ -246,6 +247,11 @@ impl Slate {
 
 	/// Recieve a slate, upgrade it to the latest version internally
 	pub fn deserialize_upgrade(slate_json: &str) -> Result<Slate, Error> {
+		// Compact slate might come instead of json, it is always V3
+		if CompactSlate::is_armored(slate_json) {
+			return Ok(CompactSlate::from_armored(slate_json)?.0.into());
+		}
+
 		let version = Slate::parse_slate_version(slate_json)?;
 
 		//I don't think we need to do this for coin_type and network_type, the slate containing these two
@@ -0,0 +1,435 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact binary encoding of the slate. Binary data uses grin_core::ser and contains the same
+//! fields as SlateV3. For the text transports (json, files, mwcmqs) the binary data
+//! is armored into a base64 string with a prefix.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::transaction::OutputFeatures;
+use crate::grin_core::ser::{self, Readable, Reader, Writeable, Writer};
+use crate::grin_keychain::BlindingFactor;
+use crate::grin_util::secp::key::PublicKey;
+use crate::grin_util::secp::pedersen::{Commitment, RangeProof};
+use crate::grin_util::secp::{ContextFlag, Secp256k1, Signature};
+use crate::proof::proofaddress::ProvableAddress;
+use crate::slate::CompatKernelFeatures;
+use crate::slate_versions::v3::{
+	InputV3, OutputV3, ParticipantDataV3, PaymentInfoV3, SlateV3, TransactionBodyV3, TransactionV3,
+	TxKernelV3, VersionCompatInfoV3,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+/// Prefix of the armored compact slate string
+pub const COMPACT_SLATE_PREFIX: &str = "BSLATE";
+
+/// Version of the binary layout. Must be updated if the layout is changed.
+const COMPACT_SLATE_FORMAT: u8 = 1;
+
+/// Max number of the items in the slate lists (inputs, outputs, kernels, participants).
+/// Just a sanity check, real slates are limited by the block weight.
+const MAX_LIST_ITEMS: u64 = 100_000;
+
+/// Slate in the compact binary encoding. In json it is serialized as an armored string.
+#[derive(Debug, Clone)]
+pub struct CompactSlate(pub SlateV3);
+
+impl CompactSlate {
+	/// Check if string is looks like an armored compact slate
+	pub fn is_armored(data: &str) -> bool {
+		data.trim().starts_with(COMPACT_SLATE_PREFIX)
+	}
+
+	/// Serialize the slate into the binary data
+	pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+		ser::ser_vec(self, ser::ProtocolVersion(1)).map_err(|e| {
+			ErrorKind::SlateSer(format!("Unable to build compact slate, {}", e)).into()
+		})
+	}
+
+	/// Read the slate from the binary data
+	pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+		let mut data = data;
+		ser::deserialize::<CompactSlate>(&mut data, ser::ProtocolVersion(1)).map_err(|e| {
+			ErrorKind::SlateDeser(format!("Unable to read compact slate, {}", e)).into()
+		})
+	}
+
+	/// Serialize the slate into the armored string
+	pub fn to_armored(&self) -> Result<String, Error> {
+		Ok(format!(
+			"{}{}",
+			COMPACT_SLATE_PREFIX,
+			base64::encode(&self.to_bytes()?)
+		))
+	}
+
+	/// Read the slate from the armored string
+	pub fn from_armored(data: &str) -> Result<Self, Error> {
+		let data = data.trim();
+		if !data.starts_with(COMPACT_SLATE_PREFIX) {
+			return Err(ErrorKind::SlateDeser(
+				"Compact slate data doesn't have expected prefix".to_string(),
+			)
+			.into());
+		}
+		let bin = base64::decode(&data[COMPACT_SLATE_PREFIX.len()..])
+			.map_err(|e| ErrorKind::SlateDeser(format!("Unable to decode compact slate, {}", e)))?;
+		CompactSlate::from_bytes(&bin)
+	}
+}
+
+impl Serialize for CompactSlate {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		use serde::ser::Error;
+		let armored = self.to_armored().map_err(S::Error::custom)?;
+		serializer.serialize_str(&armored)
+	}
+}
+
+impl<'de> Deserialize<'de> for CompactSlate {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		use serde::de::Error;
+		let armored = String::deserialize(deserializer)?;
+		CompactSlate::from_armored(&armored).map_err(D::Error::custom)
+	}
+}
+
+impl Writeable for CompactSlate {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u8(COMPACT_SLATE_FORMAT)?;
+		self.0.write(writer)
+	}
+}
+
+impl Readable for CompactSlate {
+	fn read(reader: &mut dyn Reader) -> Result<CompactSlate, ser::Error> {
+		let format = reader.read_u8()?;
+		if format != COMPACT_SLATE_FORMAT {
+			return Err(ser::Error::CorruptedData(format!(
+				"Unsupported compact slate format {}",
+				format
+			)));
+		}
+		Ok(CompactSlate(SlateV3::read(reader)?))
+	}
+}
+
+impl Writeable for SlateV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u16(self.version_info.version)?;
+		writer.write_u16(self.version_info.orig_version)?;
+		writer.write_u16(self.version_info.block_header_version)?;
+		writer.write_u64(self.num_participants as u64)?;
+		writer.write_fixed_bytes(self.id.as_bytes())?;
+		self.tx.offset.write(writer)?;
+		write_list(writer, &self.tx.body.inputs)?;
+		write_list(writer, &self.tx.body.outputs)?;
+		write_list(writer, &self.tx.body.kernels)?;
+		writer.write_u64(self.amount)?;
+		writer.write_u64(self.fee)?;
+		writer.write_u64(self.height)?;
+		writer.write_u64(self.lock_height)?;
+		match self.ttl_cutoff_height {
+			Some(h) => {
+				writer.write_u8(1)?;
+				writer.write_u64(h)?;
+			}
+			None => writer.write_u8(0)?,
+		}
+		write_opt_string(writer, &self.coin_type)?;
+		write_opt_string(writer, &self.network_type)?;
+		write_list(writer, &self.participant_data)?;
+		match &self.payment_proof {
+			Some(p) => {
+				writer.write_u8(1)?;
+				p.write(writer)?;
+			}
+			None => writer.write_u8(0)?,
+		}
+		Ok(())
+	}
+}
+
+impl Readable for SlateV3 {
+	fn read(reader: &mut dyn Reader) -> Result<SlateV3, ser::Error> {
+		let version_info = VersionCompatInfoV3 {
+			version: reader.read_u16()?,
+			orig_version: reader.read_u16()?,
+			block_header_version: reader.read_u16()?,
+		};
+		let num_participants = reader.read_u64()? as usize;
+		let id = Uuid::from_slice(&reader.read_fixed_bytes(16)?)
+			.map_err(|e| ser::Error::CorruptedData(format!("Invalid slate id, {}", e)))?;
+		let offset = BlindingFactor::read(reader)?;
+		let inputs = read_list(reader)?;
+		let outputs = read_list(reader)?;
+		let kernels = read_list(reader)?;
+		let amount = reader.read_u64()?;
+		let fee = reader.read_u64()?;
+		let height = reader.read_u64()?;
+		let lock_height = reader.read_u64()?;
+		let ttl_cutoff_height = match read_flag(reader)? {
+			true => Some(reader.read_u64()?),
+			false => None,
+		};
+		let coin_type = read_opt_string(reader)?;
+		let network_type = read_opt_string(reader)?;
+		let participant_data = read_list(reader)?;
+		let payment_proof = match read_flag(reader)? {
+			true => Some(PaymentInfoV3::read(reader)?),
+			false => None,
+		};
+
+		Ok(SlateV3 {
+			version_info,
+			num_participants,
+			id,
+			tx: TransactionV3 {
+				offset,
+				body: TransactionBodyV3 {
+					inputs,
+					outputs,
+					kernels,
+				},
+			},
+			amount,
+			fee,
+			height,
+			lock_height,
+			ttl_cutoff_height,
+			coin_type,
+			network_type,
+			participant_data,
+			payment_proof,
+		})
+	}
+}
+
+impl Writeable for InputV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		self.features.write(writer)?;
+		self.commit.write(writer)
+	}
+}
+
+impl Readable for InputV3 {
+	fn read(reader: &mut dyn Reader) -> Result<InputV3, ser::Error> {
+		Ok(InputV3 {
+			features: OutputFeatures::read(reader)?,
+			commit: Commitment::read(reader)?,
+		})
+	}
+}
+
+impl Writeable for OutputV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		self.features.write(writer)?;
+		self.commit.write(writer)?;
+		self.proof.write(writer)
+	}
+}
+
+impl Readable for OutputV3 {
+	fn read(reader: &mut dyn Reader) -> Result<OutputV3, ser::Error> {
+		Ok(OutputV3 {
+			features: OutputFeatures::read(reader)?,
+			commit: Commitment::read(reader)?,
+			proof: RangeProof::read(reader)?,
+		})
+	}
+}
+
+impl Writeable for TxKernelV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let features = match self.features {
+			CompatKernelFeatures::Plain => 0,
+			CompatKernelFeatures::Coinbase => 1,
+			CompatKernelFeatures::HeightLocked => 2,
+		};
+		writer.write_u8(features)?;
+		writer.write_u64(self.fee)?;
+		writer.write_u64(self.lock_height)?;
+		self.excess.write(writer)?;
+		self.excess_sig.write(writer)
+	}
+}
+
+impl Readable for TxKernelV3 {
+	fn read(reader: &mut dyn Reader) -> Result<TxKernelV3, ser::Error> {
+		let features = match reader.read_u8()? {
+			0 => CompatKernelFeatures::Plain,
+			1 => CompatKernelFeatures::Coinbase,
+			2 => CompatKernelFeatures::HeightLocked,
+			f => {
+				return Err(ser::Error::CorruptedData(format!(
+					"Unknown kernel features {}",
+					f
+				)))
+			}
+		};
+		Ok(TxKernelV3 {
+			features,
+			fee: reader.read_u64()?,
+			lock_height: reader.read_u64()?,
+			excess: Commitment::read(reader)?,
+			excess_sig: Signature::read(reader)?,
+		})
+	}
+}
+
+impl Writeable for ParticipantDataV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u64(self.id)?;
+		write_pubkey(writer, &self.public_blind_excess)?;
+		write_pubkey(writer, &self.public_nonce)?;
+		write_opt_signature(writer, &self.part_sig)?;
+		write_opt_string(writer, &self.message)?;
+		write_opt_signature(writer, &self.message_sig)
+	}
+}
+
+impl Readable for ParticipantDataV3 {
+	fn read(reader: &mut dyn Reader) -> Result<ParticipantDataV3, ser::Error> {
+		Ok(ParticipantDataV3 {
+			id: reader.read_u64()?,
+			public_blind_excess: read_pubkey(reader)?,
+			public_nonce: read_pubkey(reader)?,
+			part_sig: read_opt_signature(reader)?,
+			message: read_opt_string(reader)?,
+			message_sig: read_opt_signature(reader)?,
+		})
+	}
+}
+
+impl Writeable for PaymentInfoV3 {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_bytes(self.sender_address.public_key.as_bytes())?;
+		writer.write_bytes(self.receiver_address.public_key.as_bytes())?;
+		write_opt_string(writer, &self.receiver_signature)
+	}
+}
+
+impl Readable for PaymentInfoV3 {
+	fn read(reader: &mut dyn Reader) -> Result<PaymentInfoV3, ser::Error> {
+		Ok(PaymentInfoV3 {
+			sender_address: read_address(reader)?,
+			receiver_address: read_address(reader)?,
+			receiver_signature: read_opt_string(reader)?,
+		})
+	}
+}
+
+fn write_list<W: Writer, T: Writeable>(writer: &mut W, items: &Vec<T>) -> Result<(), ser::Error> {
+	writer.write_u64(items.len() as u64)?;
+	for item in items {
+		item.write(writer)?;
+	}
+	Ok(())
+}
+
+fn read_list<T: Readable>(reader: &mut dyn Reader) -> Result<Vec<T>, ser::Error> {
+	let len = reader.read_u64()?;
+	if len > MAX_LIST_ITEMS {
+		return Err(ser::Error::CorruptedData(format!(
+			"Too many items in the slate list: {}",
+			len
+		)));
+	}
+	let mut res = Vec::with_capacity(len as usize);
+	for _ in 0..len {
+		res.push(T::read(reader)?);
+	}
+	Ok(res)
+}
+
+fn read_flag(reader: &mut dyn Reader) -> Result<bool, ser::Error> {
+	match reader.read_u8()? {
+		0 => Ok(false),
+		1 => Ok(true),
+		f => Err(ser::Error::CorruptedData(format!(
+			"Invalid option flag {}",
+			f
+		))),
+	}
+}
+
+fn write_opt_string<W: Writer>(writer: &mut W, s: &Option<String>) -> Result<(), ser::Error> {
+	match s {
+		Some(s) => {
+			writer.write_u8(1)?;
+			writer.write_bytes(s.as_bytes())
+		}
+		None => writer.write_u8(0),
+	}
+}
+
+fn read_string(reader: &mut dyn Reader) -> Result<String, ser::Error> {
+	let data = reader.read_bytes_len_prefix()?;
+	String::from_utf8(data)
+		.map_err(|e| ser::Error::CorruptedData(format!("Invalid utf8 string, {}", e)))
+}
+
+fn read_opt_string(reader: &mut dyn Reader) -> Result<Option<String>, ser::Error> {
+	match read_flag(reader)? {
+		true => Ok(Some(read_string(reader)?)),
+		false => Ok(None),
+	}
+}
+
+fn write_opt_signature<W: Writer>(
+	writer: &mut W,
+	sig: &Option<Signature>,
+) -> Result<(), ser::Error> {
+	match sig {
+		Some(sig) => {
+			writer.write_u8(1)?;
+			sig.write(writer)
+		}
+		None => writer.write_u8(0),
+	}
+}
+
+fn read_opt_signature(reader: &mut dyn Reader) -> Result<Option<Signature>, ser::Error> {
+	match read_flag(reader)? {
+		true => Ok(Some(Signature::read(reader)?)),
+		false => Ok(None),
+	}
+}
+
+// Public keys are stored compressed, 33 bytes
+fn write_pubkey<W: Writer>(writer: &mut W, key: &PublicKey) -> Result<(), ser::Error> {
+	let secp = Secp256k1::with_caps(ContextFlag::None);
+	writer.write_fixed_bytes(&key.serialize_vec(&secp, true)[..])
+}
+
+fn read_pubkey(reader: &mut dyn Reader) -> Result<PublicKey, ser::Error> {
+	let secp = Secp256k1::with_caps(ContextFlag::None);
+	let data = reader.read_fixed_bytes(33)?;
+	PublicKey::from_slice(&secp, &data)
+		.map_err(|e| ser::Error::CorruptedData(format!("Invalid public key, {}", e)))
+}
+
+fn read_address(reader: &mut dyn Reader) -> Result<ProvableAddress, ser::Error> {
+	let address = read_string(reader)?;
+	ProvableAddress::from_str(&address)
+		.map_err(|e| ser::Error::CorruptedData(format!("Invalid address {}, {}", address, e)))
+}
@@ -18,10 +18,12 @@
 //! remains for future needs
 
 use crate::slate::Slate;
+use crate::slate_versions::binary::CompactSlate;
 use crate::slate_versions::v2::{CoinbaseV2, SlateV2};
 use crate::slate_versions::v3::{CoinbaseV3, SlateV3};
 use crate::types::CbData;
 
+pub mod binary;
 pub mod ser;
 
 #[allow(missing_docs)]
@@ -48,6 +50,15 @@ pub enum SlateVersion {
 	V2,
 }
 
+/// Slate encodings that wallet is able to accept
+#[derive(EnumIter, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SlateEncoding {
+	/// Json representation of the versioned slate
+	Json,
+	/// Compact binary slate, armored into a string. See slate_versions::binary
+	Compact,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 /// Versions are ordered newest to oldest so serde attempts to
@@ -57,6 +68,8 @@ pub enum VersionedSlate {
 	V3(SlateV3),
 	/// V2 (2.0.0 - Onwards)
 	V2(SlateV2),
+	/// V3 in the compact binary encoding
+	Compact(CompactSlate),
 }
 
 impl VersionedSlate {
@@ -65,9 +78,23 @@ impl VersionedSlate {
 		match *self {
 			VersionedSlate::V3(_) => SlateVersion::V3,
 			VersionedSlate::V2(_) => SlateVersion::V2,
+			VersionedSlate::Compact(_) => SlateVersion::V3,
+		}
+	}
+
+	/// Check if slate is in the compact binary encoding
+	pub fn is_compact(&self) -> bool {
+		match *self {
+			VersionedSlate::Compact(_) => true,
+			_ => false,
 		}
 	}
 
+	/// convert this slate into the compact binary encoding. Compact slate always carries V3 data
+	pub fn into_compact(slate: Slate) -> VersionedSlate {
+		VersionedSlate::Compact(CompactSlate(slate.into()))
+	}
+
 	/// convert this slate type to a specified older version
 	pub fn into_version(slate: Slate, version: SlateVersion) -> VersionedSlate {
 		match version {
@@ -92,6 +119,7 @@ impl From<VersionedSlate> for Slate {
 				let s = SlateV3::from(s);
 				Slate::from(s)
 			}
+			VersionedSlate::Compact(s) => Slate::from(s.0),
 		}
 	}
 }
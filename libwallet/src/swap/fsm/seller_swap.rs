@@ -1173,7 +1173,7 @@ where
 
 				if !swap.redeem_kernel_updated {
 					debug_assert!(false); // That shouldn't happen
-					  // let's go back to the waiting since the data is not ready
+						   // let's go back to the waiting since the data is not ready
 					return Ok(StateProcessRespond::new(
 						StateId::SellerWaitingForBuyerToRedeemMwc,
 					));
@@ -103,7 +103,7 @@ mod tests {
 	use bitcoin_lib::{Address, Transaction as BtcTransaction, TxOut};
 	use grin_core::core::transaction::Weighting;
 	use grin_core::core::verifier_cache::LruVerifierCache;
-	use grin_core::core::{KernelFeatures, Transaction, TxKernel};
+	use grin_core::core::{BlockHeader, KernelFeatures, Transaction, TxKernel};
 	use grin_keychain::{ExtKeychain, Identifier, Keychain, SwitchCommitmentType};
 	use grin_util::secp::key::{PublicKey, SecretKey};
 	use grin_util::secp::pedersen::{Commitment, RangeProof};
@@ -299,6 +299,9 @@ mod tests {
 		fn get_header_info(&self, _height: u64) -> Result<crate::HeaderInfo, crate::Error> {
 			unimplemented!()
 		}
+		fn get_block_header(&self, _height: u64) -> Result<BlockHeader, crate::Error> {
+			unimplemented!()
+		}
 		fn get_connected_peer_info(
 			&self,
 		) -> Result<Vec<grin_p2p::types::PeerInfoDisplayLegacy>, crate::Error> {
@@ -4113,7 +4116,7 @@ mod tests {
 				let res = buyer.process(Input::Check).unwrap();
 				assert_eq!(res.next_state_id, StateId::BuyerRedeemMwc);
 				assert_eq!(buyer.process(Input::Execute).is_err(), true); // For test node, repost doesn't work by some reasons
-														  // We should be good now
+															  // We should be good now
 				nc.mine_block();
 				let res = buyer.process(Input::Check).unwrap();
 				assert_eq!(
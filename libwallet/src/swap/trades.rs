@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use super::ErrorKind;
+use crate::api_impl::events;
 use crate::swap::types::{Context, Currency};
 use crate::swap::Swap;
 use base64;
@@ -332,6 +333,7 @@ pub fn store_swap_trade(
 		)
 	})?;
 
+	events::publish_swap_state(&swap_id, &swap.state);
 	Ok(())
 }
 
@@ -15,10 +15,12 @@
 //! Types and traits that should be provided by a wallet
 //! implementation
 
-use crate::config::{MQSConfig, TorConfig, WalletConfig};
+use crate::config::{
+	FeePolicyConfig, MQSConfig, RebroadcastConfig, ReceivePolicyConfig, TorConfig, WalletConfig,
+};
 use crate::error::{Error, ErrorKind};
-use crate::grin_core::core::hash::Hash;
-use crate::grin_core::core::{Output, Transaction, TxKernel};
+use crate::grin_core::core::hash::{Hash, Hashed};
+use crate::grin_core::core::{BlockHeader, Output, Transaction, TxKernel};
 use crate::grin_core::libtx::{aggsig, secp_ser};
 use crate::grin_core::{global, ser};
 use crate::grin_keychain::{Identifier, Keychain};
@@ -27,8 +29,10 @@ use crate::grin_util::secp::key::{PublicKey, SecretKey, ZERO_KEY};
 use crate::grin_util::secp::pedersen::Commitment;
 use crate::grin_util::secp::{self, pedersen, Secp256k1};
 use crate::grin_util::ZeroingString;
+use crate::internal::scan::OutputResult;
 use crate::proof::proofaddress::ProvableAddress;
 use crate::slate::ParticipantMessages;
+use crate::slate_versions::v3::SlateV3;
 use crate::Slate;
 use chrono::prelude::*;
 use serde;
@@ -138,6 +142,50 @@ where
 
 	/// return wallet instance
 	fn wallet_inst(&mut self) -> Result<&mut Box<dyn WalletBackend<'a, C, K> + 'a>, Error>;
+
+	/// Settings that are applied to the wallet instance every time it is opened
+	fn wallet_settings(&self) -> WalletSettings;
+
+	/// Update the settings, the opened wallet instance gets them as well
+	fn set_wallet_settings(&mut self, settings: WalletSettings);
+}
+
+/// Settings of the wallet instance that come from the wallet configuration. The lifecycle
+/// provider keeps them and passes them to the wallet backend when the wallet is opened.
+#[derive(Clone, Debug)]
+pub struct WalletSettings {
+	/// Receive policy of the foreign API, None accepts all slates
+	pub receive_policy: Option<ReceivePolicyConfig>,
+	/// If true, the node data is verified against the header chain
+	pub header_verification: bool,
+	/// Height and hash of the header where the header chain is anchored. None trusts
+	/// the node header one day below the tip at the first sync.
+	pub header_checkpoint: Option<(u64, String)>,
+	/// Minimum confirmations of the balance that the updater publishes
+	pub minimum_confirmations: u64,
+	/// Number of threads that rewind the range proofs during the chain scan
+	pub scan_threads: usize,
+	/// Rebroadcast of the posted transactions, None disables it
+	pub rebroadcast: Option<RebroadcastConfig>,
+	/// If true, the wallet update cancels the transactions with an expired TTL
+	pub auto_cancel_expired: bool,
+	/// Fee policy of the transactions that this wallet sends
+	pub fee_policy: FeePolicyConfig,
+}
+
+impl Default for WalletSettings {
+	fn default() -> WalletSettings {
+		WalletSettings {
+			receive_policy: None,
+			header_verification: false,
+			header_checkpoint: None,
+			minimum_confirmations: 10,
+			scan_threads: 4,
+			rebroadcast: None,
+			auto_cancel_expired: true,
+			fee_policy: FeePolicyConfig::default(),
+		}
+	}
 }
 
 /// TODO:
@@ -152,6 +200,12 @@ where
 	/// data file directory. mwc713 needs it
 	fn get_data_file_dir(&self) -> &str;
 
+	/// Settings of this wallet instance
+	fn settings(&self) -> &WalletSettings;
+
+	/// Replace the settings of this wallet instance
+	fn set_settings(&mut self, settings: WalletSettings);
+
 	/// Set the keychain, which should already be initialized
 	/// Optionally return a token value used to XOR the stored
 	/// key value
@@ -217,6 +271,42 @@ where
 	/// Gets an account path for a given label
 	fn get_acct_path(&self, label: String) -> Result<Option<AcctPathMapping>, Error>;
 
+	/// Get an (Optional) invoice by the invoice slate id
+	fn get_invoice(&self, id: &Uuid) -> Result<Option<Invoice>, Error>;
+
+	/// Iterate over all invoices issued by the wallet
+	fn invoice_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Invoice> + 'a>;
+
+	/// Get an (Optional) slate from the approval queue by the slate id
+	fn get_pending_slate(&self, id: &Uuid) -> Result<Option<PendingSlate>, Error>;
+
+	/// Iterate over the approval queue
+	fn pending_slate_iter<'a>(&'a self) -> Box<dyn Iterator<Item = PendingSlate> + 'a>;
+
+	/// Get the (Optional) spending policy of the account
+	fn get_spend_policy(&self, parent_key_id: &Identifier) -> Result<Option<SpendPolicy>, Error>;
+
+	/// Iterate over the spending policies of all accounts
+	fn spend_policy_iter<'a>(&'a self) -> Box<dyn Iterator<Item = SpendPolicy> + 'a>;
+
+	/// Get an (Optional) address book contact by name
+	fn get_contact(&self, name: &str) -> Result<Option<Contact>, Error>;
+
+	/// Iterate over the address book, ordered by name
+	fn contact_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Contact> + 'a>;
+
+	/// Get the header of the verified header chain at the height
+	fn get_verified_header(&self, height: u64) -> Result<Option<VerifiedHeader>, Error>;
+
+	/// Iterate over the verified header chain, ordered by height
+	fn verified_header_iter<'a>(&'a self) -> Box<dyn Iterator<Item = VerifiedHeader> + 'a>;
+
+	/// Get the (Optional) checkpoint of the interrupted scan
+	fn get_scan_checkpoint(&self) -> Result<Option<ScanCheckpoint>, Error>;
+
+	/// Iterate over the outputs that the interrupted scan found, ordered by the PMMR index
+	fn scan_output_iter<'a>(&'a self) -> Box<dyn Iterator<Item = OutputResult> + 'a>;
+
 	/// Stores a transaction
 	fn store_tx(&self, uuid: &str, tx: &Transaction) -> Result<(), Error>;
 
@@ -319,6 +409,42 @@ where
 	/// Iterate over account names stored in backend
 	fn acct_path_iter(&self) -> Box<dyn Iterator<Item = AcctPathMapping>>;
 
+	/// save an invoice record
+	fn save_invoice(&mut self, invoice: Invoice) -> Result<(), Error>;
+
+	/// save a slate in the approval queue
+	fn save_pending_slate(&mut self, pending: PendingSlate) -> Result<(), Error>;
+
+	/// delete a slate from the approval queue
+	fn delete_pending_slate(&mut self, id: &Uuid) -> Result<(), Error>;
+
+	/// save the spending policy of an account
+	fn save_spend_policy(&mut self, policy: SpendPolicy) -> Result<(), Error>;
+
+	/// delete the spending policy of an account
+	fn delete_spend_policy(&mut self, parent_key_id: &Identifier) -> Result<(), Error>;
+
+	/// save an address book contact
+	fn save_contact(&mut self, contact: Contact) -> Result<(), Error>;
+
+	/// delete the address book contact
+	fn delete_contact(&mut self, name: &str) -> Result<(), Error>;
+
+	/// save a header of the verified header chain
+	fn save_verified_header(&mut self, header: VerifiedHeader) -> Result<(), Error>;
+
+	/// delete the header of the verified header chain at the height
+	fn delete_verified_header(&mut self, height: u64) -> Result<(), Error>;
+
+	/// save the checkpoint of the running scan
+	fn save_scan_checkpoint(&mut self, checkpoint: ScanCheckpoint) -> Result<(), Error>;
+
+	/// save an output that the running scan found
+	fn save_scan_output(&mut self, output: OutputResult) -> Result<(), Error>;
+
+	/// delete the scan checkpoint together with the found outputs
+	fn delete_scan_checkpoint(&mut self) -> Result<(), Error>;
+
 	/// Save an output as locked in the backend
 	fn lock_output(&mut self, out: &mut OutputData) -> Result<(), Error>;
 
@@ -347,6 +473,12 @@ pub trait NodeClient: Send + Sync + Clone {
 	/// Return the URL of the check node
 	fn node_url(&self) -> &str;
 
+	/// Return the URL of the node that is used now. It differs from the check node
+	/// if the client failed over to another node.
+	fn active_node_url(&self) -> String {
+		self.node_url().to_string()
+	}
+
 	/// Set the node URL
 	fn set_node_url(&mut self, node_url: &str);
 
@@ -373,6 +505,9 @@ pub trait NodeClient: Send + Sync + Clone {
 	/// Return header info by height
 	fn get_header_info(&self, height: u64) -> Result<HeaderInfo, Error>;
 
+	/// Return the full block header by height, including the proof of work
+	fn get_block_header(&self, height: u64) -> Result<BlockHeader, Error>;
+
 	/// Return Connected peers
 	fn get_connected_peer_info(&self)
 		-> Result<Vec<grin_p2p::types::PeerInfoDisplayLegacy>, Error>;
@@ -477,6 +612,24 @@ pub struct OutputData {
 	pub is_coinbase: bool,
 	/// Optional corresponding internal entry in tx entry log
 	pub tx_log_entry: Option<u32>,
+	/// Frozen output is never selected automatically, it can be spent only if it is
+	/// listed in the send outputs explicitly
+	#[serde(default)]
+	#[serde(skip_serializing_if = "std::ops::Not::not")]
+	pub frozen: bool,
+	/// Note from the wallet owner
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+	/// Tags from the wallet owner
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	/// Output of the offline cold wallet. The online wallet can't rewind it, the output
+	/// is tracked by its commitment.
+	#[serde(default)]
+	#[serde(skip_serializing_if = "std::ops::Not::not")]
+	pub cold: bool,
 }
 
 impl ser::Writeable for OutputData {
@@ -857,6 +1010,24 @@ impl fmt::Display for TxLogEntryType {
 	}
 }
 
+/// Reference to the transaction log entry of another account of the wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LinkedTx {
+	/// BIP32 account path of the entry
+	pub parent_key_id: Identifier,
+	/// Local id of the entry in that account
+	pub id: u32,
+}
+
+/// Rebroadcast progress of the posted transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RebroadcastState {
+	/// Number of rebroadcasts done
+	pub retries: u32,
+	/// Time of the last post
+	pub last_post_ts: DateTime<Utc>,
+}
+
 /// Optional transaction information, recorded when an event happens
 /// to add or remove funds from a wallet. One Transaction log entry
 /// maps to one or many outputs
@@ -904,6 +1075,11 @@ pub struct TxLogEntry {
 	#[serde(with = "secp_ser::opt_string_or_u64")]
 	#[serde(default)]
 	pub ttl_cutoff_height: Option<u64>,
+	/// Kernel lock height, the transaction can't be mined below it
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub lock_height: Option<u64>,
 	/// Message data, stored as json
 	pub messages: Option<ParticipantMessages>,
 	/// Location of the store transaction, (reference or resending)
@@ -925,6 +1101,26 @@ pub struct TxLogEntry {
 	/// Output commits as Strings, defined for send & recieve
 	#[serde(default = "TxLogEntry::default_commits")]
 	pub output_commits: Vec<pedersen::Commitment>,
+	/// Note from the wallet owner. Unlike the messages it is never shared with the other party
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+	/// Tags from the wallet owner, never shared with the other party
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	/// The other side of the transfer between the accounts of this wallet
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub linked_tx: Option<LinkedTx>,
+	/// Rebroadcast tracking, set when the wallet posts the transaction
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rebroadcast: Option<RebroadcastState>,
+	/// Why the wallet cancelled the transaction, None if it wasn't cancelled automatically
+	#[serde(default)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cancel_reason: Option<String>,
 }
 
 impl ser::Writeable for TxLogEntry {
@@ -970,6 +1166,7 @@ impl TxLogEntry {
 			num_outputs: 0,
 			fee: None,
 			ttl_cutoff_height: None,
+			lock_height: None,
 			messages: None,
 			stored_tx: None,
 			kernel_excess: None,
@@ -977,6 +1174,11 @@ impl TxLogEntry {
 			payment_proof: None,
 			input_commits: vec![],
 			output_commits: vec![],
+			note: None,
+			tags: vec![],
+			linked_tx: None,
+			rebroadcast: None,
+			cancel_reason: None,
 		}
 	}
 
@@ -1021,6 +1223,7 @@ impl TxLogEntry {
 			num_outputs,
 			fee,
 			ttl_cutoff_height,
+			lock_height: None,
 			messages,
 			stored_tx,
 			kernel_excess,
@@ -1028,9 +1231,28 @@ impl TxLogEntry {
 			payment_proof,
 			input_commits,
 			output_commits,
+			note: None,
+			tags: vec![],
+			linked_tx: None,
+			rebroadcast: None,
+			cancel_reason: None,
 		}
 	}
 
+	/// Case insensitive search in the note, tags, address and messages of the transaction
+	pub fn matches(&self, query: &str) -> bool {
+		let query = query.to_lowercase();
+		let found = |s: &str| s.to_lowercase().contains(&query);
+		self.note.as_ref().map_or(false, |n| found(n))
+			|| self.tags.iter().any(|t| found(t))
+			|| self.address.as_ref().map_or(false, |a| found(a))
+			|| self.messages.as_ref().map_or(false, |m| {
+				m.messages
+					.iter()
+					.any(|d| d.message.as_ref().map_or(false, |s| found(s)))
+			})
+	}
+
 	/// Given a vec of TX log entries, return credited + debited sums
 	pub fn sum_confirmed(txs: &[TxLogEntry]) -> (u64, u64) {
 		txs.iter().fold((0, 0), |acc, tx| match tx.confirmed {
@@ -1088,6 +1310,7 @@ impl TxLogEntry {
 			any => any.clone(),
 		};
 		self.ttl_cutoff_height = None;
+		self.cancel_reason = None;
 	}
 }
 
@@ -1211,6 +1434,436 @@ impl ser::Readable for ScannedBlockInfo {
 	}
 }
 
+/// Header of the locally verified header chain. Node responses are checked against
+/// this chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifiedHeader {
+	/// Header height
+	pub height: u64,
+	/// Header hash
+	pub hash: String,
+	/// Hash of the previous header
+	pub prev_hash: String,
+	/// Total chain difficulty at this header
+	pub total_difficulty: u64,
+	/// Header timestamp
+	pub timestamp: DateTime<Utc>,
+	/// Secondary proof of work scaling factor of the header
+	#[serde(default)]
+	pub secondary_scaling: u32,
+	/// True if the header has the secondary proof of work
+	#[serde(default)]
+	pub is_secondary: bool,
+	/// Output MMR size after this block
+	#[serde(default)]
+	pub output_mmr_size: u64,
+	/// Kernel MMR size after this block
+	#[serde(default)]
+	pub kernel_mmr_size: u64,
+}
+
+impl VerifiedHeader {
+	/// Build the record from a block header
+	pub fn from_header(header: &BlockHeader) -> VerifiedHeader {
+		VerifiedHeader {
+			height: header.height,
+			hash: header.hash().to_hex(),
+			prev_hash: header.prev_hash.to_hex(),
+			total_difficulty: header.total_difficulty().to_num(),
+			timestamp: header.timestamp,
+			secondary_scaling: header.pow.secondary_scaling,
+			is_secondary: header.pow.is_secondary(),
+			output_mmr_size: header.output_mmr_size,
+			kernel_mmr_size: header.kernel_mmr_size,
+		}
+	}
+}
+
+impl ser::Writeable for VerifiedHeader {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("VerifiedHeader to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"VerifiedHeader data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for VerifiedHeader {
+	fn read(reader: &mut dyn ser::Reader) -> Result<VerifiedHeader, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to VerifiedHeader conversion failed, {}", e))
+		})
+	}
+}
+
+/// Progress of the full chain scan, saved after every batch of outputs. An interrupted
+/// scan of the same range continues from `last_pmmr_index` instead of the start.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+	/// Height the scan started from
+	pub start_height: u64,
+	/// Height the scan goes up to
+	pub end_height: u64,
+	/// Hash of the block at `end_height`. The checkpoint is dropped if the block was reorged.
+	pub end_hash: String,
+	/// First PMMR index of the scan
+	pub start_pmmr_index: u64,
+	/// Last PMMR index of the scan
+	pub end_pmmr_index: u64,
+	/// Last processed PMMR index
+	pub last_pmmr_index: u64,
+	/// Number of the wallet outputs found so far
+	pub outputs_found: u64,
+	/// Highest child index found so far, per parent key
+	pub child_indexes: Vec<(Identifier, u32)>,
+	/// Time of the last update
+	pub updated: DateTime<Utc>,
+}
+
+impl ScanCheckpoint {
+	/// Scanned part of the PMMR range, in percents
+	pub fn progress(&self) -> u8 {
+		if self.end_pmmr_index <= self.start_pmmr_index {
+			return 100;
+		}
+		let range = (self.end_pmmr_index - self.start_pmmr_index) as f64;
+		let done = self.last_pmmr_index.saturating_sub(self.start_pmmr_index) as f64;
+		std::cmp::min((done / range * 100.0) as u8, 100)
+	}
+
+	/// Account the outputs of the processed batch
+	pub fn add_outputs(&mut self, last_pmmr_index: u64, outputs: &[OutputResult]) {
+		self.last_pmmr_index = last_pmmr_index;
+		self.outputs_found += outputs.len() as u64;
+		for out in outputs {
+			let parent_key_id = out.key_id.parent_path();
+			match self
+				.child_indexes
+				.iter_mut()
+				.find(|(id, _)| *id == parent_key_id)
+			{
+				Some((_, n)) => *n = std::cmp::max(*n, out.n_child),
+				None => self.child_indexes.push((parent_key_id, out.n_child)),
+			}
+		}
+		self.updated = Utc::now();
+	}
+}
+
+impl ser::Writeable for ScanCheckpoint {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("ScanCheckpoint to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"ScanCheckpoint data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for ScanCheckpoint {
+	fn read(reader: &mut dyn ser::Reader) -> Result<ScanCheckpoint, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to ScanCheckpoint conversion failed, {}", e))
+		})
+	}
+}
+
+/// Status of the invoice, issued by the wallet
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum InvoiceStatus {
+	/// Invoice is waiting for the payment
+	Open,
+	/// Invoice slate was finalized
+	Paid,
+	/// Invoice was not paid before the expiry height
+	Expired,
+	/// Invoice was cancelled by user
+	Cancelled,
+}
+
+impl fmt::Display for InvoiceStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			InvoiceStatus::Open => write!(f, "Open"),
+			InvoiceStatus::Paid => write!(f, "Paid"),
+			InvoiceStatus::Expired => write!(f, "Expired"),
+			InvoiceStatus::Cancelled => write!(f, "Cancelled"),
+		}
+	}
+}
+
+/// Invoice record, stored by the wallet that issued the invoice. Merchants can
+/// use the reference to match the invoice with the order.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Invoice {
+	/// Slate id of the invoice transaction
+	pub id: Uuid,
+	/// BIP32 account path that receives the funds
+	pub parent_key_id: Identifier,
+	/// Invoice amount
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Invoice memo, it is sent with the slate as a participant message
+	pub memo: Option<String>,
+	/// External reference, for example the merchant order id
+	pub reference: Option<String>,
+	/// Time when invoice was issued
+	pub creation_ts: DateTime<Utc>,
+	/// Height when the invoice expires, None if it never expires
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub expiry_height: Option<u64>,
+	/// Current status
+	pub status: InvoiceStatus,
+	/// Time when the invoice slate was finalized
+	pub paid_ts: Option<DateTime<Utc>>,
+}
+
+impl Invoice {
+	/// Return true if the invoice can't be paid at this height any more
+	pub fn is_expired_at(&self, height: u64) -> bool {
+		match self.expiry_height {
+			Some(h) => height >= h,
+			None => false,
+		}
+	}
+}
+
+impl ser::Writeable for Invoice {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("Invoice to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"Invoice data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for Invoice {
+	fn read(reader: &mut dyn ser::Reader) -> Result<Invoice, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to Invoice conversion failed, {}", e))
+		})
+	}
+}
+
+/// Foreign API request that is parked in the approval queue
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PendingSlateRequest {
+	/// receive_tx, the wallet is a recipient of the send
+	ReceiveTx,
+	/// finalize_invoice_tx, the wallet issued the invoice
+	FinalizeInvoiceTx,
+}
+
+impl fmt::Display for PendingSlateRequest {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			PendingSlateRequest::ReceiveTx => write!(f, "Receive"),
+			PendingSlateRequest::FinalizeInvoiceTx => write!(f, "Finalize Invoice"),
+		}
+	}
+}
+
+/// Status of the slate in the approval queue
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PendingSlateStatus {
+	/// Waiting for the wallet owner decision
+	Pending,
+	/// Approved and processed, the response slate is ready
+	Approved,
+	/// Rejected by the wallet owner
+	Rejected,
+	/// Nobody approved the slate in time
+	Expired,
+}
+
+impl fmt::Display for PendingSlateStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			PendingSlateStatus::Pending => write!(f, "Pending"),
+			PendingSlateStatus::Approved => write!(f, "Approved"),
+			PendingSlateStatus::Rejected => write!(f, "Rejected"),
+			PendingSlateStatus::Expired => write!(f, "Expired"),
+		}
+	}
+}
+
+/// Incoming slate, parked by the foreign API listener until the wallet owner
+/// approves or rejects it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingSlate {
+	/// Slate id
+	pub id: Uuid,
+	/// Foreign API request that was called
+	pub request: PendingSlateRequest,
+	/// Incoming slate
+	pub slate: SlateV3,
+	/// Address of the sender, if known
+	pub address: Option<String>,
+	/// Account that receives the funds
+	pub dest_acct_name: Option<String>,
+	/// Message for the receive participant data
+	pub message: Option<String>,
+	/// Time when the slate was parked
+	pub creation_ts: DateTime<Utc>,
+	/// Time when the slate expires if nobody approves it
+	pub expiry_ts: DateTime<Utc>,
+	/// Current status
+	pub status: PendingSlateStatus,
+	/// Processed slate for the sender, available after the approval
+	pub response: Option<SlateV3>,
+	/// True if the response was delivered to the sender
+	#[serde(default)]
+	pub delivered: bool,
+}
+
+impl PendingSlate {
+	/// Return true if the slate can't be approved any more
+	pub fn is_expired_at(&self, time: DateTime<Utc>) -> bool {
+		time >= self.expiry_ts
+	}
+}
+
+impl ser::Writeable for PendingSlate {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("PendingSlate to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"PendingSlate data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for PendingSlate {
+	fn read(reader: &mut dyn ser::Reader) -> Result<PendingSlate, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to PendingSlate conversion failed, {}", e))
+		})
+	}
+}
+
+/// Destination that the outbound payments of an account are allowed to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpendPolicyDestination {
+	/// Address: payment proof address, mwcmqs address or onion address
+	pub address: String,
+	/// Time when the destination was added. New destinations are usable after the cool-down.
+	pub added_ts: DateTime<Utc>,
+}
+
+/// Outbound spending policy of an account. The policy is checked by every send and
+/// invoice payment from the account.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpendPolicy {
+	/// BIP32 account path the policy applies to
+	pub parent_key_id: Identifier,
+	/// Maximum amount of a single transaction
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub max_per_tx: Option<u64>,
+	/// Maximum amount sent during the last 24 hours
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub max_24h: Option<u64>,
+	/// Maximum amount sent during the last 7 days
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub max_7d: Option<u64>,
+	/// If set, payments are allowed only to these destinations
+	pub allowed_destinations: Option<Vec<SpendPolicyDestination>>,
+	/// Time in seconds before a newly allowed destination can be paid
+	pub new_destination_cooldown_secs: Option<u64>,
+	/// Time of the last policy update
+	pub update_ts: DateTime<Utc>,
+}
+
+impl ser::Writeable for SpendPolicy {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("SpendPolicy to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"SpendPolicy data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for SpendPolicy {
+	fn read(reader: &mut dyn ser::Reader) -> Result<SpendPolicy, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to SpendPolicy conversion failed, {}", e))
+		})
+	}
+}
+
+/// Counterparty from the local address book, `send --dest @name` sends to it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Contact {
+	/// Name of the contact, unique in the wallet
+	pub name: String,
+	/// Wallet address: http(s) url, tor or MWCMQS address
+	pub address: String,
+	/// Method to send the slates with: http, tor or mwcmqs
+	pub method: String,
+	/// Slate version to send, None to negotiate it with the other wallet
+	#[serde(default)]
+	pub slate_version: Option<u16>,
+	/// Address to request the payment proofs for
+	#[serde(default)]
+	pub proof_address: Option<String>,
+}
+
+impl ser::Writeable for Contact {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("Contact to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"Contact data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for Contact {
+	fn read(reader: &mut dyn ser::Reader) -> Result<Contact, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to Contact conversion failed, {}", e))
+		})
+	}
+}
+
 /// Wrapper for reward output and kernel used when building a coinbase for a mining node.
 /// Note: Not serializable, must be converted to necesssary "versioned" representation
 /// before serializing to json to ensure compatibility with mining node.
@@ -12,15 +12,21 @@
 // limitations under the License.
 
 //! core::libtx specific tests
-use grin_wallet_libwallet::Context;
-use grin_wallet_util::grin_core::core::transaction;
+use grin_wallet_libwallet::internal::scan::{collect_chain_outputs, identify_utxo_outputs};
+use grin_wallet_libwallet::{Context, Error, ErrorKind, HeaderInfo, NodeClient, NodeVersionInfo};
+use grin_wallet_util::grin_api;
+use grin_wallet_util::grin_core::core::{transaction, BlockHeader, Transaction, TxKernel};
 use grin_wallet_util::grin_core::libtx::{aggsig, proof};
 use grin_wallet_util::grin_keychain::{
 	BlindSum, BlindingFactor, ExtKeychain, ExtKeychainPath, Keychain, SwitchCommitmentType,
 };
 use grin_wallet_util::grin_util::secp;
 use grin_wallet_util::grin_util::secp::key::{PublicKey, SecretKey};
+use grin_wallet_util::grin_util::secp::pedersen;
 use rand::thread_rng;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 fn kernel_sig_msg() -> secp::Message {
 	transaction::KernelFeatures::Plain { fee: 0 }
@@ -522,3 +528,187 @@ fn test_rewind_range_proof() {
 	.unwrap();
 	assert!(proof_info.is_none());
 }
+
+#[test]
+fn test_parallel_rewind_keeps_order() {
+	let wallet = ExtKeychain::from_random_seed(true).unwrap();
+	let other = ExtKeychain::from_random_seed(true).unwrap();
+	let switch = SwitchCommitmentType::Regular;
+
+	let outputs: Vec<_> = (0..200u32)
+		.map(|i| {
+			let keychain = if i % 7 == 0 { &wallet } else { &other };
+			let builder = proof::ProofBuilder::new(keychain);
+			let key_id = ExtKeychainPath::new(3, 0, 0, i, 0).to_identifier();
+			let commit = keychain.commit(i as u64 + 1, &key_id, switch).unwrap();
+			let proof = proof::create(
+				keychain,
+				&builder,
+				i as u64 + 1,
+				&key_id,
+				switch,
+				commit,
+				None,
+			)
+			.unwrap();
+			(commit, proof, false, 1_000_000, i as u64 + 1)
+		})
+		.collect();
+
+	let serial = identify_utxo_outputs(&wallet, outputs.clone(), 1).unwrap();
+	let parallel = identify_utxo_outputs(&wallet, outputs, 4).unwrap();
+	assert_eq!(serial.len(), 29);
+	assert_eq!(parallel.len(), serial.len());
+	for (s, p) in serial.iter().zip(parallel.iter()) {
+		assert_eq!(s.commit, p.commit);
+		assert_eq!(s.value, p.value);
+		assert_eq!(s.mmr_index, p.mmr_index);
+	}
+	assert!(parallel.windows(2).all(|w| w[0].mmr_index < w[1].mmr_index));
+}
+
+type ChainOutput = (pedersen::Commitment, pedersen::RangeProof, bool, u64, u64);
+
+// Node that returns the UTXO set in batches and fails on the `fail_batch` one
+#[derive(Clone)]
+struct BatchNodeClient {
+	batches: Arc<Vec<Vec<ChainOutput>>>,
+	fail_batch: Option<usize>,
+	requests: Arc<AtomicUsize>,
+}
+
+impl NodeClient for BatchNodeClient {
+	fn node_url(&self) -> &str {
+		"batch_node_url"
+	}
+	fn set_node_url(&mut self, _node_url: &str) {
+		unimplemented!()
+	}
+	fn node_api_secret(&self) -> Option<String> {
+		unimplemented!()
+	}
+	fn set_node_api_secret(&mut self, _node_api_secret: Option<String>) {
+		unimplemented!()
+	}
+	fn reset_cache(&self) {
+		unimplemented!()
+	}
+	fn post_tx(&self, _tx: &Transaction, _fluff: bool) -> Result<(), Error> {
+		unimplemented!()
+	}
+	fn get_version_info(&mut self) -> Option<NodeVersionInfo> {
+		unimplemented!()
+	}
+	fn get_chain_tip(&self) -> Result<(u64, String, u64), Error> {
+		unimplemented!()
+	}
+	fn get_header_info(&self, _height: u64) -> Result<HeaderInfo, Error> {
+		unimplemented!()
+	}
+	fn get_block_header(&self, _height: u64) -> Result<BlockHeader, Error> {
+		unimplemented!()
+	}
+	fn get_connected_peer_info(
+		&self,
+	) -> Result<Vec<grin_p2p::types::PeerInfoDisplayLegacy>, Error> {
+		unimplemented!()
+	}
+	fn get_kernel(
+		&self,
+		_excess: &pedersen::Commitment,
+		_min_height: Option<u64>,
+		_max_height: Option<u64>,
+	) -> Result<Option<(TxKernel, u64, u64)>, Error> {
+		unimplemented!()
+	}
+	fn get_outputs_from_node(
+		&self,
+		_wallet_outputs: &Vec<pedersen::Commitment>,
+	) -> Result<HashMap<pedersen::Commitment, (String, u64, u64)>, Error> {
+		unimplemented!()
+	}
+	fn get_outputs_by_pmmr_index(
+		&self,
+		start_height: u64,
+		_end_height: Option<u64>,
+		_max_outputs: u64,
+	) -> Result<(u64, u64, Vec<ChainOutput>), Error> {
+		self.requests.fetch_add(1, Ordering::SeqCst);
+		let index = self
+			.batches
+			.iter()
+			.position(|b| b[0].4 == start_height)
+			.unwrap();
+		if self.fail_batch == Some(index) {
+			return Err(ErrorKind::ClientCallback("Node is not responding".to_string()).into());
+		}
+		let batch = self.batches[index].clone();
+		let highest_index = self.batches.last().unwrap().last().unwrap().4;
+		Ok((highest_index, batch.last().unwrap().4, batch))
+	}
+	fn height_range_to_pmmr_indices(
+		&self,
+		_start_height: u64,
+		_end_height: Option<u64>,
+	) -> Result<(u64, u64), Error> {
+		unimplemented!()
+	}
+	fn get_blocks_by_height(
+		&self,
+		_start_height: u64,
+		_end_height: u64,
+		_threads_number: usize,
+	) -> Result<Vec<grin_api::BlockPrintable>, Error> {
+		unimplemented!()
+	}
+}
+
+#[test]
+fn test_chain_scan_stops_on_failed_batch() {
+	let wallet = ExtKeychain::from_random_seed(true).unwrap();
+	let switch = SwitchCommitmentType::Regular;
+	let builder = proof::ProofBuilder::new(&wallet);
+
+	// 8 batches of 5 outputs, every output belongs to the wallet
+	let batches: Vec<Vec<ChainOutput>> = (0..8u64)
+		.map(|b| {
+			(1..=5u64)
+				.map(|i| {
+					let mmr_index = b * 5 + i;
+					let key_id = ExtKeychainPath::new(3, 0, 0, mmr_index as u32, 0).to_identifier();
+					let commit = wallet.commit(mmr_index, &key_id, switch).unwrap();
+					let proof =
+						proof::create(&wallet, &builder, mmr_index, &key_id, switch, commit, None)
+							.unwrap();
+					(commit, proof, false, mmr_index, mmr_index)
+				})
+				.collect()
+		})
+		.collect();
+	let batches = Arc::new(batches);
+
+	let client = BatchNodeClient {
+		batches: batches.clone(),
+		fail_batch: None,
+		requests: Arc::new(AtomicUsize::new(0)),
+	};
+	let found = collect_chain_outputs(&wallet, client.clone(), 1, None, &None, false).unwrap();
+	assert_eq!(found.len(), 40);
+	assert_eq!(client.requests.load(Ordering::SeqCst), 8);
+
+	// The scan returns the node error and the fetcher doesn't request the batches after it
+	let client = BatchNodeClient {
+		batches,
+		fail_batch: Some(3),
+		requests: Arc::new(AtomicUsize::new(0)),
+	};
+	let res = collect_chain_outputs(&wallet, client.clone(), 1, None, &None, false);
+	match res {
+		Err(e) => assert_eq!(
+			e.kind(),
+			ErrorKind::ClientCallback("Node is not responding".to_string())
+		),
+		Ok(_) => panic!("Scan must fail on the failed batch"),
+	}
+	assert_eq!(client.requests.load(Ordering::SeqCst), 4);
+}
@@ -94,3 +94,53 @@ fn slate_conversions() {
 	println!("v2 -> v0: {}", s);
 }
 */
+
+// Compact binary encoding must keep all the slate data
+#[test]
+fn compact_slate_roundtrip() {
+	use grin_wallet_libwallet::slate_versions::binary::CompactSlate;
+	use grin_wallet_libwallet::slate_versions::v3::SlateV3;
+	use grin_wallet_libwallet::{Slate, SlateVersion, VersionedSlate};
+
+	let v2 = include_str!("slates/v2.slate");
+	let slate = Slate::deserialize_upgrade(&v2).unwrap();
+	let json = serde_json::to_string(&VersionedSlate::into_version(
+		slate.clone(),
+		SlateVersion::V3,
+	))
+	.unwrap();
+
+	// binary data
+	let compact = CompactSlate(SlateV3::from(slate.clone()));
+	let bin = compact.to_bytes().unwrap();
+	assert!(bin.len() < json.len() / 2);
+	let res = CompactSlate::from_bytes(&bin).unwrap();
+	assert_eq!(serde_json::to_string(&res.0).unwrap(), json);
+
+	// armored string, it is accepted by deserialize_upgrade as any other slate
+	let armored = compact.to_armored().unwrap();
+	assert!(CompactSlate::is_armored(&armored));
+	let res = Slate::deserialize_upgrade(&armored).unwrap();
+	let res_json =
+		serde_json::to_string(&VersionedSlate::into_version(res, SlateVersion::V3)).unwrap();
+	assert_eq!(res_json, json);
+
+	// json api gets compact slate as a string
+	let versioned = VersionedSlate::into_compact(slate.clone());
+	assert!(versioned.is_compact());
+	let versioned_json = serde_json::to_string(&versioned).unwrap();
+	assert_eq!(versioned_json, format!("\"{}\"", armored));
+	let versioned: VersionedSlate = serde_json::from_str(&versioned_json).unwrap();
+	assert!(versioned.is_compact());
+	assert_eq!(versioned.version(), SlateVersion::V3);
+	let res = serde_json::to_string(&VersionedSlate::into_version(
+		Slate::from(versioned),
+		SlateVersion::V3,
+	))
+	.unwrap();
+	assert_eq!(res, json);
+
+	// broken data must be rejected
+	assert!(CompactSlate::from_bytes(&bin[..bin.len() - 1]).is_err());
+	assert!(Slate::deserialize_upgrade(&armored[..armored.len() - 8]).is_err());
+}
@@ -0,0 +1,123 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Main for building the self hosted MWCMQS broker binary
+
+#[macro_use]
+extern crate log;
+
+use clap::{App, Arg};
+use grin_wallet_mqs_broker::{start_broker, BrokerConfig};
+use grin_wallet_util::grin_util::{init_logger, LogLevel, LoggingConfig};
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn parse_secs(value: Option<&str>, name: &str, default: Duration) -> Duration {
+	match value {
+		Some(v) => match v.parse::<u64>() {
+			Ok(secs) => Duration::from_secs(secs),
+			Err(_) => {
+				eprintln!("Invalid {} value {}", name, v);
+				std::process::exit(1);
+			}
+		},
+		None => default,
+	}
+}
+
+fn main() {
+	let args = App::new("mwcmqs-broker")
+		.version(env!("CARGO_PKG_VERSION"))
+		.about("Self hosted MWCMQS broker for the mwc wallet listeners")
+		.arg(
+			Arg::with_name("bind")
+				.long("bind")
+				.help("Address to listen on")
+				.takes_value(true)
+				.default_value("127.0.0.1:3420"),
+		)
+		.arg(
+			Arg::with_name("message_ttl")
+				.long("message-ttl")
+				.help("How long the undelivered messages are kept, in seconds")
+				.takes_value(true),
+		)
+		.arg(
+			Arg::with_name("poll_timeout")
+				.long("poll-timeout")
+				.help("How long the listener poll waits for the messages, in seconds")
+				.takes_value(true),
+		)
+		.arg(
+			Arg::with_name("max_messages")
+				.long("max-messages")
+				.help("Max number of waiting messages per address")
+				.takes_value(true),
+		)
+		.get_matches();
+
+	init_logger(
+		Some(LoggingConfig {
+			log_to_stdout: true,
+			stdout_log_level: LogLevel::Info,
+			log_to_file: false,
+			..LoggingConfig::default()
+		}),
+		None,
+	);
+
+	let default = BrokerConfig::default();
+	let bind = args.value_of("bind").unwrap();
+	let config = BrokerConfig {
+		bind_addr: match bind.parse() {
+			Ok(addr) => addr,
+			Err(_) => {
+				eprintln!("Invalid bind address {}", bind);
+				std::process::exit(1);
+			}
+		},
+		message_ttl: parse_secs(
+			args.value_of("message_ttl"),
+			"message-ttl",
+			default.message_ttl,
+		),
+		poll_timeout: parse_secs(
+			args.value_of("poll_timeout"),
+			"poll-timeout",
+			default.poll_timeout,
+		),
+		max_messages: match args.value_of("max_messages").map(|v| v.parse::<usize>()) {
+			Some(Ok(n)) => n,
+			Some(Err(_)) => {
+				eprintln!("Invalid max-messages value");
+				std::process::exit(1);
+			}
+			None => default.max_messages,
+		},
+		..default
+	};
+
+	let _broker = match start_broker(config) {
+		Ok(broker) => broker,
+		Err(e) => {
+			error!("Unable to start the broker, {}", e);
+			eprintln!("Unable to start the broker, {}", e);
+			std::process::exit(1);
+		}
+	};
+
+	// The broker runs until the process is killed
+	let (_tx, rx) = mpsc::channel::<()>();
+	let _ = rx.recv();
+}
@@ -0,0 +1,94 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Broker error types
+use failure::{Backtrace, Context, Fail};
+use std::fmt::{self, Display};
+
+/// Error definition
+#[derive(Debug)]
+pub struct Error {
+	inner: Context<ErrorKind>,
+}
+
+/// Broker errors
+#[derive(Clone, Eq, PartialEq, Debug, Fail)]
+pub enum ErrorKind {
+	/// Request has missing or invalid parameters
+	#[fail(display = "Invalid request, {}", _0)]
+	InvalidRequest(String),
+
+	/// Invalid MQS address
+	#[fail(display = "Invalid address {}", _0)]
+	InvalidAddress(String),
+
+	/// Signature doesn't match the address
+	#[fail(display = "Invalid signature, {}", _0)]
+	InvalidSignature(String),
+
+	/// Too many messages are waiting for the recipient
+	#[fail(display = "Mailbox of {} is full", _0)]
+	MailboxFull(String),
+
+	/// IO error
+	#[fail(display = "IO error, {}", _0)]
+	IO(String),
+
+	/// HTTP server error
+	#[fail(display = "Server error, {}", _0)]
+	Server(String),
+}
+
+impl Fail for Error {
+	fn cause(&self) -> Option<&dyn Fail> {
+		self.inner.cause()
+	}
+
+	fn backtrace(&self) -> Option<&Backtrace> {
+		self.inner.backtrace()
+	}
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		Display::fmt(&self.inner, f)
+	}
+}
+
+impl Error {
+	/// get kind
+	pub fn kind(&self) -> ErrorKind {
+		self.inner.get_context().clone()
+	}
+}
+
+impl From<ErrorKind> for Error {
+	fn from(kind: ErrorKind) -> Error {
+		Error {
+			inner: Context::new(kind),
+		}
+	}
+}
+
+impl From<Context<ErrorKind>> for Error {
+	fn from(inner: Context<ErrorKind>) -> Error {
+		Error { inner }
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(error: std::io::Error) -> Error {
+		Error::from(ErrorKind::IO(format!("{}", error)))
+	}
+}
@@ -0,0 +1,34 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self hosted MWCMQS broker. It is compatible with the wallet MWCMQS listener, so wallets
+//! can exchange slates and swap messages without the public mqs.mwc.mw servers.
+//! Messages are kept in memory until the recipient receives them or the TTL is over.
+//! The broker verifies that the listener owns the address and that the posted messages
+//! are signed by the sender address.
+
+#[macro_use]
+extern crate failure_derive;
+#[macro_use]
+extern crate log;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_util as util;
+
+mod error;
+mod mailbox;
+mod server;
+
+pub use crate::error::{Error, ErrorKind};
+pub use crate::server::{start_broker, BrokerConfig, BrokerHandle};
@@ -0,0 +1,323 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Message queues of the MQS addresses. A message stays in the queue until the listener
+//! acknowledges it with `delTo` or its TTL is over, so a message that was sent to a
+//! dropped long poll is delivered again by the next one.
+//!
+//! A listener logs in with a challenge that the broker issued for the address, the
+//! challenge is used once. The polls and the wake requests of the session must carry
+//! the challenge of the login.
+
+use crate::error::{Error, ErrorKind};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How long an issued challenge can be used for the login
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(300);
+/// Max number of the unused challenges per address, the oldest ones are dropped
+const MAX_CHALLENGES: usize = 16;
+
+/// Events that wake up the waiting listeners of the address
+#[derive(Clone, Debug, PartialEq)]
+pub enum MailboxEvent {
+	/// New message is posted
+	Message,
+	/// Listener is stopping, the waiting poll should return
+	Wake,
+	/// New listener logged in with this session, the older sessions must stop
+	NewLogin(u64),
+}
+
+/// Message that is waiting for the listener
+#[derive(Clone, Debug)]
+pub struct StoredMessage {
+	/// Message id, listener acknowledges the messages up to the id
+	pub id: u64,
+	/// Message in the listener wire format
+	pub payload: String,
+	expires: Instant,
+}
+
+struct Mailbox {
+	messages: VecDeque<StoredMessage>,
+	last_seen: Option<Instant>,
+	session: u64,
+	// Issued challenges that are not used yet, with the issue time
+	challenges: VecDeque<(String, Instant)>,
+	// Challenge of the current session login
+	session_challenge: Option<String>,
+	events: broadcast::Sender<MailboxEvent>,
+}
+
+impl Mailbox {
+	fn new() -> Self {
+		let (events, _) = broadcast::channel(16);
+		Mailbox {
+			messages: VecDeque::new(),
+			last_seen: None,
+			session: 0,
+			challenges: VecDeque::new(),
+			session_challenge: None,
+			events,
+		}
+	}
+
+	fn notify(&self, event: MailboxEvent) {
+		// Error means there are no waiting listeners
+		let _ = self.events.send(event);
+	}
+
+	fn expire_challenges(&mut self, now: Instant) {
+		self.challenges
+			.retain(|(_, issued)| now.saturating_duration_since(*issued) < CHALLENGE_TTL);
+	}
+}
+
+/// Message queues by the address public key
+pub struct Mailboxes {
+	boxes: HashMap<String, Mailbox>,
+	next_id: u64,
+	next_challenge: u64,
+	ttl: Duration,
+	max_messages: usize,
+}
+
+impl Mailboxes {
+	/// New queues. Messages are dropped after `ttl`, an address can have up to
+	/// `max_messages` waiting messages.
+	pub fn new(ttl: Duration, max_messages: usize) -> Self {
+		Mailboxes {
+			boxes: HashMap::new(),
+			next_id: 1,
+			next_challenge: 1,
+			ttl,
+			max_messages,
+		}
+	}
+
+	fn mailbox(&mut self, address: &str) -> &mut Mailbox {
+		self.boxes
+			.entry(address.to_string())
+			.or_insert_with(Mailbox::new)
+	}
+
+	/// Queue the message for the address. Returns the time since the address listener
+	/// was seen last time, None if it was never seen.
+	pub fn post(
+		&mut self,
+		address: &str,
+		payload: String,
+		now: Instant,
+	) -> Result<Option<Duration>, Error> {
+		let id = self.next_id;
+		let expires = now + self.ttl;
+		let max_messages = self.max_messages;
+		let mailbox = self.mailbox(address);
+		mailbox.messages.retain(|m| m.expires > now);
+		if mailbox.messages.len() >= max_messages {
+			return Err(ErrorKind::MailboxFull(address.to_string()).into());
+		}
+		mailbox.messages.push_back(StoredMessage {
+			id,
+			payload,
+			expires,
+		});
+		mailbox.notify(MailboxEvent::Message);
+		let last_seen = mailbox.last_seen.map(|t| now.saturating_duration_since(t));
+		self.next_id += 1;
+		Ok(last_seen)
+	}
+
+	/// Wake up the waiting listeners of the address
+	pub fn wake(&mut self, address: &str) {
+		if let Some(mailbox) = self.boxes.get(address) {
+			mailbox.notify(MailboxEvent::Wake);
+		}
+	}
+
+	/// Issue the login challenge for the address. The unix time makes the challenges of
+	/// the restarted broker different from the old ones.
+	pub fn issue_challenge(&mut self, address: &str, unix_time: u64, now: Instant) -> String {
+		let challenge = format!("{}-{}", unix_time, self.next_challenge);
+		self.next_challenge += 1;
+		let mailbox = self.mailbox(address);
+		mailbox.expire_challenges(now);
+		if mailbox.challenges.len() >= MAX_CHALLENGES {
+			mailbox.challenges.pop_front();
+		}
+		mailbox.challenges.push_back((challenge.clone(), now));
+		challenge
+	}
+
+	/// Start a new listener session with the challenge that was issued for the address.
+	/// The listeners of the previous sessions are closed.
+	pub fn login(&mut self, address: &str, challenge: &str, now: Instant) -> Result<u64, Error> {
+		let mailbox = self.mailbox(address);
+		mailbox.expire_challenges(now);
+		let index = mailbox
+			.challenges
+			.iter()
+			.position(|(c, _)| c == challenge)
+			.ok_or_else(|| {
+				ErrorKind::InvalidSignature(format!(
+					"time_now {} is not a recent challenge of the broker",
+					challenge
+				))
+			})?;
+		mailbox.challenges.remove(index);
+		mailbox.session_challenge = Some(challenge.to_string());
+		mailbox.session += 1;
+		mailbox.notify(MailboxEvent::NewLogin(mailbox.session));
+		Ok(mailbox.session)
+	}
+
+	/// Current listener session of the address, the challenge must be the one of the login
+	pub fn session(&mut self, address: &str, challenge: &str) -> Result<u64, Error> {
+		let mailbox = self.mailbox(address);
+		if mailbox.session_challenge.as_ref().map(|c| c.as_str()) != Some(challenge) {
+			return Err(ErrorKind::InvalidSignature(format!(
+				"time_now {} doesn't belong to the listener session",
+				challenge
+			))
+			.into());
+		}
+		Ok(mailbox.session)
+	}
+
+	/// Drop the messages that the listener received, up to and including `del_to`
+	pub fn ack(&mut self, address: &str, del_to: u64) {
+		if let Some(mailbox) = self.boxes.get_mut(address) {
+			mailbox.messages.retain(|m| m.id > del_to);
+		}
+	}
+
+	/// Mark the address listener as seen
+	pub fn touch(&mut self, address: &str, now: Instant) {
+		self.mailbox(address).last_seen = Some(now);
+	}
+
+	/// Messages that are waiting for the address
+	pub fn pending(&mut self, address: &str, now: Instant) -> Vec<StoredMessage> {
+		match self.boxes.get_mut(address) {
+			Some(mailbox) => {
+				mailbox.messages.retain(|m| m.expires > now);
+				mailbox.messages.iter().cloned().collect()
+			}
+			None => vec![],
+		}
+	}
+
+	/// Subscribe to the events of the address
+	pub fn subscribe(&mut self, address: &str) -> broadcast::Receiver<MailboxEvent> {
+		self.mailbox(address).events.subscribe()
+	}
+
+	/// Drop the expired messages and the mailboxes that are empty and idle for the TTL
+	pub fn expire(&mut self, now: Instant) {
+		let ttl = self.ttl;
+		for mailbox in self.boxes.values_mut() {
+			mailbox.messages.retain(|m| m.expires > now);
+			mailbox.expire_challenges(now);
+		}
+		self.boxes.retain(|_, m| {
+			let idle = match m.last_seen {
+				Some(t) => now.saturating_duration_since(t) > ttl,
+				None => true,
+			};
+			!(m.messages.is_empty()
+				&& m.challenges.is_empty()
+				&& idle && m.events.receiver_count() == 0)
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ack_and_ttl() {
+		let now = Instant::now();
+		let mut boxes = Mailboxes::new(Duration::from_secs(60), 2);
+		assert_eq!(boxes.post("a", "m1".to_string(), now).unwrap(), None);
+		boxes.touch("a", now);
+		let seen = boxes
+			.post("a", "m2".to_string(), now + Duration::from_secs(5))
+			.unwrap();
+		assert_eq!(seen, Some(Duration::from_secs(5)));
+		assert!(boxes.post("a", "m3".to_string(), now).is_err());
+
+		let pending = boxes.pending("a", now);
+		assert_eq!(pending.len(), 2);
+		boxes.ack("a", pending[0].id);
+		let pending = boxes.pending("a", now);
+		assert_eq!(pending.len(), 1);
+		assert_eq!(pending[0].payload, "m2");
+
+		// Expired messages are not delivered
+		assert!(boxes.pending("a", now + Duration::from_secs(70)).is_empty());
+		boxes.expire(now + Duration::from_secs(130));
+		assert!(boxes.boxes.is_empty());
+	}
+
+	#[test]
+	fn login_closes_previous_session() {
+		let now = Instant::now();
+		let mut boxes = Mailboxes::new(Duration::from_secs(60), 10);
+		let c1 = boxes.issue_challenge("a", 1000, now);
+		let first = boxes.login("a", &c1, now).unwrap();
+		let mut rx = boxes.subscribe("a");
+		let c2 = boxes.issue_challenge("a", 1000, now);
+		assert_ne!(c1, c2);
+		let second = boxes.login("a", &c2, now).unwrap();
+		assert!(second > first);
+		assert_eq!(boxes.session("a", &c2).unwrap(), second);
+		assert!(boxes.session("a", &c1).is_err());
+		assert_eq!(rx.try_recv().unwrap(), MailboxEvent::NewLogin(second));
+		boxes.wake("a");
+		assert_eq!(rx.try_recv().unwrap(), MailboxEvent::Wake);
+	}
+
+	#[test]
+	fn challenge_is_used_once() {
+		let now = Instant::now();
+		let mut boxes = Mailboxes::new(Duration::from_secs(60), 10);
+		let challenge = boxes.issue_challenge("a", 1000, now);
+		// Challenge belongs to the address
+		assert!(boxes.login("b", &challenge, now).is_err());
+		assert!(boxes.login("a", &challenge, now).is_ok());
+		assert!(boxes.login("a", &challenge, now).is_err());
+
+		// Old challenges are rejected
+		let challenge = boxes.issue_challenge("a", 1000, now);
+		assert!(boxes
+			.login(
+				"a",
+				&challenge,
+				now + CHALLENGE_TTL + Duration::from_secs(1)
+			)
+			.is_err());
+		assert!(boxes.login("a", "999-1", now).is_err());
+
+		// Unused challenges keep the mailbox until they expire
+		let mut boxes = Mailboxes::new(Duration::from_secs(60), 10);
+		boxes.issue_challenge("a", 1000, now);
+		boxes.expire(now + Duration::from_secs(61));
+		assert_eq!(boxes.boxes.len(), 1);
+		boxes.expire(now + CHALLENGE_TTL);
+		assert!(boxes.boxes.is_empty());
+	}
+}
@@ -0,0 +1,531 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP server that speaks the MWCMQS protocol of the wallet listener:
+//!
+//! * `GET /timenow` issues the challenge for the address that the listener signs. The
+//!   challenge is used once by the login and expires after `CHALLENGE_TTL`.
+//! * `GET /listener` is the long poll of the listener. The `delTo` parameter acknowledges
+//!   the messages that were received by the previous poll.
+//! * `POST /sender` queues a slate (`mapmessage`) or a swap message (`swapmessage`)
+//!   for the address. `mapmessage=nil` with the listener signature of the session
+//!   wakes up the waiting poll.
+
+use crate::error::{Error, ErrorKind};
+use crate::libwallet::proof::crypto;
+use crate::libwallet::proof::proofaddress::ProvableAddress;
+use crate::mailbox::{MailboxEvent, Mailboxes};
+use crate::util::from_hex;
+use crate::util::secp::key::PublicKey;
+use crate::util::secp::Signature;
+use futures::channel::oneshot;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{body, Body, Method, Request, Response, Server, StatusCode};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::RecvError;
+
+/// Response of the poll that has no messages
+const NO_MESSAGES: &str = "message: mapmessage=nil";
+/// Response of the poll that was replaced by a newer login
+const CLOSE_NEW_LOGIN: &str = "message: closenewlogin\n";
+/// How often the expired messages are dropped
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Broker settings
+#[derive(Clone, Debug)]
+pub struct BrokerConfig {
+	/// Address to listen on
+	pub bind_addr: SocketAddr,
+	/// How long the undelivered messages are kept
+	pub message_ttl: Duration,
+	/// How long the listener poll waits for the messages
+	pub poll_timeout: Duration,
+	/// Max number of waiting messages per address
+	pub max_messages: usize,
+	/// Max size of the posted message in bytes
+	pub max_message_size: usize,
+}
+
+impl Default for BrokerConfig {
+	fn default() -> Self {
+		BrokerConfig {
+			bind_addr: "127.0.0.1:3420".parse().unwrap(),
+			message_ttl: Duration::from_secs(24 * 3600),
+			poll_timeout: Duration::from_secs(60),
+			max_messages: 1000,
+			max_message_size: 1_000_000,
+		}
+	}
+}
+
+struct Broker {
+	config: BrokerConfig,
+	mailboxes: Mutex<Mailboxes>,
+}
+
+/// Running broker. The broker is stopped when the handle is dropped.
+pub struct BrokerHandle {
+	local_addr: SocketAddr,
+	shutdown: Option<oneshot::Sender<()>>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl BrokerHandle {
+	/// Address the broker is listening on
+	pub fn local_addr(&self) -> SocketAddr {
+		self.local_addr
+	}
+
+	/// Stop the broker and wait for its thread
+	pub fn stop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			let _ = shutdown.send(());
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Drop for BrokerHandle {
+	fn drop(&mut self) {
+		self.stop();
+	}
+}
+
+/// Start the broker in a background thread
+pub fn start_broker(config: BrokerConfig) -> Result<BrokerHandle, Error> {
+	let listener = TcpListener::bind(config.bind_addr)?;
+	listener.set_nonblocking(true)?;
+	let local_addr = listener.local_addr()?;
+
+	let broker = Arc::new(Broker {
+		mailboxes: Mutex::new(Mailboxes::new(config.message_ttl, config.max_messages)),
+		config,
+	});
+	let mut rt = tokio::runtime::Runtime::new()
+		.map_err(|e| ErrorKind::Server(format!("Unable to start the runtime, {}", e)))?;
+	let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+
+	let thread = thread::Builder::new()
+		.name("mwcmqs-broker".to_string())
+		.spawn(move || {
+			rt.block_on(async move {
+				let expire_broker = broker.clone();
+				tokio::spawn(async move {
+					let mut interval = tokio::time::interval(EXPIRE_INTERVAL);
+					loop {
+						interval.tick().await;
+						expire_broker
+							.mailboxes
+							.lock()
+							.unwrap()
+							.expire(Instant::now());
+					}
+				});
+
+				let make_svc = make_service_fn(move |_| {
+					let broker = broker.clone();
+					async move {
+						Ok::<_, Infallible>(service_fn(move |req| {
+							handle_request(broker.clone(), req)
+						}))
+					}
+				});
+				let server = match Server::from_tcp(listener) {
+					Ok(builder) => builder.serve(make_svc),
+					Err(e) => {
+						error!("Unable to start the MWCMQS broker, {}", e);
+						return;
+					}
+				};
+				info!("MWCMQS broker is listening on {}", local_addr);
+				// Dropping the runtime closes the waiting polls, no need to drain them
+				tokio::select! {
+					res = server => {
+						if let Err(e) = res {
+							error!("MWCMQS broker failed, {}", e);
+						}
+					}
+					_ = shutdown_rx => (),
+				}
+			});
+			info!("MWCMQS broker on {} is stopped", local_addr);
+		})?;
+
+	Ok(BrokerHandle {
+		local_addr,
+		shutdown: Some(shutdown),
+		thread: Some(thread),
+	})
+}
+
+async fn handle_request(
+	broker: Arc<Broker>,
+	req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+	let params = parse_params(req.uri().query().unwrap_or("").as_bytes());
+	let method = req.method().clone();
+	let path = req.uri().path().to_string();
+	let res = match (&method, path.as_str()) {
+		(&Method::GET, "/timenow") => handle_timenow(&broker, &params),
+		(&Method::GET, "/listener") => handle_listener(&broker, &params).await,
+		(&Method::POST, "/sender") => match body::to_bytes(req.into_body()).await {
+			Ok(data) => handle_sender(&broker, &params, &data),
+			Err(e) => Err(ErrorKind::InvalidRequest(format!("Unable to read body, {}", e)).into()),
+		},
+		_ => {
+			let mut resp = Response::new(Body::from("Not found"));
+			*resp.status_mut() = StatusCode::NOT_FOUND;
+			return Ok(resp);
+		}
+	};
+
+	Ok(match res {
+		Ok(text) => Response::new(Body::from(text)),
+		Err(e) => {
+			debug!("MWCMQS broker request failed, {}", e);
+			let status = match e.kind() {
+				ErrorKind::MailboxFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+				ErrorKind::IO(_) | ErrorKind::Server(_) => StatusCode::INTERNAL_SERVER_ERROR,
+				_ => StatusCode::BAD_REQUEST,
+			};
+			let mut resp = Response::new(Body::from(format!("{}", e)));
+			*resp.status_mut() = status;
+			resp
+		}
+	})
+}
+
+/// Issue the login challenge for the listener of the address
+fn handle_timenow(broker: &Broker, params: &HashMap<String, String>) -> Result<String, Error> {
+	let (mailbox, _) = parse_address(get_param(params, "address")?)?;
+	Ok(broker
+		.mailboxes
+		.lock()
+		.unwrap()
+		.issue_challenge(&mailbox, unix_time(), Instant::now()))
+}
+
+/// Long poll of the listener
+async fn handle_listener(
+	broker: &Broker,
+	params: &HashMap<String, String>,
+) -> Result<String, Error> {
+	let address = get_param(params, "address")?;
+	let time_now = get_param(params, "time_now")?;
+	let signature = get_param(params, "signature")?;
+	let del_to = get_param(params, "delTo")?;
+	let first = params.get("first").map(|s| s == "true").unwrap_or(false);
+
+	let (mailbox, public_key) = parse_address(address)?;
+	verify_listener(time_now, signature, &public_key)?;
+
+	let (session, mut events) = {
+		let mut mailboxes = broker.mailboxes.lock().unwrap();
+		let session = if first {
+			mailboxes.login(&mailbox, time_now, Instant::now())?
+		} else {
+			mailboxes.session(&mailbox, time_now)?
+		};
+		if del_to != "nil" {
+			let del_to = del_to.parse::<u64>().map_err(|_| {
+				ErrorKind::InvalidRequest(format!("Invalid delTo value {}", del_to))
+			})?;
+			mailboxes.ack(&mailbox, del_to);
+		}
+		mailboxes.touch(&mailbox, Instant::now());
+		// Subscribe before checking the queue, so a message posted in between is not missed
+		let events = mailboxes.subscribe(&mailbox);
+		if let Some(list) = message_list(&mut mailboxes, &mailbox) {
+			return Ok(list);
+		}
+		(session, events)
+	};
+
+	let deadline = tokio::time::Instant::now() + broker.config.poll_timeout;
+	let res = loop {
+		let event = match tokio::time::timeout_at(deadline, events.recv()).await {
+			Ok(event) => event,
+			Err(_) => break NO_MESSAGES.to_string(),
+		};
+		match event {
+			Ok(MailboxEvent::Message) | Err(RecvError::Lagged(_)) => {
+				let mut mailboxes = broker.mailboxes.lock().unwrap();
+				if let Some(list) = message_list(&mut mailboxes, &mailbox) {
+					break list;
+				}
+			}
+			Ok(MailboxEvent::NewLogin(s)) if s != session => break CLOSE_NEW_LOGIN.to_string(),
+			Ok(MailboxEvent::NewLogin(_)) => (),
+			Ok(MailboxEvent::Wake) | Err(RecvError::Closed) => break NO_MESSAGES.to_string(),
+		}
+	};
+	broker
+		.mailboxes
+		.lock()
+		.unwrap()
+		.touch(&mailbox, Instant::now());
+	Ok(res)
+}
+
+/// Queue the message for the address
+fn handle_sender(
+	broker: &Broker,
+	params: &HashMap<String, String>,
+	data: &[u8],
+) -> Result<String, Error> {
+	if data.len() > broker.config.max_message_size {
+		return Err(ErrorKind::InvalidRequest("Message is too large".to_string()).into());
+	}
+	let to = get_param(params, "address")?;
+	let (to_mailbox, to_key) = parse_address(to)?;
+	let form = parse_params(data);
+
+	// Listener stop request, wakes up the waiting poll of the session
+	if form.get("mapmessage").map(|s| s.as_str()) == Some("nil") {
+		let time_now = get_param(&form, "time_now")?;
+		verify_listener(time_now, get_param(&form, "signature")?, &to_key)?;
+		let mut mailboxes = broker.mailboxes.lock().unwrap();
+		mailboxes.session(&to_mailbox, time_now)?;
+		mailboxes.wake(&to_mailbox);
+		return Ok("lastseen: 0".to_string());
+	}
+
+	let (kind, message) = if let Some(message) = form.get("mapmessage") {
+		("mapmessage", message)
+	} else if let Some(message) = form.get("swapmessage") {
+		("swapmessage", message)
+	} else {
+		return Err(ErrorKind::InvalidRequest("Message is not found".to_string()).into());
+	};
+	let from = get_param(&form, "from")?;
+	let signature = get_param(&form, "signature")?;
+
+	let (_, from_key) = parse_address(from)?;
+	let sig = crypto::signature_from_string(signature)
+		.map_err(|e| ErrorKind::InvalidSignature(format!("{}", e)))?;
+	crypto::verify_signature(message, &sig, &from_key)
+		.map_err(|_| ErrorKind::InvalidSignature(format!("message is not signed by {}", from)))?;
+
+	// Listener splits the message list by spaces and the fields by '&' and '='
+	let forbidden = |s: &str| s.contains(|c| c == ' ' || c == '&' || c == '=' || c == '\n');
+	if forbidden(message) || forbidden(from) || forbidden(signature) {
+		return Err(ErrorKind::InvalidRequest("Message has invalid characters".to_string()).into());
+	}
+	let payload = format!(
+		"{}={}&from={}&signature={}",
+		kind,
+		encode_message(message),
+		from,
+		signature
+	);
+
+	let last_seen = broker
+		.mailboxes
+		.lock()
+		.unwrap()
+		.post(&to_mailbox, payload, Instant::now())?;
+	let last_seen = match last_seen {
+		Some(d) => d.as_millis() as i64,
+		None => i64::MAX,
+	};
+	Ok(format!("lastseen: {}", last_seen))
+}
+
+/// Waiting messages in the listener format, None if there are no messages
+fn message_list(mailboxes: &mut Mailboxes, mailbox: &str) -> Option<String> {
+	let messages = mailboxes.pending(mailbox, Instant::now());
+	if messages.is_empty() {
+		return None;
+	}
+	let mut res = "messagelist: \n".to_string();
+	for m in messages {
+		res.push_str(&format!("message: {};{}\n", m.id, m.payload));
+	}
+	Some(res)
+}
+
+/// Listener signs the challenge once per session. The signature is the hex of the raw
+/// signature data.
+fn verify_listener(time_now: &str, signature: &str, public_key: &PublicKey) -> Result<(), Error> {
+	let data = from_hex(signature)
+		.map_err(|e| ErrorKind::InvalidSignature(format!("Invalid signature hex, {}", e)))?;
+	if data.len() != 64 {
+		return Err(ErrorKind::InvalidSignature("Invalid signature length".to_string()).into());
+	}
+	let mut raw = [0u8; 64];
+	raw.copy_from_slice(&data);
+	let sig = Signature::from_raw_data(&raw)
+		.map_err(|e| ErrorKind::InvalidSignature(format!("{}", e)))?;
+	crypto::verify_signature(time_now, &sig, public_key)
+		.map_err(|_| ErrorKind::InvalidSignature("time_now is not signed".to_string()))?;
+	Ok(())
+}
+
+/// Mailbox name and public key of the `<public_key>@<domain>:<port>` address
+fn parse_address(address: &str) -> Result<(String, PublicKey), Error> {
+	let address = address.trim_start_matches("mwcmqs://");
+	let key = address.split('@').next().unwrap_or("");
+	let public_key = ProvableAddress::from_str(key)
+		.and_then(|a| a.public_key())
+		.map_err(|e| ErrorKind::InvalidAddress(format!("{}, {}", address, e)))?;
+	Ok((key.to_string(), public_key))
+}
+
+/// Listener expects the JSON with these characters escaped
+fn encode_message(message: &str) -> String {
+	message
+		.replace("{", "%7B")
+		.replace("}", "%7D")
+		.replace(":", "%3A")
+		.replace(",", "%2C")
+		.replace("\"", "%22")
+}
+
+fn parse_params(data: &[u8]) -> HashMap<String, String> {
+	url::form_urlencoded::parse(data).into_owned().collect()
+}
+
+fn get_param<'a>(params: &'a HashMap<String, String>, name: &str) -> Result<&'a str, Error> {
+	params
+		.get(name)
+		.map(|s| s.as_str())
+		.ok_or_else(|| ErrorKind::InvalidRequest(format!("Parameter {} is missing", name)).into())
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::libwallet::proof::crypto::Hex;
+	use crate::util::secp::key::SecretKey;
+	use crate::util::secp::Secp256k1;
+	use crate::util::to_hex;
+
+	fn test_key() -> (SecretKey, String) {
+		let secp = Secp256k1::new();
+		let sk = SecretKey::from_slice(&secp, &[7u8; 32]).unwrap();
+		let pk = PublicKey::from_secret_key(&secp, &sk).unwrap();
+		(sk, ProvableAddress::from_pub_key(&pk).public_key)
+	}
+
+	// Listener sends the Debug output of the signature
+	fn sign_listener(time_now: &str, sk: &SecretKey) -> String {
+		let sig = crypto::sign_challenge(time_now, sk).unwrap();
+		format!("{:?}", sig)
+			.replace("Signature(", "")
+			.replace(")", "")
+	}
+
+	fn test_broker() -> Broker {
+		Broker {
+			config: BrokerConfig::default(),
+			mailboxes: Mutex::new(Mailboxes::new(Duration::from_secs(60), 10)),
+		}
+	}
+
+	#[test]
+	fn listener_signature() {
+		let (sk, address) = test_key();
+		let (mailbox, pk) = parse_address(&format!("{}@127.0.0.1:3420", address)).unwrap();
+		assert_eq!(mailbox, address);
+
+		let time_now = unix_time().to_string();
+		let sig = sign_listener(&time_now, &sk);
+		assert!(verify_listener(&time_now, &sig, &pk).is_ok());
+		assert!(verify_listener("12345", &sig, &pk).is_err());
+
+		let der = to_hex(vec![1u8; 70]);
+		assert!(verify_listener(&time_now, &der, &pk).is_err());
+	}
+
+	#[test]
+	fn sender_checks_signature() {
+		let (sk, address) = test_key();
+		let broker = test_broker();
+		let mut params = HashMap::new();
+		params.insert("address".to_string(), format!("{}@127.0.0.1", address));
+
+		let message = "{\"destination\":\"x\",\"data\":\"00ff\"}";
+		let sig = crypto::sign_challenge(message, &sk).unwrap();
+		let form = |sig: &str| {
+			url::form_urlencoded::Serializer::new(String::new())
+				.append_pair("mapmessage", message)
+				.append_pair("from", &format!("{}@127.0.0.1", address))
+				.append_pair("signature", sig)
+				.finish()
+		};
+
+		assert!(handle_sender(&broker, &params, form("3044").as_bytes()).is_err());
+		let res = handle_sender(&broker, &params, form(&sig.to_hex()).as_bytes()).unwrap();
+		assert_eq!(res, format!("lastseen: {}", i64::MAX));
+
+		let mut mailboxes = broker.mailboxes.lock().unwrap();
+		let list = message_list(&mut mailboxes, &address).unwrap();
+		let expected = format!(
+			"messagelist: \nmessage: 1;mapmessage=%7B%22destination%22%3A%22x%22%2C%22data%22%3A%2200ff%22%7D&from={}@127.0.0.1&signature={}\n",
+			address,
+			sig.to_hex()
+		);
+		assert_eq!(list, expected);
+	}
+
+	#[test]
+	fn wake_requires_session_signature() {
+		let (sk, address) = test_key();
+		let broker = test_broker();
+		let mut params = HashMap::new();
+		params.insert("address".to_string(), format!("{}@127.0.0.1", address));
+		let wake = |time_now: &str, sig: &str| {
+			url::form_urlencoded::Serializer::new(String::new())
+				.append_pair("mapmessage", "nil")
+				.append_pair("time_now", time_now)
+				.append_pair("signature", sig)
+				.finish()
+		};
+
+		let challenge = handle_timenow(&broker, &params).unwrap();
+		let sig = sign_listener(&challenge, &sk);
+		assert!(handle_sender(&broker, &params, b"mapmessage=nil").is_err());
+		// Wake needs the login of the session
+		assert!(handle_sender(&broker, &params, wake(&challenge, &sig).as_bytes()).is_err());
+		broker
+			.mailboxes
+			.lock()
+			.unwrap()
+			.login(&address, &challenge, Instant::now())
+			.unwrap();
+		let res = handle_sender(&broker, &params, wake(&challenge, &sig).as_bytes()).unwrap();
+		assert_eq!(res, "lastseen: 0");
+		let bad_sig = sign_listener("12345", &sk);
+		assert!(handle_sender(&broker, &params, wake(&challenge, &bad_sig).as_bytes()).is_err());
+
+		// Signed challenge of another session is rejected
+		let other = handle_timenow(&broker, &params).unwrap();
+		let other_sig = sign_listener(&other, &sk);
+		assert!(handle_sender(&broker, &params, wake(&other, &other_sig).as_bytes()).is_err());
+	}
+}
@@ -162,8 +162,9 @@ fn real_main() -> i32 {
 	// Default derive index is 1 to match what mwc713 has by default...
 	proofaddress::set_address_index(wallet_config.grinbox_address_index.unwrap_or(0));
 
-	let node_client = HTTPNodeClient::new(&wallet_config.check_node_api_http_addr, None)
+	let mut node_client = HTTPNodeClient::new(&wallet_config.check_node_api_http_addr, None)
 		.expect("Unable create HTTP client for mwc-node connection");
+	node_client.set_failover(config.members.as_ref().unwrap().node_failover.clone());
 
 	cmd::wallet_command(&args, config, node_client)
 }
@@ -15,8 +15,11 @@
 use crate::cmd::wallet_args;
 use crate::config::GlobalWalletConfig;
 use clap::ArgMatches;
-use grin_wallet_libwallet::NodeClient;
+use grin_wallet_controller::executor::{set_api_call_limits, API_CALL_THREADS, API_CALL_TIMEOUT};
+use grin_wallet_controller::owner_access::set_owner_api_config;
+use grin_wallet_libwallet::{NodeClient, WalletSettings};
 use semver::Version;
+use std::path::Path;
 use std::thread;
 use std::time::Duration;
 
@@ -34,7 +37,49 @@ where
 	let wallet_config = config.members.clone().unwrap().wallet;
 
 	let tor_config = config.members.clone().unwrap().tor;
-	let mqs_config = config.members.unwrap().mqs;
+	let mqs_config = config.members.clone().unwrap().mqs;
+
+	// Settings of the wallet instance, they are applied when the wallet is opened
+	let wallet_settings = WalletSettings {
+		receive_policy: config.members.clone().unwrap().receive_policy,
+		header_verification: wallet_config.verify_node_headers.unwrap_or(false),
+		header_checkpoint: match (
+			wallet_config.header_checkpoint_height,
+			wallet_config.header_checkpoint_hash.clone(),
+		) {
+			(Some(height), Some(hash)) => Some((height, hash)),
+			_ => None,
+		},
+		minimum_confirmations: wallet_config.minimum_confirmations.unwrap_or(10),
+		scan_threads: wallet_config.scan_threads.unwrap_or(4),
+		rebroadcast: config.members.clone().unwrap().rebroadcast,
+		auto_cancel_expired: wallet_config.auto_cancel_expired.unwrap_or(true),
+		fee_policy: config
+			.members
+			.clone()
+			.unwrap()
+			.fee_policy
+			.unwrap_or_default(),
+	};
+
+	// Audit log path is relative to the wallet data dir
+	let mut owner_api_config = config.members.unwrap().owner_api;
+	if let Some(c) = owner_api_config.as_mut() {
+		c.audit_log_path = c.audit_log_path.as_ref().map(|p| {
+			Path::new(&wallet_config.data_file_dir)
+				.join(p)
+				.to_string_lossy()
+				.into_owned()
+		});
+	}
+	set_owner_api_config(owner_api_config);
+	set_api_call_limits(
+		wallet_config.api_call_threads.unwrap_or(API_CALL_THREADS),
+		wallet_config
+			.api_call_timeout_secs
+			.map(Duration::from_secs)
+			.unwrap_or(API_CALL_TIMEOUT),
+	);
 
 	// Check the node version info, and exit with report if we're not compatible
 	let global_wallet_args = wallet_args::parse_global_args(&wallet_config, &wallet_args)
@@ -59,6 +104,7 @@ where
 		wallet_config,
 		tor_config,
 		mqs_config,
+		wallet_settings,
 		node_client,
 		false,
 		|_| {},
@@ -34,7 +34,8 @@ use grin_wallet_libwallet::proof::proofaddress;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
 use grin_wallet_libwallet::Slate;
 use grin_wallet_libwallet::{
-	IssueInvoiceTxArgs, NodeClient, SwapStartArgs, WalletInst, WalletLCProvider,
+	BatchSendEntry, Contact, IssueInvoiceTxArgs, NodeClient, SpendPolicyArgs, SwapStartArgs,
+	WalletInst, WalletLCProvider, WalletSettings,
 };
 use grin_wallet_util::grin_core as core;
 use grin_wallet_util::grin_core::core::amount_to_hr_string;
@@ -53,8 +54,8 @@ macro_rules! arg_parse {
 			Ok(res) => res,
 			Err(e) => {
 				return Err(ErrorKind::ArgumentError(format!("{}", e)).into());
-				}
 			}
+		}
 	};
 }
 /// Simple error definition, just so we can return errors from all commands
@@ -479,9 +480,16 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		}
 	};
 
+	// '@name' is the address book contact, it defines the method and the address
+	let contact = match method != "file" && method != "self" && dest.starts_with('@') {
+		true => Some(dest[1..].to_string()),
+		false => None,
+	};
+
 	let apisecret = args.value_of("apisecret").map(|s| String::from(s));
 
 	if !estimate_selection_strategies
+		&& contact.is_none()
 		&& method == "http"
 		&& !dest.starts_with("http://")
 		&& !dest.starts_with("https://")
@@ -504,6 +512,12 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 	// ttl_blocks
 	let ttl_blocks = parse_u64_or_none(args.value_of("ttl_blocks"));
 
+	// lock_height
+	let lock_height = match args.value_of("lock_height") {
+		Some(h) => Some(parse_u64(h, "lock_height")?),
+		None => None,
+	};
+
 	// max_outputs
 	let max_outputs = 500;
 
@@ -518,10 +532,12 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		}
 	};
 
+	let request_payment_proof =
+		args.is_present("request_payment_proof") || (args.is_present("proof") && method != "file");
 	let payment_proof_address = {
-		match args.is_present("request_payment_proof")
-			|| (args.is_present("proof") && method != "file")
-		{
+		match request_payment_proof {
+			// contact proof address is used if it is not specified
+			true if contact.is_some() && !args.is_present("proof_address") => None,
 			true => {
 				// if the destination address is a TOR address, we don't need the address
 				// separately
@@ -557,6 +573,14 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 	)?;
 	let exclude_change_outputs = args.is_present("exclude_change_outputs");
 
+	// compact slate makes sense for files only, other methods negotiate the encoding
+	let compact_slate = args.is_present("compact_slate");
+	if compact_slate && method != "file" {
+		return Err(ParseError::ArgumentError(
+			"compact_slate can be used with file method only".to_string(),
+		));
+	}
+
 	let outputs = match args.is_present("outputs") {
 		true => Some(
 			args.value_of("outputs")
@@ -585,15 +609,216 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 			max_outputs: max_outputs,
 			payment_proof_address,
 			ttl_blocks,
+			lock_height,
 			target_slate_version: target_slate_version,
 			exclude_change_outputs: exclude_change_outputs,
 			minimum_confirmations_change_outputs: minimum_confirmations_change_outputs,
 			address: address,
 			outputs,
+			compact_slate,
+			contact,
+			request_payment_proof,
 		})
 	}
 }
 
+// Batch recipients file, one recipient per line: <dest>,<amount>,<method>[,<proof_address>]
+// Empty lines and lines started with '#' are skipped
+fn parse_batch_entries(file_name: &str) -> Result<Vec<BatchSendEntry>, ParseError> {
+	let content = std::fs::read_to_string(file_name).map_err(|e| {
+		ParseError::IOError(format!("Unable to read batch file {}, {}", file_name, e))
+	})?;
+
+	let mut entries = Vec::new();
+	for (i, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with("#") {
+			continue;
+		}
+		let fields: Vec<&str> = line.split(",").map(|f| f.trim()).collect();
+		if fields.len() < 3 || fields.len() > 4 {
+			let msg = format!(
+				"Line {} of {}, expected '<dest>,<amount>,<method>[,<proof_address>]', get '{}'",
+				i + 1,
+				file_name,
+				line
+			);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		let amount = core::core::amount_from_hr_string(fields[1]).map_err(|e| {
+			ParseError::ArgumentError(format!(
+				"Line {} of {}, unable to parse amount {}, {}",
+				i + 1,
+				file_name,
+				fields[1],
+				e
+			))
+		})?;
+		let method = fields[2];
+		if method != "http" && method != "mwcmqs" {
+			let msg = format!(
+				"Line {} of {}, method '{}' is not supported for batch, please use http or mwcmqs",
+				i + 1,
+				file_name,
+				method
+			);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		let payment_proof_recipient_address = match fields.get(3) {
+			Some(addr) => Some(
+				ProvableAddress::from_str(&proofaddress::address_to_pubkey(addr.to_string()))
+					.map_err(|e| {
+						ParseError::ArgumentError(format!(
+							"Line {} of {}, invalid proof address: {:?}",
+							i + 1,
+							file_name,
+							e
+						))
+					})?,
+			),
+			None => None,
+		};
+		entries.push(BatchSendEntry {
+			method: method.to_owned(),
+			dest: fields[0].to_owned(),
+			amount,
+			apisecret: None,
+			message: None,
+			payment_proof_recipient_address,
+		});
+	}
+
+	if entries.is_empty() {
+		let msg = format!("Batch file {} doesn't have any recipients", file_name);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(entries)
+}
+
+pub fn parse_send_batch_args(args: &ArgMatches) -> Result<command::SendBatchArgs, ParseError> {
+	let input = parse_required(args, "input")?;
+	let entries = parse_batch_entries(input)?;
+
+	// minimum_confirmations
+	let min_c = parse_required(args, "minimum_confirmations")?;
+	let min_c = parse_u64(min_c, "minimum_confirmations")?;
+
+	// selection_strategy
+	let selection_strategy = parse_required(args, "selection_strategy")?;
+
+	// target slate version to create/send
+	let target_slate_version = match args.value_of("slate_version") {
+		Some(v) => Some(parse_u64(v, "slate_version")? as u16),
+		None => None,
+	};
+
+	Ok(command::SendBatchArgs {
+		entries,
+		minimum_confirmations: min_c,
+		selection_strategy: selection_strategy.to_owned(),
+		fluff: args.is_present("fluff"),
+		target_slate_version,
+		ttl_blocks: parse_u64_or_none(args.value_of("ttl_blocks")),
+	})
+}
+
+pub fn parse_transfer_args(args: &ArgMatches) -> Result<command::TransferCmdArgs, ParseError> {
+	let to = parse_required(args, "to")?;
+
+	// amount
+	let amount = parse_required(args, "amount")?;
+	let amount = core::core::amount_from_hr_string(amount).map_err(|e| {
+		ParseError::ArgumentError(format!(
+			"Could not parse amount as a number with optional decimal point. e={}",
+			e
+		))
+	})?;
+
+	// minimum_confirmations
+	let min_c = parse_required(args, "minimum_confirmations")?;
+	let min_c = parse_u64(min_c, "minimum_confirmations")?;
+
+	// selection_strategy
+	let selection_strategy = parse_required(args, "selection_strategy")?;
+
+	// change_outputs
+	let change_outputs = parse_required(args, "change_outputs")?;
+	let change_outputs = parse_u64(change_outputs, "change_outputs")? as usize;
+
+	Ok(command::TransferCmdArgs {
+		from: args.value_of("from").map(|s| s.to_owned()),
+		to: to.to_owned(),
+		amount,
+		minimum_confirmations: min_c,
+		selection_strategy: selection_strategy.to_owned(),
+		change_outputs,
+		message: args.value_of("message").map(|s| s.to_owned()),
+		fluff: args.is_present("fluff"),
+	})
+}
+
+pub fn parse_cold_args(args: &ArgMatches) -> Result<command::ColdArgs, ParseError> {
+	let action = match parse_required(args, "action")? {
+		"export_outputs" => command::ColdAction::ExportOutputs,
+		"import_outputs" => command::ColdAction::ImportOutputs,
+		"send" => command::ColdAction::Send,
+		"receive" => command::ColdAction::Receive,
+		"finalize" => command::ColdAction::Finalize,
+		"sign" => command::ColdAction::Sign,
+		"process" => command::ColdAction::Process,
+		a => {
+			let msg = format!("Unknown cold wallet action {}", a);
+			return Err(ParseError::ArgumentError(msg));
+		}
+	};
+
+	// amount, required by the send only
+	let amount = match action {
+		command::ColdAction::Send => {
+			let amount = parse_required(args, "amount")?;
+			core::core::amount_from_hr_string(amount).map_err(|e| {
+				ParseError::ArgumentError(format!(
+					"Could not parse amount as a number with optional decimal point. e={}",
+					e
+				))
+			})?
+		}
+		_ => 0,
+	};
+
+	let input = args.value_of("input").map(|s| s.to_owned());
+	if let Some(f) = &input {
+		if !Path::new(f).is_file() {
+			let msg = format!("File {} not found.", f);
+			return Err(ParseError::ArgumentError(msg));
+		}
+	}
+
+	// minimum_confirmations
+	let min_c = parse_required(args, "minimum_confirmations")?;
+	let min_c = parse_u64(min_c, "minimum_confirmations")?;
+
+	// selection_strategy
+	let selection_strategy = parse_required(args, "selection_strategy")?;
+
+	// change_outputs
+	let change_outputs = parse_required(args, "change_outputs")?;
+	let change_outputs = parse_u64(change_outputs, "change_outputs")? as usize;
+
+	Ok(command::ColdArgs {
+		action,
+		input,
+		dest: args.value_of("dest").map(|s| s.to_owned()),
+		amount,
+		minimum_confirmations: min_c,
+		selection_strategy: selection_strategy.to_owned(),
+		change_outputs,
+		message: args.value_of("message").map(|s| s.to_owned()),
+		ttl_blocks: parse_u64_or_none(args.value_of("ttl_blocks")),
+		fluff: args.is_present("fluff"),
+	})
+}
+
 pub fn parse_receive_args(receive_args: &ArgMatches) -> Result<command::ReceiveArgs, ParseError> {
 	// message
 	let message = match receive_args.is_present("message") {
@@ -669,6 +894,13 @@ pub fn parse_issue_invoice_args(
 			false => None,
 		}
 	};
+	// external reference
+	let reference = args.value_of("reference").map(|r| r.to_owned());
+	// expiry blocks
+	let expiry_blocks = match args.value_of("expiry") {
+		Some(e) => Some(parse_u64(e, "expiry")?),
+		None => None,
+	};
 	// dest (output file)
 	let dest = parse_required(args, "dest")?;
 	Ok(command::IssueInvoiceArgs {
@@ -679,7 +911,163 @@ pub fn parse_issue_invoice_args(
 			amount,
 			message,
 			target_slate_version,
+			reference,
+			expiry_blocks,
+		},
+	})
+}
+
+pub fn parse_invoices_args(args: &ArgMatches) -> Result<command::InvoicesArgs, ParseError> {
+	let id = match args.value_of("id") {
+		None => None,
+		Some(id) => match id.parse() {
+			Ok(i) => Some(i),
+			Err(e) => {
+				let msg = format!("Could not parse id parameter. e={}", e);
+				return Err(ParseError::ArgumentError(msg));
+			}
 		},
+	};
+	Ok(command::InvoicesArgs {
+		id,
+		reference: args.value_of("reference").map(|r| r.to_owned()),
+	})
+}
+
+pub fn parse_cancel_invoice_args(
+	args: &ArgMatches,
+) -> Result<command::CancelInvoiceArgs, ParseError> {
+	let id = parse_required(args, "id")?;
+	let id = match id.parse() {
+		Ok(i) => i,
+		Err(e) => {
+			let msg = format!("Could not parse id parameter. e={}", e);
+			return Err(ParseError::ArgumentError(msg));
+		}
+	};
+	Ok(command::CancelInvoiceArgs { id })
+}
+
+pub fn parse_pending_slates_args(
+	args: &ArgMatches,
+) -> Result<command::PendingSlatesArgs, ParseError> {
+	let id = match args.value_of("id") {
+		None => None,
+		Some(id) => match id.parse() {
+			Ok(i) => Some(i),
+			Err(e) => {
+				let msg = format!("Could not parse id parameter. e={}", e);
+				return Err(ParseError::ArgumentError(msg));
+			}
+		},
+	};
+	let approve = args.is_present("approve");
+	let reject = args.is_present("reject");
+	if approve && reject {
+		let msg = "Slate can't be approved and rejected at the same time".to_string();
+		return Err(ParseError::ArgumentError(msg));
+	}
+	if (approve || reject) && id.is_none() {
+		let msg = "Slate ID (--id) is required to approve or reject the slate".to_string();
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(command::PendingSlatesArgs {
+		id,
+		approve,
+		reject,
+	})
+}
+
+pub fn parse_spend_policy_args(
+	g_args: &command::GlobalArgs,
+	args: &ArgMatches,
+) -> Result<command::SpendPolicyCmdArgs, ParseError> {
+	let parse_amount = |name: &str| -> Result<Option<u64>, ParseError> {
+		match args.value_of(name) {
+			None => Ok(None),
+			Some(a) => match core::core::amount_from_hr_string(a) {
+				Ok(v) => Ok(Some(v)),
+				Err(e) => {
+					let msg = format!(
+						"Could not parse {} as a number with optional decimal point. e={}",
+						name, e
+					);
+					Err(ParseError::ArgumentError(msg))
+				}
+			},
+		}
+	};
+	let max_per_tx = parse_amount("max_per_tx")?;
+	let max_24h = parse_amount("max_24h")?;
+	let max_7d = parse_amount("max_7d")?;
+	let new_destination_cooldown_secs = match args.value_of("cooldown") {
+		Some(c) => Some(parse_u64(c, "cooldown")?),
+		None => None,
+	};
+	let allowed_destinations: Option<Vec<String>> = args
+		.values_of("allow")
+		.map(|v| v.map(|a| a.to_string()).collect());
+
+	let set = args.is_present("set");
+	let clear = args.is_present("clear");
+	if set && clear {
+		let msg = "Policy can't be set and cleared at the same time".to_string();
+		return Err(ParseError::ArgumentError(msg));
+	}
+	let has_settings = max_per_tx.is_some()
+		|| max_24h.is_some()
+		|| max_7d.is_some()
+		|| new_destination_cooldown_secs.is_some()
+		|| allowed_destinations.is_some();
+	if has_settings && !set {
+		let msg = "Please use --set to replace the policy of the account".to_string();
+		return Err(ParseError::ArgumentError(msg));
+	}
+
+	let password = match set || clear {
+		true => Some(prompt_password(&g_args.password)),
+		false => None,
+	};
+	let policy = match set {
+		true => Some(SpendPolicyArgs {
+			src_acct_name: None,
+			max_per_tx,
+			max_24h,
+			max_7d,
+			allowed_destinations,
+			new_destination_cooldown_secs,
+		}),
+		false => None,
+	};
+	Ok(command::SpendPolicyCmdArgs {
+		policy,
+		clear,
+		password,
+	})
+}
+
+pub fn parse_freeze_output_args(
+	args: &ArgMatches,
+	frozen: bool,
+) -> Result<command::FreezeOutputArgs, ParseError> {
+	let commit = parse_required(args, "commitment")?;
+	Ok(command::FreezeOutputArgs {
+		commit: commit.to_string(),
+		frozen,
+	})
+}
+
+pub fn parse_label_output_args(args: &ArgMatches) -> Result<command::LabelOutputArgs, ParseError> {
+	let commit = parse_required(args, "commitment")?;
+	let note = args.value_of("note").map(|n| n.to_string());
+	let tags = match args.values_of("tag") {
+		Some(t) => t.map(|t| t.to_string()).collect(),
+		None => vec![],
+	};
+	Ok(command::LabelOutputArgs {
+		commit: commit.to_string(),
+		note,
+		tags,
 	})
 }
 
@@ -812,12 +1200,71 @@ pub fn parse_txs_args(args: &ArgMatches) -> Result<command::TxsArgs, ParseError>
 		let msg = format!("At most one of 'id' (-i) or 'txid' (-t) may be provided.");
 		return Err(ParseError::ArgumentError(msg));
 	}
+	let search = args.value_of("search").map(|s| s.to_string());
 	Ok(command::TxsArgs {
 		id: tx_id,
 		tx_slate_id: tx_slate_id,
+		search,
+	})
+}
+
+pub fn parse_label_tx_args(args: &ArgMatches) -> Result<command::LabelTxArgs, ParseError> {
+	let id = match args.value_of("id") {
+		None => None,
+		Some(tx) => Some(parse_u64(tx, "id")? as u32),
+	};
+	let tx_slate_id = match args.value_of("txid") {
+		None => None,
+		Some(tx) => match tx.parse() {
+			Ok(t) => Some(t),
+			Err(e) => {
+				let msg = format!("Could not parse txid parameter. e={}", e);
+				return Err(ParseError::ArgumentError(msg));
+			}
+		},
+	};
+	if id.is_some() == tx_slate_id.is_some() {
+		let msg = format!("Exactly one of 'id' (-i) or 'txid' (-t) must be provided.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	let note = args.value_of("note").map(|n| n.to_string());
+	let tags = match args.values_of("tag") {
+		Some(t) => t.map(|t| t.to_string()).collect(),
+		None => vec![],
+	};
+	Ok(command::LabelTxArgs {
+		id,
+		tx_slate_id,
+		note,
+		tags,
 	})
 }
 
+pub fn parse_address_book_args(args: &ArgMatches) -> Result<command::AddressBookArgs, ParseError> {
+	let remove = args.value_of("remove").map(|n| n.to_string());
+	let add = match args.value_of("add") {
+		Some(name) => {
+			if remove.is_some() {
+				let msg = "Contact can't be added and removed at the same time".to_string();
+				return Err(ParseError::ArgumentError(msg));
+			}
+			let slate_version = match args.value_of("slate_version") {
+				Some(v) => Some(parse_u64(v, "slate_version")? as u16),
+				None => None,
+			};
+			Some(Contact {
+				name: name.to_string(),
+				address: parse_required(args, "address")?.to_string(),
+				method: parse_required(args, "method")?.to_string(),
+				slate_version,
+				proof_address: args.value_of("proof_address").map(|a| a.to_string()),
+			})
+		}
+		None => None,
+	};
+	Ok(command::AddressBookArgs { add, remove })
+}
+
 pub fn parse_post_args(args: &ArgMatches) -> Result<command::PostArgs, ParseError> {
 	let tx_file = parse_required(args, "input")?;
 	let fluff = args.is_present("fluff");
@@ -926,6 +1373,7 @@ pub fn parse_export_proof_args(args: &ArgMatches) -> Result<command::ProofExport
 		output_file: output_file.to_owned(),
 		id: tx_id,
 		tx_slate_id: tx_slate_id,
+		document: args.is_present("document"),
 	})
 }
 
@@ -933,6 +1381,7 @@ pub fn parse_verify_proof_args(args: &ArgMatches) -> Result<command::ProofVerify
 	let input_file = parse_required(args, "input")?;
 	Ok(command::ProofVerifyArgs {
 		input_file: input_file.to_owned(),
+		check_node: args.is_present("node"),
 	})
 }
 
@@ -1102,6 +1551,7 @@ pub fn wallet_command<C, F>(
 	mut wallet_config: WalletConfig,
 	tor_config: Option<TorConfig>,
 	mqs_config: Option<MQSConfig>,
+	wallet_settings: WalletSettings,
 	mut node_client: C,
 	test_mode: bool,
 	wallet_inst_cb: F,
@@ -1187,6 +1637,7 @@ where
 		let mut wallet_lock = wallet.lock();
 		let lc = wallet_lock.lc_provider().unwrap();
 		let _ = lc.set_top_level_directory(&wallet_config.data_file_dir);
+		lc.set_wallet_settings(wallet_settings);
 	}
 
 	// provide wallet instance back to the caller (handy for testing with
@@ -1353,6 +1804,20 @@ where
 				wallet_config.dark_background_color_scheme.unwrap_or(true),
 			)
 		}
+		("transfer", Some(args)) => {
+			let a = arg_parse!(parse_transfer_args(&args));
+			command::transfer(owner_api, km, a)
+		}
+		("send_batch", Some(args)) => {
+			let a = arg_parse!(parse_send_batch_args(&args));
+			command::send_batch(
+				owner_api,
+				km,
+				Some(mqs_config.clone()),
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
 		("receive", Some(args)) => {
 			let a = arg_parse!(parse_receive_args(&args));
 			command::receive(owner_api, km, &global_wallet_args, a)
@@ -1369,6 +1834,38 @@ where
 			let a = arg_parse!(parse_issue_invoice_args(&args));
 			command::issue_invoice_tx(owner_api, km, a)
 		}
+		("invoices", Some(args)) => {
+			let a = arg_parse!(parse_invoices_args(&args));
+			command::invoices(
+				owner_api,
+				km,
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
+		("cancel_invoice", Some(args)) => {
+			let a = arg_parse!(parse_cancel_invoice_args(&args));
+			command::cancel_invoice(owner_api, km, a)
+		}
+		("pending_slates", Some(args)) => {
+			let a = arg_parse!(parse_pending_slates_args(&args));
+			command::pending_slates(
+				owner_api,
+				km,
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
+		("spend_policy", Some(args)) => {
+			let a = arg_parse!(parse_spend_policy_args(&global_wallet_args, &args));
+			command::spend_policy(
+				owner_api,
+				km,
+				wallet_config.wallet_data_dir.as_deref(),
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
 		("pay", Some(args)) => {
 			let a = arg_parse!(parse_process_invoice_args(&args, !test_mode));
 			command::process_invoice(
@@ -1395,6 +1892,31 @@ where
 			&global_wallet_args,
 			wallet_config.dark_background_color_scheme.unwrap_or(true),
 		),
+		("label_tx", Some(args)) => {
+			let a = arg_parse!(parse_label_tx_args(&args));
+			command::label_tx(owner_api, km, a)
+		}
+		("address_book", Some(args)) => {
+			let a = arg_parse!(parse_address_book_args(&args));
+			command::address_book(
+				owner_api,
+				km,
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
+		("freeze_output", Some(args)) => {
+			let a = arg_parse!(parse_freeze_output_args(&args, true));
+			command::freeze_output(owner_api, km, a)
+		}
+		("unfreeze_output", Some(args)) => {
+			let a = arg_parse!(parse_freeze_output_args(&args, false));
+			command::freeze_output(owner_api, km, a)
+		}
+		("label_output", Some(args)) => {
+			let a = arg_parse!(parse_label_output_args(&args));
+			command::label_output(owner_api, km, a)
+		}
 		("txs", Some(args)) => {
 			let a = arg_parse!(parse_txs_args(&args));
 			command::txs(
@@ -1422,6 +1944,15 @@ where
 			let a = arg_parse!(parse_cancel_args(&args));
 			command::cancel(owner_api, km, a)
 		}
+		("expired_txs", Some(_)) => command::expired_txs(
+			owner_api,
+			km,
+			wallet_config.dark_background_color_scheme.unwrap_or(true),
+		),
+		("cold", Some(args)) => {
+			let a = arg_parse!(parse_cold_args(&args));
+			command::cold(owner_api, km, a)
+		}
 		("export_proof", Some(args)) => {
 			let a = arg_parse!(parse_export_proof_args(&args));
 			command::proof_export(owner_api, km, a)
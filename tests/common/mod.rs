@@ -27,7 +27,7 @@ use util::{Mutex, ZeroingString};
 use grin_wallet_api::{EncryptedRequest, EncryptedResponse, JsonId};
 use grin_wallet_config::{GlobalWalletConfig, WalletConfig, GRIN_WALLET_DIR};
 use grin_wallet_impls::{DefaultLCProvider, DefaultWalletImpl};
-use grin_wallet_libwallet::{NodeClient, WalletInfo, WalletInst};
+use grin_wallet_libwallet::{NodeClient, WalletInfo, WalletInst, WalletSettings};
 use grin_wallet_util::grin_core::global::{self, ChainTypes};
 use grin_wallet_util::grin_keychain::ExtKeychain;
 use grin_wallet_util::grin_util::{from_hex, static_secp_instance};
@@ -68,7 +68,7 @@ macro_rules! setup_proxy {
 		println!("{:?}", target);
 		if !target.exists() {
 			execute_command(&app, $test_dir, "wallet1", &$client1, arg_vec.clone())?;
-			}
+		}
 
 		// add wallet to proxy
 		let config1 = initial_setup_wallet($test_dir, "wallet1");
@@ -79,14 +79,14 @@ macro_rules! setup_proxy {
 			$client1.clone(),
 			"password",
 			"default",
-			)?;
+		)?;
 		let $mask1 = (&mask1_i).as_ref();
 		wallet_proxy.add_wallet(
 			"wallet1",
 			$client1.get_send_instance(),
 			$wallet1.clone(),
 			mask1_i.clone(),
-			);
+		);
 
 		// Create wallet 2, which will run a listener
 		let $client2 = LocalWalletClient::new("wallet2", wallet_proxy.tx.clone());
@@ -94,7 +94,7 @@ macro_rules! setup_proxy {
 		let target = std::path::PathBuf::from(format!("{}/wallet2/mwc-wallet.toml", $test_dir));
 		if !target.exists() {
 			execute_command(&app, $test_dir, "wallet2", &$client2, arg_vec.clone())?;
-			}
+		}
 
 		let config2 = initial_setup_wallet($test_dir, "wallet2");
 		let wallet_config2 = config2.clone().members.unwrap().wallet;
@@ -104,21 +104,21 @@ macro_rules! setup_proxy {
 			$client2.clone(),
 			"password",
 			"default",
-			)?;
+		)?;
 		let $mask2 = (&mask2_i).as_ref();
 		wallet_proxy.add_wallet(
 			"wallet2",
 			$client2.get_send_instance(),
 			$wallet2.clone(),
 			mask2_i.clone(),
-			);
+		);
 
 		// Set the wallet proxy listener running
 		thread::spawn(move || {
 			if let Err(e) = wallet_proxy.run() {
 				error!("Wallet Proxy error: {}", e);
-				}
-			});
+			}
+		});
 	};
 }
 
@@ -281,6 +281,7 @@ pub fn execute_command(
 		wallet_config.clone(),
 		tor_config,
 		mqs_config,
+		WalletSettings::default(),
 		client.clone(),
 		true,
 		|_| {},
@@ -329,6 +330,7 @@ where
 		wallet_config,
 		tor_config,
 		mqs_config,
+		WalletSettings::default(),
 		client.clone(),
 		true,
 		f,